@@ -63,7 +63,8 @@ pub fn new_partial(
 		.clone()
 		.filter(|x| !x.is_empty())
 		.map(|endpoints| -> Result<_, sc_telemetry::Error> {
-			let worker = TelemetryWorker::new(16)?;
+			let worker =
+				TelemetryWorker::new(16)?.with_reconnect_policy(config.telemetry_reconnect);
 			let telemetry = worker.handle().new_telemetry(endpoints);
 			Ok((worker, telemetry))
 		})
@@ -338,7 +339,8 @@ pub fn new_light(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		.clone()
 		.filter(|x| !x.is_empty())
 		.map(|endpoints| -> Result<_, sc_telemetry::Error> {
-			let worker = TelemetryWorker::new(16)?;
+			let worker =
+				TelemetryWorker::new(16)?.with_reconnect_policy(config.telemetry_reconnect);
 			let telemetry = worker.handle().new_telemetry(endpoints);
 			Ok((worker, telemetry))
 		})
@@ -21,6 +21,10 @@ fn main() {
 	cli::main();
 }
 
+#[cfg(feature = "cli")]
+#[path = "src/arg_enums.rs"]
+mod arg_enums;
+
 #[cfg(feature = "cli")]
 mod cli {
 	include!("src/cli.rs");
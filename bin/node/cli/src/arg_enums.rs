@@ -0,0 +1,125 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The modes and presets of the node options, parsed from the command line.
+//!
+//! The build script includes this file together with the command line definition, so it only
+//! depends on the standard library.
+
+/// The rule choosing the chain the node builds on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectChainMode {
+	/// The longest chain.
+	Longest,
+	/// The longest chain among the ones descending from the last finalized block.
+	FinalizedDescendantLongest,
+}
+
+impl Default for SelectChainMode {
+	fn default() -> Self {
+		SelectChainMode::Longest
+	}
+}
+
+impl SelectChainMode {
+	/// The values accepted on the command line.
+	pub fn variants() -> [&'static str; 2] {
+		["Longest", "FinalizedDescendantLongest"]
+	}
+}
+
+impl std::str::FromStr for SelectChainMode {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, String> {
+		match s {
+			_ if s.eq_ignore_ascii_case("longest") => Ok(Self::Longest),
+			_ if s.eq_ignore_ascii_case("finalizeddescendantlongest") =>
+				Ok(Self::FinalizedDescendantLongest),
+			_ => Err(format!("Unknown variant `{}`, known variants: {:?}", s, Self::variants())),
+		}
+	}
+}
+
+/// How a long-running essential task is spawned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpawnMode {
+	/// On the blocking thread pool, where the task gets a thread of its own.
+	Blocking,
+	/// On the async thread pool, which requires the task to never block.
+	Async,
+}
+
+impl Default for SpawnMode {
+	fn default() -> Self {
+		SpawnMode::Blocking
+	}
+}
+
+impl SpawnMode {
+	/// The values accepted on the command line.
+	pub fn variants() -> [&'static str; 2] {
+		["Blocking", "Async"]
+	}
+}
+
+impl std::str::FromStr for SpawnMode {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, String> {
+		match s {
+			_ if s.eq_ignore_ascii_case("blocking") => Ok(Self::Blocking),
+			_ if s.eq_ignore_ascii_case("async") => Ok(Self::Async),
+			_ => Err(format!("Unknown variant `{}`, known variants: {:?}", s, Self::variants())),
+		}
+	}
+}
+
+/// Preset of the GRANDPA timings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FinalityProfile {
+	/// Timings suited to public networks.
+	Standard,
+	/// Shorter timings for private networks with low latency between the nodes.
+	Fast,
+}
+
+impl Default for FinalityProfile {
+	fn default() -> Self {
+		FinalityProfile::Standard
+	}
+}
+
+impl FinalityProfile {
+	/// The values accepted on the command line.
+	pub fn variants() -> [&'static str; 2] {
+		["Standard", "Fast"]
+	}
+}
+
+impl std::str::FromStr for FinalityProfile {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, String> {
+		match s {
+			_ if s.eq_ignore_ascii_case("standard") => Ok(Self::Standard),
+			_ if s.eq_ignore_ascii_case("fast") => Ok(Self::Fast),
+			_ => Err(format!("Unknown variant `{}`, known variants: {:?}", s, Self::variants())),
+		}
+	}
+}
@@ -0,0 +1,155 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Authoring during daily time windows.
+//!
+//! The windows are given as `HH:MM-HH:MM` time ranges in a fixed offset from UTC, a window
+//! ending before it starts spans midnight. Outside of the windows the slots are skipped, the
+//! node keeps syncing and serving the RPC.
+
+use std::{
+	fmt,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+
+/// The daily windows during which blocks are authored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuthorSchedule {
+	/// The windows, as start and end minutes of the day in the local offset.
+	windows: Vec<(i64, i64)>,
+	/// The offset of the windows from UTC, in minutes.
+	utc_offset: i64,
+}
+
+impl AuthorSchedule {
+	/// Parse the comma separated `HH:MM-HH:MM` windows of `spec`, in the `±HH:MM` `utc_offset`.
+	pub fn new(spec: &str, utc_offset: &str) -> Result<Self, String> {
+		let windows = spec
+			.split(',')
+			.map(|window| {
+				let (start, end) = window
+					.trim()
+					.split_once('-')
+					.ok_or_else(|| format!("Invalid author schedule window `{}`", window))?;
+				let (start, end) = (minute_of_day(start)?, minute_of_day(end)?);
+				if start == end {
+					return Err(format!("The author schedule window `{}` is empty", window))
+				}
+				Ok((start, end))
+			})
+			.collect::<Result<_, String>>()?;
+
+		let (sign, offset) = match utc_offset.trim() {
+			offset if offset.starts_with('+') => (1, &offset[1..]),
+			offset if offset.starts_with('-') => (-1, &offset[1..]),
+			_ => return Err(format!("Invalid UTC offset `{}`, expected `±HH:MM`", utc_offset)),
+		};
+		let utc_offset = sign * minute_of_day(offset)?;
+
+		Ok(Self { windows, utc_offset })
+	}
+
+	/// Whether blocks are authored now.
+	pub fn is_open(&self) -> bool {
+		let now = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.expect("Current time is always after unix epoch; qed");
+		self.is_open_at(now.as_secs())
+	}
+
+	/// Whether blocks are authored at `timestamp`, in seconds since the UNIX epoch.
+	fn is_open_at(&self, timestamp: u64) -> bool {
+		let minute = (timestamp as i64 / 60 + self.utc_offset).rem_euclid(MINUTES_PER_DAY);
+		self.windows.iter().any(|&(start, end)| {
+			if start < end {
+				start <= minute && minute < end
+			} else {
+				start <= minute || minute < end
+			}
+		})
+	}
+}
+
+impl fmt::Display for AuthorSchedule {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let time = |minute: i64| format!("{:02}:{:02}", minute / 60, minute % 60);
+		let windows: Vec<_> = self
+			.windows
+			.iter()
+			.map(|&(start, end)| format!("{}-{}", time(start), time(end)))
+			.collect();
+		let sign = if self.utc_offset < 0 { '-' } else { '+' };
+		write!(f, "{} UTC{}{}", windows.join(","), sign, time(self.utc_offset.abs()))
+	}
+}
+
+/// Parse `HH:MM` as a minute of the day.
+fn minute_of_day(time: &str) -> Result<i64, String> {
+	let invalid = || format!("Invalid time `{}`, expected `HH:MM`", time);
+	let (hours, minutes) = time.trim().split_once(':').ok_or_else(invalid)?;
+	let hours: i64 = hours.parse().map_err(|_| invalid())?;
+	let minutes: i64 = minutes.parse().map_err(|_| invalid())?;
+	if hours >= 24 || minutes >= 60 {
+		return Err(invalid())
+	}
+	Ok(hours * 60 + minutes)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const HOUR: u64 = 3600;
+
+	#[test]
+	fn authors_inside_the_windows() {
+		let schedule = AuthorSchedule::new("08:00-20:00", "+00:00").unwrap();
+		assert!(!schedule.is_open_at(7 * HOUR + 59 * 60));
+		assert!(schedule.is_open_at(8 * HOUR));
+		assert!(!schedule.is_open_at(20 * HOUR));
+		assert!(schedule.is_open_at(24 * HOUR + 12 * HOUR));
+
+		let schedule = AuthorSchedule::new("22:00-06:00, 12:00-13:00", "+00:00").unwrap();
+		assert!(schedule.is_open_at(23 * HOUR));
+		assert!(schedule.is_open_at(5 * HOUR));
+		assert!(schedule.is_open_at(12 * HOUR + 30 * 60));
+		assert!(!schedule.is_open_at(8 * HOUR));
+	}
+
+	#[test]
+	fn applies_the_utc_offset() {
+		let schedule = AuthorSchedule::new("08:00-20:00", "+02:00").unwrap();
+		assert!(schedule.is_open_at(6 * HOUR));
+		assert!(!schedule.is_open_at(18 * HOUR));
+
+		let schedule = AuthorSchedule::new("08:00-20:00", "-05:30").unwrap();
+		assert!(!schedule.is_open_at(13 * HOUR));
+		assert!(schedule.is_open_at(14 * HOUR));
+		assert_eq!(schedule.to_string(), "08:00-20:00 UTC-05:30");
+	}
+
+	#[test]
+	fn rejects_invalid_specs() {
+		assert!(AuthorSchedule::new("08:00", "+00:00").is_err());
+		assert!(AuthorSchedule::new("08:00-24:00", "+00:00").is_err());
+		assert!(AuthorSchedule::new("08:00-08:00", "+00:00").is_err());
+		assert!(AuthorSchedule::new("08:00-20:00", "02:00").is_err());
+	}
+}
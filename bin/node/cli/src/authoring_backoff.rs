@@ -0,0 +1,347 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Backing off from authoring blocks.
+//!
+//! On top of the generic backoff strategy, the node skips its slots while the authoring is paused
+//! through the RPC or outside of its [`AuthorSchedule`], and can skip them while the transaction
+//! pool has no ready transactions, so that it doesn't author blocks holding only inherents. The
+//! first slot of every epoch is claimed regardless, as BABE can't recover from an epoch without any
+//! block.
+
+use crate::author_schedule::AuthorSchedule;
+use node_primitives::{Block, BlockNumber};
+use node_rpc::AuthoringPause;
+use sc_client_api::HeaderBackend;
+use sc_consensus_babe::find_pre_digest;
+use sc_consensus_slots::BackoffAuthoringBlocksStrategy;
+use sc_transaction_pool_api::TransactionPool;
+use sp_consensus_babe::Slot;
+use sp_runtime::generic::BlockId;
+use std::sync::Arc;
+
+/// Skip the slots in which the block would be empty.
+pub struct NoEmptyBlocks<C, P> {
+	client: Arc<C>,
+	transaction_pool: Arc<P>,
+	epoch_length: u64,
+}
+
+impl<C, P> NoEmptyBlocks<C, P>
+where
+	C: HeaderBackend<Block>,
+	P: TransactionPool,
+{
+	/// Skip empty blocks, for a chain with BABE epochs of `epoch_length` slots.
+	pub fn new(client: Arc<C>, transaction_pool: Arc<P>, epoch_length: u64) -> Self {
+		Self { client, transaction_pool, epoch_length }
+	}
+
+	/// Whether the block authored at `slot` on top of the chain head would be empty, and isn't
+	/// needed to start a new epoch.
+	fn should_skip(
+		&self,
+		chain_head_number: BlockNumber,
+		chain_head_slot: Slot,
+		slot: Slot,
+	) -> bool {
+		if self.transaction_pool.status().ready > 0 || chain_head_number == 0 {
+			return false
+		}
+
+		// Epochs start every `epoch_length` slots from the slot of the first block.
+		let genesis_slot = match self.client.header(BlockId::Number(1)) {
+			Ok(Some(header)) => match find_pre_digest::<Block>(&header) {
+				Ok(pre_digest) => pre_digest.slot(),
+				Err(_) => return false,
+			},
+			_ => return false,
+		};
+		let epoch = |slot: Slot| (*slot).saturating_sub(*genesis_slot) / self.epoch_length;
+
+		epoch(slot) == epoch(chain_head_slot)
+	}
+}
+
+/// The backoff strategy of the node, skipping the slots while paused or outside of the schedule,
+/// and empty blocks if enabled.
+pub struct AuthoringBackoff<S, C, P> {
+	inner: Option<S>,
+	no_empty_blocks: Option<NoEmptyBlocks<C, P>>,
+	pause: AuthoringPause,
+	schedule: Option<AuthorSchedule>,
+}
+
+impl<S, C, P> AuthoringBackoff<S, C, P> {
+	/// Back off while `pause` is set, outside of the `schedule`, when `inner` does, or when the
+	/// block would be empty with `no_empty_blocks`.
+	pub fn new(
+		inner: Option<S>,
+		no_empty_blocks: Option<NoEmptyBlocks<C, P>>,
+		pause: AuthoringPause,
+		schedule: Option<AuthorSchedule>,
+	) -> Self {
+		Self { inner, no_empty_blocks, pause, schedule }
+	}
+}
+
+impl<S, C, P> BackoffAuthoringBlocksStrategy<BlockNumber> for AuthoringBackoff<S, C, P>
+where
+	S: BackoffAuthoringBlocksStrategy<BlockNumber>,
+	C: HeaderBackend<Block>,
+	P: TransactionPool,
+{
+	fn should_backoff(
+		&self,
+		chain_head_number: BlockNumber,
+		chain_head_slot: Slot,
+		finalized_number: BlockNumber,
+		slot_now: Slot,
+		logging_target: &str,
+	) -> bool {
+		if self.pause.is_paused() {
+			log::debug!(target: logging_target, "Skipping slot {}, authoring is paused", slot_now);
+			return true
+		}
+
+		match &self.schedule {
+			Some(schedule) if !schedule.is_open() => {
+				log::debug!(
+					target: logging_target,
+					"Skipping slot {}, outside of the author schedule {}",
+					slot_now,
+					schedule,
+				);
+				return true
+			},
+			_ => {},
+		}
+
+		if let Some(inner) = &self.inner {
+			if inner.should_backoff(
+				chain_head_number,
+				chain_head_slot,
+				finalized_number,
+				slot_now,
+				logging_target,
+			) {
+				return true
+			}
+		}
+
+		match &self.no_empty_blocks {
+			Some(no_empty_blocks)
+				if no_empty_blocks.should_skip(chain_head_number, chain_head_slot, slot_now) =>
+			{
+				log::debug!(
+					target: logging_target,
+					"Skipping slot {}, there are no transactions to include",
+					slot_now,
+				);
+				true
+			},
+			_ => false,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::Future;
+	use node_primitives::{Hash, Header};
+	use sc_client_api::{in_mem::Blockchain, NewBlockState};
+	use sc_consensus_babe::CompatibleDigestItem;
+	use sc_transaction_pool_api::{
+		ImportNotificationStream, InPoolTransaction, PoolFuture, PoolStatus, TransactionFor,
+		TransactionSource, TransactionStatusStreamFor, TxHash,
+	};
+	use sp_consensus_babe::digests::{PreDigest, SecondaryPlainPreDigest};
+	use sp_runtime::{
+		traits::{Header as _, NumberFor},
+		Digest, DigestItem, OpaqueExtrinsic,
+	};
+	use std::{collections::HashMap, pin::Pin};
+
+	struct PoolTransaction(OpaqueExtrinsic, Hash);
+
+	impl InPoolTransaction for PoolTransaction {
+		type Transaction = OpaqueExtrinsic;
+		type Hash = Hash;
+
+		fn data(&self) -> &Self::Transaction {
+			&self.0
+		}
+
+		fn hash(&self) -> &Self::Hash {
+			&self.1
+		}
+
+		fn priority(&self) -> &u64 {
+			unimplemented!()
+		}
+
+		fn longevity(&self) -> &u64 {
+			unimplemented!()
+		}
+
+		fn requires(&self) -> &[Vec<u8>] {
+			unimplemented!()
+		}
+
+		fn provides(&self) -> &[Vec<u8>] {
+			unimplemented!()
+		}
+
+		fn is_propagable(&self) -> bool {
+			unimplemented!()
+		}
+	}
+
+	/// A pool only reporting its number of ready transactions.
+	struct ReadyPool(usize);
+
+	impl TransactionPool for ReadyPool {
+		type Block = Block;
+		type Hash = Hash;
+		type InPoolTransaction = PoolTransaction;
+		type Error = sc_transaction_pool_api::error::Error;
+
+		fn submit_at(
+			&self,
+			_at: &BlockId<Self::Block>,
+			_source: TransactionSource,
+			_xts: Vec<TransactionFor<Self>>,
+		) -> PoolFuture<Vec<Result<Hash, Self::Error>>, Self::Error> {
+			unimplemented!()
+		}
+
+		fn submit_one(
+			&self,
+			_at: &BlockId<Self::Block>,
+			_source: TransactionSource,
+			_xt: TransactionFor<Self>,
+		) -> PoolFuture<TxHash<Self>, Self::Error> {
+			unimplemented!()
+		}
+
+		fn submit_and_watch(
+			&self,
+			_at: &BlockId<Self::Block>,
+			_source: TransactionSource,
+			_xt: TransactionFor<Self>,
+		) -> PoolFuture<Pin<Box<TransactionStatusStreamFor<Self>>>, Self::Error> {
+			unimplemented!()
+		}
+
+		fn ready_at(
+			&self,
+			_at: NumberFor<Self::Block>,
+		) -> Pin<
+			Box<
+				dyn Future<Output = Box<dyn Iterator<Item = Arc<Self::InPoolTransaction>> + Send>>
+					+ Send,
+			>,
+		> {
+			unimplemented!()
+		}
+
+		fn ready(&self) -> Box<dyn Iterator<Item = Arc<Self::InPoolTransaction>> + Send> {
+			unimplemented!()
+		}
+
+		fn remove_invalid(&self, _hashes: &[TxHash<Self>]) -> Vec<Arc<Self::InPoolTransaction>> {
+			unimplemented!()
+		}
+
+		fn status(&self) -> PoolStatus {
+			PoolStatus { ready: self.0, ready_bytes: 0, future: 0, future_bytes: 0 }
+		}
+
+		fn import_notification_stream(&self) -> ImportNotificationStream<TxHash<Self>> {
+			unimplemented!()
+		}
+
+		fn on_broadcasted(&self, _propagations: HashMap<TxHash<Self>, Vec<String>>) {
+			unimplemented!()
+		}
+
+		fn hash_of(&self, _xt: &TransactionFor<Self>) -> TxHash<Self> {
+			unimplemented!()
+		}
+
+		fn ready_transaction(&self, _hash: &TxHash<Self>) -> Option<Arc<Self::InPoolTransaction>> {
+			unimplemented!()
+		}
+	}
+
+	/// A chain whose first block is authored at slot 100.
+	fn client() -> Arc<Blockchain<Block>> {
+		let client = Blockchain::new();
+		let genesis = Header::new(
+			0,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		);
+		let pre_digest = PreDigest::SecondaryPlain(SecondaryPlainPreDigest {
+			authority_index: 0,
+			slot: 100.into(),
+		});
+		let block1 = Header::new(
+			1,
+			Default::default(),
+			Default::default(),
+			genesis.hash(),
+			Digest {
+				logs: vec![<DigestItem<Hash> as CompatibleDigestItem>::babe_pre_digest(pre_digest)],
+			},
+		);
+		client
+			.insert(genesis.hash(), genesis, None, None, NewBlockState::Final)
+			.unwrap();
+		client.insert(block1.hash(), block1, None, None, NewBlockState::Best).unwrap();
+		Arc::new(client)
+	}
+
+	#[test]
+	fn skips_the_empty_blocks_within_an_epoch() {
+		let no_empty_blocks = NoEmptyBlocks::new(client(), Arc::new(ReadyPool(0)), 10);
+
+		assert!(no_empty_blocks.should_skip(1, 100.into(), 105.into()));
+		assert!(no_empty_blocks.should_skip(3, 112.into(), 119.into()));
+		// The block authored on top of the genesis block starts the first epoch.
+		assert!(!no_empty_blocks.should_skip(0, 0.into(), 100.into()));
+	}
+
+	#[test]
+	fn claims_the_first_slot_of_an_epoch() {
+		let no_empty_blocks = NoEmptyBlocks::new(client(), Arc::new(ReadyPool(0)), 10);
+
+		assert!(!no_empty_blocks.should_skip(1, 100.into(), 110.into()));
+		assert!(!no_empty_blocks.should_skip(3, 108.into(), 125.into()));
+	}
+
+	#[test]
+	fn authors_the_blocks_with_transactions() {
+		let no_empty_blocks = NoEmptyBlocks::new(client(), Arc::new(ReadyPool(1)), 10);
+
+		assert!(!no_empty_blocks.should_skip(1, 100.into(), 105.into()));
+	}
+}
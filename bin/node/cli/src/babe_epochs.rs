@@ -0,0 +1,173 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tracking of the BABE epochs.
+//!
+//! Whenever the best block enters an epoch that wasn't seen yet, the epoch is passed to the
+//! handlers of the node. The epochs can be logged, with their index, authorities and randomness,
+//! along with the authorities whose keys are in the local keystore, which tells whether the node
+//! can claim slots in that epoch.
+
+use futures::prelude::*;
+use node_primitives::{Block, Header};
+use sc_client_api::{
+	blockchain::{Error as BlockchainError, HeaderMetadata},
+	BlockchainEvents, HeaderBackend,
+};
+use sc_consensus_babe::{find_pre_digest, BabeLink, Epoch};
+use sc_consensus_epochs::descendent_query;
+use sp_core::{
+	crypto::{key_types::BABE, Public},
+	hexdisplay::HexDisplay,
+};
+use sp_keystore::{SyncCryptoStore, SyncCryptoStorePtr};
+use sp_runtime::{generic::BlockId, traits::Header as HeaderT};
+use std::sync::Arc;
+
+const LOG_TARGET: &str = "babe-epochs";
+
+/// Call `on_epoch` with the epoch of the best block at startup, then with every new epoch the
+/// best block enters.
+pub async fn run<C>(client: Arc<C>, babe_link: BabeLink<Block>, mut on_epoch: impl FnMut(&Epoch))
+where
+	C: BlockchainEvents<Block>
+		+ HeaderBackend<Block>
+		+ HeaderMetadata<Block, Error = BlockchainError>,
+{
+	let mut last_epoch = None;
+
+	let best_hash = client.info().best_hash;
+	match client.header(BlockId::Hash(best_hash)) {
+		Ok(Some(header)) =>
+			new_epoch_of(&*client, &babe_link, &header, &mut last_epoch, &mut on_epoch),
+		Ok(None) => {},
+		Err(err) => log::warn!(target: LOG_TARGET, "Failed to get the best block: {}", err),
+	}
+
+	let mut imports = client.import_notification_stream();
+	while let Some(notification) = imports.next().await {
+		if notification.is_new_best {
+			new_epoch_of(
+				&*client,
+				&babe_link,
+				&notification.header,
+				&mut last_epoch,
+				&mut on_epoch,
+			);
+		}
+	}
+}
+
+/// Call `on_epoch` with the epoch of the block with the given `header`, unless it is
+/// `last_epoch`.
+fn new_epoch_of<C>(
+	client: &C,
+	babe_link: &BabeLink<Block>,
+	header: &Header,
+	last_epoch: &mut Option<u64>,
+	on_epoch: &mut impl FnMut(&Epoch),
+) where
+	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockchainError>,
+{
+	// The genesis block isn't part of any epoch.
+	if *header.number() == 0 {
+		return
+	}
+
+	let epoch = match epoch_of(client, babe_link, header) {
+		Ok(epoch) => epoch,
+		Err(err) => {
+			log::debug!(
+				target: LOG_TARGET,
+				"Failed to get the epoch of block #{}: {}",
+				header.number(),
+				err,
+			);
+			return
+		},
+	};
+
+	if *last_epoch == Some(epoch.epoch_index) {
+		return
+	}
+	*last_epoch = Some(epoch.epoch_index);
+
+	on_epoch(&epoch);
+}
+
+/// Log `epoch`, along with the authorities of the epoch whose keys are in `keystore`.
+pub fn log_epoch(keystore: &SyncCryptoStorePtr, epoch: &Epoch) {
+	log::info!(
+		target: LOG_TARGET,
+		"👶 BABE epoch #{} starts at slot {} and lasts {} slots, randomness 0x{}",
+		epoch.epoch_index,
+		epoch.start_slot,
+		epoch.duration,
+		HexDisplay::from(&epoch.randomness),
+	);
+
+	let mut local_authorities = Vec::new();
+	for (index, (authority, weight)) in epoch.authorities.iter().enumerate() {
+		log::info!(
+			target: LOG_TARGET,
+			"👶 Authority #{} of epoch #{}: {} (weight {})",
+			index,
+			epoch.epoch_index,
+			authority,
+			weight,
+		);
+		if SyncCryptoStore::has_keys(&**keystore, &[(authority.to_raw_vec(), BABE)]) {
+			local_authorities.push(index);
+		}
+	}
+
+	if local_authorities.is_empty() {
+		log::info!(
+			target: LOG_TARGET,
+			"👶 No local key is an authority of epoch #{}, the node won't claim any slot",
+			epoch.epoch_index,
+		);
+	} else {
+		log::info!(
+			target: LOG_TARGET,
+			"👶 Local keys are the authorities {:?} of epoch #{}",
+			local_authorities,
+			epoch.epoch_index,
+		);
+	}
+}
+
+fn epoch_of<C>(client: &C, babe_link: &BabeLink<Block>, header: &Header) -> Result<Epoch, String>
+where
+	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockchainError>,
+{
+	let slot = find_pre_digest::<Block>(header).map_err(|e| e.to_string())?.slot();
+
+	babe_link
+		.epoch_changes()
+		.shared_data()
+		.epoch_data_for_child_of(
+			descendent_query(client),
+			header.parent_hash(),
+			header.number() - 1,
+			slot,
+			|slot| Epoch::genesis(babe_link.config(), slot),
+		)
+		.map_err(|e| format!("{:?}", e))?
+		.ok_or_else(|| "no epoch found".into())
+}
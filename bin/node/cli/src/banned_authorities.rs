@@ -0,0 +1,303 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Rejection of the blocks authored by banned authorities.
+//!
+//! Meant as a coarse defense against a compromised validator during an incident: once the BABE
+//! verifier accepted a block, its author is looked up in the epoch from the index of the
+//! pre-runtime digest, and the block is rejected if the author is banned.
+
+use node_primitives::Block;
+use sc_consensus::{BlockImportParams, Verifier};
+use sc_consensus_babe::{
+	find_pre_digest, BabeIntermediate, Config, Epoch, PreDigest, INTERMEDIATE_KEY,
+};
+use sc_consensus_epochs::SharedEpochChanges;
+use sp_consensus::CacheKeyId;
+use sp_consensus_babe::AuthorityId;
+use sp_runtime::traits::Header as HeaderT;
+use std::collections::HashSet;
+
+const LOG_TARGET: &str = "banned-authorities";
+
+/// Wraps the BABE verifier, rejecting the blocks authored by the `banned` authorities.
+pub struct BannedAuthoritiesVerifier<V> {
+	inner: V,
+	banned: HashSet<AuthorityId>,
+	epoch_changes: SharedEpochChanges<Block, Epoch>,
+	config: Config,
+}
+
+impl<V> BannedAuthoritiesVerifier<V> {
+	/// Wrap `inner`, looking up the authors in the `epoch_changes` of the BABE link.
+	pub fn new(
+		inner: V,
+		banned: HashSet<AuthorityId>,
+		epoch_changes: SharedEpochChanges<Block, Epoch>,
+		config: Config,
+	) -> Self {
+		Self { inner, banned, epoch_changes, config }
+	}
+
+	/// The author of the verified `block`, or `None` if the BABE verifier skipped the block.
+	fn author(&self, block: &BlockImportParams<Block, ()>) -> Result<Option<AuthorityId>, String> {
+		let intermediate = match block
+			.intermediates
+			.get(INTERMEDIATE_KEY)
+			.and_then(|intermediate| intermediate.downcast_ref::<BabeIntermediate<Block>>())
+		{
+			Some(intermediate) => intermediate,
+			// Blocks imported along with their state aren't verified.
+			None => return Ok(None),
+		};
+		let pre_digest = find_pre_digest::<Block>(&block.header).map_err(|e| e.to_string())?;
+
+		let epoch_changes = self.epoch_changes.shared_data();
+		let epoch = epoch_changes
+			.viable_epoch(&intermediate.epoch_descriptor, |slot| Epoch::genesis(&self.config, slot))
+			.ok_or("The epoch of the block is unknown")?;
+
+		Ok(author(epoch.as_ref(), &pre_digest).cloned())
+	}
+}
+
+/// The authority of `epoch` that claimed the slot with `pre_digest`.
+fn author<'a>(epoch: &'a Epoch, pre_digest: &PreDigest) -> Option<&'a AuthorityId> {
+	epoch
+		.authorities
+		.get(pre_digest.authority_index() as usize)
+		.map(|(authority, _)| authority)
+}
+
+#[async_trait::async_trait]
+impl<V: Verifier<Block>> Verifier<Block> for BannedAuthoritiesVerifier<V> {
+	async fn verify(
+		&mut self,
+		block: BlockImportParams<Block, ()>,
+	) -> Result<(BlockImportParams<Block, ()>, Option<Vec<(CacheKeyId, Vec<u8>)>>), String> {
+		let (block, cache) = self.inner.verify(block).await?;
+		if self.banned.is_empty() {
+			return Ok((block, cache))
+		}
+
+		match self.author(&block)? {
+			Some(author) if self.banned.contains(&author) => {
+				log::warn!(
+					target: LOG_TARGET,
+					"⛔ Rejecting #{} ({}) authored by the banned authority {}",
+					block.header.number(),
+					block.header.hash(),
+					author,
+				);
+				Err(format!("The block is authored by the banned authority {}", author))
+			},
+			_ => Ok((block, cache)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use node_primitives::Header;
+	use sc_client_api::{blockchain, AuxStore, ClientInfo, UsageProvider};
+	use sc_consensus_babe::CompatibleDigestItem;
+	use sc_consensus_epochs::{EpochChangesFor, ViableEpochDescriptor};
+	use sp_api::{ApiRef, ProvideRuntimeApi};
+	use sp_consensus::{BlockOrigin, SlotData};
+	use sp_consensus_babe::{
+		digests::SecondaryPlainPreDigest, AllowedSlots, BabeApi, BabeEpochConfiguration,
+		BabeGenesisConfiguration, EquivocationProof, OpaqueKeyOwnershipProof, Slot,
+	};
+	use sp_core::crypto::Public;
+	use sp_runtime::{Digest, DigestItem};
+	use std::borrow::Cow;
+
+	/// A client only able to provide the BABE configuration, from its auxiliary storage.
+	struct ConfigClient(BabeGenesisConfiguration);
+
+	impl AuxStore for ConfigClient {
+		fn insert_aux<
+			'a,
+			'b: 'a,
+			'c: 'a,
+			I: IntoIterator<Item = &'a (&'c [u8], &'c [u8])>,
+			D: IntoIterator<Item = &'a &'b [u8]>,
+		>(
+			&self,
+			_insert: I,
+			_delete: D,
+		) -> blockchain::Result<()> {
+			unimplemented!()
+		}
+
+		fn get_aux(&self, key: &[u8]) -> blockchain::Result<Option<Vec<u8>>> {
+			Ok(Some(self.0.encode()).filter(|_| key == BabeGenesisConfiguration::SLOT_KEY))
+		}
+	}
+
+	impl UsageProvider<Block> for ConfigClient {
+		fn usage_info(&self) -> ClientInfo<Block> {
+			unimplemented!()
+		}
+	}
+
+	impl ProvideRuntimeApi<Block> for ConfigClient {
+		type Api = RuntimeApi;
+
+		fn runtime_api<'a>(&'a self) -> ApiRef<'a, Self::Api> {
+			RuntimeApi.into()
+		}
+	}
+
+	struct RuntimeApi;
+
+	sp_api::mock_impl_runtime_apis! {
+		impl BabeApi<Block> for RuntimeApi {
+			fn configuration() -> BabeGenesisConfiguration {
+				unimplemented!()
+			}
+
+			fn current_epoch_start() -> Slot {
+				unimplemented!()
+			}
+
+			fn current_epoch() -> sp_consensus_babe::Epoch {
+				unimplemented!()
+			}
+
+			fn next_epoch() -> sp_consensus_babe::Epoch {
+				unimplemented!()
+			}
+
+			fn generate_key_ownership_proof(
+				_slot: Slot,
+				_authority_id: AuthorityId,
+			) -> Option<OpaqueKeyOwnershipProof> {
+				unimplemented!()
+			}
+
+			fn submit_report_equivocation_unsigned_extrinsic(
+				_equivocation_proof: EquivocationProof<Header>,
+				_key_owner_proof: OpaqueKeyOwnershipProof,
+			) -> Option<()> {
+				unimplemented!()
+			}
+		}
+	}
+
+	/// A verifier accepting all the blocks, as verified in the genesis epoch if `babe` is set.
+	struct AcceptAll {
+		babe: bool,
+	}
+
+	#[async_trait::async_trait]
+	impl Verifier<Block> for AcceptAll {
+		async fn verify(
+			&mut self,
+			mut block: BlockImportParams<Block, ()>,
+		) -> Result<(BlockImportParams<Block, ()>, Option<Vec<(CacheKeyId, Vec<u8>)>>), String> {
+			if self.babe {
+				let epoch_descriptor = ViableEpochDescriptor::UnimportedGenesis(1.into());
+				block.intermediates.insert(
+					Cow::from(INTERMEDIATE_KEY),
+					Box::new(BabeIntermediate::<Block> { epoch_descriptor }) as Box<_>,
+				);
+			}
+			Ok((block, None))
+		}
+	}
+
+	fn verifier(babe: bool, banned: &[AuthorityId]) -> BannedAuthoritiesVerifier<AcceptAll> {
+		let genesis = BabeGenesisConfiguration {
+			slot_duration: 6000,
+			epoch_length: 10,
+			c: (1, 4),
+			genesis_authorities: vec![
+				(AuthorityId::from_slice(&[1; 32]), 1),
+				(AuthorityId::from_slice(&[2; 32]), 1),
+			],
+			randomness: [0; 32],
+			allowed_slots: AllowedSlots::PrimaryAndSecondaryPlainSlots,
+		};
+		BannedAuthoritiesVerifier::new(
+			AcceptAll { babe },
+			banned.iter().cloned().collect(),
+			SharedEpochChanges::<Block, Epoch>::new(EpochChangesFor::<Block, Epoch>::new()),
+			Config::get_or_compute::<Block, _>(&ConfigClient(genesis)).unwrap(),
+		)
+	}
+
+	/// A block claiming slot 1 for the authority at `authority_index`.
+	fn block(authority_index: u32) -> BlockImportParams<Block, ()> {
+		let pre_digest =
+			PreDigest::SecondaryPlain(SecondaryPlainPreDigest { authority_index, slot: 1.into() });
+		let header = Header::new(
+			1,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Digest { logs: vec![DigestItem::babe_pre_digest(pre_digest)] },
+		);
+		BlockImportParams::new(BlockOrigin::NetworkBroadcast, header)
+	}
+
+	#[test]
+	fn rejects_the_blocks_of_the_banned_authorities() {
+		let bob = AuthorityId::from_slice(&[2; 32]);
+		let mut verifier = verifier(true, &[bob]);
+
+		assert!(futures::executor::block_on(verifier.verify(block(0))).is_ok());
+		match futures::executor::block_on(verifier.verify(block(1))) {
+			Err(e) => assert!(e.contains("banned authority")),
+			Ok(_) => panic!("The block of the banned authority was accepted"),
+		}
+	}
+
+	#[test]
+	fn accepts_the_blocks_skipped_by_babe() {
+		let alice = AuthorityId::from_slice(&[1; 32]);
+		let mut verifier = verifier(false, &[alice]);
+
+		assert!(futures::executor::block_on(verifier.verify(block(0))).is_ok());
+	}
+
+	#[test]
+	fn finds_the_author_from_the_authority_index() {
+		let alice = AuthorityId::from_slice(&[1; 32]);
+		let bob = AuthorityId::from_slice(&[2; 32]);
+		let epoch = Epoch {
+			epoch_index: 0,
+			start_slot: 0.into(),
+			duration: 10,
+			authorities: vec![(alice, 1), (bob.clone(), 1)],
+			randomness: [0; 32],
+			config: BabeEpochConfiguration {
+				c: (1, 4),
+				allowed_slots: AllowedSlots::PrimaryAndSecondaryPlainSlots,
+			},
+		};
+		let pre_digest = |authority_index| {
+			PreDigest::SecondaryPlain(SecondaryPlainPreDigest { authority_index, slot: 1.into() })
+		};
+
+		assert_eq!(author(&epoch, &pre_digest(1)), Some(&bob));
+		assert_eq!(author(&epoch, &pre_digest(2)), None);
+	}
+}
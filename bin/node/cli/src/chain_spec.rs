@@ -229,6 +229,22 @@ pub fn authority_keys_from_seed(
 	)
 }
 
+/// Seeds of the well-known development accounts, endowed by default by [`testnet_genesis`].
+pub const DEV_ACCOUNT_SEEDS: [&str; 12] = [
+	"Alice",
+	"Bob",
+	"Charlie",
+	"Dave",
+	"Eve",
+	"Ferdie",
+	"Alice//stash",
+	"Bob//stash",
+	"Charlie//stash",
+	"Dave//stash",
+	"Eve//stash",
+	"Ferdie//stash",
+];
+
 /// Helper function to create GenesisConfig for testing
 pub fn testnet_genesis(
 	initial_authorities: Vec<(
@@ -244,20 +260,10 @@ pub fn testnet_genesis(
 	endowed_accounts: Option<Vec<AccountId>>,
 ) -> GenesisConfig {
 	let mut endowed_accounts: Vec<AccountId> = endowed_accounts.unwrap_or_else(|| {
-		vec![
-			get_account_id_from_seed::<sr25519::Public>("Alice"),
-			get_account_id_from_seed::<sr25519::Public>("Bob"),
-			get_account_id_from_seed::<sr25519::Public>("Charlie"),
-			get_account_id_from_seed::<sr25519::Public>("Dave"),
-			get_account_id_from_seed::<sr25519::Public>("Eve"),
-			get_account_id_from_seed::<sr25519::Public>("Ferdie"),
-			get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
-			get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
-			get_account_id_from_seed::<sr25519::Public>("Charlie//stash"),
-			get_account_id_from_seed::<sr25519::Public>("Dave//stash"),
-			get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
-			get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
-		]
+		DEV_ACCOUNT_SEEDS
+			.iter()
+			.map(|seed| get_account_id_from_seed::<sr25519::Public>(seed))
+			.collect()
 	});
 	// endow all authorities and nominators.
 	initial_authorities
@@ -366,6 +372,26 @@ pub fn testnet_genesis(
 	}
 }
 
+/// Set the balances of the development accounts in `balances`.
+///
+/// `balance` is called with the seed of every account of [`DEV_ACCOUNT_SEEDS`] and returns its
+/// balance, or `None` to leave the account as it is. The stash accounts of the authorities bond
+/// part of their balance at genesis, so they need to keep enough of it.
+pub fn fund_dev_accounts(balances: &mut BalancesConfig, balance: impl Fn(&str) -> Option<Balance>) {
+	for seed in DEV_ACCOUNT_SEEDS.iter() {
+		let amount = match balance(seed) {
+			Some(amount) => amount,
+			None => continue,
+		};
+		let account = get_account_id_from_seed::<sr25519::Public>(seed);
+
+		match balances.balances.iter_mut().find(|(who, _)| *who == account) {
+			Some((_, current)) => *current = amount,
+			None => balances.balances.push((account, amount)),
+		}
+	}
+}
+
 fn development_config_genesis() -> GenesisConfig {
 	testnet_genesis(
 		vec![authority_keys_from_seed("Alice")],
@@ -377,11 +403,23 @@ fn development_config_genesis() -> GenesisConfig {
 
 /// Development config (single validator Alice)
 pub fn development_config() -> ChainSpec {
+	development_config_with_balances(|_| None)
+}
+
+/// Development config (single validator Alice), with the development accounts funded by
+/// `balance`, see [`fund_dev_accounts`].
+pub fn development_config_with_balances(
+	balance: impl Fn(&str) -> Option<Balance> + Send + Sync + 'static,
+) -> ChainSpec {
 	ChainSpec::from_genesis(
 		"Development",
 		"dev",
 		ChainType::Development,
-		development_config_genesis,
+		move || {
+			let mut genesis = development_config_genesis();
+			fund_dev_accounts(&mut genesis.balances, &balance);
+			genesis
+		},
 		vec![],
 		None,
 		None,
@@ -401,11 +439,23 @@ fn local_testnet_genesis() -> GenesisConfig {
 
 /// Local testnet config (multivalidator Alice + Bob)
 pub fn local_testnet_config() -> ChainSpec {
+	local_testnet_config_with_balances(|_| None)
+}
+
+/// Local testnet config (multivalidator Alice + Bob), with the development accounts funded by
+/// `balance`, see [`fund_dev_accounts`].
+pub fn local_testnet_config_with_balances(
+	balance: impl Fn(&str) -> Option<Balance> + Send + Sync + 'static,
+) -> ChainSpec {
 	ChainSpec::from_genesis(
 		"Local Testnet",
 		"local_testnet",
 		ChainType::Local,
-		local_testnet_genesis,
+		move || {
+			let mut genesis = local_testnet_genesis();
+			fund_dev_accounts(&mut genesis.balances, &balance);
+			genesis
+		},
 		vec![],
 		None,
 		None,
@@ -417,10 +467,11 @@ pub fn local_testnet_config() -> ChainSpec {
 #[cfg(test)]
 pub(crate) mod tests {
 	use super::*;
+	#[cfg(feature = "cli")]
 	use crate::service::{new_full_base, new_light_base, NewFullBase};
-	use sc_service_test;
 	use sp_runtime::BuildStorage;
 
+	#[cfg(feature = "cli")]
 	fn local_testnet_genesis_instant_single() -> GenesisConfig {
 		testnet_genesis(
 			vec![authority_keys_from_seed("Alice")],
@@ -431,6 +482,7 @@ pub(crate) mod tests {
 	}
 
 	/// Local testnet config (single validator - Alice)
+	#[cfg(feature = "cli")]
 	pub fn integration_test_config_with_single_authority() -> ChainSpec {
 		ChainSpec::from_genesis(
 			"Integration Test",
@@ -446,6 +498,7 @@ pub(crate) mod tests {
 	}
 
 	/// Local testnet config (multivalidator Alice + Bob)
+	#[cfg(feature = "cli")]
 	pub fn integration_test_config_with_two_authorities() -> ChainSpec {
 		ChainSpec::from_genesis(
 			"Integration Test",
@@ -462,12 +515,13 @@ pub(crate) mod tests {
 
 	#[test]
 	#[ignore]
+	#[cfg(feature = "cli")]
 	fn test_connectivity() {
 		sc_service_test::connectivity(
 			integration_test_config_with_two_authorities(),
 			|config| {
 				let NewFullBase { task_manager, client, network, transaction_pool, .. } =
-					new_full_base(config, |_, _| ())?;
+					new_full_base(config, Default::default(), |_, _| ())?;
 				Ok(sc_service_test::TestNetComponents::new(
 					task_manager,
 					client,
@@ -476,7 +530,8 @@ pub(crate) mod tests {
 				))
 			},
 			|config| {
-				let (keep_alive, _, client, network, transaction_pool) = new_light_base(config)?;
+				let (keep_alive, _, client, network, transaction_pool) =
+					new_light_base(config, Default::default())?;
 				Ok(sc_service_test::TestNetComponents::new(
 					keep_alive,
 					client,
@@ -497,6 +552,27 @@ pub(crate) mod tests {
 		local_testnet_config().build_storage().unwrap();
 	}
 
+	#[test]
+	fn test_create_funded_development_chain_spec() {
+		development_config_with_balances(|seed| (seed == "Bob").then(|| 42 * DOLLARS))
+			.build_storage()
+			.unwrap();
+	}
+
+	#[test]
+	fn fund_dev_accounts_only_changes_the_given_accounts() {
+		let mut balances = development_config_genesis().balances;
+		let endowed = balances.balances.clone();
+		let bob = get_account_id_from_seed::<sr25519::Public>("Bob");
+
+		fund_dev_accounts(&mut balances, |seed| (seed == "Bob").then(|| 42));
+
+		assert_eq!(balances.balances.len(), endowed.len());
+		for ((who, balance), (_, endowment)) in balances.balances.iter().zip(&endowed) {
+			assert_eq!(*balance, if *who == bob { 42 } else { *endowment });
+		}
+	}
+
 	#[test]
 	fn test_staging_test_net_chain_spec() {
 		staging_testnet_config().build_storage().unwrap();
@@ -16,7 +16,9 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+pub use crate::arg_enums::{FinalityProfile, SelectChainMode, SpawnMode};
 use sc_cli::{KeySubcommand, RunCmd, SignCmd, VanityCmd, VerifyCmd};
+use std::path::PathBuf;
 use structopt::StructOpt;
 
 /// An overarching CLI command definition.
@@ -28,6 +30,615 @@ pub struct Cli {
 	#[allow(missing_docs)]
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub metrics_push: MetricsPushParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub metrics_dump: MetricsDumpParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub rpc_tls: RpcTlsParams,
+
+	/// Accept at most this many connections to the HTTP and WebSocket RPC servers together.
+	///
+	/// The connections beyond the limit are closed right away. The servers are then served
+	/// through a proxy, like with TLS, and the WebSocket connections stay bounded by
+	/// `--ws-max-connections` as well. With `--rpc-methods Auto`, only the safe methods are
+	/// served when one of the proxied addresses is exposed.
+	#[structopt(long, value_name = "COUNT")]
+	pub rpc_max_connections: Option<usize>,
+
+	/// Verify imported blocks against the BABE slot duration in effect at their parent block.
+	///
+	/// By default every block is checked against the current slot duration, which makes sync
+	/// halt on chains whose slot duration was changed at some point in their history.
+	#[structopt(long)]
+	pub historical_slot_duration: bool,
+
+	/// How far in the future, in seconds, the timestamp of an imported block may be before the
+	/// block is rejected.
+	///
+	/// The import waits until the timestamp of the block is valid up to this drift. The runtime
+	/// still rejects the blocks more than 30 seconds ahead of the local clock. Defaults to 60.
+	#[structopt(long, value_name = "SECONDS")]
+	pub max_timestamp_drift: Option<u64>,
+
+	/// How the BABE proposer task is spawned.
+	///
+	/// `Blocking` gives the proposer a thread of the blocking pool, `Async` runs it on the async
+	/// pool, which is only advisable if the proposer never blocks.
+	#[structopt(
+		long,
+		value_name = "MODE",
+		possible_values = &SpawnMode::variants(),
+		case_insensitive = true,
+		default_value = "Blocking"
+	)]
+	pub proposer_spawn_mode: SpawnMode,
+
+	/// How the GRANDPA voter task is spawned.
+	///
+	/// `Blocking` gives the voter a thread of the blocking pool, `Async` runs it on the async pool
+	/// instead, which saves a thread when the blocking pool is contended.
+	#[structopt(
+		long,
+		value_name = "MODE",
+		possible_values = &SpawnMode::variants(),
+		case_insensitive = true,
+		default_value = "Blocking"
+	)]
+	pub grandpa_voter_spawn_mode: SpawnMode,
+
+	/// The rule choosing the chain the node builds on.
+	///
+	/// `Longest` builds on the longest chain, `FinalizedDescendantLongest` on the longest chain
+	/// among the ones descending from the last finalized block, which avoids authoring on forks
+	/// that can never be finalized on networks prone to deep reorgs.
+	#[structopt(
+		long,
+		value_name = "MODE",
+		possible_values = &SelectChainMode::variants(),
+		case_insensitive = true,
+		default_value = "Longest"
+	)]
+	pub select_chain: SelectChainMode,
+
+	/// Number of worker threads of the async thread pool.
+	///
+	/// Default is the number of CPUs, which is also the maximum.
+	#[structopt(long, value_name = "COUNT")]
+	pub tokio_worker_threads: Option<usize>,
+
+	/// Maximum number of threads of the blocking thread pool, which runs the BABE proposer among
+	/// other blocking tasks.
+	#[structopt(long, alias = "proposer-blocking-threads", value_name = "COUNT")]
+	pub tokio_blocking_threads: Option<usize>,
+
+	/// Preset of the GRANDPA timings.
+	///
+	/// `Standard` suits public networks, `Fast` gossips votes more often and produces
+	/// justifications more frequently, for private networks with low latency between the nodes.
+	#[structopt(
+		long,
+		value_name = "PROFILE",
+		possible_values = &FinalityProfile::variants(),
+		case_insensitive = true,
+		default_value = "Standard"
+	)]
+	pub finality_profile: FinalityProfile,
+
+	/// Do not provide the uncles inherent, for runtimes that don't handle uncles.
+	///
+	/// Runtimes without the authorship pallet reject blocks containing the uncles inherent.
+	#[structopt(long)]
+	pub no_uncles: bool,
+
+	/// Provide the transaction storage proof inherent when authoring blocks.
+	///
+	/// Required by runtimes with the transaction storage pallet, which also need the indexed
+	/// transactions to be kept with `--storage-chain`.
+	#[structopt(long)]
+	pub transaction_storage: bool,
+
+	/// Post every finalized block to the given URL.
+	///
+	/// The number and hash of the block are sent as a JSON object with an HTTP POST. Failed
+	/// requests are retried a few times, then logged and dropped.
+	#[structopt(long, value_name = "URL")]
+	pub finalized_webhook_url: Option<String>,
+
+	/// Publish every finalized block to a message broker, as `nats://HOST:PORT/SUBJECT`.
+	///
+	/// The number, hash, parent hash and extrinsic count of the block are published as a JSON
+	/// object. The blocks are queued while the broker is unreachable, and dropped once the queue
+	/// is full. NATS needs a node built with the `nats-event-sink` feature.
+	#[structopt(long, value_name = "URL")]
+	pub event_sink: Option<String>,
+
+	/// Log the index, authorities and randomness of every BABE epoch the best block enters.
+	///
+	/// The authorities whose keys are in the local keystore are logged as well, which shows
+	/// whether the node can author blocks in the epoch.
+	#[structopt(long)]
+	pub log_babe_epochs: bool,
+
+	/// Only author BABE blocks with the given public key, as SS58 or hex.
+	///
+	/// By default the node authors with every BABE key of the keystore that is an authority of
+	/// the epoch. The node fails to start if the key isn't in the keystore.
+	#[structopt(long, value_name = "PUBLIC_KEY")]
+	pub babe_authoring_key: Option<String>,
+
+	/// Restart the authority discovery, telemetry and offchain workers up to this many times in a
+	/// row, with an exponential backoff, when they fail. The node keeps running without the
+	/// workers that failed more often.
+	#[structopt(long, value_name = "COUNT")]
+	pub auxiliary_task_restarts: Option<u32>,
+
+	/// Don't author blocks that would only contain inherents.
+	///
+	/// The slots are skipped while there are no ready transactions, except for the first block
+	/// of every epoch, which is needed for the chain to make progress.
+	#[structopt(long)]
+	pub no_empty_blocks: bool,
+
+	/// Only author blocks during the given daily time windows, like `08:00-20:00`.
+	///
+	/// Several windows are separated by commas, and a window ending before it starts spans
+	/// midnight. Outside of the windows the node keeps syncing and serving the RPC. The times are
+	/// in the offset of `--author-schedule-utc-offset`.
+	#[structopt(long, value_name = "WINDOWS")]
+	pub author_schedule: Option<String>,
+
+	/// The offset from UTC of the `--author-schedule` times, as `+HH:MM` or `-HH:MM`.
+	///
+	/// The offset is fixed, it doesn't follow the daylight saving time changes.
+	#[structopt(
+		long,
+		value_name = "OFFSET",
+		default_value = "+00:00",
+		requires = "author-schedule"
+	)]
+	pub author_schedule_utc_offset: String,
+
+	/// Log the decoded digest items of every imported header, at debug level.
+	///
+	/// The items are logged under the `header-digests` target, use
+	/// `--log header-digests=debug` to see them.
+	#[structopt(long)]
+	pub log_header_digests: bool,
+
+	/// Log an error whenever a reorg retracts more than this many blocks of the best chain.
+	#[structopt(long, value_name = "BLOCKS")]
+	pub max_reorg_depth: Option<u32>,
+
+	/// Shut down the node after a reorg deeper than `--max-reorg-depth`.
+	#[structopt(long, requires = "max-reorg-depth")]
+	pub halt_on_deep_reorg: bool,
+
+	/// Limit the size of the GRANDPA warp sync proofs served to other nodes, in bytes.
+	///
+	/// Larger proofs are truncated to the authority set changes that fit, the requesting node
+	/// fetches the remaining ones with further requests. The size is capped to 8 MiB.
+	#[structopt(long, value_name = "BYTES")]
+	pub max_warp_proof_size: Option<usize>,
+
+	/// Wait a random duration of up to the given milliseconds before starting to author BABE
+	/// blocks.
+	///
+	/// Spreads the first slot claims of validators that are restarted together.
+	#[structopt(long, value_name = "MS")]
+	pub author_startup_jitter_ms: Option<u64>,
+
+	/// Prefix the Prometheus metrics with the given namespace instead of `substrate`.
+	///
+	/// Applies to the metrics of the node as well as to the ones of the client and the service,
+	/// which keeps them apart from the metrics of other nodes scraped by the same Prometheus.
+	#[structopt(long, value_name = "NAMESPACE")]
+	pub metrics_namespace: Option<String>,
+
+	/// Re-execute every imported block and check that the resulting state root matches the one
+	/// of its header.
+	///
+	/// The import already checks the state root, the second execution reads the state of the
+	/// parent block back from the database and detects its corruption. Mismatches are logged
+	/// and counted by the `state_root_mismatches_total` metric. This roughly doubles the time
+	/// spent executing blocks. The blocks of the initial sync aren't checked, and the oldest
+	/// blocks are skipped when the check falls too far behind the import.
+	#[structopt(long)]
+	pub verify_state_root: bool,
+
+	/// Shut down the node after a state root mismatch found by `--verify-state-root`.
+	#[structopt(long, requires = "verify-state-root")]
+	pub halt_on_state_root_mismatch: bool,
+
+	/// Don't compare the native runtime with the on-chain runtime of the best block.
+	///
+	/// By default, they are compared at startup and on every on-chain runtime upgrade. A warning
+	/// is logged when the native runtime isn't used anymore, and an error when it keeps the node
+	/// from authoring blocks. The `native_runtime_divergence` metric reports the same.
+	#[structopt(long)]
+	pub no_runtime_version_check: bool,
+
+	/// Disconnect and refuse the peers whose reputation falls under the given value.
+	///
+	/// The reputation of a peer decreases with every misbehaviour it is reported for, a value
+	/// closer to zero than the default of -1760936552 bans misbehaving peers faster.
+	#[structopt(long, value_name = "REPUTATION", allow_hyphen_values = true)]
+	pub reputation_ban_threshold: Option<i32>,
+
+	/// The number of seconds it takes for the reputation of a peer to move halfway back to
+	/// zero, by default about 34 seconds.
+	///
+	/// A longer half-life keeps the peers that misbehaved banned for longer.
+	#[structopt(long, value_name = "SECONDS")]
+	pub reputation_half_life: Option<u32>,
+
+	/// Initialize a fresh database from the chain spec downloaded from the given URL.
+	///
+	/// The downloaded chain spec, usually with a raw genesis state, must give the genesis block
+	/// of `--genesis-hash`. At most 1 GiB is downloaded. It is ignored when the database isn't
+	/// empty.
+	#[structopt(long, value_name = "URL", requires = "genesis-hash")]
+	pub genesis_snapshot_url: Option<String>,
+
+	/// The hash of the genesis block the `--genesis-snapshot-url` snapshot must give.
+	#[structopt(long, value_name = "HASH", requires = "genesis-snapshot-url")]
+	pub genesis_hash: Option<String>,
+
+	/// Override the genesis storage value at a key, as `HEX_KEY=HEX_VALUE`.
+	///
+	/// Can be repeated. The overrides are only applied when initializing an empty database, and
+	/// give a different genesis block than the one of the chain spec, which the peers running
+	/// the chain spec don't follow.
+	#[structopt(long = "genesis-override", value_name = "KEY=VALUE")]
+	pub genesis_overrides: Vec<String>,
+
+	/// Run as a transaction relay, accepting transactions over RPC and gossiping them.
+	///
+	/// Needs `--light`, the node keeps no state and validates the transactions against the state
+	/// of its full peers. Only the `author` and `system` RPC methods are served, and neither
+	/// the GRANDPA observer nor the offchain workers run. Without the observer the relay only
+	/// finalizes the blocks ending an authority set, which come with a justification.
+	#[structopt(long, requires = "light")]
+	pub tx_relay: bool,
+
+	/// Include at most this many transactions in the authored blocks.
+	///
+	/// The inherents are always included and don't count towards the limit.
+	#[structopt(long, value_name = "COUNT")]
+	pub max_block_txs: Option<usize>,
+
+	/// Keep trying the next transactions when the authored block seems full, until the given
+	/// percentage of the time left to build the block elapsed.
+	///
+	/// By default the proposer stops after a few transactions didn't fit. A higher value fills the
+	/// blocks better under load, at the cost of slot time.
+	#[structopt(long, value_name = "PERCENT")]
+	pub proposer_soft_deadline: Option<u8>,
+
+	/// Reject the blocks authored by these BABE public keys, as SS58 or hex.
+	///
+	/// The author is identified from the pre-runtime digest of the blocks, once they passed the
+	/// BABE verification. Meant to contain a compromised validator during an incident.
+	#[structopt(long, value_name = "PUBLIC_KEY", use_delimiter = true)]
+	pub banned_authorities: Vec<String>,
+
+	/// Wait this many seconds before starting the GRANDPA voter.
+	///
+	/// Lets the finality lag the first blocks of a freshly launched network. By default the voter
+	/// starts right away.
+	#[structopt(long, value_name = "SECONDS", conflicts_with = "grandpa-start-block")]
+	pub grandpa_start_delay: Option<u64>,
+
+	/// Start the GRANDPA voter once the best block reaches the given number.
+	#[structopt(long, value_name = "NUMBER")]
+	pub grandpa_start_block: Option<u32>,
+
+	/// Run a single node without any networking, for local development and testing.
+	///
+	/// The node doesn't listen for, look for nor connect to peers, and authors blocks on its own.
+	/// The RPC is served as usual.
+	#[structopt(long)]
+	pub isolated: bool,
+
+	/// Run the node as a finality gateway for bridges and light clients.
+	///
+	/// The node syncs and serves the warp sync and finality proofs, over the network and the
+	/// GRANDPA RPC, but never authors blocks, doesn't gossip transactions, runs no offchain
+	/// worker, and only serves the safe methods of the chain, state, system, GRANDPA and sync
+	/// state RPC modules. It can't be combined with `--validator` nor `--rpc-methods Unsafe`.
+	#[structopt(long, conflicts_with_all = &["isolated", "validator"])]
+	pub finality_gateway: bool,
+
+	/// Only connect to the peers whose ids are listed in the given file, one per line.
+	///
+	/// This also applies to the reserved nodes and the boot nodes. Empty lines and lines
+	/// starting with `#` are ignored.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	pub allowed_peer_ids: Option<PathBuf>,
+
+	/// Never connect to the peers whose ids are listed in the given file, one per line.
+	///
+	/// The denied peers are also refused when listed by `--allowed-peer-ids` or as reserved
+	/// nodes. Empty lines and lines starting with `#` are ignored.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	pub denied_peer_ids: Option<PathBuf>,
+
+	/// Periodically write the status of the node to the given file, as JSON.
+	///
+	/// The file holds the best and finalized blocks, the number of connected peers and whether
+	/// the node is syncing, and is replaced on every write.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	pub status_file: Option<PathBuf>,
+
+	/// Interval in seconds between two writes of the status file.
+	#[structopt(long, value_name = "SECONDS", default_value = "5")]
+	pub status_file_interval: u64,
+
+	/// Import the SCALE encoded block in the given file as the first block of the chain.
+	///
+	/// The block must be the child of the genesis block, and is imported before the node
+	/// connects to the network. It is ignored once the database has it.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	pub import_first_block: Option<PathBuf>,
+
+	/// Refuse to start, and stop the node, when the filesystem of the database has less than
+	/// the given free space, in MiB.
+	///
+	/// The free space is checked every 30 seconds, a database running out of space can be left
+	/// corrupted.
+	#[structopt(long, value_name = "MiB")]
+	pub min_free_disk: Option<u64>,
+
+	/// Resolve the secrets of the node by running the given command, usually a secrets manager.
+	///
+	/// The command is run at startup with the name of a secret as its last argument, and prints
+	/// the secret on its standard output: `keystore-password` unless a password is given,
+	/// and `telemetry-url` as `URL VERBOSITY`, replacing the other telemetry endpoints. An empty
+	/// output leaves the secret unset, and the node doesn't start if the command fails.
+	#[structopt(long, value_name = "COMMAND")]
+	pub secret_command: Option<String>,
+
+	/// Loader of the chain specs passed by path, see [`ChainSpecLoader`].
+	#[structopt(skip)]
+	pub chain_spec_loader: Option<ChainSpecLoader>,
+}
+
+/// Loads the chain spec at the given path, for chain specs in other formats than JSON.
+pub type ChainSpecLoader = fn(PathBuf) -> Result<Box<dyn sc_cli::ChainSpec>, String>;
+
+/// Parameters used to push the Prometheus metrics to a Pushgateway.
+#[derive(Debug, StructOpt)]
+pub struct MetricsPushParams {
+	/// Periodically push the Prometheus metrics to the given Pushgateway URL.
+	///
+	/// The encoded metrics are sent with an HTTP POST, e.g. to
+	/// `http://localhost:9091/metrics/job/substrate`. Nothing is pushed when the Prometheus
+	/// exporter is disabled.
+	#[structopt(long, value_name = "URL")]
+	pub metrics_push_url: Option<String>,
+
+	/// Interval in seconds between two metrics pushes.
+	#[structopt(long, value_name = "SECONDS", default_value = "15")]
+	pub metrics_push_interval: u64,
+}
+
+/// Parameters used to write the Prometheus metrics to a local file.
+#[derive(Debug, StructOpt)]
+pub struct MetricsDumpParams {
+	/// Periodically write the Prometheus metrics to the given file.
+	///
+	/// The file is replaced with the metrics in the Prometheus text format on every write.
+	/// The metrics are still collected when the Prometheus exporter is disabled.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	pub metrics_dump_file: Option<PathBuf>,
+
+	/// Interval in seconds between two writes of the metrics file.
+	#[structopt(long, value_name = "SECONDS", default_value = "15")]
+	pub metrics_dump_interval: u64,
+}
+
+/// Parameters used to serve the RPC over TLS.
+#[derive(Debug, StructOpt)]
+pub struct RpcTlsParams {
+	/// Serve the HTTP and WebSocket RPC over TLS, with the PEM encoded certificate chain at the
+	/// given path.
+	///
+	/// TLS is terminated by the node on the `--rpc-port` and `--ws-port` addresses, the RPC
+	/// servers themselves listen on a free port of the loopback interface.
+	#[structopt(long, value_name = "PATH", parse(from_os_str), requires = "rpc-tls-key")]
+	pub rpc_tls_cert: Option<PathBuf>,
+
+	/// The PEM encoded private key of the RPC TLS certificate, in PKCS#8 or RSA format.
+	#[structopt(long, value_name = "PATH", parse(from_os_str), requires = "rpc-tls-cert")]
+	pub rpc_tls_key: Option<PathBuf>,
+}
+
+/// The `print-config` command.
+///
+/// Accepts the same arguments as running the node, so the printed configuration is the one the
+/// node would start with.
+#[derive(Debug, StructOpt)]
+pub struct PrintConfigCmd {
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub run: RunCmd,
+}
+
+/// The `tail` command.
+///
+/// Accepts the same arguments as running the node, which only syncs while the finalized blocks
+/// are printed.
+#[derive(Debug, StructOpt)]
+pub struct TailCmd {
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub run: RunCmd,
+}
+
+/// The `throughput` command.
+///
+/// Accepts the same arguments as running the node, which runs as usual while its throughput is
+/// reported.
+#[derive(Debug, StructOpt)]
+pub struct ThroughputCmd {
+	/// Seconds between two reports of the throughput.
+	#[structopt(long, value_name = "SECONDS", default_value = "60")]
+	pub report_interval: u64,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub run: RunCmd,
+}
+
+/// The `gen-systemd` command.
+///
+/// The node arguments are given after `--`, and are resolved like when running the node.
+#[derive(Debug, StructOpt)]
+pub struct GenSystemdCmd {
+	/// Write the unit to the given file instead of stdout.
+	#[structopt(long, short = "o", value_name = "PATH", parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	/// The user running the node, by default the current user.
+	#[structopt(long, value_name = "USER")]
+	pub user: Option<String>,
+
+	/// The arguments the node is started with.
+	#[structopt(last = true, value_name = "NODE ARGS")]
+	pub args: Vec<String>,
+}
+
+/// The `verify-justification` command used to check a GRANDPA justification of a block.
+///
+/// The justification is verified against the authority set that finalizes the block, as known
+/// by the local database.
+#[derive(Debug, StructOpt)]
+pub struct VerifyJustificationCmd {
+	/// Hash or number of the block the justification finalizes.
+	#[structopt(value_name = "HASH or NUMBER")]
+	pub block: sc_cli::BlockNumberOrHash,
+
+	/// The SCALE encoded justification, as hex prefixed with `0x` or as the path of a file
+	/// containing it.
+	#[structopt(value_name = "HEX or PATH")]
+	pub justification: String,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: sc_cli::ImportParams,
+}
+
+/// The `keystore-export` command used to move the keys of a validator to another machine.
+///
+/// The keys are encrypted with a passphrase, read from `--passphrase-file` or prompted for.
+#[derive(Debug, StructOpt)]
+pub struct KeystoreExportCmd {
+	/// The file to write the keys to, which must not exist yet.
+	#[structopt(value_name = "PATH", parse(from_os_str))]
+	pub output: PathBuf,
+
+	/// Read the passphrase of the bundle from the given file instead of prompting for it.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	pub passphrase_file: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub keystore_params: sc_cli::KeystoreParams,
+}
+
+/// The `keystore-import` command used to restore the keys written by `keystore-export`.
+///
+/// The keys already in the keystore are kept as they are.
+#[derive(Debug, StructOpt)]
+pub struct KeystoreImportCmd {
+	/// The file written by `keystore-export`.
+	#[structopt(value_name = "PATH", parse(from_os_str))]
+	pub input: PathBuf,
+
+	/// Read the passphrase of the bundle from the given file instead of prompting for it.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	pub passphrase_file: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub keystore_params: sc_cli::KeystoreParams,
+}
+
+/// The `offchain-replay` command used to run the offchain workers of a block without sending
+/// anything to the network.
+///
+/// The HTTP requests, offchain storage accesses and transactions of the offchain workers are
+/// recorded and printed instead.
+#[derive(Debug, StructOpt)]
+pub struct OffchainReplayCmd {
+	/// Hash or number of the block to run the offchain workers of.
+	#[structopt(value_name = "HASH or NUMBER")]
+	pub block: sc_cli::BlockNumberOrHash,
+
+	/// Run the offchain workers as on a validator.
+	#[structopt(long)]
+	pub validator: bool,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: sc_cli::ImportParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub keystore_params: sc_cli::KeystoreParams,
+}
+
+/// The `weigh-extrinsic` command used to spot-check the weight of an extrinsic.
+///
+/// The extrinsic is applied in a new block on top of the given one, without the inherents, so the
+/// timestamp and the author of that block aren't set.
+#[derive(Debug, StructOpt)]
+pub struct WeighExtrinsicCmd {
+	/// The SCALE encoded extrinsic, as hex.
+	#[structopt(value_name = "HEX")]
+	pub extrinsic: String,
+
+	/// Hash or number of the block to apply the extrinsic on top of. Default is the best block.
+	#[structopt(long, value_name = "HASH or NUMBER")]
+	pub at: Option<sc_cli::BlockNumberOrHash>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: sc_cli::ImportParams,
+}
+
+/// The `decode-spec` command used to decode the genesis storage of a chain specification.
+#[derive(Debug, StructOpt)]
+pub struct DecodeSpecCmd {
+	/// The chain spec to decode, as passed to `--chain`.
+	#[structopt(value_name = "CHAIN")]
+	pub chain: String,
 }
 
 /// Possible subcommands of the main binary.
@@ -67,21 +678,76 @@ pub enum Subcommand {
 	/// Build a chain specification.
 	BuildSpec(sc_cli::BuildSpecCmd),
 
+	/// Validate a chain specification without starting the node.
+	CheckSpec(sc_cli::CheckSpecCmd),
+
+	/// Print the peer ID of the node key, generating the node key file if it doesn't exist.
+	PeerId(sc_cli::PeerIdCmd),
+
+	/// Compare the genesis storage of two chain specifications.
+	DiffSpec(sc_cli::DiffSpecCmd),
+
+	/// Decode the genesis storage of a chain spec as JSON, with the types of the runtime.
+	DecodeSpec(DecodeSpecCmd),
+
+	/// Print the effective node configuration as JSON, without starting the node.
+	PrintConfig(PrintConfigCmd),
+
+	/// Generate a systemd service unit running the node with the given arguments.
+	GenSystemd(GenSystemdCmd),
+
 	/// Validate blocks.
 	CheckBlock(sc_cli::CheckBlockCmd),
 
+	/// Call a runtime API function at a block and print the SCALE encoded result.
+	ApiCall(sc_cli::ApiCallCmd),
+
+	/// Verify a GRANDPA justification against the authority set that finalizes its block.
+	VerifyJustification(VerifyJustificationCmd),
+
+	/// Run the offchain workers of a block in a mocked environment, printing their interactions.
+	OffchainReplay(OffchainReplayCmd),
+
+	/// Apply an extrinsic on top of a block and print its weight, without storing anything.
+	WeighExtrinsic(WeighExtrinsicCmd),
+
+	/// Export the keys of the keystore to a file encrypted with a passphrase.
+	KeystoreExport(KeystoreExportCmd),
+
+	/// Import the keys of a file written by `keystore-export` into the keystore.
+	KeystoreImport(KeystoreImportCmd),
+
 	/// Export blocks.
 	ExportBlocks(sc_cli::ExportBlocksCmd),
 
+	/// Stream the finalized blocks to stdout, as JSON or SCALE.
+	ExportFinalized(sc_cli::ExportFinalizedCmd),
+
+	/// Sync the chain and print the number and hash of every finalized block, until interrupted.
+	Tail(TailCmd),
+
+	/// Run the node, periodically reporting the extrinsics per second, the block fullness and
+	/// the depth of the transaction pool.
+	Throughput(ThroughputCmd),
+
 	/// Export the state of a given block into a chain spec.
 	ExportState(sc_cli::ExportStateCmd),
 
 	/// Import blocks.
 	ImportBlocks(sc_cli::ImportBlocksCmd),
 
+	/// Re-execute imported blocks with a new wasm runtime, reporting the blocks that diverge.
+	MigrationTest(sc_cli::MigrationTestCmd),
+
 	/// Remove the whole chain.
 	PurgeChain(sc_cli::PurgeChainCmd),
 
+	/// Compact the database, without starting the node.
+	CompactDb(sc_cli::CompactDbCmd),
+
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
+
+	/// List the leaves of the chain, and remove the stale forks.
+	PruneForks(sc_cli::PruneForksCmd),
 }
@@ -0,0 +1,149 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Detection of a skewed system clock.
+//!
+//! The blocks are authored at the start of their BABE slot, so the blocks announced by the peers
+//! at the tip of the chain arrive within a slot after it started. Blocks consistently arriving
+//! before their slot, or long after it, tell that the local clock is behind or ahead of the ones
+//! of the peers, which makes the node reject valid blocks and miss its own slots.
+
+use futures::prelude::*;
+use node_primitives::Block;
+use sc_client_api::BlockchainEvents;
+use sc_consensus_babe::find_pre_digest;
+use sc_network::NetworkService;
+use sp_consensus::BlockOrigin;
+use sp_runtime::traits::Block as BlockT;
+use std::{
+	sync::Arc,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const LOG_TARGET: &str = "clock-skew";
+
+/// Number of blocks announced by the peers the skew is estimated from.
+const SAMPLES: usize = 8;
+
+/// Offset on top of the propagation of the blocks that isn't reported as a skew.
+const TOLERANCE: Duration = Duration::from_secs(1);
+
+/// A skew of the local clock relative to the peers.
+#[derive(Debug, PartialEq)]
+enum Skew {
+	/// The local clock is behind by about this duration.
+	Behind(Duration),
+	/// The local clock is ahead by at least this duration.
+	Ahead(Duration),
+}
+
+/// Estimate the skew of the local clock from the first blocks announced by the peers once the
+/// node is synced, and log a warning if it's skewed.
+///
+/// The future returns once the skew is estimated.
+pub async fn run<C>(
+	client: Arc<C>,
+	network: Arc<NetworkService<Block, <Block as BlockT>::Hash>>,
+	slot_duration: Duration,
+) where
+	C: BlockchainEvents<Block>,
+{
+	let mut imports = client.import_notification_stream();
+	let mut offsets = Vec::with_capacity(SAMPLES);
+	while let Some(notification) = imports.next().await {
+		if notification.origin != BlockOrigin::NetworkBroadcast ||
+			!notification.is_new_best ||
+			network.is_major_syncing()
+		{
+			continue
+		}
+
+		let slot = match find_pre_digest::<Block>(&notification.header) {
+			Ok(pre_digest) => pre_digest.slot(),
+			Err(_) => continue,
+		};
+		let slot_start = *slot as i128 * slot_duration.as_millis() as i128;
+		let now = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.expect("Current time is always after unix epoch; qed")
+			.as_millis() as i128;
+		offsets.push(slot_start - now);
+
+		if offsets.len() == SAMPLES {
+			break
+		}
+	}
+
+	match skew(offsets, slot_duration) {
+		Some(Skew::Behind(skew)) => log::warn!(
+			target: LOG_TARGET,
+			"⏰ The system clock seems to be {:?} behind the peers, check that it is synchronized",
+			skew,
+		),
+		Some(Skew::Ahead(skew)) => log::warn!(
+			target: LOG_TARGET,
+			"⏰ The system clock seems to be at least {:?} ahead of the peers, check that it is \
+			 synchronized",
+			skew,
+		),
+		None => log::debug!(target: LOG_TARGET, "The system clock is in sync with the peers"),
+	}
+}
+
+/// The skew given by the median of the `offsets` in milliseconds between the start of the slot of
+/// the announced blocks and their import.
+fn skew(mut offsets: Vec<i128>, slot_duration: Duration) -> Option<Skew> {
+	if offsets.is_empty() {
+		return None
+	}
+	offsets.sort_unstable();
+	let median = offsets[offsets.len() / 2];
+
+	let tolerance = TOLERANCE.as_millis() as i128;
+	// A block can't be imported before its slot started, unless the local clock is behind.
+	if median > tolerance {
+		return Some(Skew::Behind(Duration::from_millis(median as u64)))
+	}
+	// Nor long after the end of its slot, unless it's ahead.
+	let late = -median - slot_duration.as_millis() as i128;
+	if late > tolerance {
+		return Some(Skew::Ahead(Duration::from_millis(late as u64)))
+	}
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn estimates_the_skew_from_the_median() {
+		let slot_duration = Duration::from_secs(3);
+
+		assert_eq!(skew(vec![], slot_duration), None);
+		assert_eq!(skew(vec![-200, -300, -2_500, 400], slot_duration), None);
+		assert_eq!(
+			skew(vec![4_800, 5_000, -10_000, 5_100], slot_duration),
+			Some(Skew::Behind(Duration::from_millis(5_000))),
+		);
+		assert_eq!(
+			skew(vec![-9_000, -9_500, -9_200], slot_duration),
+			Some(Skew::Ahead(Duration::from_millis(6_200))),
+		);
+	}
+}
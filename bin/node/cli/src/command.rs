@@ -16,11 +16,17 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{chain_spec, service, service::new_partial, Cli, Subcommand};
+use crate::{chain_spec, service, service::new_partial, ChainSpecLoader, Cli, Subcommand};
 use node_executor::ExecutorDispatch;
 use node_runtime::{Block, RuntimeApi};
-use sc_cli::{ChainSpec, Result, Role, RuntimeVersion, SubstrateCli};
+use sc_cli::{ChainSpec, LoggerBuilder, Result, Role, RuntimeVersion, SubstrateCli};
+use sc_executor::NativeElseWasmExecutor;
+use sc_network::{
+	config::{PeerFilter, ReputationConfig},
+	PeerId,
+};
 use sc_service::PartialComponents;
+use std::{collections::HashSet, sync::Arc, time::Duration};
 
 impl SubstrateCli for Cli {
 	fn impl_name() -> String {
@@ -58,8 +64,13 @@ impl SubstrateCli for Cli {
 			"local" => Box::new(chain_spec::local_testnet_config()),
 			"fir" | "flaming-fir" => Box::new(chain_spec::flaming_fir_config()?),
 			"staging" => Box::new(chain_spec::staging_testnet_config()),
-			path =>
-				Box::new(chain_spec::ChainSpec::from_json_file(std::path::PathBuf::from(path))?),
+			path => {
+				let path = std::path::PathBuf::from(path);
+				match self.chain_spec_loader {
+					Some(loader) => loader(path)?,
+					None => Box::new(chain_spec::ChainSpec::from_json_file(path)?),
+				}
+			},
 		};
 		Ok(spec)
 	}
@@ -69,29 +80,367 @@ impl SubstrateCli for Cli {
 	}
 }
 
+impl Cli {
+	/// Collect the node-specific options that aren't part of the service configuration.
+	fn node_options(&self) -> Result<service::NodeOptions> {
+		let metrics_push = self
+			.metrics_push
+			.metrics_push_url
+			.as_ref()
+			.map(|url| {
+				let interval = Duration::from_secs(self.metrics_push.metrics_push_interval);
+				crate::metrics_push::Config::new(url, interval)
+			})
+			.transpose()
+			.map_err(sc_cli::Error::Input)?;
+
+		let metrics_dump = self
+			.metrics_dump
+			.metrics_dump_file
+			.clone()
+			.map(|path| {
+				let interval = Duration::from_secs(self.metrics_dump.metrics_dump_interval);
+				crate::file_output::Config::new("metrics dump file", path, interval)
+			})
+			.transpose()
+			.map_err(sc_cli::Error::Input)?;
+
+		let rpc_tls = match (&self.rpc_tls.rpc_tls_cert, &self.rpc_tls.rpc_tls_key) {
+			(Some(cert), Some(key)) =>
+				Some(crate::rpc_proxy::TlsConfig::new(cert, key).map_err(sc_cli::Error::Input)?),
+			_ => None,
+		};
+
+		let finalized_webhook = self
+			.finalized_webhook_url
+			.as_deref()
+			.map(crate::finalized_webhook::Config::new)
+			.transpose()
+			.map_err(sc_cli::Error::Input)?;
+
+		let event_sink = self
+			.event_sink
+			.as_deref()
+			.map(crate::event_sink::Config::new)
+			.transpose()
+			.map_err(sc_cli::Error::Input)?;
+
+		let genesis_snapshot = match (&self.genesis_snapshot_url, &self.genesis_hash) {
+			(Some(url), Some(hash)) => Some(
+				crate::genesis_snapshot::Config::new(url, hash).map_err(sc_cli::Error::Input)?,
+			),
+			_ => None,
+		};
+
+		let genesis_overrides = self
+			.genesis_overrides
+			.iter()
+			.map(|genesis_override| genesis_override.parse())
+			.collect::<std::result::Result<_, _>>()
+			.map_err(sc_cli::Error::Input)?;
+
+		let author_schedule = self
+			.author_schedule
+			.as_deref()
+			.map(|spec| {
+				crate::author_schedule::AuthorSchedule::new(spec, &self.author_schedule_utc_offset)
+			})
+			.transpose()
+			.map_err(sc_cli::Error::Input)?;
+
+		let babe_authoring_key = self
+			.babe_authoring_key
+			.as_deref()
+			.map(|key| parse_babe_key(key, "BABE authoring key"))
+			.transpose()
+			.map_err(sc_cli::Error::Input)?;
+
+		let proposer_soft_deadline = self
+			.proposer_soft_deadline
+			.map(|percent| {
+				if percent > 100 {
+					return Err(format!(
+						"Invalid proposer soft deadline {}%, expected at most 100%",
+						percent
+					))
+				}
+				Ok(sp_runtime::Percent::from_percent(percent))
+			})
+			.transpose()
+			.map_err(sc_cli::Error::Input)?;
+
+		let banned_authorities = self
+			.banned_authorities
+			.iter()
+			.map(|key| parse_babe_key(key, "banned authority"))
+			.collect::<std::result::Result<_, _>>()
+			.map_err(sc_cli::Error::Input)?;
+
+		let metrics_namespace = self
+			.metrics_namespace
+			.clone()
+			.map(|namespace| {
+				let mut chars = namespace.chars();
+				let valid = chars.next().map_or(false, |c| c.is_ascii_alphabetic() || c == '_') &&
+					chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+				if valid {
+					Ok(namespace)
+				} else {
+					Err(format!("Invalid metrics namespace `{}`", namespace))
+				}
+			})
+			.transpose()
+			.map_err(sc_cli::Error::Input)?;
+
+		let reputation = match (self.reputation_ban_threshold, self.reputation_half_life) {
+			(None, None) => None,
+			(_, Some(0)) => return Err("--reputation-half-life must be at least 1".into()),
+			(Some(threshold), _) if threshold >= 0 =>
+				return Err("--reputation-ban-threshold must be negative".into()),
+			(threshold, half_life) => {
+				let default = ReputationConfig::default();
+				Some(ReputationConfig {
+					banned_threshold: threshold.unwrap_or(default.banned_threshold),
+					decay_divisor: half_life.map_or(default.decay_divisor, decay_divisor),
+				})
+			},
+		};
+
+		let peer_filter = PeerFilter {
+			allowed: self
+				.allowed_peer_ids
+				.as_deref()
+				.map(read_peer_ids)
+				.transpose()
+				.map_err(sc_cli::Error::Input)?,
+			denied: self
+				.denied_peer_ids
+				.as_deref()
+				.map(read_peer_ids)
+				.transpose()
+				.map_err(sc_cli::Error::Input)?
+				.unwrap_or_default(),
+		};
+
+		let status_file = self
+			.status_file
+			.clone()
+			.map(|path| {
+				let interval = Duration::from_secs(self.status_file_interval);
+				crate::file_output::Config::new("status file", path, interval)
+			})
+			.transpose()
+			.map_err(sc_cli::Error::Input)?;
+
+		let first_block = self
+			.import_first_block
+			.as_deref()
+			.map(crate::first_block::read)
+			.transpose()
+			.map_err(sc_cli::Error::Input)?;
+
+		if self.rpc_max_connections == Some(0) {
+			return Err("--rpc-max-connections must be at least 1".into())
+		}
+		if self.min_free_disk == Some(0) {
+			return Err("--min-free-disk must be at least 1".into())
+		}
+		// The unsafe methods can't conflict with the flag, `--rpc-methods` always has a value.
+		if self.finality_gateway && self.run.rpc_methods == sc_cli::RpcMethods::Unsafe {
+			return Err("--finality-gateway only serves the safe RPC methods".into())
+		}
+
+		Ok(service::NodeOptions {
+			metrics_push,
+			metrics_dump,
+			rpc_tls,
+			rpc_max_connections: self.rpc_max_connections,
+			historical_slot_duration: self.historical_slot_duration,
+			max_timestamp_drift: self.max_timestamp_drift.map(Duration::from_secs),
+			proposer_spawn_mode: self.proposer_spawn_mode,
+			grandpa_voter_spawn_mode: self.grandpa_voter_spawn_mode,
+			select_chain: self.select_chain,
+			justification_import: None,
+			block_import_wrapper: None,
+			transaction_ordering: None,
+			tx_relay: self.tx_relay,
+			import_inherent_data_providers: None,
+			finality_profile: self.finality_profile,
+			disable_uncles: self.no_uncles,
+			transaction_storage_proof: self.transaction_storage,
+			finalized_webhook,
+			event_sink,
+			log_babe_epochs: self.log_babe_epochs,
+			epoch_change_hook: None,
+			babe_authoring_key,
+			auxiliary_task_restarts: self.auxiliary_task_restarts,
+			no_empty_blocks: self.no_empty_blocks,
+			author_schedule,
+			log_header_digests: self.log_header_digests,
+			max_reorg_depth: self.max_reorg_depth,
+			halt_on_deep_reorg: self.halt_on_deep_reorg,
+			max_warp_proof_size: self.max_warp_proof_size,
+			author_startup_jitter: self.author_startup_jitter_ms.map(Duration::from_millis),
+			metrics_namespace,
+			verify_state_root: self.verify_state_root,
+			halt_on_state_root_mismatch: self.halt_on_state_root_mismatch,
+			check_runtime_version: !self.no_runtime_version_check,
+			reputation,
+			admission_filter: None,
+			genesis_snapshot,
+			genesis_overrides,
+			max_block_transactions: self.max_block_txs,
+			proposer_soft_deadline,
+			banned_authorities,
+			grandpa_start: match (self.grandpa_start_delay, self.grandpa_start_block) {
+				(Some(delay), _) => Some(service::GrandpaStart::Delay(Duration::from_secs(delay))),
+				(None, Some(number)) => Some(service::GrandpaStart::Block(number)),
+				(None, None) => None,
+			},
+			isolated: self.isolated,
+			finality_gateway: self.finality_gateway,
+			peer_filter,
+			status_file,
+			first_block,
+			min_free_disk: self
+				.min_free_disk
+				.map(|mib| crate::disk_space::MinFreeDisk(mib.saturating_mul(1024 * 1024))),
+		})
+	}
+
+	/// Build the tokio runtime the node runs on.
+	fn tokio_runtime(&self) -> Result<tokio::runtime::Runtime> {
+		let mut builder = sc_cli::runtime_builder();
+
+		if let Some(threads) = self.tokio_worker_threads {
+			let cpus = num_cpus::get();
+			if threads == 0 || threads > cpus {
+				return Err(format!(
+					"--tokio-worker-threads must be between 1 and the number of CPUs ({})",
+					cpus,
+				)
+				.into())
+			}
+			builder.worker_threads(threads);
+		}
+
+		if let Some(threads) = self.tokio_blocking_threads {
+			if threads == 0 {
+				return Err("--tokio-blocking-threads must be at least 1".into())
+			}
+			builder.max_blocking_threads(threads);
+		}
+
+		builder.build().map_err(Into::into)
+	}
+}
+
+/// The reputation decay divisor halving the reputations every `half_life` seconds.
+///
+/// The reputations are multiplied by `1 - 1 / divisor` every second.
+fn decay_divisor(half_life: u32) -> u32 {
+	let factor = 0.5f64.powf(1.0 / f64::from(half_life));
+	(1.0 / (1.0 - factor)).round() as u32
+}
+
+/// Read the peer ids listed in the file at `path`, one per line, skipping the empty lines and
+/// the lines starting with `#`.
+fn read_peer_ids(path: &std::path::Path) -> std::result::Result<HashSet<PeerId>, String> {
+	let content = std::fs::read_to_string(path)
+		.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+	content
+		.lines()
+		.map(str::trim)
+		.filter(|line| !line.is_empty() && !line.starts_with('#'))
+		.map(|line| {
+			line.parse()
+				.map_err(|e| format!("Invalid peer id `{}` in {}: {}", line, path.display(), e))
+		})
+		.collect()
+}
+
+/// Parse a BABE public key given as SS58 or hex, `what` names the key in the error.
+fn parse_babe_key(
+	key: &str,
+	what: &str,
+) -> std::result::Result<sp_consensus_babe::AuthorityId, String> {
+	use sp_core::crypto::Ss58Codec;
+	use std::convert::TryFrom;
+
+	let invalid = || format!("Invalid {} `{}`", what, key);
+	let public = if key.starts_with("0x") {
+		let raw = sc_cli::utils::decode_hex(key).map_err(|_| invalid())?;
+		sp_core::sr25519::Public::try_from(&raw[..]).map_err(|()| invalid())?
+	} else {
+		sp_core::sr25519::Public::from_string(key).map_err(|_| invalid())?
+	};
+
+	Ok(public.into())
+}
+
 /// Parse command line arguments into service configuration.
 pub fn run() -> Result<()> {
-	let cli = Cli::from_args();
+	run_with_logger_hook(|_| {})
+}
+
+/// Like [`run`], but calling `logger_hook` on the [`LoggerBuilder`] before the logger is
+/// initialized.
+///
+/// This allows adding a custom tracing layer, e.g. exporting the spans of all tasks to a
+/// distributed tracing system, with [`LoggerBuilder::with_custom_layer`].
+pub fn run_with_logger_hook(logger_hook: impl FnOnce(&mut LoggerBuilder)) -> Result<()> {
+	run_cli(Cli::from_args(), logger_hook)
+}
 
+/// Like [`run`], but loading the chain specs passed by path with `chain_spec_loader` instead of
+/// parsing them as JSON.
+///
+/// The built-in chains, like `dev` or `local`, are not affected.
+pub fn run_with_chain_spec_loader(chain_spec_loader: ChainSpecLoader) -> Result<()> {
+	let mut cli = Cli::from_args();
+	cli.chain_spec_loader = Some(chain_spec_loader);
+	run_cli(cli, |_| {})
+}
+
+fn run_cli(cli: Cli, logger_hook: impl FnOnce(&mut LoggerBuilder)) -> Result<()> {
 	match &cli.subcommand {
 		None => {
-			let runner = cli.create_runner(&cli.run)?;
-			runner.run_node_until_exit(|config| async move {
+			let runner =
+				cli.create_runner_with_tokio_runtime(&cli.run, cli.tokio_runtime()?, logger_hook)?;
+			let options = cli.node_options()?;
+			let secret_command = cli
+				.secret_command
+				.as_deref()
+				.map(crate::secrets::SecretCommand::new)
+				.transpose()
+				.map_err(sc_cli::Error::Input)?;
+			let no_telemetry = cli.run.no_telemetry;
+			runner.run_node_until_exit(|mut config| async move {
+				if let Some(command) = &secret_command {
+					crate::secrets::resolve(command, &mut config, no_telemetry)?;
+				}
+				if let Some(snapshot) = &options.genesis_snapshot {
+					crate::genesis_snapshot::load(snapshot, &mut config).await?;
+				}
+				if !options.genesis_overrides.is_empty() {
+					crate::genesis_overrides::apply(&options.genesis_overrides, &mut config)?;
+				}
 				match config.role {
-					Role::Light => service::new_light(config),
-					_ => service::new_full(config),
+					Role::Light if options.tx_relay => service::new_tx_relay(config, options),
+					Role::Light => service::new_light(config, options),
+					_ => service::new_full(config, options),
 				}
 				.map_err(sc_cli::Error::Service)
 			})
 		},
 		Some(Subcommand::Inspect(cmd)) => {
-			let runner = cli.create_runner(cmd)?;
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
 
 			runner.sync_run(|config| cmd.run::<Block, RuntimeApi, ExecutorDispatch>(config))
 		},
 		Some(Subcommand::Benchmark(cmd)) =>
 			if cfg!(feature = "runtime-benchmarks") {
-				let runner = cli.create_runner(cmd)?;
+				let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
 
 				runner.sync_run(|config| cmd.run::<Block, ExecutorDispatch>(config))
 			} else {
@@ -104,53 +453,193 @@ pub fn run() -> Result<()> {
 		Some(Subcommand::Verify(cmd)) => cmd.run(),
 		Some(Subcommand::Vanity(cmd)) => cmd.run(),
 		Some(Subcommand::BuildSpec(cmd)) => {
-			let runner = cli.create_runner(cmd)?;
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
 			runner.sync_run(|config| cmd.run(config.chain_spec, config.network))
 		},
+		Some(Subcommand::CheckSpec(cmd)) => cmd.run::<Block, _>(&cli),
+		Some(Subcommand::PeerId(cmd)) => {
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
+			runner.sync_run(|config| cmd.run(config.network))
+		},
+		Some(Subcommand::DiffSpec(cmd)) => cmd.run(&cli),
+		Some(Subcommand::DecodeSpec(cmd)) => cmd.run(&cli),
+		Some(Subcommand::PrintConfig(cmd)) => {
+			let runner = cli.create_runner_with_logger_hook(&cmd.run, logger_hook)?;
+			let options = cli.node_options()?;
+			runner.sync_run(|config| {
+				let json = crate::print_config::config_json(&config, &options);
+				println!("{}", serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?);
+				Ok(())
+			})
+		},
+		Some(Subcommand::GenSystemd(cmd)) => {
+			let args = std::iter::once(Cli::executable_name()).chain(cmd.args.iter().cloned());
+			let mut node = Cli::try_from_iter(args).map_err(|e| e.to_string())?;
+			if node.subcommand.is_some() {
+				return Err("The node arguments can't contain a subcommand".into())
+			}
+			node.chain_spec_loader = cli.chain_spec_loader;
+
+			let runner = node.create_runner_with_logger_hook(&node.run, logger_hook)?;
+			runner.sync_run(|config| {
+				let unit = crate::systemd::unit(cmd, &config)?;
+				match &cmd.output {
+					Some(path) => std::fs::write(path, unit)?,
+					None => print!("{}", unit),
+				}
+				Ok(())
+			})
+		},
 		Some(Subcommand::CheckBlock(cmd)) => {
-			let runner = cli.create_runner(cmd)?;
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
+			let options = cli.node_options()?;
 			runner.async_run(|config| {
 				let PartialComponents { client, task_manager, import_queue, .. } =
-					new_partial(&config)?;
+					new_partial(&config, &options)?;
 				Ok((cmd.run(client, import_queue), task_manager))
 			})
 		},
+		Some(Subcommand::ApiCall(cmd)) => {
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
+			let options = cli.node_options()?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, .. } = new_partial(&config, &options)?;
+				cmd.run(client)
+			})
+		},
+		Some(Subcommand::VerifyJustification(cmd)) => {
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
+			let options = cli.node_options()?;
+			runner.sync_run(|config| {
+				let PartialComponents {
+					client, backend, other: (_, (_, grandpa_link, _), ..), ..
+				} = new_partial(&config, &options)?;
+				cmd.run(&*backend, grandpa_link.shared_authority_set(), &(client as Arc<_>))
+			})
+		},
+		Some(Subcommand::OffchainReplay(cmd)) => {
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
+			let options = cli.node_options()?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, backend, keystore_container, .. } =
+					new_partial(&config, &options)?;
+				cmd.run(client, &*backend, keystore_container.sync_keystore())
+			})
+		},
+		Some(Subcommand::WeighExtrinsic(cmd)) => {
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
+			let options = cli.node_options()?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, backend, .. } = new_partial(&config, &options)?;
+				cmd.run(client, &*backend)
+			})
+		},
+		Some(Subcommand::KeystoreExport(cmd)) => {
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
+			runner.sync_run(|config| cmd.run(&config.keystore))
+		},
+		Some(Subcommand::KeystoreImport(cmd)) => {
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
+			runner.sync_run(|config| cmd.run(&config.keystore))
+		},
 		Some(Subcommand::ExportBlocks(cmd)) => {
-			let runner = cli.create_runner(cmd)?;
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
+			let options = cli.node_options()?;
 			runner.async_run(|config| {
-				let PartialComponents { client, task_manager, .. } = new_partial(&config)?;
+				let PartialComponents { client, task_manager, .. } =
+					new_partial(&config, &options)?;
 				Ok((cmd.run(client, config.database), task_manager))
 			})
 		},
+		Some(Subcommand::ExportFinalized(cmd)) => {
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
+			let options = cli.node_options()?;
+			runner.async_run(|config| {
+				let PartialComponents { client, task_manager, .. } =
+					new_partial(&config, &options)?;
+				Ok((cmd.run(client, config.database), task_manager))
+			})
+		},
+		Some(Subcommand::Tail(cmd)) => {
+			let runner = cli.create_runner_with_logger_hook(&cmd.run, logger_hook)?;
+			let options = cli.node_options()?;
+			runner.run_node_until_exit(|config| async move {
+				crate::tail::new_service(config, options).map_err(sc_cli::Error::Service)
+			})
+		},
+		Some(Subcommand::Throughput(cmd)) => {
+			if cmd.report_interval == 0 {
+				return Err("--report-interval must be at least 1".into())
+			}
+			let interval = Duration::from_secs(cmd.report_interval);
+			let runner = cli.create_runner_with_logger_hook(&cmd.run, logger_hook)?;
+			let options = cli.node_options()?;
+			runner.run_node_until_exit(|config| async move {
+				crate::throughput::new_service(config, options, interval)
+					.map_err(sc_cli::Error::Service)
+			})
+		},
 		Some(Subcommand::ExportState(cmd)) => {
-			let runner = cli.create_runner(cmd)?;
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
+			let options = cli.node_options()?;
 			runner.async_run(|config| {
-				let PartialComponents { client, task_manager, .. } = new_partial(&config)?;
+				let PartialComponents { client, task_manager, .. } =
+					new_partial(&config, &options)?;
 				Ok((cmd.run(client, config.chain_spec), task_manager))
 			})
 		},
 		Some(Subcommand::ImportBlocks(cmd)) => {
-			let runner = cli.create_runner(cmd)?;
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
+			let options = cli.node_options()?;
 			runner.async_run(|config| {
 				let PartialComponents { client, task_manager, import_queue, .. } =
-					new_partial(&config)?;
+					new_partial(&config, &options)?;
 				Ok((cmd.run(client, import_queue), task_manager))
 			})
 		},
+		Some(Subcommand::MigrationTest(cmd)) => {
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
+			let options = cli.node_options()?;
+			runner.sync_run(|config| {
+				let executor = NativeElseWasmExecutor::<ExecutorDispatch>::new(
+					config.wasm_method,
+					config.default_heap_pages,
+					config.max_runtime_instances,
+				);
+				let PartialComponents { client, backend, task_manager, .. } =
+					new_partial(&config, &options)?;
+				cmd.run(client, backend, executor, task_manager.spawn_handle())
+			})
+		},
 		Some(Subcommand::PurgeChain(cmd)) => {
-			let runner = cli.create_runner(cmd)?;
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
+			runner.sync_run(|config| cmd.run(config.database))
+		},
+		Some(Subcommand::CompactDb(cmd)) => {
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
 			runner.sync_run(|config| cmd.run(config.database))
 		},
 		Some(Subcommand::Revert(cmd)) => {
-			let runner = cli.create_runner(cmd)?;
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
+			let options = cli.node_options()?;
 			runner.async_run(|config| {
-				let PartialComponents { client, task_manager, backend, .. } = new_partial(&config)?;
+				let PartialComponents { client, task_manager, backend, .. } =
+					new_partial(&config, &options)?;
 				Ok((cmd.run(client, backend), task_manager))
 			})
 		},
+		Some(Subcommand::PruneForks(cmd)) => {
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
+			let options = cli.node_options()?;
+			runner.async_run(|config| {
+				let PartialComponents { task_manager, backend, .. } =
+					new_partial(&config, &options)?;
+				Ok((cmd.run(backend), task_manager))
+			})
+		},
 		#[cfg(feature = "try-runtime")]
 		Some(Subcommand::TryRuntime(cmd)) => {
-			let runner = cli.create_runner(cmd)?;
+			let runner = cli.create_runner_with_logger_hook(cmd, logger_hook)?;
 			runner.async_run(|config| {
 				// we don't need any of the components of new_partial, just a runtime, or a task
 				// manager to do `async_run`.
@@ -0,0 +1,343 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `decode-spec` command.
+//!
+//! Raw chain specs only hold the hex encoded keys and values of the genesis storage. The keys are
+//! named after the storage items of the metadata of the spec's runtime, and the values of the items
+//! worth auditing, like the balances or the sudo key, are decoded with the types of the native
+//! runtime, as long as it has the version of the spec's runtime. The other values are kept as hex,
+//! along with their type in the metadata.
+
+use crate::cli::DecodeSpecCmd;
+use codec::Decode;
+use frame_support::metadata::{
+	DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed, StorageEntryType,
+};
+use node_executor::ExecutorDispatch;
+use node_primitives::{AccountId, Balance, Index};
+use node_runtime::Runtime;
+use sc_cli::SubstrateCli;
+use sc_executor::{NativeElseWasmExecutor, RuntimeVersionOf, WasmExecutionMethod};
+use serde_json::{json, Map, Value};
+use sp_api::RuntimeVersion;
+use sp_core::{
+	bytes::to_hex,
+	crypto::Ss58Codec,
+	hashing::{blake2_256, twox_128},
+	storage::{well_known_keys, Storage},
+	traits::{CodeExecutor, RuntimeCode, WrappedRuntimeCode},
+	NativeOrEncoded, NeverNativeValue,
+};
+use std::collections::BTreeMap;
+
+/// Storage item of the runtime metadata.
+struct StorageItem {
+	/// `Pallet.Item` name of the item.
+	name: String,
+	/// Type of the values, as named by the metadata.
+	ty: String,
+	/// Whether the item is a map, whose keys have a suffix after the prefix of the item.
+	is_map: bool,
+}
+
+/// Entries of the genesis storage, by key suffix.
+type Entries<'a> = BTreeMap<&'a [u8], &'a [u8]>;
+
+type AccountInfo = frame_system::AccountInfo<Index, <Runtime as frame_system::Config>::AccountData>;
+
+impl DecodeSpecCmd {
+	/// Run the `decode-spec` command.
+	pub fn run<C: SubstrateCli>(&self, cli: &C) -> sc_cli::Result<()> {
+		let spec = cli.load_spec(&self.chain)?;
+		let storage = sc_service::chain_ops::build_genesis_storage(&*spec).map_err(|e| {
+			format!("Failed to build the genesis storage of `{}`: {}", self.chain, e)
+		})?;
+
+		let (version, metadata) = spec_runtime(&storage)?;
+		// The native types only match the ones of the same runtime version.
+		let typed = version.spec_name == node_runtime::VERSION.spec_name &&
+			version.spec_version == node_runtime::VERSION.spec_version;
+		if !typed {
+			log::warn!(
+				"The runtime of `{}` is {}, not the native {}: only decoding the storage as hex",
+				self.chain,
+				version,
+				node_runtime::VERSION,
+			);
+		}
+
+		let mut json = decode_storage(&storage, &storage_items(&metadata)?, typed);
+		json["name"] = spec.name().into();
+		json["id"] = spec.id().into();
+		println!("{}", serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?);
+
+		Ok(())
+	}
+}
+
+/// The version and the encoded metadata of the runtime in the genesis storage, executed in wasm.
+fn spec_runtime(storage: &Storage) -> Result<(RuntimeVersion, Vec<u8>), String> {
+	let code = storage
+		.top
+		.get(well_known_keys::CODE)
+		.ok_or("The genesis storage has no runtime code")?;
+	let code_fetcher = WrappedRuntimeCode(code.into());
+	let runtime_code = RuntimeCode {
+		code_fetcher: &code_fetcher,
+		heap_pages: storage
+			.top
+			.get(well_known_keys::HEAP_PAGES)
+			.and_then(|value| u64::decode(&mut &value[..]).ok()),
+		hash: blake2_256(code).to_vec(),
+	};
+	let executor =
+		NativeElseWasmExecutor::<ExecutorDispatch>::new(WasmExecutionMethod::Interpreted, None, 1);
+	let mut ext = frame_support::BasicExternalities::new(storage.clone());
+
+	let version = executor
+		.runtime_version(&mut ext, &runtime_code)
+		.map_err(|e| format!("Failed to read the runtime version: {}", e))?;
+	let metadata = match executor
+		.call::<NeverNativeValue, fn() -> _>(
+			&mut ext,
+			&runtime_code,
+			"Metadata_metadata",
+			&[],
+			false,
+			None,
+		)
+		.0
+	{
+		Ok(NativeOrEncoded::Encoded(metadata)) => Vec::<u8>::decode(&mut &metadata[..])
+			.map_err(|e| format!("Invalid runtime metadata: {}", e))?,
+		Ok(NativeOrEncoded::Native(_)) => unreachable!("Never executed natively; qed"),
+		Err(e) => return Err(format!("Failed to get the runtime metadata: {}", e)),
+	};
+
+	Ok((version, metadata))
+}
+
+/// The storage items of the encoded runtime `metadata`, by the prefix of their keys.
+fn storage_items(mut metadata: &[u8]) -> Result<BTreeMap<Vec<u8>, StorageItem>, String> {
+	let modules = match RuntimeMetadataPrefixed::decode(&mut metadata) {
+		Ok(RuntimeMetadataPrefixed(_, RuntimeMetadata::V13(metadata))) =>
+			decoded(metadata.modules)?,
+		Ok(_) => return Err("Unsupported runtime metadata version".into()),
+		Err(e) => return Err(format!("Invalid runtime metadata: {}", e)),
+	};
+
+	let mut items = BTreeMap::new();
+	for module in modules {
+		let storage = match module.storage {
+			Some(storage) => decoded(storage)?,
+			None => continue,
+		};
+		let prefix = decoded(storage.prefix)?;
+		for entry in decoded(storage.entries)? {
+			let name = decoded(entry.name)?;
+			let (ty, is_map) = match entry.ty {
+				StorageEntryType::Plain(ty) => (decoded(ty)?, false),
+				StorageEntryType::Map { value, .. } |
+				StorageEntryType::DoubleMap { value, .. } |
+				StorageEntryType::NMap { value, .. } => (decoded(value)?, true),
+			};
+			let key = [twox_128(prefix.as_bytes()), twox_128(name.as_bytes())].concat();
+			items.insert(key, StorageItem { name: format!("{}.{}", prefix, name), ty, is_map });
+		}
+	}
+
+	Ok(items)
+}
+
+fn decoded<B, O>(value: DecodeDifferent<B, O>) -> Result<O, String> {
+	match value {
+		DecodeDifferent::Decoded(value) => Ok(value),
+		DecodeDifferent::Encode(_) => Err("Undecoded runtime metadata".into()),
+	}
+}
+
+/// Decode the main trie of `storage` as JSON, naming its keys after `items`, and decoding the
+/// values with the native types if `typed`.
+fn decode_storage(storage: &Storage, items: &BTreeMap<Vec<u8>, StorageItem>, typed: bool) -> Value {
+	let mut by_item: BTreeMap<&[u8], Entries> = BTreeMap::new();
+	let mut unknown = Vec::new();
+	for (key, value) in &storage.top {
+		match key.get(..32).filter(|prefix| items.contains_key(*prefix)) {
+			Some(prefix) => {
+				by_item.entry(prefix).or_default().insert(&key[32..], value);
+			},
+			None if key.starts_with(b":") => {},
+			None => unknown.push(to_hex(key, false)),
+		}
+	}
+
+	let mut decoded = Map::new();
+	for (prefix, entries) in by_item {
+		let item = &items[prefix];
+		let value = typed
+			.then(|| decode_item(&item.name, &entries).ok())
+			.flatten()
+			.unwrap_or_else(|| raw_item(item, &entries));
+		decoded.insert(item.name.clone(), value);
+	}
+
+	let code = storage.top.get(well_known_keys::CODE);
+	let heap_pages = storage
+		.top
+		.get(well_known_keys::HEAP_PAGES)
+		.and_then(|value| u64::decode(&mut &value[..]).ok());
+	let grandpa_authorities = storage
+		.top
+		.get(grandpa_primitives::GRANDPA_AUTHORITIES_KEY)
+		.and_then(|value| grandpa_primitives::VersionedAuthorityList::decode(&mut &value[..]).ok())
+		.map(|authorities| {
+			let authorities: grandpa_primitives::AuthorityList = authorities.into();
+			authorities
+				.into_iter()
+				.map(|(id, weight)| json!({ "id": id.to_ss58check(), "weight": weight }))
+				.collect::<Vec<_>>()
+		});
+
+	json!({
+		"code": code.map(|code| json!({
+			"size": code.len(),
+			"hash": to_hex(&blake2_256(code), false),
+		})),
+		"heapPages": heap_pages,
+		"grandpaAuthorities": grandpa_authorities,
+		"storage": decoded,
+		"unknownKeys": unknown,
+		"childTries": storage.children_default.len(),
+	})
+}
+
+/// Decode the entries of the item `name`, if it is one worth auditing.
+fn decode_item(name: &str, entries: &Entries) -> Result<Value, codec::Error> {
+	let accounts = |accounts: Vec<AccountId>| {
+		Value::from(accounts.iter().map(|account| account.to_ss58check()).collect::<Vec<_>>())
+	};
+
+	Ok(match name {
+		"System.Account" => {
+			let mut decoded = Map::new();
+			for (suffix, value) in entries {
+				// The account follows the 16 bytes of its `Blake2_128Concat` hash.
+				let account = AccountId::decode(&mut suffix.get(16..).unwrap_or_default())?;
+				let info = AccountInfo::decode(&mut &value[..])?;
+				decoded.insert(
+					account.to_ss58check(),
+					json!({
+						"nonce": info.nonce,
+						"free": info.data.free.to_string(),
+						"reserved": info.data.reserved.to_string(),
+						"miscFrozen": info.data.misc_frozen.to_string(),
+						"feeFrozen": info.data.fee_frozen.to_string(),
+					}),
+				);
+			}
+			decoded.into()
+		},
+		"Balances.TotalIssuance" => plain::<Balance>(entries)?.to_string().into(),
+		"Sudo.Key" => plain::<AccountId>(entries)?.to_ss58check().into(),
+		"Babe.Authorities" => plain::<Vec<(sp_consensus_babe::AuthorityId, u64)>>(entries)?
+			.into_iter()
+			.map(|(id, weight)| json!({ "id": id.to_ss58check(), "weight": weight }))
+			.collect::<Vec<_>>()
+			.into(),
+		"Session.Validators" |
+		"Council.Members" |
+		"TechnicalCommittee.Members" |
+		"TechnicalMembership.Members" |
+		"Staking.Invulnerables" => accounts(plain(entries)?),
+		"Staking.ValidatorCount" | "Staking.MinimumValidatorCount" => plain::<u32>(entries)?.into(),
+		_ => return Err("Not decoded".into()),
+	})
+}
+
+/// Decode the value of a plain storage item.
+fn plain<T: Decode>(entries: &Entries) -> Result<T, codec::Error> {
+	match (entries.len(), entries.get(&[][..])) {
+		(1, Some(value)) => T::decode(&mut &value[..]),
+		_ => Err("Not a plain storage item".into()),
+	}
+}
+
+/// The hex encoded entries of `item`.
+fn raw_item(item: &StorageItem, entries: &Entries) -> Value {
+	if !item.is_map {
+		if let Some(value) = entries.get(&[][..]) {
+			return json!({ "type": item.ty, "value": to_hex(value, false) })
+		}
+	}
+
+	let entries: Map<_, _> = entries
+		.iter()
+		.map(|(suffix, value)| (to_hex(suffix, false), to_hex(value, false).into()))
+		.collect();
+	json!({ "type": item.ty, "entries": entries })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use node_runtime::{BalancesConfig, GenesisConfig, SudoConfig};
+	use sp_keyring::AccountKeyring;
+	use sp_runtime::BuildStorage;
+
+	#[test]
+	fn decodes_balances_and_sudo_key() {
+		let alice = AccountKeyring::Alice.to_account_id();
+		let storage = GenesisConfig {
+			balances: BalancesConfig { balances: vec![(alice.clone(), 1_000)] },
+			sudo: SudoConfig { key: alice.clone() },
+			..Default::default()
+		}
+		.build_storage()
+		.unwrap();
+
+		let items = storage_items(&Runtime::metadata().encode()).unwrap();
+		let json = decode_storage(&storage, &items, true);
+		let alice = alice.to_ss58check();
+		assert_eq!(json["storage"]["System.Account"][&alice]["free"], "1000");
+		assert_eq!(json["storage"]["Balances.TotalIssuance"], "1000");
+		assert_eq!(json["storage"]["Sudo.Key"], alice);
+		assert_eq!(json["unknownKeys"], json!([]));
+
+		let json = decode_storage(&storage, &items, false);
+		assert_eq!(json["storage"]["Sudo.Key"]["type"], "T::AccountId");
+	}
+
+	#[test]
+	fn reads_the_metadata_of_the_spec_runtime() {
+		let storage = GenesisConfig {
+			system: node_runtime::SystemConfig {
+				code: node_runtime::wasm_binary_unwrap().to_vec(),
+				changes_trie_config: Default::default(),
+			},
+			..Default::default()
+		}
+		.build_storage()
+		.unwrap();
+
+		let (version, metadata) = spec_runtime(&storage).unwrap();
+		assert_eq!(version, node_runtime::VERSION);
+		assert_eq!(metadata, Runtime::metadata().encode());
+	}
+}
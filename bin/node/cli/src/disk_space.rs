@@ -0,0 +1,98 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Guard against running out of disk space.
+//!
+//! A database running out of space in the middle of a write can be left corrupted. The free space
+//! of the filesystem holding the database is checked at startup, and then periodically, and the
+//! node stops while there is still the configured space left.
+
+use futures_timer::Delay;
+use std::{
+	io,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+const LOG_TARGET: &str = "disk-space";
+
+/// Interval between two checks of the free space.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+const MIB: u64 = 1024 * 1024;
+
+/// The free space, in bytes, below which the node refuses to start or stops.
+#[derive(Clone, Copy, Debug)]
+pub struct MinFreeDisk(pub u64);
+
+impl MinFreeDisk {
+	/// Check that the filesystem of `path` has more than the minimum free space.
+	pub fn check(&self, path: &Path) -> Result<(), String> {
+		let available = available_space(path).map_err(|e| {
+			format!("Failed to read the free disk space of {}: {}", path.display(), e)
+		})?;
+		if available < self.0 {
+			return Err(format!(
+				"Only {} MiB of disk space left for the database at {}, below the minimum of {} MiB",
+				available / MIB,
+				path.display(),
+				self.0 / MIB,
+			))
+		}
+		Ok(())
+	}
+
+	/// Check the free space of the filesystem of `path` periodically, returning once it goes below
+	/// the minimum, which is meant to shut down the node when it runs as an essential task.
+	pub async fn run(self, path: PathBuf) {
+		loop {
+			Delay::new(CHECK_INTERVAL).await;
+			match self.check(&path) {
+				Ok(()) => {},
+				Err(err) => {
+					log::error!(target: LOG_TARGET, "💾 {}, stopping the node", err);
+					return
+				},
+			}
+		}
+	}
+}
+
+/// The space available on the filesystem of `path`, which is created with the database and may
+/// not exist yet.
+fn available_space(path: &Path) -> io::Result<u64> {
+	let existing = path.ancestors().find(|path| path.exists()).unwrap_or(path);
+	fs2::available_space(existing)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn checks_the_nearest_existing_directory() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("chains").join("db");
+
+		assert!(MinFreeDisk(1).check(&path).is_ok());
+		assert!(MinFreeDisk(u64::MAX)
+			.check(&path)
+			.unwrap_err()
+			.contains("MiB of disk space left"));
+	}
+}
@@ -0,0 +1,446 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Publication of the finalized blocks to a message broker.
+//!
+//! Every finalized block is published as a JSON summary, e.g.
+//! `{"number":42,"hash":"0x…","parentHash":"0x…","extrinsics":2}`, in order, to the sink of the
+//! configured URL. The brokers implement [`EventSink`], NATS is supported with the
+//! `nats-event-sink` feature, as `nats://HOST:PORT/SUBJECT`.
+//!
+//! The summaries are queued while the broker is slow or unreachable, and the new ones are dropped
+//! once the queue is full, which never slows down the node.
+
+use futures::{channel::mpsc, prelude::*};
+use futures_timer::Delay;
+use node_primitives::{Block, BlockNumber, Hash};
+use sc_client_api::{BlockBackend, BlockchainEvents, HeaderBackend};
+use serde_json::json;
+use sp_runtime::{generic::BlockId, traits::Header as HeaderT};
+use std::{fmt, sync::Arc, time::Duration};
+
+const LOG_TARGET: &str = "event-sink";
+
+/// Number of summaries waiting to be published before the new ones are dropped.
+const QUEUE_SIZE: usize = 1024;
+
+/// Delay before the first retry of a summary, doubled on every retry up to [`MAX_RETRY_DELAY`].
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest delay between two retries of a summary.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// A message broker the summaries are published to.
+#[async_trait::async_trait]
+pub trait EventSink: Send {
+	/// Publish `payload`, returning once the broker received it.
+	///
+	/// A failed publication is retried with the same payload.
+	async fn publish(&mut self, payload: &[u8]) -> Result<(), String>;
+}
+
+/// Configuration of the event sink.
+#[derive(Clone)]
+pub struct Config {
+	/// The URL of the sink.
+	pub url: String,
+	kind: SinkKind,
+}
+
+#[derive(Clone)]
+enum SinkKind {
+	#[cfg(feature = "nats-event-sink")]
+	Nats { address: String, subject: String },
+}
+
+impl fmt::Debug for Config {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Config").field("url", &self.url).finish()
+	}
+}
+
+impl Config {
+	/// Create a new configuration, checking that `url` is the URL of a supported broker.
+	pub fn new(url: &str) -> Result<Self, String> {
+		match url.split_once("://") {
+			#[cfg(feature = "nats-event-sink")]
+			Some(("nats", rest)) => {
+				let (address, subject) = rest
+					.split_once('/')
+					.filter(|(address, subject)| !address.is_empty() && !subject.is_empty())
+					.ok_or_else(|| {
+						format!(
+							"Invalid event sink URL `{}`, expected `nats://HOST:PORT/SUBJECT`",
+							url
+						)
+					})?;
+				let address = if address.contains(':') {
+					address.to_owned()
+				} else {
+					format!("{}:{}", address, nats::DEFAULT_PORT)
+				};
+				Ok(Self {
+					url: url.into(),
+					kind: SinkKind::Nats { address, subject: subject.into() },
+				})
+			},
+			#[cfg(not(feature = "nats-event-sink"))]
+			Some(("nats", _)) => Err("The node was built without the `nats-event-sink` feature".into()),
+			_ => Err(format!("Unsupported event sink URL `{}`, expected `nats://`", url)),
+		}
+	}
+
+	fn sink(&self) -> Box<dyn EventSink> {
+		match self.kind {
+			#[cfg(feature = "nats-event-sink")]
+			SinkKind::Nats { ref address, ref subject } =>
+				Box::new(nats::NatsSink::new(address.clone(), subject.clone())),
+		}
+	}
+}
+
+/// Publish a summary of every block finalized by `client` to the configured sink.
+///
+/// The publications are retried until they succeed, the task never terminates on a failure.
+pub async fn run<C>(config: Config, client: Arc<C>)
+where
+	C: BlockchainEvents<Block> + BlockBackend<Block> + HeaderBackend<Block>,
+{
+	log::info!(target: LOG_TARGET, "📤 Publishing finalized blocks to {}", config.url);
+
+	let (mut queue, mut summaries) = mpsc::channel(QUEUE_SIZE);
+
+	let mut last_finalized = None;
+	let mut dropped = 0u64;
+	let enqueue = client
+		.finality_notification_stream()
+		.flat_map(|notification| {
+			stream::iter(crate::finalized_webhook::finalized_blocks(
+				&*client,
+				&mut last_finalized,
+				notification,
+			))
+		})
+		.for_each(|(number, hash)| {
+			match summary(&*client, number, hash) {
+				Ok(summary) => match queue.try_send(summary) {
+					Ok(()) if dropped > 0 => {
+						log::warn!(
+							target: LOG_TARGET,
+							"Dropped the summaries of {} blocks while the queue was full",
+							dropped,
+						);
+						dropped = 0;
+					},
+					Ok(()) => {},
+					Err(_) => {
+						if dropped == 0 {
+							log::warn!(
+								target: LOG_TARGET,
+								"The queue of {} is full, dropping the summaries of the new blocks",
+								config.url,
+							);
+						}
+						dropped += 1;
+					},
+				},
+				Err(err) => log::warn!(
+					target: LOG_TARGET,
+					"Failed to build the summary of block #{}: {}",
+					number,
+					err,
+				),
+			}
+			future::ready(())
+		});
+
+	let publish = async {
+		let mut sink = config.sink();
+		while let Some(summary) = summaries.next().await {
+			let mut delay = RETRY_DELAY;
+			while let Err(err) = sink.publish(summary.as_bytes()).await {
+				log::warn!(
+					target: LOG_TARGET,
+					"Failed to publish to {}, retrying in {:?}: {}",
+					config.url,
+					delay,
+					err,
+				);
+				Delay::new(delay).await;
+				delay = (delay * 2).min(MAX_RETRY_DELAY);
+			}
+		}
+	};
+
+	future::join(enqueue, publish).await;
+}
+
+/// The JSON summary of the block `number` with `hash`.
+fn summary(
+	client: &(impl BlockBackend<Block> + HeaderBackend<Block>),
+	number: BlockNumber,
+	hash: Hash,
+) -> Result<String, String> {
+	let id = BlockId::Hash(hash);
+	let header = client
+		.header(id)
+		.map_err(|e| e.to_string())?
+		.ok_or("The header of the block is missing")?;
+	let extrinsics = client.block_body(&id).map_err(|e| e.to_string())?.map(|body| body.len());
+
+	Ok(json!({
+		"number": number,
+		"hash": hash,
+		"parentHash": header.parent_hash(),
+		"extrinsics": extrinsics,
+	})
+	.to_string())
+}
+
+/// A minimal NATS publisher, speaking the text protocol of the NATS server.
+#[cfg(feature = "nats-event-sink")]
+mod nats {
+	use super::EventSink;
+	use futures::future::{self, Either};
+	use futures_timer::Delay;
+	use std::{io, time::Duration};
+	use tokio::{
+		io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+		net::TcpStream,
+	};
+
+	/// The port of a NATS server when the URL doesn't give one.
+	pub const DEFAULT_PORT: u16 = 4222;
+
+	/// Timeout of the connection to the server, and of every publication.
+	const TIMEOUT: Duration = Duration::from_secs(10);
+
+	/// Publishes to a subject of a NATS server, connecting again after a failure.
+	pub struct NatsSink {
+		address: String,
+		subject: String,
+		connection: Option<BufReader<TcpStream>>,
+	}
+
+	impl NatsSink {
+		pub fn new(address: String, subject: String) -> Self {
+			Self { address, subject, connection: None }
+		}
+	}
+
+	#[async_trait::async_trait]
+	impl EventSink for NatsSink {
+		async fn publish(&mut self, payload: &[u8]) -> Result<(), String> {
+			let mut connection = match self.connection.take() {
+				Some(connection) => connection,
+				None => timeout(connect(&self.address)).await.map_err(|e| e.to_string())?,
+			};
+
+			let result = timeout(publish(&mut connection, &self.subject, payload)).await;
+			if result.is_ok() {
+				self.connection = Some(connection);
+			}
+			result.map_err(|e| e.to_string())
+		}
+	}
+
+	async fn timeout<T>(future: impl std::future::Future<Output = io::Result<T>>) -> io::Result<T> {
+		match future::select(Box::pin(future), Delay::new(TIMEOUT)).await {
+			Either::Left((result, _)) => result,
+			Either::Right(((), _)) => Err(io::ErrorKind::TimedOut.into()),
+		}
+	}
+
+	async fn connect(address: &str) -> io::Result<BufReader<TcpStream>> {
+		let mut connection = BufReader::new(TcpStream::connect(address).await?);
+
+		// The server greets every new client with its `INFO`.
+		let info = read_line(&mut connection).await?;
+		if !info.starts_with("INFO") {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "not a NATS server"))
+		}
+		connection
+			.write_all(b"CONNECT {\"verbose\":false,\"pedantic\":false,\"name\":\"substrate\"}\r\n")
+			.await?;
+		Ok(connection)
+	}
+
+	/// Publish `payload`, then wait for the `PONG` answering a `PING`, which the server sends
+	/// once it processed the publication.
+	async fn publish(
+		connection: &mut BufReader<TcpStream>,
+		subject: &str,
+		payload: &[u8],
+	) -> io::Result<()> {
+		let mut message = format!("PUB {} {}\r\n", subject, payload.len()).into_bytes();
+		message.extend_from_slice(payload);
+		message.extend_from_slice(b"\r\nPING\r\n");
+		connection.write_all(&message).await?;
+
+		loop {
+			let line = read_line(connection).await?;
+			match line.as_str() {
+				"PONG" => return Ok(()),
+				"PING" => connection.write_all(b"PONG\r\n").await?,
+				line if line.starts_with("-ERR") =>
+					return Err(io::Error::new(io::ErrorKind::Other, line.to_owned())),
+				// `+OK` and the `INFO` updates of the servers.
+				_ => {},
+			}
+		}
+	}
+
+	async fn read_line(connection: &mut BufReader<TcpStream>) -> io::Result<String> {
+		let mut line = String::new();
+		if connection.read_line(&mut line).await? == 0 {
+			return Err(io::ErrorKind::UnexpectedEof.into())
+		}
+		Ok(line.trim_end().to_owned())
+	}
+
+	#[cfg(test)]
+	mod tests {
+		use super::*;
+		use tokio::net::TcpListener;
+
+		/// Accept a client, as a NATS server, and check its `CONNECT`.
+		async fn accept(listener: &TcpListener) -> BufReader<TcpStream> {
+			let mut connection = BufReader::new(listener.accept().await.unwrap().0);
+			connection.write_all(b"INFO {\"server_id\":\"test\"}\r\n").await.unwrap();
+			assert!(read_line(&mut connection).await.unwrap().starts_with("CONNECT {"));
+			connection
+		}
+
+		/// Read a publication of `payload` to `blocks`, followed by a `PING`.
+		async fn expect_pub(connection: &mut BufReader<TcpStream>, payload: &str) {
+			assert_eq!(
+				read_line(connection).await.unwrap(),
+				format!("PUB blocks {}", payload.len())
+			);
+			assert_eq!(read_line(connection).await.unwrap(), payload);
+			assert_eq!(read_line(connection).await.unwrap(), "PING");
+		}
+
+		async fn sink() -> (NatsSink, TcpListener) {
+			let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+			let address = listener.local_addr().unwrap().to_string();
+			(NatsSink::new(address, "blocks".into()), listener)
+		}
+
+		#[tokio::test]
+		async fn publishes_and_waits_for_the_pong() {
+			let (mut sink, listener) = sink().await;
+			let server = async {
+				let mut connection = accept(&listener).await;
+				for payload in ["first", "second"] {
+					expect_pub(&mut connection, payload).await;
+					connection.write_all(b"+OK\r\nPONG\r\n").await.unwrap();
+				}
+			};
+			let client = async {
+				sink.publish(b"first").await.unwrap();
+				// The connection is kept between the publications.
+				sink.publish(b"second").await.unwrap();
+			};
+			future::join(server, client).await;
+		}
+
+		#[tokio::test]
+		async fn answers_the_pings_of_the_server() {
+			let (mut sink, listener) = sink().await;
+			let server = async {
+				let mut connection = accept(&listener).await;
+				expect_pub(&mut connection, "block").await;
+				connection.write_all(b"PING\r\n").await.unwrap();
+				assert_eq!(read_line(&mut connection).await.unwrap(), "PONG");
+				connection.write_all(b"PONG\r\n").await.unwrap();
+			};
+			let (_, result) = future::join(server, sink.publish(b"block")).await;
+			result.unwrap();
+		}
+
+		#[tokio::test]
+		async fn reports_the_errors_of_the_server() {
+			let (mut sink, listener) = sink().await;
+			let server = async {
+				let mut connection = accept(&listener).await;
+				expect_pub(&mut connection, "block").await;
+				connection.write_all(b"-ERR 'Permissions Violation'\r\n").await.unwrap();
+			};
+			let (_, result) = future::join(server, sink.publish(b"block")).await;
+			assert_eq!(result.unwrap_err(), "-ERR 'Permissions Violation'");
+		}
+
+		#[tokio::test]
+		async fn reconnects_after_a_failure() {
+			let (mut sink, listener) = sink().await;
+			let server = async {
+				// The first connection is closed before answering the `PING`.
+				let mut connection = accept(&listener).await;
+				expect_pub(&mut connection, "block").await;
+				drop(connection);
+
+				let mut connection = accept(&listener).await;
+				expect_pub(&mut connection, "block").await;
+				connection.write_all(b"PONG\r\n").await.unwrap();
+			};
+			let client = async {
+				assert!(sink.publish(b"block").await.is_err());
+				sink.publish(b"block").await.unwrap();
+			};
+			future::join(server, client).await;
+		}
+
+		#[tokio::test]
+		async fn rejects_servers_not_speaking_nats() {
+			let (mut sink, listener) = sink().await;
+			let server = async {
+				let (mut connection, _) = listener.accept().await.unwrap();
+				connection.write_all(b"HTTP/1.1 400 Bad Request\r\n").await.unwrap();
+			};
+			let (_, result) = future::join(server, sink.publish(b"block")).await;
+			assert_eq!(result.unwrap_err(), "not a NATS server");
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	#[cfg(feature = "nats-event-sink")]
+	fn config_parses_nats_urls() {
+		let config = Config::new("nats://localhost/blocks").unwrap();
+		assert!(matches!(
+			config.kind,
+			SinkKind::Nats { ref address, ref subject }
+				if address == "localhost:4222" && subject == "blocks"
+		));
+
+		assert!(Config::new("nats://localhost:4223/chain.blocks").is_ok());
+		assert!(Config::new("nats://localhost:4222").is_err());
+		assert!(Config::new("nats:///blocks").is_err());
+	}
+
+	#[test]
+	fn config_rejects_unsupported_urls() {
+		assert!(Config::new("kafka://localhost:9092/blocks").is_err());
+		assert!(Config::new("localhost:4222").is_err());
+	}
+}
@@ -0,0 +1,93 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Files periodically rewritten by the node.
+//!
+//! Both the metrics dump and the status file replace a local file at a fixed interval. The file
+//! is replaced atomically, so readers never see a partially written file.
+
+use std::{
+	ffi::OsString,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+/// Configuration of a task periodically writing a file.
+#[derive(Clone, Debug)]
+pub struct Config {
+	/// The file written to.
+	pub path: PathBuf,
+	/// The interval between two writes.
+	pub interval: Duration,
+}
+
+impl Config {
+	/// Create a new configuration, checking that `path` can be a file.
+	///
+	/// `name` describes the file in the errors.
+	pub fn new(name: &str, path: PathBuf, interval: Duration) -> Result<Self, String> {
+		if path.file_name().is_none() || path.is_dir() {
+			return Err(format!("Invalid {} `{}`", name, path.display()))
+		}
+
+		if interval.as_secs() == 0 {
+			return Err(format!("The {} interval must be at least one second", name))
+		}
+
+		Ok(Self { path, interval })
+	}
+}
+
+/// Replace the file at `path` with `contents`, without readers ever seeing a partial write.
+pub async fn replace(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+	// Renaming within the directory of the file atomically replaces it.
+	let mut tmp = OsString::from(path.as_os_str());
+	tmp.push(".tmp");
+	let tmp = PathBuf::from(tmp);
+	tokio::fs::write(&tmp, contents).await?;
+	tokio::fs::rename(&tmp, path).await
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn config_rejects_invalid_paths() {
+		let interval = Duration::from_secs(15);
+		let dir = tempfile::tempdir().unwrap();
+
+		assert!(Config::new("test file", dir.path().join("test.out"), interval).is_ok());
+		assert!(Config::new("test file", dir.path().into(), interval).is_err());
+		assert!(
+			Config::new("test file", dir.path().join("test.out"), Duration::from_secs(0)).is_err()
+		);
+	}
+
+	#[tokio::test]
+	async fn replaces_the_file() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("test.out");
+
+		replace(&path, b"first").await.unwrap();
+		replace(&path, b"second").await.unwrap();
+
+		assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+		assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+	}
+}
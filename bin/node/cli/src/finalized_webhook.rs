@@ -0,0 +1,169 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Notification of the finalized blocks to a webhook.
+//!
+//! Every finalized block is posted as a small JSON object, e.g.
+//! `{"number":42,"hash":"0x…"}`, to the configured URL. Requests run concurrently, so the
+//! notifications of close blocks may arrive out of order.
+
+use futures::prelude::*;
+use futures_timer::Delay;
+use hyper::{client::HttpConnector, header::CONTENT_TYPE, Body, Client, Method, Request, Uri};
+use node_primitives::{Block, BlockNumber, Hash};
+use sc_client_api::{BlockchainEvents, FinalityNotification, HeaderBackend};
+use sp_runtime::traits::Header as HeaderT;
+use std::{sync::Arc, time::Duration};
+
+const LOG_TARGET: &str = "finalized-webhook";
+
+/// Maximum number of notifications being delivered at the same time.
+const MAX_CONCURRENT_REQUESTS: usize = 8;
+
+/// Number of attempts to deliver a notification before giving up on it.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry of a notification, doubled on every retry.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Configuration of the finalized block webhook.
+#[derive(Clone, Debug)]
+pub struct Config {
+	/// The URL the finalized blocks are posted to.
+	pub url: Uri,
+}
+
+impl Config {
+	/// Create a new configuration, checking that `url` is a valid `http` URL.
+	pub fn new(url: &str) -> Result<Self, String> {
+		let url: Uri = url.parse().map_err(|e| format!("Invalid finalized webhook URL: {}", e))?;
+
+		match url.scheme_str() {
+			Some("http") => {},
+			_ =>
+				return Err(format!(
+					"Unsupported finalized webhook URL `{}`, expected `http://`",
+					url
+				)),
+		}
+
+		Ok(Self { url })
+	}
+}
+
+/// Post every block finalized by `client` to the configured URL.
+///
+/// A notification that still fails after [`MAX_ATTEMPTS`] is logged and dropped, it never
+/// terminates the task.
+pub async fn run<C>(config: Config, client: Arc<C>)
+where
+	C: BlockchainEvents<Block> + HeaderBackend<Block>,
+{
+	let http = Client::new();
+	let url = &config.url;
+
+	log::info!(target: LOG_TARGET, "🪝 Posting finalized blocks to {}", url);
+
+	let mut last_finalized = None;
+	client
+		.finality_notification_stream()
+		.flat_map(|notification| {
+			stream::iter(finalized_blocks(&*client, &mut last_finalized, notification))
+		})
+		.for_each_concurrent(MAX_CONCURRENT_REQUESTS, |(number, hash)| {
+			notify(&http, url, number, hash)
+		})
+		.await
+}
+
+/// The blocks finalized by `notification`, including the ones finalized along with it since the
+/// previous notification.
+pub(crate) fn finalized_blocks(
+	client: &impl HeaderBackend<Block>,
+	last_finalized: &mut Option<BlockNumber>,
+	notification: FinalityNotification<Block>,
+) -> Vec<(BlockNumber, Hash)> {
+	let number = *notification.header.number();
+	let from = last_finalized.map_or(number, |last| last + 1);
+	*last_finalized = Some(number);
+
+	let mut blocks: Vec<_> = (from..number)
+		.filter_map(|n| client.hash(n).ok().flatten().map(|hash| (n, hash)))
+		.collect();
+	blocks.push((number, notification.hash));
+	blocks
+}
+
+async fn notify(client: &Client<HttpConnector>, url: &Uri, number: BlockNumber, hash: Hash) {
+	let payload = serde_json::json!({ "number": number, "hash": hash }).to_string();
+	let mut delay = RETRY_DELAY;
+
+	for attempt in 1..=MAX_ATTEMPTS {
+		match post(client, url, payload.clone()).await {
+			Ok(()) => return,
+			Err(err) if attempt < MAX_ATTEMPTS => {
+				log::debug!(
+					target: LOG_TARGET,
+					"Failed to post block #{} to {}, retrying in {:?}: {}",
+					number,
+					url,
+					delay,
+					err,
+				);
+				Delay::new(delay).await;
+				delay *= 2;
+			},
+			Err(err) => log::warn!(
+				target: LOG_TARGET,
+				"Failed to post block #{} to {}: {}",
+				number,
+				url,
+				err,
+			),
+		}
+	}
+}
+
+async fn post(client: &Client<HttpConnector>, url: &Uri, payload: String) -> Result<(), String> {
+	let request = Request::builder()
+		.method(Method::POST)
+		.uri(url.clone())
+		.header(CONTENT_TYPE, "application/json")
+		.body(Body::from(payload))
+		.map_err(|e| e.to_string())?;
+
+	let response = client.request(request).await.map_err(|e| e.to_string())?;
+
+	if response.status().is_success() {
+		Ok(())
+	} else {
+		Err(format!("unexpected response status {}", response.status()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn config_rejects_invalid_urls() {
+		assert!(Config::new("http://localhost:8080/finalized").is_ok());
+		assert!(Config::new("https://localhost:8080").is_err());
+		assert!(Config::new("localhost:8080").is_err());
+	}
+}
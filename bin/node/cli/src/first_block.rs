@@ -0,0 +1,289 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Import of a first block produced ahead of the launch of the chain.
+//!
+//! Instead of authoring their first block, some chains launch with one sealed beforehand. The
+//! block goes through the import queue like a block received from the network, before the
+//! network starts, so the node never builds on the genesis block itself.
+
+use codec::Decode;
+use futures::{future::Either, task::Poll};
+use futures_timer::Delay;
+use node_primitives::{Block, BlockNumber, Hash};
+use sc_client_api::HeaderBackend;
+use sc_consensus::{BlockImportError, BlockImportStatus, ImportQueue, IncomingBlock, Link};
+use sp_consensus::BlockOrigin;
+use sp_runtime::{
+	generic::{BlockId, SignedBlock},
+	traits::{Block as BlockT, Header as HeaderT},
+	Justifications,
+};
+use std::{path::Path, time::Duration};
+
+const LOG_TARGET: &str = "first-block";
+
+/// How long the import queue may take to import the first block, after which its worker is
+/// assumed dead.
+const IMPORT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Read the SCALE encoded block in the file at `path`, optionally followed by its justifications
+/// like in the files of `export-blocks --binary`.
+pub fn read(path: &Path) -> Result<SignedBlock<Block>, String> {
+	let encoded = std::fs::read(path)
+		.map_err(|e| format!("Failed to read the first block from {}: {}", path.display(), e))?;
+	decode(&encoded).map_err(|e| format!("Invalid first block in {}: {}", path.display(), e))
+}
+
+fn decode(mut input: &[u8]) -> Result<SignedBlock<Block>, codec::Error> {
+	let block = Block::decode(&mut input)?;
+	let justifications =
+		if input.is_empty() { None } else { Option::<Justifications>::decode(&mut input)? };
+	if !input.is_empty() {
+		return Err("Unexpected bytes after the block".into())
+	}
+	Ok(SignedBlock { block, justifications })
+}
+
+/// Import `block` with `import_queue`, which must not be polled by anything else yet.
+///
+/// The block must be the child of the genesis block. Nothing is done when the database already
+/// has it, so the node restarts as usual.
+pub fn import<C: HeaderBackend<Block>>(
+	client: &C,
+	import_queue: &mut dyn ImportQueue<Block>,
+	block: SignedBlock<Block>,
+) -> Result<(), String> {
+	import_within(client, import_queue, block, IMPORT_TIMEOUT)
+}
+
+fn import_within<C: HeaderBackend<Block>>(
+	client: &C,
+	import_queue: &mut dyn ImportQueue<Block>,
+	block: SignedBlock<Block>,
+	timeout: Duration,
+) -> Result<(), String> {
+	let header = block.block.header();
+	let hash = header.hash();
+	let genesis_hash = client.info().genesis_hash;
+	if *header.number() != 1 || *header.parent_hash() != genesis_hash {
+		return Err(format!(
+			"The first block #{} ({}) doesn't build on the genesis block {}, its parent is {}",
+			header.number(),
+			hash,
+			genesis_hash,
+			header.parent_hash(),
+		))
+	}
+
+	if client.header(BlockId::Hash(hash)).map_err(|e| e.to_string())?.is_some() {
+		log::info!(target: LOG_TARGET, "The first block {} is already imported", hash);
+		return Ok(())
+	}
+	if let Some(existing) = client.hash(1).map_err(|e| e.to_string())? {
+		return Err(format!(
+			"The database already has a different block #1 ({}) than the first block {}",
+			existing, hash,
+		))
+	}
+
+	log::info!(target: LOG_TARGET, "📦 Importing the first block {}", hash);
+	let (header, body) = block.block.deconstruct();
+	import_queue.import_blocks(
+		BlockOrigin::File,
+		vec![IncomingBlock {
+			hash,
+			header: Some(header),
+			body: Some(body),
+			indexed_body: None,
+			justifications: block.justifications,
+			origin: None,
+			allow_missing_state: false,
+			skip_execution: false,
+			import_existing: false,
+			state: None,
+		}],
+	);
+
+	let mut link = ResultLink(None);
+	let imported = futures::future::poll_fn(|cx| {
+		import_queue.poll_actions(cx, &mut link);
+		match link.0.take() {
+			Some(result) => Poll::Ready(result),
+			None => Poll::Pending,
+		}
+	});
+	// The import queue doesn't tell when its worker is gone, the result would never come.
+	match futures::executor::block_on(futures::future::select(imported, Delay::new(timeout))) {
+		Either::Left((result, _)) => result,
+		Either::Right(_) => Err(format!(
+			"The first block {} wasn't imported within {:?}, the import queue is stuck",
+			hash, timeout,
+		)),
+	}
+}
+
+/// Link keeping the result of the import of the block.
+struct ResultLink(Option<Result<(), String>>);
+
+impl Link<Block> for ResultLink {
+	fn blocks_processed(
+		&mut self,
+		_imported: usize,
+		_count: usize,
+		results: Vec<(Result<BlockImportStatus<BlockNumber>, BlockImportError>, Hash)>,
+	) {
+		if let Some((result, hash)) = results.into_iter().next() {
+			self.0 = Some(
+				result
+					.map(|_| ())
+					.map_err(|e| format!("Failed to import the first block {}: {:?}", hash, e)),
+			);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use node_primitives::Header;
+	use sc_client_api::{in_mem::Blockchain, NewBlockState};
+	use sc_consensus::ImportedAux;
+
+	/// Import queue recording the imported blocks, and answering with `result` once polled.
+	#[derive(Default)]
+	struct TestQueue {
+		imported: Vec<(BlockOrigin, Hash)>,
+		result: Option<Result<BlockImportStatus<BlockNumber>, BlockImportError>>,
+	}
+
+	impl ImportQueue<Block> for TestQueue {
+		fn import_blocks(&mut self, origin: BlockOrigin, blocks: Vec<IncomingBlock<Block>>) {
+			self.imported.extend(blocks.into_iter().map(|block| (origin, block.hash)));
+		}
+
+		fn import_justifications(
+			&mut self,
+			_who: sc_consensus::import_queue::Origin,
+			_hash: Hash,
+			_number: BlockNumber,
+			_justifications: Justifications,
+		) {
+		}
+
+		fn poll_actions(&mut self, _cx: &mut futures::task::Context, link: &mut dyn Link<Block>) {
+			if let (Some(result), Some((_, hash))) = (self.result.take(), self.imported.last()) {
+				link.blocks_processed(1, 1, vec![(result, *hash)]);
+			}
+		}
+	}
+
+	fn header(number: BlockNumber, parent_hash: Hash) -> Header {
+		Header::new(number, Default::default(), Default::default(), parent_hash, Default::default())
+	}
+
+	/// A chain with only a genesis block, and the first block building on it.
+	fn chain() -> (Blockchain<Block>, SignedBlock<Block>) {
+		let blockchain = Blockchain::new();
+		let genesis = header(0, Default::default());
+		let genesis_hash = genesis.hash();
+		blockchain
+			.insert(genesis_hash, genesis, None, Some(Vec::new()), NewBlockState::Final)
+			.unwrap();
+		let block = Block::new(header(1, genesis_hash), Vec::new());
+		(blockchain, SignedBlock { block, justifications: None })
+	}
+
+	fn imported() -> Result<BlockImportStatus<BlockNumber>, BlockImportError> {
+		Ok(BlockImportStatus::ImportedUnknown(1, ImportedAux::default(), None))
+	}
+
+	#[test]
+	fn imports_the_first_block_from_a_file() {
+		let (client, block) = chain();
+		let hash = block.block.hash();
+		let mut queue = TestQueue { result: Some(imported()), ..Default::default() };
+
+		import(&client, &mut queue, block).unwrap();
+		assert_eq!(queue.imported, vec![(BlockOrigin::File, hash)]);
+	}
+
+	#[test]
+	fn reports_the_import_errors() {
+		let (client, block) = chain();
+		let mut queue =
+			TestQueue { result: Some(Err(BlockImportError::BadBlock(None))), ..Default::default() };
+
+		assert!(import(&client, &mut queue, block).is_err());
+	}
+
+	#[test]
+	fn gives_up_on_a_stuck_import_queue() {
+		let (client, block) = chain();
+		let mut queue = TestQueue::default();
+
+		let error = import_within(&client, &mut queue, block, Duration::from_millis(50));
+		assert!(error.unwrap_err().contains("stuck"));
+	}
+
+	#[test]
+	fn only_imports_a_child_of_the_genesis_block() {
+		let (client, _) = chain();
+		let block = Block::new(header(1, Hash::repeat_byte(1)), Vec::new());
+		let mut queue = TestQueue { result: Some(imported()), ..Default::default() };
+
+		assert!(import(&client, &mut queue, SignedBlock { block, justifications: None }).is_err());
+		assert!(queue.imported.is_empty());
+	}
+
+	#[test]
+	fn skips_the_first_block_already_imported() {
+		let (client, block) = chain();
+		let (header, body) = block.block.clone().deconstruct();
+		client
+			.insert(header.hash(), header, None, Some(body), NewBlockState::Best)
+			.unwrap();
+		let mut queue = TestQueue::default();
+
+		import(&client, &mut queue, block).unwrap();
+		assert!(queue.imported.is_empty());
+	}
+
+	#[test]
+	fn decodes_blocks_with_and_without_justifications() {
+		let header = Header::new(
+			1,
+			Default::default(),
+			Default::default(),
+			[1; 32].into(),
+			Default::default(),
+		);
+		let block = Block::new(header, Vec::new());
+		let justifications = Justifications::from((*b"FRNK", vec![1, 2, 3]));
+
+		assert_eq!(decode(&block.encode()).unwrap().justifications, None);
+
+		let signed = SignedBlock { block, justifications: Some(justifications.clone()) };
+		assert_eq!(decode(&signed.encode()).unwrap().justifications, Some(justifications));
+
+		let mut trailing = signed.encode();
+		trailing.push(0);
+		assert!(decode(&trailing).is_err());
+	}
+}
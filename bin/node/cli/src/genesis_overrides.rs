@@ -0,0 +1,116 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Overrides of genesis storage values given on the command line.
+//!
+//! The overrides are applied on top of the genesis storage of the chain spec when the node starts
+//! with an empty database, which gives a different genesis block than the one of the chain spec.
+
+use node_primitives::Block;
+use sc_service::{chain_ops, Configuration};
+use sp_core::{bytes::from_hex, storage::well_known_keys};
+use sp_runtime::traits::Block as BlockT;
+use std::str::FromStr;
+
+const LOG_TARGET: &str = "genesis-overrides";
+
+/// A genesis storage value, replacing the one of the chain spec.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenesisOverride {
+	/// The storage key.
+	pub key: Vec<u8>,
+	/// The value stored at the key.
+	pub value: Vec<u8>,
+}
+
+impl FromStr for GenesisOverride {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (key, value) = s
+			.split_once('=')
+			.ok_or_else(|| format!("Invalid genesis override `{}`, expected `KEY=VALUE`", s))?;
+		let key = from_hex(key.trim())
+			.map_err(|e| format!("Invalid genesis override key `{}`: {}", key, e))?;
+		let value = from_hex(value.trim())
+			.map_err(|e| format!("Invalid genesis override value `{}`: {}", value, e))?;
+
+		if key.is_empty() {
+			return Err("The genesis override key is empty".into())
+		}
+		if well_known_keys::is_child_storage_key(&key) {
+			return Err(format!(
+				"The genesis override key `{}` is a child storage key, only the top storage can \
+				 be overridden",
+				s,
+			))
+		}
+
+		Ok(Self { key, value })
+	}
+}
+
+/// Apply `overrides` to the genesis storage of the chain spec of `node`, if the database of the
+/// node is empty.
+pub fn apply(overrides: &[GenesisOverride], node: &mut Configuration) -> Result<(), String> {
+	if !crate::genesis_snapshot::is_empty(&node.database)? {
+		log::info!(target: LOG_TARGET, "The database isn't empty, ignoring the genesis overrides");
+		return Ok(())
+	}
+
+	let mut storage = chain_ops::build_genesis_storage(&*node.chain_spec)
+		.map_err(|e| format!("Failed to build the genesis storage of the chain spec: {}", e))?;
+	let spec_hash = chain_ops::genesis_block::<Block>(storage.clone()).hash();
+	for genesis_override in overrides {
+		storage.top.insert(genesis_override.key.clone(), genesis_override.value.clone());
+	}
+	let hash = chain_ops::genesis_block::<Block>(storage.clone()).hash();
+
+	log::warn!(
+		target: LOG_TARGET,
+		"⚠️  Overriding {} genesis storage values, the genesis block is {} instead of {}",
+		overrides.len(),
+		hash,
+		spec_hash,
+	);
+	node.chain_spec.set_storage(storage);
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_hex_keys_and_values() {
+		assert_eq!("0x0102=0x".parse(), Ok(GenesisOverride { key: vec![1, 2], value: vec![] }),);
+		assert_eq!("0a=0b0c".parse(), Ok(GenesisOverride { key: vec![10], value: vec![11, 12] }));
+
+		assert!("0x0102".parse::<GenesisOverride>().is_err());
+		assert!("0xzz=0x00".parse::<GenesisOverride>().is_err());
+		assert!("0x=0x00".parse::<GenesisOverride>().is_err());
+		let child_key =
+			format!("0x{}=0x00", hex(well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX));
+		assert!(child_key.parse::<GenesisOverride>().is_err());
+	}
+
+	fn hex(bytes: &[u8]) -> String {
+		bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+	}
+}
@@ -0,0 +1,213 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Initialization of a fresh database from a genesis snapshot.
+//!
+//! The snapshot is a chain spec, usually with a raw genesis state, downloaded when the node
+//! starts with an empty database. Its genesis storage replaces the one of the local chain spec,
+//! whose other fields like the boot nodes are kept, after checking that it gives the expected
+//! genesis block.
+
+use hyper::{body::HttpBody, Client, Uri};
+use node_primitives::{Block, Hash};
+use sc_service::{chain_ops, ChainSpec, Configuration, DatabaseSource};
+use sp_core::storage::Storage;
+use sp_runtime::traits::Block as BlockT;
+use std::path::Path;
+
+const LOG_TARGET: &str = "genesis-snapshot";
+
+/// Maximum size of a downloaded snapshot.
+const MAX_SNAPSHOT_SIZE: usize = 1024 * 1024 * 1024;
+
+/// Configuration of the genesis snapshot.
+#[derive(Clone, Debug)]
+pub struct Config {
+	/// The URL the snapshot is downloaded from.
+	pub url: Uri,
+	/// The hash of the genesis block the snapshot must give.
+	pub genesis_hash: Hash,
+	/// The maximum size of the snapshot, in bytes.
+	pub max_size: usize,
+}
+
+impl Config {
+	/// Create a new configuration, checking that `url` is a valid `http` URL and `genesis_hash` a
+	/// valid hash.
+	pub fn new(url: &str, genesis_hash: &str) -> Result<Self, String> {
+		let url: Uri = url.parse().map_err(|e| format!("Invalid genesis snapshot URL: {}", e))?;
+		let genesis_hash = genesis_hash
+			.parse()
+			.map_err(|e| format!("Invalid genesis hash `{}`: {:?}", genesis_hash, e))?;
+
+		match url.scheme_str() {
+			Some("http") => {},
+			_ =>
+				return Err(format!(
+					"Unsupported genesis snapshot URL `{}`, expected `http://`",
+					url
+				)),
+		}
+
+		Ok(Self { url, genesis_hash, max_size: MAX_SNAPSHOT_SIZE })
+	}
+}
+
+/// Replace the genesis storage of the chain spec of `node` with the one of the snapshot, if the
+/// database of the node is empty.
+pub async fn load(config: &Config, node: &mut Configuration) -> Result<(), String> {
+	if !is_empty(&node.database)? {
+		log::info!(
+			target: LOG_TARGET,
+			"The database isn't empty, ignoring the genesis snapshot at {}",
+			config.url,
+		);
+		return Ok(())
+	}
+
+	log::info!(target: LOG_TARGET, "📥 Downloading the genesis snapshot from {}", config.url);
+	let storage = download(config).await?;
+	node.chain_spec.set_storage(storage);
+	log::info!(target: LOG_TARGET, "Initializing the database from the genesis snapshot");
+
+	Ok(())
+}
+
+/// Download the snapshot and build its genesis storage, checking that it gives the expected
+/// genesis block.
+async fn download(config: &Config) -> Result<Storage, String> {
+	let error = |e| format!("Failed to download the genesis snapshot: {}", e);
+	let mut response = Client::new().get(config.url.clone()).await.map_err(error)?;
+	if !response.status().is_success() {
+		return Err(format!("Failed to download the genesis snapshot: {}", response.status()))
+	}
+
+	let too_large = || format!("The genesis snapshot is larger than {} bytes", config.max_size);
+	let mut json = Vec::new();
+	while let Some(chunk) = response.body_mut().data().await {
+		let chunk = chunk.map_err(error)?;
+		if json.len() + chunk.len() > config.max_size {
+			return Err(too_large())
+		}
+		json.extend_from_slice(&chunk);
+	}
+
+	let snapshot = crate::chain_spec::ChainSpec::from_json_bytes(json)
+		.map_err(|e| format!("Invalid genesis snapshot: {}", e))?;
+	let storage = chain_ops::build_genesis_storage(&snapshot as &dyn ChainSpec)
+		.map_err(|e| format!("Invalid genesis snapshot: {}", e))?;
+	let snapshot_hash = chain_ops::genesis_block::<Block>(storage.clone()).hash();
+	if snapshot_hash != config.genesis_hash {
+		return Err(format!(
+			"The genesis snapshot is for the genesis block {}, expected {}",
+			snapshot_hash, config.genesis_hash,
+		))
+	}
+
+	Ok(storage)
+}
+
+/// Whether the database doesn't exist yet, or is an empty directory.
+pub(crate) fn is_empty(database: &DatabaseSource) -> Result<bool, String> {
+	let paths = match database {
+		DatabaseSource::Auto { paritydb_path, rocksdb_path, .. } =>
+			vec![paritydb_path, rocksdb_path],
+		DatabaseSource::RocksDb { path, .. } | DatabaseSource::ParityDb { path } => vec![path],
+		DatabaseSource::Custom(_) =>
+			return Err("Genesis snapshots need a database stored in a directory".into()),
+	};
+	for path in paths {
+		match dir_is_empty(path) {
+			Ok(true) => {},
+			Ok(false) => return Ok(false),
+			Err(e) => return Err(format!("Failed to read the database directory: {}", e)),
+		}
+	}
+	Ok(true)
+}
+
+fn dir_is_empty(path: &Path) -> std::io::Result<bool> {
+	match std::fs::read_dir(path) {
+		Ok(mut entries) => Ok(entries.next().is_none()),
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(true),
+		Err(e) => Err(e),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tokio::{io::AsyncWriteExt, net::TcpListener};
+
+	/// Serve `body` once over HTTP, returning the URL it is served at.
+	async fn serve(body: &'static [u8]) -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let url = format!("http://{}/spec.json", listener.local_addr().unwrap());
+		tokio::spawn(async move {
+			let (mut stream, _) = listener.accept().await.unwrap();
+			let head = format!(
+				"HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+				body.len()
+			);
+			stream.write_all(head.as_bytes()).await.unwrap();
+			stream.write_all(body).await.unwrap();
+		});
+		url
+	}
+
+	const SPEC: &[u8] = include_bytes!("../res/flaming-fir.json");
+
+	fn genesis_hash() -> Hash {
+		let spec = crate::chain_spec::flaming_fir_config().unwrap();
+		let storage = chain_ops::build_genesis_storage(&spec as &dyn ChainSpec).unwrap();
+		chain_ops::genesis_block::<Block>(storage).hash()
+	}
+
+	#[tokio::test]
+	async fn downloads_the_snapshot_of_the_expected_genesis() {
+		let config = Config::new(&serve(SPEC).await, &format!("{:?}", genesis_hash())).unwrap();
+		let storage = download(&config).await.unwrap();
+		assert_eq!(chain_ops::genesis_block::<Block>(storage).hash(), genesis_hash());
+	}
+
+	#[tokio::test]
+	async fn rejects_snapshots_of_other_genesis() {
+		let other = format!("{:?}", Hash::repeat_byte(1));
+		let config = Config::new(&serve(SPEC).await, &other).unwrap();
+		assert!(download(&config).await.unwrap_err().contains("is for the genesis block"));
+	}
+
+	#[tokio::test]
+	async fn rejects_snapshots_larger_than_the_limit() {
+		let mut config = Config::new(&serve(SPEC).await, &format!("{:?}", genesis_hash())).unwrap();
+		config.max_size = SPEC.len() - 1;
+		assert!(download(&config).await.unwrap_err().contains("is larger than"));
+	}
+
+	#[test]
+	fn only_empty_databases_are_initialized() {
+		let dir = tempfile::tempdir().unwrap();
+		let database = |path: &Path| DatabaseSource::RocksDb { path: path.into(), cache_size: 0 };
+
+		assert_eq!(is_empty(&database(&dir.path().join("db"))), Ok(true));
+		assert_eq!(is_empty(&database(dir.path())), Ok(true));
+
+		std::fs::write(dir.path().join("CURRENT"), b"").unwrap();
+		assert_eq!(is_empty(&database(dir.path())), Ok(false));
+	}
+}
@@ -0,0 +1,133 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Logging of the digests of the imported headers.
+//!
+//! The digest items of the BABE and GRANDPA engines are decoded, the items of other engines and
+//! the items that fail to decode are logged as hex.
+
+use codec::Decode;
+use futures::prelude::*;
+use node_primitives::{BlockNumber, Hash, Header};
+use sc_client_api::BlockchainEvents;
+use sp_consensus_babe::{digests::PreDigest, AuthoritySignature, BABE_ENGINE_ID};
+use sp_core::hexdisplay::HexDisplay;
+use sp_runtime::{generic::DigestItem, traits::Header as HeaderT, ConsensusEngineId};
+use std::sync::Arc;
+
+const LOG_TARGET: &str = "header-digests";
+
+/// Log the digest items of every header imported by `client`, at debug level.
+pub async fn run<C>(client: Arc<C>)
+where
+	C: BlockchainEvents<node_primitives::Block>,
+{
+	let mut imports = client.import_notification_stream();
+	while let Some(notification) = imports.next().await {
+		log_digest(&notification.header);
+	}
+}
+
+fn log_digest(header: &Header) {
+	for item in header.digest().logs() {
+		log::debug!(
+			target: LOG_TARGET,
+			"#{} ({}): {}",
+			header.number(),
+			header.hash(),
+			describe(item),
+		);
+	}
+}
+
+/// A human readable description of a digest item.
+fn describe(item: &DigestItem<Hash>) -> String {
+	match item {
+		DigestItem::PreRuntime(engine, data) =>
+			format!("pre-runtime {}", describe_engine_data(engine, data, describe_pre_runtime)),
+		DigestItem::Consensus(engine, data) =>
+			format!("consensus {}", describe_engine_data(engine, data, describe_consensus)),
+		DigestItem::Seal(engine, data) =>
+			format!("seal {}", describe_engine_data(engine, data, describe_seal)),
+		DigestItem::ChangesTrieRoot(root) => format!("changes trie root {}", root),
+		DigestItem::ChangesTrieSignal(signal) => format!("changes trie signal {:?}", signal),
+		DigestItem::Other(data) => format!("other 0x{}", HexDisplay::from(data)),
+	}
+}
+
+fn describe_engine_data(
+	engine: &ConsensusEngineId,
+	data: &[u8],
+	decode: fn(&ConsensusEngineId, &[u8]) -> Option<String>,
+) -> String {
+	let decoded = decode(engine, data).unwrap_or_else(|| format!("0x{}", HexDisplay::from(&data)));
+	format!("{}: {}", String::from_utf8_lossy(engine), decoded)
+}
+
+fn describe_pre_runtime(engine: &ConsensusEngineId, mut data: &[u8]) -> Option<String> {
+	match *engine {
+		BABE_ENGINE_ID => PreDigest::decode(&mut data).ok().map(|digest| format!("{:?}", digest)),
+		_ => None,
+	}
+}
+
+fn describe_consensus(engine: &ConsensusEngineId, mut data: &[u8]) -> Option<String> {
+	use sp_consensus_babe::ConsensusLog as BabeLog;
+
+	match *engine {
+		// The BABE log doesn't implement `Debug`.
+		BABE_ENGINE_ID => BabeLog::decode(&mut data).ok().map(|log| match log {
+			BabeLog::NextEpochData(descriptor) => format!("NextEpochData({:?})", descriptor),
+			BabeLog::OnDisabled(index) => format!("OnDisabled({})", index),
+			BabeLog::NextConfigData(descriptor) => format!("NextConfigData({:?})", descriptor),
+		}),
+		grandpa_primitives::GRANDPA_ENGINE_ID =>
+			grandpa_primitives::ConsensusLog::<BlockNumber>::decode(&mut data)
+				.ok()
+				.map(|log| format!("{:?}", log)),
+		_ => None,
+	}
+}
+
+fn describe_seal(engine: &ConsensusEngineId, mut data: &[u8]) -> Option<String> {
+	match *engine {
+		BABE_ENGINE_ID => AuthoritySignature::decode(&mut data)
+			.ok()
+			.map(|signature| format!("{:?}", signature)),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+
+	#[test]
+	fn describes_known_and_unknown_items() {
+		let log = grandpa_primitives::ConsensusLog::<BlockNumber>::OnDisabled(3);
+		let item = DigestItem::Consensus(grandpa_primitives::GRANDPA_ENGINE_ID, log.encode());
+		assert_eq!(describe(&item), "consensus FRNK: OnDisabled(3)");
+
+		let item = DigestItem::Consensus(BABE_ENGINE_ID, vec![0xff]);
+		assert_eq!(describe(&item), "consensus BABE: 0xff");
+
+		let item = DigestItem::Seal(*b"TEST", vec![1, 2]);
+		assert_eq!(describe(&item), "seal TEST: 0x0102");
+	}
+}
@@ -0,0 +1,284 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Export and import of the keystore as an encrypted bundle.
+//!
+//! The bundle holds the secret URIs of all the keys of the keystore, encrypted with
+//! ChaCha20-Poly1305 under a key derived from a passphrase with PBKDF2. The keys are only ever
+//! held in memory in plaintext, and wiped once the command is done with them.
+
+use crate::cli::{KeystoreExportCmd, KeystoreImportCmd};
+use chacha20poly1305::{
+	aead::{Aead, NewAead},
+	ChaCha20Poly1305, Key, Nonce,
+};
+use codec::{Decode, Encode};
+use hmac::Hmac;
+use rand::{rngs::OsRng, RngCore};
+use sc_cli::{CliConfiguration, KeystoreParams, SharedParams};
+use sc_keystore::LocalKeystore;
+use sc_service::config::KeystoreConfig;
+use sha2::Sha512;
+use sp_core::crypto::KeyTypeId;
+use sp_keystore::SyncCryptoStore;
+use std::{fs, io::Write, path::Path};
+use zeroize::{Zeroize, Zeroizing};
+
+/// Prefix of the bundles, followed by the salt, the nonce and the encrypted keys.
+const MAGIC: &[u8] = b"substrate-keystore-bundle-v1\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const PBKDF2_ROUNDS: u32 = 100_000;
+
+/// A key of the keystore.
+#[derive(Encode, Decode)]
+struct Entry {
+	key_type: [u8; 4],
+	public: Vec<u8>,
+	suri: String,
+}
+
+impl Drop for Entry {
+	fn drop(&mut self) {
+		self.suri.zeroize();
+	}
+}
+
+impl KeystoreExportCmd {
+	/// Write all the keys of the keystore of `config` to an encrypted bundle.
+	pub fn run(&self, config: &KeystoreConfig) -> sc_cli::Result<()> {
+		if self.output.exists() {
+			return Err(format!("{} already exists", self.output.display()).into())
+		}
+		let entries = read_keystore(keystore_path(config)?)?;
+		let passphrase = read_passphrase(self.passphrase_file.as_deref(), true)?;
+
+		let bundle = seal(&entries, &passphrase)?;
+		write_private(&self.output, &bundle)?;
+		println!("Exported {} keys to {}", entries.len(), self.output.display());
+
+		Ok(())
+	}
+}
+
+impl KeystoreImportCmd {
+	/// Insert all the keys of an encrypted bundle into the keystore of `config`.
+	pub fn run(&self, config: &KeystoreConfig) -> sc_cli::Result<()> {
+		let bundle = fs::read(&self.input)
+			.map_err(|e| format!("Failed to read {}: {}", self.input.display(), e))?;
+		let passphrase = read_passphrase(self.passphrase_file.as_deref(), false)?;
+		let entries = open(&bundle, &passphrase)?;
+
+		let password = match config {
+			KeystoreConfig::Path { password, .. } => password.clone(),
+			KeystoreConfig::InMemory => None,
+		};
+		let keystore = LocalKeystore::open(keystore_path(config)?, password)?;
+		let imported = write_keystore(&keystore, &entries)?;
+		println!(
+			"Imported {} keys, {} were already in the keystore",
+			imported,
+			entries.len() - imported,
+		);
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for KeystoreExportCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		Some(&self.keystore_params)
+	}
+}
+
+impl CliConfiguration for KeystoreImportCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		Some(&self.keystore_params)
+	}
+}
+
+fn keystore_path(config: &KeystoreConfig) -> Result<&Path, String> {
+	match config {
+		KeystoreConfig::Path { path, .. } => Ok(path),
+		KeystoreConfig::InMemory => Err("The keystore isn't stored on disk".into()),
+	}
+}
+
+/// Read the keys of the keystore at `path`, whose files are named after the hex encoded key type
+/// and public key, and hold the JSON encoded secret URI.
+fn read_keystore(path: &Path) -> Result<Vec<Entry>, String> {
+	let error = |e: std::io::Error| format!("Failed to read the keystore: {}", e);
+
+	let mut entries = Vec::new();
+	for file in fs::read_dir(path).map_err(error)? {
+		let file = file.map_err(error)?;
+		let name =
+			match file.file_name().to_str().and_then(|name| sp_core::bytes::from_hex(name).ok()) {
+				Some(name) if name.len() > 4 => name,
+				// Not a key.
+				_ => continue,
+			};
+
+		let mut key_type = [0; 4];
+		key_type.copy_from_slice(&name[..4]);
+		let contents = Zeroizing::new(fs::read(file.path()).map_err(error)?);
+		let suri = serde_json::from_slice(&contents)
+			.map_err(|e| format!("Invalid key file {}: {}", file.path().display(), e))?;
+		entries.push(Entry { key_type, public: name[4..].to_vec(), suri });
+	}
+
+	Ok(entries)
+}
+
+/// Insert the `entries` missing from `keystore`, returning how many were inserted.
+fn write_keystore(keystore: &LocalKeystore, entries: &[Entry]) -> Result<usize, String> {
+	let mut imported = 0;
+	for entry in entries {
+		let key_type = KeyTypeId(entry.key_type);
+		if SyncCryptoStore::has_keys(keystore, &[(entry.public.clone(), key_type)]) {
+			continue
+		}
+		SyncCryptoStore::insert_unknown(keystore, key_type, &entry.suri, &entry.public)
+			.map_err(|()| "Failed to write to the keystore")?;
+		imported += 1;
+	}
+
+	Ok(imported)
+}
+
+/// Read the passphrase from `file`, or prompt for it, twice with `confirm`.
+fn read_passphrase(file: Option<&Path>, confirm: bool) -> Result<Zeroizing<String>, String> {
+	let passphrase = match file {
+		Some(file) => {
+			let mut passphrase = Zeroizing::new(
+				fs::read_to_string(file)
+					.map_err(|e| format!("Failed to read {}: {}", file.display(), e))?,
+			);
+			let len = passphrase.trim_end_matches(&['\r', '\n'][..]).len();
+			passphrase.truncate(len);
+			passphrase
+		},
+		None => {
+			let prompt = |prompt| {
+				rpassword::read_password_from_tty(Some(prompt))
+					.map(Zeroizing::new)
+					.map_err(|e| format!("Failed to read the passphrase: {}", e))
+			};
+			let passphrase = prompt("Bundle passphrase: ")?;
+			if confirm && *prompt("Repeat the bundle passphrase: ")? != *passphrase {
+				return Err("The passphrases don't match".into())
+			}
+			passphrase
+		},
+	};
+
+	if passphrase.is_empty() {
+		return Err("The passphrase can't be empty".into())
+	}
+	Ok(passphrase)
+}
+
+fn cipher(passphrase: &str, salt: &[u8]) -> ChaCha20Poly1305 {
+	let mut key = Zeroizing::new([0; 32]);
+	pbkdf2::pbkdf2::<Hmac<Sha512>>(passphrase.as_bytes(), salt, PBKDF2_ROUNDS, &mut key[..]);
+	ChaCha20Poly1305::new(Key::from_slice(&key[..]))
+}
+
+/// Encrypt the `entries` with `passphrase`.
+fn seal(entries: &[Entry], passphrase: &str) -> Result<Vec<u8>, String> {
+	let mut salt = [0; SALT_LEN];
+	let mut nonce = [0; NONCE_LEN];
+	OsRng.fill_bytes(&mut salt);
+	OsRng.fill_bytes(&mut nonce);
+
+	let plaintext = Zeroizing::new(entries.encode());
+	let ciphertext = cipher(passphrase, &salt)
+		.encrypt(Nonce::from_slice(&nonce), &plaintext[..])
+		.map_err(|_| "Failed to encrypt the keys")?;
+
+	Ok([MAGIC, &salt, &nonce, &ciphertext].concat())
+}
+
+/// Decrypt the entries of `bundle` with `passphrase`.
+fn open(bundle: &[u8], passphrase: &str) -> Result<Vec<Entry>, String> {
+	let bundle = bundle.strip_prefix(MAGIC).ok_or("Not a keystore bundle")?;
+	if bundle.len() < SALT_LEN + NONCE_LEN {
+		return Err("The keystore bundle is truncated".into())
+	}
+	let (salt, bundle) = bundle.split_at(SALT_LEN);
+	let (nonce, ciphertext) = bundle.split_at(NONCE_LEN);
+
+	let plaintext = Zeroizing::new(
+		cipher(passphrase, salt)
+			.decrypt(Nonce::from_slice(nonce), ciphertext)
+			.map_err(|_| "Wrong passphrase, or corrupted keystore bundle")?,
+	);
+	Decode::decode(&mut &plaintext[..]).map_err(|e| format!("Invalid keystore bundle: {}", e))
+}
+
+/// Write `contents` to a new file only readable by the current user.
+fn write_private(path: &Path, contents: &[u8]) -> Result<(), String> {
+	let mut options = fs::OpenOptions::new();
+	options.write(true).create_new(true);
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::OpenOptionsExt;
+		options.mode(0o600);
+	}
+
+	options
+		.open(path)
+		.and_then(|mut file| file.write_all(contents))
+		.map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::{
+		crypto::{key_types::BABE, Pair},
+		sr25519,
+	};
+
+	#[test]
+	fn bundle_roundtrip() {
+		let source = tempfile::tempdir().unwrap();
+		let suri = "//Alice";
+		let public = sr25519::Pair::from_string(suri, None).unwrap().public();
+		let keystore = LocalKeystore::open(source.path(), None).unwrap();
+		SyncCryptoStore::insert_unknown(&keystore, BABE, suri, public.as_ref()).unwrap();
+
+		let bundle = seal(&read_keystore(source.path()).unwrap(), "passphrase").unwrap();
+		assert!(open(&bundle, "wrong").is_err());
+		let entries = open(&bundle, "passphrase").unwrap();
+
+		let target = tempfile::tempdir().unwrap();
+		let keystore = LocalKeystore::open(target.path(), None).unwrap();
+		assert_eq!(write_keystore(&keystore, &entries), Ok(1));
+		assert!(SyncCryptoStore::has_keys(&keystore, &[(public.to_vec(), BABE)]));
+		assert_eq!(write_keystore(&keystore, &entries), Ok(0));
+	}
+}
@@ -29,15 +29,88 @@
 //! hasn't been tested.
 
 #![warn(missing_docs)]
+// The `json!` of the node options in `print_config` goes beyond the default limit.
+#![recursion_limit = "256"]
 
 pub mod chain_spec;
 
+#[cfg(feature = "cli")]
+mod arg_enums;
+#[cfg(feature = "cli")]
+mod author_schedule;
+#[cfg(feature = "cli")]
+mod authoring_backoff;
+#[cfg(feature = "cli")]
+mod babe_epochs;
+#[cfg(feature = "cli")]
+mod banned_authorities;
+#[cfg(feature = "cli")]
+mod clock_skew;
+#[cfg(feature = "cli")]
+mod disk_space;
+#[cfg(feature = "cli")]
+mod event_sink;
+#[cfg(feature = "cli")]
+mod file_output;
+#[cfg(feature = "cli")]
+mod finalized_webhook;
+#[cfg(feature = "cli")]
+mod first_block;
+#[cfg(feature = "cli")]
+mod genesis_overrides;
+#[cfg(feature = "cli")]
+mod genesis_snapshot;
+#[cfg(feature = "cli")]
+mod header_digests;
+#[cfg(feature = "cli")]
+mod metrics_dump;
+#[cfg(feature = "cli")]
+mod metrics_push;
+#[cfg(feature = "cli")]
+mod protocol_traffic;
+#[cfg(feature = "cli")]
+mod reorg_guard;
+#[cfg(feature = "cli")]
+mod rpc_proxy;
+#[cfg(feature = "cli")]
+mod runtime_version_check;
+#[cfg(feature = "cli")]
+mod select_chain;
+#[cfg(feature = "cli")]
+mod state_root_check;
+#[cfg(feature = "cli")]
+mod status_file;
+#[cfg(feature = "cli")]
+mod sync_metrics;
+#[cfg(feature = "cli")]
 #[macro_use]
 mod service;
 #[cfg(feature = "cli")]
 mod cli;
 #[cfg(feature = "cli")]
 mod command;
+#[cfg(feature = "cli")]
+mod decode_spec;
+#[cfg(feature = "cli")]
+mod keystore_bundle;
+#[cfg(feature = "cli")]
+mod offchain_replay;
+#[cfg(feature = "cli")]
+mod print_config;
+#[cfg(feature = "cli")]
+mod secrets;
+#[cfg(feature = "cli")]
+mod systemd;
+#[cfg(feature = "cli")]
+mod tail;
+#[cfg(all(test, feature = "cli"))]
+mod test_client;
+#[cfg(feature = "cli")]
+mod throughput;
+#[cfg(feature = "cli")]
+mod verify_justification;
+#[cfg(feature = "cli")]
+mod weigh_extrinsic;
 
 #[cfg(feature = "cli")]
 pub use cli::*;
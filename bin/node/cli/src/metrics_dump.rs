@@ -0,0 +1,91 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Export of the Prometheus metrics to a local file.
+//!
+//! For deployments where nothing can scrape the node or receive pushed metrics, the registry is
+//! encoded to a file at a fixed interval, to be collected later, see [`crate::file_output`].
+
+use crate::file_output::{replace, Config};
+use futures_timer::Delay;
+use prometheus_endpoint::{
+	prometheus::{Encoder, TextEncoder},
+	Registry,
+};
+use std::path::Path;
+
+const LOG_TARGET: &str = "metrics-dump";
+
+/// Write the content of `registry` to the configured file every `config.interval`.
+///
+/// A failed write is logged and retried on the next tick, it never terminates the task.
+pub async fn run(config: Config, registry: Registry) {
+	log::info!(
+		target: LOG_TARGET,
+		"〽️ Writing metrics to {} every {:?}",
+		config.path.display(),
+		config.interval,
+	);
+
+	loop {
+		Delay::new(config.interval).await;
+
+		if let Err(err) = dump(&config.path, &registry).await {
+			log::warn!(
+				target: LOG_TARGET,
+				"Failed to write metrics to {}: {}",
+				config.path.display(),
+				err,
+			);
+		}
+	}
+}
+
+async fn dump(path: &Path, registry: &Registry) -> Result<(), String> {
+	let mut buffer = Vec::new();
+	TextEncoder::new()
+		.encode(&registry.gather(), &mut buffer)
+		.map_err(|e| e.to_string())?;
+
+	replace(path, &buffer).await.map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use prometheus_endpoint::{register, Counter, U64};
+
+	#[tokio::test]
+	async fn dumps_the_registry() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("metrics.prom");
+		let registry = Registry::new();
+		let counter =
+			register(Counter::<U64>::new("dumped_total", "Test counter").unwrap(), &registry)
+				.unwrap();
+
+		counter.inc();
+		dump(&path, &registry).await.unwrap();
+		counter.inc();
+		dump(&path, &registry).await.unwrap();
+
+		let dumped = std::fs::read_to_string(&path).unwrap();
+		assert!(dumped.contains("dumped_total 2"), "{}", dumped);
+		assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+	}
+}
@@ -0,0 +1,117 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Push-based export of the Prometheus metrics.
+//!
+//! Some environments can't scrape the node, so instead the metrics registry is encoded and sent
+//! to a Prometheus Pushgateway at a fixed interval.
+
+use futures_timer::Delay;
+use hyper::{client::HttpConnector, header::CONTENT_TYPE, Body, Client, Method, Request, Uri};
+use prometheus_endpoint::{
+	prometheus::{Encoder, TextEncoder},
+	Registry,
+};
+use std::time::Duration;
+
+const LOG_TARGET: &str = "metrics-push";
+
+/// Configuration of the metrics push task.
+#[derive(Clone, Debug)]
+pub struct Config {
+	/// The URL the encoded metrics are posted to, e.g.
+	/// `http://localhost:9091/metrics/job/substrate`.
+	pub url: Uri,
+	/// The interval between two pushes.
+	pub interval: Duration,
+}
+
+impl Config {
+	/// Create a new configuration, checking that `url` is a valid `http` URL.
+	pub fn new(url: &str, interval: Duration) -> Result<Self, String> {
+		let url: Uri = url.parse().map_err(|e| format!("Invalid metrics push URL: {}", e))?;
+
+		match url.scheme_str() {
+			Some("http") => {},
+			_ => return Err(format!("Unsupported metrics push URL `{}`, expected `http://`", url)),
+		}
+
+		if interval.as_secs() == 0 {
+			return Err("The metrics push interval must be at least one second".into())
+		}
+
+		Ok(Self { url, interval })
+	}
+}
+
+/// Push the content of `registry` to the configured URL every `config.interval`.
+///
+/// A failed push is logged and retried on the next tick, it never terminates the task.
+pub async fn run(config: Config, registry: Registry) {
+	let client = Client::new();
+
+	log::info!(target: LOG_TARGET, "〽️ Pushing metrics to {} every {:?}", config.url, config.interval);
+
+	loop {
+		Delay::new(config.interval).await;
+
+		if let Err(err) = push(&client, &config.url, &registry).await {
+			log::warn!(target: LOG_TARGET, "Failed to push metrics to {}: {}", config.url, err);
+		}
+	}
+}
+
+async fn push(
+	client: &Client<HttpConnector>,
+	url: &Uri,
+	registry: &Registry,
+) -> Result<(), String> {
+	let encoder = TextEncoder::new();
+	let mut buffer = Vec::new();
+	encoder.encode(&registry.gather(), &mut buffer).map_err(|e| e.to_string())?;
+
+	let request = Request::builder()
+		.method(Method::POST)
+		.uri(url.clone())
+		.header(CONTENT_TYPE, encoder.format_type())
+		.body(Body::from(buffer))
+		.map_err(|e| e.to_string())?;
+
+	let response = client.request(request).await.map_err(|e| e.to_string())?;
+
+	if response.status().is_success() {
+		Ok(())
+	} else {
+		Err(format!("unexpected response status {}", response.status()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn config_rejects_invalid_urls() {
+		let interval = Duration::from_secs(15);
+
+		assert!(Config::new("http://localhost:9091/metrics/job/substrate", interval).is_ok());
+		assert!(Config::new("https://localhost:9091", interval).is_err());
+		assert!(Config::new("localhost:9091", interval).is_err());
+		assert!(Config::new("http://localhost:9091", Duration::from_secs(0)).is_err());
+	}
+}
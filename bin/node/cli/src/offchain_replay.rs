@@ -0,0 +1,391 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `offchain-replay` command.
+//!
+//! The offchain worker entrypoint of the runtime is executed on top of the state of a block with
+//! offchain externalities recording every interaction instead of performing it: the HTTP
+//! requests are never sent, the writes to the offchain storage only go to an overlay of the local
+//! database and the submitted transactions are only printed.
+
+use crate::cli::OffchainReplayCmd;
+use codec::Encode;
+use node_primitives::Block;
+use parking_lot::Mutex;
+use sc_cli::{CliConfiguration, ImportParams, KeystoreParams, SharedParams};
+use sc_client_api::{backend, CallExecutor, ExecutionStrategy, ExecutorProvider, HeaderBackend};
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_core::{
+	hexdisplay::HexDisplay,
+	offchain::{
+		self, DbExternalities, HttpError, HttpRequestId, HttpRequestStatus, OffchainDbExt,
+		OffchainStorage, OffchainWorkerExt, OpaqueNetworkState, StorageKind, Timestamp,
+		TransactionPoolExt,
+	},
+	OpaquePeerId,
+};
+use sp_externalities::Extensions;
+use sp_keystore::{KeystoreExt, SyncCryptoStorePtr};
+use sp_offchain::OffchainWorkerApi;
+use sp_runtime::generic::BlockId;
+use std::{collections::HashMap, sync::Arc};
+
+impl OffchainReplayCmd {
+	/// Run the offchain workers of the block and print the recorded interactions.
+	pub fn run<B, C>(
+		&self,
+		client: Arc<C>,
+		backend: &B,
+		keystore: SyncCryptoStorePtr,
+	) -> sc_cli::Result<()>
+	where
+		B: backend::Backend<Block>,
+		B::OffchainStorage: 'static,
+		C: HeaderBackend<Block> + ExecutorProvider<Block> + ProvideRuntimeApi<Block>,
+		C::Api: ApiExt<Block> + OffchainWorkerApi<Block>,
+	{
+		let block = self.block.parse::<Block>()?;
+		let header = client.header(block)?.ok_or_else(|| format!("Block {} not found", block))?;
+		let at = BlockId::Hash(header.hash());
+
+		// Before version 2 the entrypoint took the number of the block instead of its header.
+		let version = client
+			.runtime_api()
+			.api_version::<dyn OffchainWorkerApi<Block>>(&at)
+			.map_err(|e| e.to_string())?
+			.ok_or_else(|| format!("The runtime of block {} has no offchain workers", block))?;
+		let args = if version < 2 { header.number.encode() } else { header.encode() };
+
+		let recorder = Recorder::new(self.validator, backend.offchain_storage());
+		let mut extensions = Extensions::new();
+		extensions.register(OffchainWorkerExt::new(recorder.clone()));
+		extensions.register(OffchainDbExt::new(recorder.clone()));
+		extensions.register(TransactionPoolExt::new(recorder.clone()));
+		extensions.register(KeystoreExt(keystore));
+
+		let result = client.executor().call(
+			&at,
+			"OffchainWorkerApi_offchain_worker",
+			&args,
+			ExecutionStrategy::NativeWhenPossible,
+			Some(extensions),
+		);
+
+		for interaction in recorder.interactions() {
+			println!("{}", interaction);
+		}
+		result.map_err(|e| format!("The offchain workers of block {} failed: {}", block, e))?;
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for OffchainReplayCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		Some(&self.keystore_params)
+	}
+}
+
+/// Offchain externalities recording the interactions of the offchain workers.
+///
+/// The HTTP requests never get a response. The storage reads fall back to the persistent storage
+/// of the local database, which is never written to.
+struct Recorder<S> {
+	state: Arc<Mutex<State>>,
+	is_validator: bool,
+	persistent: Option<S>,
+}
+
+// Not derived, which would require `S: Clone` instead of `Option<S>: Clone`.
+impl<S: Clone> Clone for Recorder<S> {
+	fn clone(&self) -> Self {
+		Self {
+			state: self.state.clone(),
+			is_validator: self.is_validator,
+			persistent: self.persistent.clone(),
+		}
+	}
+}
+
+#[derive(Default)]
+struct State {
+	interactions: Vec<String>,
+	next_request: u16,
+	overlay: HashMap<(bool, Vec<u8>), Option<Vec<u8>>>,
+}
+
+impl<S: OffchainStorage> Recorder<S> {
+	fn new(is_validator: bool, persistent: Option<S>) -> Self {
+		Self { state: Default::default(), is_validator, persistent }
+	}
+
+	fn interactions(&self) -> Vec<String> {
+		self.state.lock().interactions.clone()
+	}
+
+	fn record(&self, interaction: String) {
+		self.state.lock().interactions.push(interaction);
+	}
+
+	fn get(&self, kind: StorageKind, key: &[u8]) -> Option<Vec<u8>> {
+		let persistent = kind == StorageKind::PERSISTENT;
+		if let Some(value) = self.state.lock().overlay.get(&(persistent, key.to_vec())) {
+			return value.clone()
+		}
+		match &self.persistent {
+			Some(storage) if persistent => storage.get(offchain::STORAGE_PREFIX, key),
+			_ => None,
+		}
+	}
+
+	fn put(&self, kind: StorageKind, key: &[u8], value: Option<&[u8]>) {
+		let persistent = kind == StorageKind::PERSISTENT;
+		self.state
+			.lock()
+			.overlay
+			.insert((persistent, key.to_vec()), value.map(<[u8]>::to_vec));
+	}
+}
+
+fn describe_kind(kind: StorageKind) -> &'static str {
+	match kind {
+		StorageKind::PERSISTENT => "persistent",
+		StorageKind::LOCAL => "local",
+	}
+}
+
+fn describe_value(value: Option<&[u8]>) -> String {
+	match value {
+		Some(value) => format!("0x{}", HexDisplay::from(&value)),
+		None => "none".into(),
+	}
+}
+
+impl<S: OffchainStorage> offchain::Externalities for Recorder<S> {
+	fn is_validator(&self) -> bool {
+		self.is_validator
+	}
+
+	fn network_state(&self) -> Result<OpaqueNetworkState, ()> {
+		self.record("network state: unavailable".into());
+		Err(())
+	}
+
+	fn timestamp(&mut self) -> Timestamp {
+		let now = std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.unwrap_or_default();
+		Timestamp::from_unix_millis(now.as_millis() as u64)
+	}
+
+	fn sleep_until(&mut self, deadline: Timestamp) {
+		self.record(format!("sleep until {}", deadline.unix_millis()));
+	}
+
+	fn random_seed(&mut self) -> [u8; 32] {
+		rand::random()
+	}
+
+	fn http_request_start(
+		&mut self,
+		method: &str,
+		uri: &str,
+		_meta: &[u8],
+	) -> Result<HttpRequestId, ()> {
+		let id = {
+			let mut state = self.state.lock();
+			let id = state.next_request;
+			state.next_request = id.checked_add(1).ok_or(())?;
+			id
+		};
+		self.record(format!("http #{}: {} {}", id, method, uri));
+		Ok(HttpRequestId(id))
+	}
+
+	fn http_request_add_header(
+		&mut self,
+		request_id: HttpRequestId,
+		name: &str,
+		value: &str,
+	) -> Result<(), ()> {
+		self.record(format!("http #{}: header {}: {}", request_id.0, name, value));
+		Ok(())
+	}
+
+	fn http_request_write_body(
+		&mut self,
+		request_id: HttpRequestId,
+		chunk: &[u8],
+		_deadline: Option<Timestamp>,
+	) -> Result<(), HttpError> {
+		if !chunk.is_empty() {
+			self.record(format!("http #{}: body 0x{}", request_id.0, HexDisplay::from(&chunk)));
+		}
+		Ok(())
+	}
+
+	fn http_response_wait(
+		&mut self,
+		ids: &[HttpRequestId],
+		_deadline: Option<Timestamp>,
+	) -> Vec<HttpRequestStatus> {
+		for id in ids {
+			self.record(format!("http #{}: no response", id.0));
+		}
+		ids.iter().map(|_| HttpRequestStatus::IoError).collect()
+	}
+
+	fn http_response_headers(&mut self, _request_id: HttpRequestId) -> Vec<(Vec<u8>, Vec<u8>)> {
+		Vec::new()
+	}
+
+	fn http_response_read_body(
+		&mut self,
+		_request_id: HttpRequestId,
+		_buffer: &mut [u8],
+		_deadline: Option<Timestamp>,
+	) -> Result<usize, HttpError> {
+		Err(HttpError::IoError)
+	}
+
+	fn set_authorized_nodes(&mut self, nodes: Vec<OpaquePeerId>, authorized_only: bool) {
+		self.record(format!(
+			"authorized nodes: {} nodes, authorized only: {}",
+			nodes.len(),
+			authorized_only,
+		));
+	}
+}
+
+impl<S: OffchainStorage> DbExternalities for Recorder<S> {
+	fn local_storage_set(&mut self, kind: StorageKind, key: &[u8], value: &[u8]) {
+		self.record(format!(
+			"storage {} set 0x{} = 0x{}",
+			describe_kind(kind),
+			HexDisplay::from(&key),
+			HexDisplay::from(&value),
+		));
+		self.put(kind, key, Some(value));
+	}
+
+	fn local_storage_clear(&mut self, kind: StorageKind, key: &[u8]) {
+		self.record(format!("storage {} clear 0x{}", describe_kind(kind), HexDisplay::from(&key)));
+		self.put(kind, key, None);
+	}
+
+	fn local_storage_compare_and_set(
+		&mut self,
+		kind: StorageKind,
+		key: &[u8],
+		old_value: Option<&[u8]>,
+		new_value: &[u8],
+	) -> bool {
+		let swapped = self.get(kind, key).as_deref() == old_value;
+		self.record(format!(
+			"storage {} compare and set 0x{}: {} -> 0x{}{}",
+			describe_kind(kind),
+			HexDisplay::from(&key),
+			describe_value(old_value),
+			HexDisplay::from(&new_value),
+			if swapped { "" } else { " (unchanged)" },
+		));
+		if swapped {
+			self.put(kind, key, Some(new_value));
+		}
+		swapped
+	}
+
+	fn local_storage_get(&mut self, kind: StorageKind, key: &[u8]) -> Option<Vec<u8>> {
+		let value = self.get(kind, key);
+		self.record(format!(
+			"storage {} get 0x{} = {}",
+			describe_kind(kind),
+			HexDisplay::from(&key),
+			describe_value(value.as_deref()),
+		));
+		value
+	}
+}
+
+impl<S: OffchainStorage> offchain::TransactionPool for Recorder<S> {
+	fn submit_transaction(&mut self, extrinsic: Vec<u8>) -> Result<(), ()> {
+		self.record(format!("transaction 0x{}", HexDisplay::from(&extrinsic)));
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::offchain::{storage::InMemOffchainStorage, Externalities as _};
+
+	#[test]
+	fn records_storage_over_the_local_database() {
+		let mut storage = InMemOffchainStorage::default();
+		storage.set(offchain::STORAGE_PREFIX, b"key", b"old");
+		let mut recorder = Recorder::new(false, Some(storage.clone()));
+
+		assert_eq!(
+			recorder.local_storage_get(StorageKind::PERSISTENT, b"key"),
+			Some(b"old".to_vec())
+		);
+		assert!(!recorder.local_storage_compare_and_set(
+			StorageKind::PERSISTENT,
+			b"key",
+			None,
+			b"x"
+		));
+		recorder.local_storage_set(StorageKind::PERSISTENT, b"key", b"new");
+		assert_eq!(
+			recorder.local_storage_get(StorageKind::PERSISTENT, b"key"),
+			Some(b"new".to_vec())
+		);
+		assert_eq!(recorder.local_storage_get(StorageKind::LOCAL, b"key"), None);
+
+		// The database itself is never written to.
+		assert_eq!(storage.get(offchain::STORAGE_PREFIX, b"key"), Some(b"old".to_vec()));
+		assert_eq!(recorder.interactions().len(), 5);
+	}
+
+	#[test]
+	fn records_http_requests_without_responses() {
+		let mut recorder = Recorder::<InMemOffchainStorage>::new(false, None);
+		let id = recorder.http_request_start("POST", "https://example.com", &[]).unwrap();
+		recorder.http_request_add_header(id, "Content-Type", "text/plain").unwrap();
+		recorder.http_request_write_body(id, b"hi", None).unwrap();
+
+		assert_eq!(recorder.http_response_wait(&[id], None), vec![HttpRequestStatus::IoError]);
+		assert_eq!(
+			recorder.interactions(),
+			vec![
+				"http #0: POST https://example.com",
+				"http #0: header Content-Type: text/plain",
+				"http #0: body 0x6869",
+				"http #0: no response",
+			],
+		);
+	}
+}
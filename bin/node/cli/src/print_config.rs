@@ -0,0 +1,215 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! JSON view of the effective node configuration.
+//!
+//! Secrets, like the keystore password or the development key seed, are never part of the
+//! output.
+
+use crate::service::{GrandpaStart, NodeOptions};
+use sc_service::{config::KeystoreConfig, Configuration};
+use serde_json::{json, Value};
+use sp_runtime::traits::Block as BlockT;
+use std::fmt::Display;
+
+fn strings<T: Display>(items: &[T]) -> Vec<String> {
+	items.iter().map(ToString::to_string).collect()
+}
+
+/// Describe the resolved `config` and node `options` as JSON.
+pub fn config_json(config: &Configuration, options: &NodeOptions) -> Value {
+	let network = &config.network;
+	let keystore_path = match config.keystore {
+		KeystoreConfig::Path { ref path, .. } => Some(path.display().to_string()),
+		KeystoreConfig::InMemory => None,
+	};
+	let wasm_runtime_overrides =
+		config.wasm_runtime_overrides.as_ref().map(|p| p.display().to_string());
+
+	json!({
+		"implName": config.impl_name,
+		"implVersion": config.impl_version,
+		"role": config.role.to_string(),
+		"basePath": config.base_path.as_ref().map(|p| p.path().display().to_string()),
+		"chain": {
+			"name": config.chain_spec.name(),
+			"id": config.chain_spec.id(),
+			"chainType": format!("{:?}", config.chain_spec.chain_type()),
+		},
+		"database": {
+			"source": config.database.to_string(),
+			"stateCacheSize": config.state_cache_size,
+			"stateCacheChildRatio": config.state_cache_child_ratio,
+			"statePruning": format!("{:?}", config.state_pruning),
+			"keepBlocks": format!("{:?}", config.keep_blocks),
+			"transactionStorage": format!("{:?}", config.transaction_storage),
+		},
+		"keystore": {
+			"path": keystore_path,
+			"remote": config.keystore_remote,
+		},
+		"execution": {
+			"wasmMethod": format!("{:?}", config.wasm_method),
+			"wasmRuntimeOverrides": wasm_runtime_overrides,
+			"strategies": format!("{:?}", config.execution_strategies),
+			"defaultHeapPages": config.default_heap_pages,
+			"maxRuntimeInstances": config.max_runtime_instances,
+		},
+		"network": {
+			"nodeName": network.node_name,
+			"listenAddresses": strings(&network.listen_addresses),
+			"publicAddresses": strings(&network.public_addresses),
+			"bootNodes": strings(&network.boot_nodes),
+			"reservedNodes": strings(&network.default_peers_set.reserved_nodes),
+			"nonReservedMode": format!("{:?}", network.default_peers_set.non_reserved_mode),
+			"inPeers": network.default_peers_set.in_peers,
+			"outPeers": network.default_peers_set.out_peers,
+			"syncMode": format!("{:?}", network.sync_mode),
+			"fastSyncPivotOffset": network.fast_sync_pivot_offset,
+			"maxParallelDownloads": network.max_parallel_downloads,
+			"maxConcurrentWarpRequests": network.max_concurrent_warp_requests,
+			"enableDhtRandomWalk": network.enable_dht_random_walk,
+			"allowNonGlobalsInDht": network.allow_non_globals_in_dht,
+			"kademliaDisjointQueryPaths": network.kademlia_disjoint_query_paths,
+			"ipfsServer": network.ipfs_server,
+		},
+		"rpc": {
+			"http": config.rpc_http.map(|a| a.to_string()),
+			"ws": config.rpc_ws.map(|a| a.to_string()),
+			"ipc": config.rpc_ipc,
+			"wsMaxConnections": config.rpc_ws_max_connections,
+			"httpThreads": config.rpc_http_threads,
+			"cors": config.rpc_cors,
+			"methods": format!("{:?}", config.rpc_methods),
+			"maxPayload": config.rpc_max_payload,
+			"maxRequestSize": config.rpc_max_request_size,
+			"maxResponseSize": config.rpc_max_response_size,
+			"rateLimit": config.rpc_rate_limit,
+			"maxSubscriptionsPerConnection": config.rpc_max_subscriptions_per_connection,
+			"confirmationDepth": config.rpc_confirmation_depth,
+			"healthWarmup": config.health_warmup.map(|d| d.as_secs()),
+			"modules": config.rpc_modules,
+		},
+		"prometheus": config.prometheus_config.as_ref().map(|c| match &c.unix_socket {
+			Some(path) => path.display().to_string(),
+			None => c.port.to_string(),
+		}),
+		"telemetryEndpoints": config.telemetry_endpoints,
+		"telemetryReconnect": {
+			"interval": config.telemetry_reconnect.interval.as_secs(),
+			"maxRetries": config.telemetry_reconnect.max_retries,
+		},
+		"transactionPool": format!("{:?}", config.transaction_pool),
+		"offchainWorker": {
+			"enabled": config.offchain_worker.enabled,
+			"indexingEnabled": config.offchain_worker.indexing_enabled,
+			"threads": config.offchain_worker.threads,
+			"httpConcurrency": config.offchain_worker.http_concurrency,
+		},
+		"forceAuthoring": config.force_authoring,
+		"disableGrandpa": config.disable_grandpa,
+		"announceBlock": config.announce_block,
+		"tracingTargets": config.tracing_targets,
+		"tracingReceiver": format!("{:?}", config.tracing_receiver),
+		"node": node_options_json(options),
+	})
+}
+
+fn node_options_json(options: &NodeOptions) -> Value {
+	json!({
+		"metricsPush": options.metrics_push.as_ref().map(|c| json!({
+			"url": c.url.to_string(),
+			"interval": c.interval.as_secs(),
+		})),
+		"metricsDump": options.metrics_dump.as_ref().map(|c| json!({
+			"path": c.path.display().to_string(),
+			"interval": c.interval.as_secs(),
+		})),
+		"rpcTlsCert": options.rpc_tls.as_ref().map(|c| c.cert.display().to_string()),
+		"rpcMaxConnections": options.rpc_max_connections,
+		"historicalSlotDuration": options.historical_slot_duration,
+		"maxTimestampDrift": options.max_timestamp_drift.map(|drift| drift.as_secs()),
+		"proposerSpawnMode": format!("{:?}", options.proposer_spawn_mode),
+		"grandpaVoterSpawnMode": format!("{:?}", options.grandpa_voter_spawn_mode),
+		"selectChain": format!("{:?}", options.select_chain),
+		"customJustificationImport": options.justification_import.is_some(),
+		"customBlockImport": options.block_import_wrapper.is_some(),
+		"customTransactionOrdering": options.transaction_ordering.is_some(),
+		"txRelay": options.tx_relay,
+		"finalityProfile": format!("{:?}", options.finality_profile),
+		"disableUncles": options.disable_uncles,
+		"transactionStorageProof": options.transaction_storage_proof,
+		"finalizedWebhookUrl": options.finalized_webhook.as_ref().map(|c| c.url.to_string()),
+		"eventSink": options.event_sink.as_ref().map(|c| c.url.clone()),
+		"logBabeEpochs": options.log_babe_epochs,
+		"babeAuthoringKey": options.babe_authoring_key.as_ref().map(ToString::to_string),
+		"auxiliaryTaskRestarts": options.auxiliary_task_restarts,
+		"noEmptyBlocks": options.no_empty_blocks,
+		"authorSchedule": options.author_schedule.as_ref().map(|s| s.to_string()),
+		"logHeaderDigests": options.log_header_digests,
+		"maxReorgDepth": options.max_reorg_depth,
+		"haltOnDeepReorg": options.halt_on_deep_reorg,
+		"maxWarpProofSize": options.max_warp_proof_size,
+		"authorStartupJitterMs": options.author_startup_jitter.map(|d| d.as_millis() as u64),
+		"metricsNamespace": options.metrics_namespace,
+		"verifyStateRoot": options.verify_state_root,
+		"haltOnStateRootMismatch": options.halt_on_state_root_mismatch,
+		"checkRuntimeVersion": options.check_runtime_version,
+		"reputation": options.reputation.map(|r| json!({
+			"bannedThreshold": r.banned_threshold,
+			"decayDivisor": r.decay_divisor,
+		})),
+		"admissionFilter": options.admission_filter.is_some(),
+		"epochChangeHook": options.epoch_change_hook.is_some(),
+		"genesisSnapshotUrl": options.genesis_snapshot.as_ref().map(|c| c.url.to_string()),
+		"genesisHash": options.genesis_snapshot.as_ref().map(|c| format!("{:?}", c.genesis_hash)),
+		"genesisOverrides": options.genesis_overrides.len(),
+		"maxBlockTxs": options.max_block_transactions,
+		"proposerSoftDeadline": options.proposer_soft_deadline.map(|p| p.deconstruct()),
+		"bannedAuthorities": options
+			.banned_authorities
+			.iter()
+			.map(ToString::to_string)
+			.collect::<Vec<_>>(),
+		"isolated": options.isolated,
+		"finalityGateway": options.finality_gateway,
+		"grandpaStart": options.grandpa_start.map(|start| match start {
+			GrandpaStart::Delay(delay) => json!({ "delaySecs": delay.as_secs() }),
+			GrandpaStart::Block(number) => json!({ "block": number }),
+		}),
+		"peerFilter": {
+			"allowed": options
+				.peer_filter
+				.allowed
+				.as_ref()
+				.map(|peers| peers.iter().map(ToString::to_string).collect::<Vec<_>>()),
+			"denied": options
+				.peer_filter
+				.denied
+				.iter()
+				.map(ToString::to_string)
+				.collect::<Vec<_>>(),
+		},
+		"statusFile": options.status_file.as_ref().map(|c| json!({
+			"path": c.path.display().to_string(),
+			"interval": c.interval.as_secs(),
+		})),
+		"firstBlock": options.first_block.as_ref().map(|b| b.block.hash().to_string()),
+		"minFreeDisk": options.min_free_disk.map(|min_free| min_free.0),
+	})
+}
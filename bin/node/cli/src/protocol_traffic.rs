@@ -0,0 +1,105 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Prometheus counters of the notifications traffic, by protocol.
+//!
+//! The generic bandwidth metrics only have the total of the connections. This task samples the
+//! bytes of notifications the network service counts for every protocol, which tells whether
+//! GRANDPA, the transactions, the block announces or another protocol dominates the bandwidth.
+
+use futures_timer::Delay;
+use node_primitives::Block;
+use prometheus_endpoint::{register, CounterVec, Opts, PrometheusError, Registry, U64};
+use sc_network::{NetworkService, NotificationsTraffic};
+use sp_runtime::traits::Block as BlockT;
+use std::{borrow::Cow, collections::HashMap, sync::Arc, time::Duration};
+
+/// The interval between two samples of the traffic.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Counters of the notifications traffic.
+pub struct Metrics {
+	bytes: CounterVec<U64>,
+	/// The traffic of the previous sample, as the counters can only be incremented.
+	last: HashMap<Cow<'static, str>, NotificationsTraffic>,
+}
+
+impl Metrics {
+	/// Register the counters in `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			bytes: register(
+				CounterVec::new(
+					Opts::new(
+						"notifications_bytes_total",
+						"Bytes of the notifications sent and received, by protocol",
+					),
+					&["protocol", "direction"],
+				)?,
+				registry,
+			)?,
+			last: HashMap::new(),
+		})
+	}
+
+	fn update(&mut self, traffic: HashMap<Cow<'static, str>, NotificationsTraffic>) {
+		for (protocol, traffic) in traffic {
+			let last = self.last.get(&protocol).copied().unwrap_or_default();
+			self.bytes
+				.with_label_values(&[&protocol, "in"])
+				.inc_by(traffic.bytes_received.saturating_sub(last.bytes_received));
+			self.bytes
+				.with_label_values(&[&protocol, "out"])
+				.inc_by(traffic.bytes_sent.saturating_sub(last.bytes_sent));
+			self.last.insert(protocol, traffic);
+		}
+	}
+}
+
+/// Update `metrics` with the notifications traffic of `network` every [`SAMPLE_INTERVAL`].
+pub async fn run(
+	mut metrics: Metrics,
+	network: Arc<NetworkService<Block, <Block as BlockT>::Hash>>,
+) {
+	loop {
+		Delay::new(SAMPLE_INTERVAL).await;
+		metrics.update(network.notifications_traffic());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn increments_by_the_traffic_since_the_last_sample() {
+		let registry = Registry::new();
+		let mut metrics = Metrics::register(&registry).unwrap();
+		let grandpa = Cow::Borrowed("/paritytech/grandpa/1");
+		let sample = |bytes_received, bytes_sent| {
+			vec![(grandpa.clone(), NotificationsTraffic { bytes_received, bytes_sent })]
+				.into_iter()
+				.collect()
+		};
+
+		metrics.update(sample(10, 4));
+		metrics.update(sample(25, 4));
+		assert_eq!(metrics.bytes.with_label_values(&[&grandpa, "in"]).get(), 25);
+		assert_eq!(metrics.bytes.with_label_values(&[&grandpa, "out"]).get(), 4);
+	}
+}
@@ -0,0 +1,182 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Detection of deep reorganizations of the best chain.
+//!
+//! The depth of a reorg is the number of blocks it retracts from the previous best chain.
+
+use futures::prelude::*;
+use node_primitives::Block;
+use sc_client_api::{BlockImportNotification, BlockchainEvents};
+use sp_runtime::traits::Header as HeaderT;
+use std::sync::Arc;
+
+const LOG_TARGET: &str = "reorg-guard";
+
+/// Log an error for every reorg of the best chain of `client` deeper than `max_depth`.
+///
+/// With `halt`, the future returns after the first of these reorgs, which is meant to shut down
+/// the node when it runs as an essential task.
+pub async fn run<C>(client: Arc<C>, max_depth: u32, halt: bool)
+where
+	C: BlockchainEvents<Block>,
+{
+	guard(client.import_notification_stream(), max_depth, halt).await
+}
+
+async fn guard(
+	mut imports: impl Stream<Item = BlockImportNotification<Block>> + Unpin,
+	max_depth: u32,
+	halt: bool,
+) {
+	while let Some(notification) = imports.next().await {
+		let depth = match &notification.tree_route {
+			Some(tree_route) if notification.is_new_best => tree_route.retracted().len(),
+			_ => continue,
+		};
+
+		if depth > max_depth as usize {
+			log::error!(
+				target: LOG_TARGET,
+				"💥 Reorg of {} blocks to #{} ({}), deeper than the maximum of {} blocks",
+				depth,
+				notification.header.number(),
+				notification.hash,
+				max_depth,
+			);
+
+			if halt {
+				log::error!(target: LOG_TARGET, "Halting the node after a deep reorg");
+				return
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use node_primitives::{BlockNumber, Hash, Header};
+	use sc_client_api::{in_mem::Blockchain, NewBlockState};
+	use sp_consensus::BlockOrigin;
+
+	fn insert(
+		client: &Blockchain<Block>,
+		number: BlockNumber,
+		parent: &Header,
+		fork: u64,
+	) -> Header {
+		let header = Header::new(
+			number,
+			Hash::from_low_u64_be(fork),
+			Default::default(),
+			parent.hash(),
+			Default::default(),
+		);
+		client
+			.insert(header.hash(), header.clone(), None, None, NewBlockState::Normal)
+			.unwrap();
+		header
+	}
+
+	/// A chain with the forks `a1 -> a2 -> a3` and `b1 -> b2` on top of the genesis block.
+	fn forks() -> (Blockchain<Block>, Vec<Header>, Vec<Header>) {
+		let client = Blockchain::new();
+		let genesis = Header::new(
+			0,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		);
+		client
+			.insert(genesis.hash(), genesis.clone(), None, None, NewBlockState::Final)
+			.unwrap();
+
+		let mut a = vec![genesis.clone()];
+		let mut b = vec![genesis];
+		for number in 1..=3 {
+			a.push(insert(&client, number, &a[number as usize - 1], 1));
+		}
+		for number in 1..=2 {
+			b.push(insert(&client, number, &b[number as usize - 1], 2));
+		}
+		(client, a, b)
+	}
+
+	/// The import of `new_best`, moving the best chain away from `old_best`.
+	fn reorg(
+		client: &Blockchain<Block>,
+		old_best: &Header,
+		new_best: &Header,
+		is_new_best: bool,
+	) -> BlockImportNotification<Block> {
+		let tree_route =
+			sc_client_api::blockchain::tree_route(client, old_best.hash(), *new_best.parent_hash())
+				.unwrap();
+		BlockImportNotification {
+			hash: new_best.hash(),
+			origin: BlockOrigin::NetworkBroadcast,
+			header: new_best.clone(),
+			is_new_best,
+			tree_route: Some(Arc::new(tree_route)),
+		}
+	}
+
+	fn halts(
+		notifications: Vec<BlockImportNotification<Block>>,
+		max_depth: u32,
+		halt: bool,
+	) -> bool {
+		let imports = stream::iter(notifications).chain(stream::pending());
+		guard(imports, max_depth, halt).now_or_never().is_some()
+	}
+
+	#[test]
+	fn halts_on_a_reorg_deeper_than_the_maximum() {
+		let (client, a, b) = forks();
+
+		// Retracts `a3`, `a2` and `a1`.
+		assert!(halts(vec![reorg(&client, &a[3], &b[2], true)], 2, true));
+		assert!(!halts(vec![reorg(&client, &a[3], &b[2], true)], 3, true));
+	}
+
+	#[test]
+	fn ignores_the_reorgs_within_the_maximum() {
+		let (client, a, b) = forks();
+
+		// Retracts `a1` only, the guard keeps following the imports after it.
+		let shallow = || reorg(&client, &a[1], &b[2], true);
+		assert!(!halts(vec![shallow()], 1, true));
+		assert!(halts(vec![shallow(), reorg(&client, &a[3], &b[2], true)], 1, true));
+	}
+
+	#[test]
+	fn ignores_the_blocks_not_becoming_the_best() {
+		let (client, a, b) = forks();
+
+		assert!(!halts(vec![reorg(&client, &a[3], &b[2], false)], 0, true));
+	}
+
+	#[test]
+	fn keeps_running_without_halt() {
+		let (client, a, b) = forks();
+
+		assert!(!halts(vec![reorg(&client, &a[3], &b[2], true)], 0, false));
+	}
+}
@@ -0,0 +1,358 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Proxy in front of the RPC servers, terminating TLS and limiting the connections.
+//!
+//! The HTTP and WebSocket RPC servers only speak plain TCP, and the HTTP server doesn't bound its
+//! connections. When TLS or a connection limit is enabled the servers are moved to a free port on
+//! the loopback interface, and the configured addresses are served by a proxy that terminates TLS
+//! and forwards the decrypted stream to them.
+//!
+//! The servers decide whether to serve the unsafe methods from the address they listen on, which
+//! is then always a loopback one. With `--rpc-methods Auto`, the unsafe methods are therefore
+//! denied as soon as one of the proxied addresses is exposed. The servers also accept the `Host`
+//! headers of the proxied addresses, as if they listened on them.
+//!
+//! The loopback ports of the servers stay reachable by the local processes, which then bypass
+//! both TLS and the connection limit. The proxy only protects the servers from the network, the
+//! local users have to be trusted or kept away from these ports by a firewall.
+
+use sc_service::{config::RpcMethods, Configuration, RpcAddresses, SpawnTaskHandle};
+use std::{
+	fmt,
+	fs::File,
+	io::{self, BufReader},
+	net::{Ipv4Addr, SocketAddr, TcpListener as StdTcpListener},
+	path::{Path, PathBuf},
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
+};
+use tokio::net::TcpStream;
+use tokio_rustls::{
+	rustls::{internal::pemfile, NoClientAuth, ServerConfig},
+	TlsAcceptor,
+};
+
+const LOG_TARGET: &str = "rpc-proxy";
+
+/// TLS configuration of the RPC servers.
+#[derive(Clone)]
+pub struct TlsConfig {
+	/// Path of the PEM encoded certificate chain.
+	pub cert: PathBuf,
+	acceptor: TlsAcceptor,
+}
+
+impl fmt::Debug for TlsConfig {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("TlsConfig").field("cert", &self.cert).finish()
+	}
+}
+
+impl TlsConfig {
+	/// Load the PEM encoded certificate chain at `cert` and private key at `key`.
+	pub fn new(cert: &Path, key: &Path) -> Result<Self, String> {
+		let open = |path: &Path| {
+			File::open(path)
+				.map(BufReader::new)
+				.map_err(|e| format!("Failed to open `{}`: {}", path.display(), e))
+		};
+
+		let certs = pemfile::certs(&mut open(cert)?)
+			.map_err(|()| format!("Invalid PEM certificate in `{}`", cert.display()))?;
+		if certs.is_empty() {
+			return Err(format!("No certificate found in `{}`", cert.display()))
+		}
+
+		let mut keys = pemfile::pkcs8_private_keys(&mut open(key)?)
+			.map_err(|()| format!("Invalid PEM private key in `{}`", key.display()))?;
+		if keys.is_empty() {
+			keys = pemfile::rsa_private_keys(&mut open(key)?)
+				.map_err(|()| format!("Invalid PEM private key in `{}`", key.display()))?;
+		}
+		let key = keys
+			.into_iter()
+			.next()
+			.ok_or_else(|| format!("No private key found in `{}`", key.display()))?;
+
+		let mut server_config = ServerConfig::new(NoClientAuth::new());
+		server_config
+			.set_single_cert(certs, key)
+			.map_err(|e| format!("Invalid RPC TLS certificate or key: {}", e))?;
+
+		Ok(Self { cert: cert.into(), acceptor: Arc::new(server_config).into() })
+	}
+}
+
+/// The connections open through the proxies, shared by the HTTP and WebSocket servers.
+struct Connections {
+	open: AtomicUsize,
+	max: usize,
+}
+
+/// An open connection, closed on drop.
+struct Connection(Arc<Connections>);
+
+impl Connections {
+	/// Open a connection, unless the limit is reached.
+	fn open(self: &Arc<Self>) -> Option<Connection> {
+		let max = self.max;
+		self.open
+			.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |open| (open < max).then(|| open + 1))
+			.ok()
+			.map(|_| Connection(self.clone()))
+	}
+}
+
+impl Drop for Connection {
+	fn drop(&mut self) {
+		self.0.open.fetch_sub(1, Ordering::SeqCst);
+	}
+}
+
+/// The RPC server behind a proxy.
+#[derive(Clone, Copy)]
+enum Server {
+	Http,
+	Ws,
+}
+
+/// A proxy in front of one of the RPC servers.
+pub struct Proxy {
+	listener: StdTcpListener,
+	server: Server,
+	acceptor: Option<TlsAcceptor>,
+	connections: Option<Arc<Connections>>,
+}
+
+/// Move the HTTP and WebSocket RPC servers of `config` behind proxies terminating `tls`, and
+/// accepting at most `max_connections` connections to both servers.
+///
+/// The public addresses are bound right away, so that any error surfaces at startup. The
+/// returned proxies have to be run once the servers are started, for them to be reachable.
+pub fn prepare(
+	tls: Option<&TlsConfig>,
+	max_connections: Option<usize>,
+	config: &mut Configuration,
+) -> Result<Vec<Proxy>, String> {
+	let mut proxies = Vec::new();
+	if tls.is_none() && max_connections.is_none() {
+		return Ok(proxies)
+	}
+	let connections =
+		max_connections.map(|max| Arc::new(Connections { open: AtomicUsize::new(0), max }));
+
+	// Once moved, the servers only see their loopback address.
+	if let RpcMethods::Auto = config.rpc_methods {
+		let exposed = config.rpc_http.iter().chain(&config.rpc_ws).find(|a| !a.ip().is_loopback());
+		if let Some(exposed) = exposed {
+			log::info!(
+				target: LOG_TARGET,
+				"RPC proxied from the exposed address {}, only serving the safe RPC methods",
+				exposed,
+			);
+			config.rpc_methods = RpcMethods::Safe;
+		}
+	}
+
+	let servers = vec![(Server::Http, &mut config.rpc_http), (Server::Ws, &mut config.rpc_ws)];
+	for (server, address) in servers
+		.into_iter()
+		.filter_map(|(server, address)| Some((server, address.as_mut()?)))
+	{
+		let listener = StdTcpListener::bind(*address)
+			.and_then(|listener| listener.set_nonblocking(true).map(|()| listener))
+			.map_err(|e| format!("Failed to listen on RPC address {}: {}", address, e))?;
+		let backend = free_local_address()
+			.map_err(|e| format!("Failed to find a local port for the RPC server: {}", e))?;
+
+		if tls.is_some() {
+			log::info!(target: LOG_TARGET, "🔒 Terminating TLS on {} for the RPC server", address);
+		}
+
+		config.rpc_allowed_hosts.extend(hosts(address));
+		*address = backend;
+		proxies.push(Proxy {
+			listener,
+			server,
+			acceptor: tls.map(|tls| tls.acceptor.clone()),
+			connections: connections.clone(),
+		});
+	}
+
+	if let Some(max_connections) = max_connections {
+		log::info!(
+			target: LOG_TARGET,
+			"Accepting at most {} HTTP and WebSocket RPC connections",
+			max_connections,
+		);
+	}
+
+	Ok(proxies)
+}
+
+/// The `Host` headers accepted by a server listening on `address`.
+fn hosts(address: &SocketAddr) -> Vec<String> {
+	let host = address.to_string();
+	let mut hosts = Vec::new();
+	if address.ip() == Ipv4Addr::UNSPECIFIED {
+		hosts.push(host.replace("0.0.0.0", "127.0.0.1"));
+		hosts.push(host.replace("0.0.0.0", "localhost"));
+	} else if address.ip() == Ipv4Addr::LOCALHOST {
+		hosts.push(host.replace("127.0.0.1", "localhost"));
+	}
+	hosts.push(host);
+	hosts
+}
+
+/// A loopback address for a server, whose port is free for now.
+///
+/// The port may be taken again before the server binds it, the server then falls back to a random
+/// port. This is why the proxies forward to the addresses the servers eventually listen on.
+fn free_local_address() -> io::Result<SocketAddr> {
+	StdTcpListener::bind((Ipv4Addr::LOCALHOST, 0))?.local_addr()
+}
+
+impl Proxy {
+	/// Accept connections, spawning a task forwarding each of them to the RPC server, listening on
+	/// one of the started `servers`.
+	///
+	/// The connections beyond the limit are closed right away.
+	pub async fn run(self, servers: RpcAddresses, spawner: SpawnTaskHandle) {
+		let backend = match self.server {
+			Server::Http => servers.http,
+			Server::Ws => servers.ws,
+		};
+		let backend = match backend {
+			Some(backend) => backend,
+			None => {
+				log::error!(target: LOG_TARGET, "The RPC server behind the proxy isn't running");
+				return
+			},
+		};
+		let listener = match tokio::net::TcpListener::from_std(self.listener) {
+			Ok(listener) => listener,
+			Err(err) => {
+				log::error!(target: LOG_TARGET, "Failed to start the RPC proxy: {}", err);
+				return
+			},
+		};
+		let mut at_limit = false;
+
+		loop {
+			let (stream, peer) = match listener.accept().await {
+				Ok(connection) => connection,
+				Err(err) => {
+					log::debug!(target: LOG_TARGET, "Failed to accept a connection: {}", err);
+					continue
+				},
+			};
+
+			let connection = match &self.connections {
+				Some(connections) => match connections.open() {
+					Some(connection) => {
+						at_limit = false;
+						Some(connection)
+					},
+					None => {
+						// Only the first rejection is logged until a connection is accepted again.
+						if !at_limit {
+							log::warn!(
+								target: LOG_TARGET,
+								"Reached the limit of {} RPC connections, rejecting the new ones",
+								connections.max,
+							);
+							at_limit = true;
+						}
+						log::debug!(target: LOG_TARGET, "Rejecting the connection from {}", peer);
+						continue
+					},
+				},
+				None => None,
+			};
+
+			let acceptor = self.acceptor.clone();
+			spawner.spawn("rpc-proxy-connection", async move {
+				if let Err(err) = forward(acceptor, stream, backend).await {
+					log::debug!(target: LOG_TARGET, "Connection from {} failed: {}", peer, err);
+				}
+				drop(connection);
+			});
+		}
+	}
+}
+
+async fn forward(
+	acceptor: Option<TlsAcceptor>,
+	stream: TcpStream,
+	backend: SocketAddr,
+) -> io::Result<()> {
+	let mut plain = TcpStream::connect(backend).await?;
+	match acceptor {
+		Some(acceptor) => {
+			let mut tls = acceptor.accept(stream).await?;
+			tokio::io::copy_bidirectional(&mut tls, &mut plain).await.map(|_| ())
+		},
+		None => {
+			let mut stream = stream;
+			tokio::io::copy_bidirectional(&mut stream, &mut plain).await.map(|_| ())
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn config_reports_invalid_files() {
+		let dir = tempfile::tempdir().unwrap();
+		let empty = dir.path().join("empty.pem");
+		std::fs::write(&empty, b"").unwrap();
+
+		let err = TlsConfig::new(&dir.path().join("missing.pem"), &empty).unwrap_err();
+		assert!(err.starts_with("Failed to open"), "{}", err);
+
+		let err = TlsConfig::new(&empty, &empty).unwrap_err();
+		assert!(err.starts_with("No certificate found"), "{}", err);
+	}
+
+	#[test]
+	fn accepts_the_hosts_of_the_proxied_address() {
+		assert_eq!(
+			hosts(&"0.0.0.0:9933".parse().unwrap()),
+			["127.0.0.1:9933", "localhost:9933", "0.0.0.0:9933"],
+		);
+		assert_eq!(hosts(&"127.0.0.1:443".parse().unwrap()), ["localhost:443", "127.0.0.1:443"]);
+		assert_eq!(hosts(&"10.0.0.1:443".parse().unwrap()), ["10.0.0.1:443"]);
+	}
+
+	#[test]
+	fn limits_the_open_connections() {
+		let connections = Arc::new(Connections { open: AtomicUsize::new(0), max: 2 });
+
+		let first = connections.open().unwrap();
+		let _second = connections.open().unwrap();
+		assert!(connections.open().is_none());
+
+		drop(first);
+		assert!(connections.open().is_some());
+	}
+}
@@ -0,0 +1,171 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Detection of a native runtime diverging from the on-chain runtime.
+//!
+//! Once an upgrade is enacted on-chain, the native runtime of an outdated node isn't used to
+//! execute the blocks anymore, and can keep the node from authoring. The only sign of the latter
+//! is otherwise a debug log of `CanAuthorWith` at every slot.
+
+use futures::prelude::*;
+use node_primitives::{Block, Hash};
+use prometheus_endpoint::{register, Gauge, PrometheusError, Registry, U64};
+use sc_client_api::{BlockchainEvents, HeaderBackend};
+use sc_executor::{NativeVersion, RuntimeVersion};
+use sp_api::CallApiAt;
+use sp_runtime::generic::BlockId;
+use std::sync::Arc;
+
+const LOG_TARGET: &str = "runtime-version";
+
+/// Gauge of the divergence between the native and on-chain runtimes.
+pub struct Metrics {
+	divergence: Gauge<U64>,
+}
+
+impl Metrics {
+	/// Register the gauge in `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			divergence: register(
+				Gauge::new(
+					"native_runtime_divergence",
+					"Divergence of the native runtime from the on-chain runtime at the best block: \
+					 0 if they match, 1 if the native runtime isn't used, 2 if it can't author",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
+/// How the native runtime diverges from the on-chain runtime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Divergence {
+	/// The native runtime can execute the blocks, differing at most by its implementation version.
+	None = 0,
+	/// The blocks are executed with the on-chain runtime.
+	Execution = 1,
+	/// The native runtime can't author blocks on top of the on-chain runtime.
+	Authoring = 2,
+}
+
+fn divergence(native: &NativeVersion, on_chain: &RuntimeVersion) -> Divergence {
+	if native.can_author_with(on_chain).is_err() {
+		Divergence::Authoring
+	} else if !native.runtime_version.can_call_with(on_chain) {
+		Divergence::Execution
+	} else {
+		Divergence::None
+	}
+}
+
+/// Compare the `native` runtime with the on-chain runtime at the best block of `client`, at
+/// startup and on every change of the on-chain runtime version.
+pub async fn run<C>(client: Arc<C>, native: NativeVersion, metrics: Option<Metrics>)
+where
+	C: BlockchainEvents<Block> + HeaderBackend<Block> + CallApiAt<Block>,
+{
+	// Subscribe before the first check, to not miss a best block imported in between.
+	let mut imports = client.import_notification_stream();
+	let mut checked = None;
+	check(&*client, client.info().best_hash, &native, &metrics, &mut checked);
+
+	while let Some(notification) = imports.next().await {
+		if notification.is_new_best {
+			check(&*client, notification.hash, &native, &metrics, &mut checked);
+		}
+	}
+}
+
+/// Check the on-chain runtime at `hash`, logging the divergence if the version differs from the
+/// `checked` one.
+fn check<C: CallApiAt<Block>>(
+	client: &C,
+	hash: Hash,
+	native: &NativeVersion,
+	metrics: &Option<Metrics>,
+	checked: &mut Option<RuntimeVersion>,
+) {
+	let on_chain = match client.runtime_version_at(&BlockId::Hash(hash)) {
+		Ok(version) => version,
+		Err(e) => {
+			log::debug!(target: LOG_TARGET, "Failed to get the runtime version at {}: {}", hash, e);
+			return
+		},
+	};
+	if checked.as_ref() == Some(&on_chain) {
+		return
+	}
+
+	let divergence = divergence(native, &on_chain);
+	match divergence {
+		Divergence::None => log::info!(
+			target: LOG_TARGET,
+			"The native runtime {} executes the blocks of the on-chain runtime {}",
+			native.runtime_version,
+			on_chain,
+		),
+		Divergence::Execution => log::warn!(
+			target: LOG_TARGET,
+			"⚠️ The native runtime {} differs from the on-chain runtime {}, the blocks are \
+			 executed with the on-chain runtime",
+			native.runtime_version,
+			on_chain,
+		),
+		Divergence::Authoring => log::error!(
+			target: LOG_TARGET,
+			"🚨 The native runtime {} can't author blocks on top of the on-chain runtime {}, \
+			 upgrade the node to resume authoring",
+			native.runtime_version,
+			on_chain,
+		),
+	}
+	if let Some(metrics) = metrics {
+		metrics.divergence.set(divergence as u64);
+	}
+
+	*checked = Some(on_chain);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn classifies_the_divergence() {
+		let native = node_runtime::native_version();
+		let on_chain = native.runtime_version.clone();
+		assert_eq!(divergence(&native, &on_chain), Divergence::None);
+
+		// Only differing by their implementation, the blocks are still executed natively.
+		let patched =
+			RuntimeVersion { impl_version: on_chain.impl_version + 1, ..on_chain.clone() };
+		assert_eq!(divergence(&native, &patched), Divergence::None);
+
+		let upgraded =
+			RuntimeVersion { spec_version: on_chain.spec_version + 1, ..on_chain.clone() };
+		assert_eq!(divergence(&native, &upgraded), Divergence::Execution);
+
+		let upgraded = RuntimeVersion {
+			authoring_version: on_chain.authoring_version + 1,
+			..on_chain.clone()
+		};
+		assert_eq!(divergence(&native, &upgraded), Divergence::Authoring);
+	}
+}
@@ -0,0 +1,139 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Resolution of the secrets of the node by an external command.
+//!
+//! This lets the secrets come from a secrets manager instead of files. The command is run once
+//! per secret, with the name of the secret as its last argument, and prints the secret on its
+//! standard output. An empty output leaves the secret to the other options of the node, a failure
+//! of the command stops the node.
+
+use sc_service::{
+	config::{KeystoreConfig, TelemetryEndpoints},
+	Configuration,
+};
+use sp_core::crypto::{ExposeSecret, SecretString};
+use std::process::Command;
+
+const LOG_TARGET: &str = "secrets";
+
+/// The password of the keystore, when not given by `--password` and its variants.
+const KEYSTORE_PASSWORD: &str = "keystore-password";
+/// The telemetry endpoint as `URL VERBOSITY`, replacing the other ones. Authenticating telemetry
+/// servers take their token in the URL, whose path the telemetry leaves out of its logs.
+const TELEMETRY_URL: &str = "telemetry-url";
+
+/// The command resolving the secrets.
+#[derive(Clone, Debug)]
+pub struct SecretCommand {
+	program: String,
+	args: Vec<String>,
+}
+
+impl SecretCommand {
+	/// Create a new command from a program followed by its arguments, separated by whitespace.
+	pub fn new(command: &str) -> Result<Self, String> {
+		let mut words = command.split_whitespace().map(String::from);
+		let program = words.next().ok_or("The secret command is empty")?;
+		Ok(Self { program, args: words.collect() })
+	}
+
+	/// Run the command for the secret `name`, returning `None` if it printed nothing.
+	fn resolve(&self, name: &str) -> Result<Option<SecretString>, String> {
+		let output =
+			Command::new(&self.program).args(&self.args).arg(name).output().map_err(|e| {
+				format!("Failed to run the secret command `{}`: {}", self.program, e)
+			})?;
+		if !output.status.success() {
+			return Err(format!(
+				"The secret command failed to resolve `{}` ({}): {}",
+				name,
+				output.status,
+				String::from_utf8_lossy(&output.stderr).trim(),
+			))
+		}
+
+		// The output is moved into the secret without being copied, to be zeroized on drop.
+		let mut secret = String::from_utf8(output.stdout)
+			.map_err(|_| format!("The secret command printed an invalid UTF-8 `{}`", name))?;
+		secret.truncate(secret.trim_end_matches(&['\r', '\n'][..]).len());
+		let secret = SecretString::new(secret);
+		Ok((!secret.expose_secret().is_empty()).then(|| secret))
+	}
+}
+
+/// Resolve the secrets of `config` with `command`.
+///
+/// The telemetry endpoint is left alone with `no_telemetry`, and the keystore password when
+/// another option already gave one.
+pub fn resolve(
+	command: &SecretCommand,
+	config: &mut Configuration,
+	no_telemetry: bool,
+) -> Result<(), String> {
+	if let KeystoreConfig::Path { password: password @ None, .. } = &mut config.keystore {
+		if let Some(secret) = command.resolve(KEYSTORE_PASSWORD)? {
+			log::info!(target: LOG_TARGET, "🔑 Using the keystore password of the secret command");
+			*password = Some(secret);
+		}
+	}
+
+	if !no_telemetry {
+		if let Some(secret) = command.resolve(TELEMETRY_URL)? {
+			let endpoint = telemetry_endpoint(secret.expose_secret()).map_err(|e| {
+				format!("Invalid `{}` printed by the secret command: {}", TELEMETRY_URL, e)
+			})?;
+			log::info!(target: LOG_TARGET, "Using the telemetry endpoint of the secret command");
+			config.telemetry_endpoints = Some(endpoint);
+		}
+	}
+
+	Ok(())
+}
+
+/// Parse `URL VERBOSITY`, like `--telemetry-url`.
+fn telemetry_endpoint(secret: &str) -> Result<TelemetryEndpoints, String> {
+	let (url, verbosity) = secret.trim().rsplit_once(' ').ok_or("Missing the verbosity")?;
+	let verbosity = verbosity.parse().map_err(|e| format!("Invalid verbosity: {}", e))?;
+	TelemetryEndpoints::new(vec![(url.trim().to_owned(), verbosity)]).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn runs_the_command_with_the_secret_name() {
+		let resolve = |command: &str| {
+			let command = SecretCommand::new(command).unwrap();
+			command.resolve(KEYSTORE_PASSWORD).map(|s| s.map(|s| s.expose_secret().clone()))
+		};
+		assert_eq!(resolve("echo  secret"), Ok(Some("secret keystore-password".into())));
+		assert_eq!(resolve("true"), Ok(None));
+
+		assert!(resolve("false").unwrap_err().contains("exit status: 1"));
+
+		assert!(SecretCommand::new(" ").is_err());
+	}
+
+	#[test]
+	fn parses_the_telemetry_endpoint() {
+		assert!(telemetry_endpoint("wss://telemetry.example/submit/token 0").is_ok());
+		assert!(telemetry_endpoint("wss://telemetry.example/submit/token").is_err());
+	}
+}
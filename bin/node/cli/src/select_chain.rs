@@ -0,0 +1,187 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Selection of the chain the node builds on.
+//!
+//! By default this is the longest chain of [`LongestChain`]. On networks prone to deep reorgs, the
+//! selection can be restricted to the leaves descending from the last finalized block, so that no
+//! block is authored on a fork that can never be finalized.
+
+use crate::arg_enums::SelectChainMode;
+use node_primitives::{Block, BlockNumber, Hash, Header};
+use sc_client_api::{
+	backend,
+	blockchain::{self, lowest_common_ancestor, Backend as _, HeaderBackend},
+};
+use sc_consensus::LongestChain;
+use sp_consensus::{Error as ConsensusError, SelectChain};
+use sp_runtime::{generic::BlockId, traits::Header as HeaderT};
+use std::sync::Arc;
+
+const LOG_TARGET: &str = "select-chain";
+
+/// Implements [`SelectChain`] by wrapping [`LongestChain`], following a [`SelectChainMode`].
+pub struct NodeSelectChain<B> {
+	longest: LongestChain<B, Block>,
+	backend: Arc<B>,
+	mode: SelectChainMode,
+}
+
+impl<B> Clone for NodeSelectChain<B> {
+	fn clone(&self) -> Self {
+		Self { longest: self.longest.clone(), backend: self.backend.clone(), mode: self.mode }
+	}
+}
+
+impl<B: backend::Backend<Block>> NodeSelectChain<B> {
+	/// Create a new chain selection on top of `backend`.
+	pub fn new(backend: Arc<B>, mode: SelectChainMode) -> Self {
+		Self { longest: LongestChain::new(backend.clone()), backend, mode }
+	}
+
+	/// Whether `hash` is the last finalized block or one of its descendants.
+	fn descends_from_finalized(&self, hash: Hash) -> blockchain::Result<bool> {
+		let blockchain = self.backend.blockchain();
+		let finalized = blockchain.info().finalized_hash;
+		Ok(lowest_common_ancestor(blockchain, finalized, hash)?.hash == finalized)
+	}
+
+	/// The leaves descending from the last finalized block, highest first.
+	fn finalized_leaves(&self) -> blockchain::Result<Vec<Hash>> {
+		let mut leaves = self.backend.blockchain().leaves()?;
+		let mut descends = Ok(());
+		leaves.retain(|leaf| match self.descends_from_finalized(*leaf) {
+			Ok(descends) => descends,
+			Err(e) => {
+				descends = Err(e);
+				false
+			},
+		});
+		descends.map(|()| leaves)
+	}
+
+	/// The best block of the longest chain if it descends from the last finalized block, else the
+	/// highest of the leaves that do, else the last finalized block.
+	fn finalized_best_chain(&self, best: Header) -> blockchain::Result<Header> {
+		if self.descends_from_finalized(best.hash())? {
+			return Ok(best)
+		}
+
+		let blockchain = self.backend.blockchain();
+		let hash = match self.finalized_leaves()?.first() {
+			Some(leaf) => *leaf,
+			None => blockchain.info().finalized_hash,
+		};
+		log::debug!(
+			target: LOG_TARGET,
+			"Best block #{} ({}) doesn't descend from the last finalized block, building on {}",
+			best.number(),
+			best.hash(),
+			hash,
+		);
+		blockchain.expect_header(BlockId::Hash(hash))
+	}
+}
+
+#[async_trait::async_trait]
+impl<B: backend::Backend<Block>> SelectChain<Block> for NodeSelectChain<B> {
+	async fn leaves(&self) -> Result<Vec<Hash>, ConsensusError> {
+		match self.mode {
+			SelectChainMode::Longest => self.longest.leaves().await,
+			SelectChainMode::FinalizedDescendantLongest =>
+				self.finalized_leaves().map_err(|e| ConsensusError::ChainLookup(e.to_string())),
+		}
+	}
+
+	async fn best_chain(&self) -> Result<Header, ConsensusError> {
+		let best = self.longest.best_chain().await?;
+		match self.mode {
+			SelectChainMode::Longest => Ok(best),
+			SelectChainMode::FinalizedDescendantLongest => self
+				.finalized_best_chain(best)
+				.map_err(|e| ConsensusError::ChainLookup(e.to_string())),
+		}
+	}
+
+	async fn finality_target(
+		&self,
+		target_hash: Hash,
+		maybe_max_number: Option<BlockNumber>,
+	) -> Result<Option<Hash>, ConsensusError> {
+		self.longest.finality_target(target_hash, maybe_max_number).await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sc_client_api::{in_mem, Backend as _, NewBlockState};
+	use sp_runtime::traits::{BlakeTwo256, Hash as _};
+
+	fn insert(backend: &in_mem::Backend<Block>, parent: &Header, fork: &[u8]) -> Header {
+		let state_root = BlakeTwo256::hash(fork);
+		let header = Header::new(
+			parent.number + 1,
+			Default::default(),
+			state_root,
+			parent.hash(),
+			Default::default(),
+		);
+		backend
+			.blockchain()
+			.insert(header.hash(), header.clone(), None, None, NewBlockState::Best)
+			.unwrap();
+		header
+	}
+
+	#[test]
+	fn builds_on_finalized_descendants() {
+		let backend = Arc::new(in_mem::Backend::<Block>::new());
+		let genesis = Header::new(
+			0,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		);
+		backend
+			.blockchain()
+			.insert(genesis.hash(), genesis.clone(), None, None, NewBlockState::Final)
+			.unwrap();
+
+		// `a1` gets finalized while the longest chain is the fork of `b2`.
+		let a1 = insert(&backend, &genesis, b"a");
+		let b1 = insert(&backend, &genesis, b"b");
+		let b2 = insert(&backend, &b1, b"b");
+		backend.finalize_block(BlockId::Hash(a1.hash()), None).unwrap();
+
+		let longest = NodeSelectChain::new(backend.clone(), SelectChainMode::Longest);
+		let finalized =
+			NodeSelectChain::new(backend.clone(), SelectChainMode::FinalizedDescendantLongest);
+		futures::executor::block_on(async {
+			assert_eq!(longest.best_chain().await.unwrap(), b2);
+			assert_eq!(longest.leaves().await.unwrap(), vec![b2.hash(), a1.hash()]);
+
+			assert_eq!(finalized.best_chain().await.unwrap(), a1);
+			assert_eq!(finalized.leaves().await.unwrap(), vec![a1.hash()]);
+
+			let a2 = insert(&backend, &a1, b"a");
+			assert_eq!(finalized.best_chain().await.unwrap(), a2);
+		});
+	}
+}
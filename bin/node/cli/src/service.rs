@@ -20,30 +20,537 @@
 
 //! Service implementation. Specialized wrapper over substrate service.
 
+use crate::{
+	arg_enums::{FinalityProfile, SpawnMode},
+	authoring_backoff::{AuthoringBackoff, NoEmptyBlocks},
+	banned_authorities::BannedAuthoritiesVerifier,
+};
+use codec::{Decode, Encode};
 use futures::prelude::*;
 use node_executor::ExecutorDispatch;
-use node_primitives::Block;
+use node_primitives::{Block, BlockNumber, Hash};
 use node_runtime::RuntimeApi;
-use sc_client_api::{ExecutorProvider, RemoteBackend};
+use prometheus_endpoint::Registry;
+use sc_client_api::{BlockchainEvents, ExecutorProvider, HeaderBackend, RemoteBackend};
 use sc_consensus_babe::{self, SlotProportion};
 use sc_executor::NativeElseWasmExecutor;
-use sc_network::{Event, NetworkService};
-use sc_service::{config::Configuration, error::Error as ServiceError, RpcHandlers, TaskManager};
+use sc_network::{
+	config::{
+		NetworkConfiguration, NonReservedPeerMode, SetConfig, SyncMode, TransactionPropagation,
+		TransportConfig,
+	},
+	Event, NetworkService,
+};
+use sc_service::{
+	config::{Configuration, KeystoreConfig, PrometheusConfig},
+	error::Error as ServiceError,
+	RestartPolicy, RpcHandlers, TaskManager,
+};
 use sc_telemetry::{Telemetry, TelemetryWorker};
-use sp_runtime::traits::Block as BlockT;
-use std::sync::Arc;
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_consensus_babe::BabeApi;
+use sp_core::traits::SpawnEssentialNamed;
+use sp_inherents::{CreateInherentDataProviders, InherentDataProvider};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, OpaqueKeys},
+};
+use std::{collections::HashSet, fmt, sync::Arc, time::Duration};
 
 type FullClient =
 	sc_service::TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<ExecutorDispatch>>;
 type FullBackend = sc_service::TFullBackend<Block>;
-type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
+type FullSelectChain = crate::select_chain::NodeSelectChain<FullBackend>;
 type FullGrandpaBlockImport =
 	grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>;
+type FullBoxBlockImport =
+	sc_consensus::BoxBlockImport<Block, sp_api::TransactionFor<FullClient, Block>>;
 type LightClient =
 	sc_service::TLightClient<Block, RuntimeApi, NativeElseWasmExecutor<ExecutorDispatch>>;
+type LightPool =
+	sc_transaction_pool::LightPool<Block, LightClient, sc_network::config::OnDemand<Block>>;
+/// The task manager, RPC handlers, client, network and transaction pool of a light client.
+type LightBase = (
+	TaskManager,
+	RpcHandlers,
+	Arc<LightClient>,
+	Arc<NetworkService<Block, <Block as BlockT>::Hash>>,
+	Arc<LightPool>,
+);
+
+impl SpawnMode {
+	fn spawn_essential(
+		self,
+		spawner: &impl SpawnEssentialNamed,
+		name: &'static str,
+		task: impl Future<Output = ()> + Send + 'static,
+	) {
+		match self {
+			SpawnMode::Blocking => spawner.spawn_essential_blocking(name, task.boxed()),
+			SpawnMode::Async => spawner.spawn_essential(name, task.boxed()),
+		}
+	}
+}
+
+/// The GRANDPA configuration preset of `profile`.
+///
+/// Only the timings are set, the node specific fields have to be filled in by the caller.
+pub fn grandpa_config_template(profile: FinalityProfile) -> grandpa::Config {
+	// FIXME #1578 make this available through chainspec
+	let (gossip_duration, justification_period) = match profile {
+		FinalityProfile::Standard => (Duration::from_millis(333), 512),
+		FinalityProfile::Fast => (Duration::from_millis(100), 32),
+	};
+
+	grandpa::Config {
+		gossip_duration,
+		justification_period,
+		name: None,
+		observer_enabled: false,
+		keystore: None,
+		local_role: sc_service::Role::Full,
+		telemetry: None,
+	}
+}
+
+/// The portion of the slot the proposer may spend building a block.
+const BLOCK_PROPOSAL_SLOT_PORTION: f32 = 0.5;
+
+/// The consensus settings resolved once, for the authoring and GRANDPA tasks as for the
+/// `hoster_consensusConfig` RPC.
+pub struct ConsensusSettings {
+	/// The backoff of the block authoring.
+	pub backoff: sc_consensus_slots::BackoffAuthoringOnFinalizedHeadLagging<BlockNumber>,
+	/// The GRANDPA configuration, see [`grandpa_config_template`].
+	pub grandpa: grandpa::Config,
+}
+
+impl ConsensusSettings {
+	fn new(options: &NodeOptions) -> Self {
+		Self {
+			backoff: Default::default(),
+			grandpa: grandpa_config_template(options.finality_profile),
+		}
+	}
+}
+
+/// The consensus settings of the node, as resolved from `config`, `options` and `settings`.
+fn consensus_config(
+	config: &Configuration,
+	options: &NodeOptions,
+	settings: &ConsensusSettings,
+) -> node_rpc::ConsensusConfig {
+	let ConsensusSettings { backoff, grandpa } = settings;
+
+	node_rpc::ConsensusConfig {
+		role: config.role.to_string(),
+		babe: node_rpc::BabeConfig {
+			force_authoring: config.force_authoring,
+			block_proposal_slot_portion: BLOCK_PROPOSAL_SLOT_PORTION,
+			max_block_proposal_slot_portion: None,
+			max_block_transactions: options.max_block_transactions,
+			proposer_soft_deadline: options.proposer_soft_deadline.map(|p| p.deconstruct()),
+		},
+		backoff: node_rpc::BackoffConfig {
+			max_interval: backoff.max_interval,
+			unfinalized_slack: backoff.unfinalized_slack,
+			authoring_bias: backoff.authoring_bias,
+			no_empty_blocks: options.no_empty_blocks,
+		},
+		grandpa: node_rpc::GrandpaConfig {
+			enabled: !config.disable_grandpa,
+			finality_profile: format!("{:?}", options.finality_profile),
+			gossip_duration_ms: grandpa.gossip_duration.as_millis() as u64,
+			justification_period: grandpa.justification_period,
+		},
+	}
+}
+
+/// Builds the [`JustificationImport`](sc_consensus::JustificationImport) used by the import
+/// queue, allowing an alternative finality gadget to handle incoming justifications.
+#[derive(Clone)]
+pub struct JustificationImportBuilder(
+	pub Arc<dyn Fn(Arc<FullClient>) -> sc_consensus::BoxJustificationImport<Block> + Send + Sync>,
+);
+
+impl fmt::Debug for JustificationImportBuilder {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("JustificationImportBuilder")
+	}
+}
+
+/// Wraps the GRANDPA block import in a custom block import, which BABE passes the imported
+/// blocks to once it verified them.
+///
+/// The wrapper sees every block before GRANDPA imports it, including the blocks authored by the
+/// node, and must pass them on to the GRANDPA block import it was given. Any block import with
+/// the GRANDPA transaction type can be boxed into a [`sc_consensus::BoxBlockImport`].
+#[derive(Clone)]
+pub struct BlockImportWrapper(
+	pub Arc<dyn Fn(FullGrandpaBlockImport) -> FullBoxBlockImport + Send + Sync>,
+);
+
+impl fmt::Debug for BlockImportWrapper {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("BlockImportWrapper")
+	}
+}
+
+/// A ready transaction of the pool, as seen by [`TransactionOrdering`].
+pub type PoolTransaction = <sc_transaction_pool::FullPool<Block, FullClient> as
+	sc_transaction_pool_api::TransactionPool>::InPoolTransaction;
+
+/// Orders the ready transactions in the authored blocks, the pool orders them by priority
+/// otherwise.
+///
+/// Returns [`std::cmp::Ordering::Less`] when the first transaction should be included first. A
+/// transaction is still only included after the ones providing the tags it requires.
+#[derive(Clone)]
+pub struct TransactionOrdering(
+	pub Arc<dyn Fn(&PoolTransaction, &PoolTransaction) -> std::cmp::Ordering + Send + Sync>,
+);
+
+impl fmt::Debug for TransactionOrdering {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("TransactionOrdering")
+	}
+}
+
+/// The block import BABE passes the verified blocks to.
+///
+/// The wrapped block import is shared by the import queue and the authoring, which import one
+/// block at a time with it.
+#[derive(Clone)]
+pub enum InnerBlockImport {
+	/// The GRANDPA block import.
+	Grandpa(FullGrandpaBlockImport),
+	/// The block import of a [`BlockImportWrapper`], around the GRANDPA block import.
+	Wrapped(Arc<futures::lock::Mutex<FullBoxBlockImport>>),
+}
+
+#[async_trait::async_trait]
+impl sc_consensus::BlockImport<Block> for InnerBlockImport {
+	type Error = sp_consensus::Error;
+	type Transaction = sp_api::TransactionFor<FullClient, Block>;
+
+	async fn check_block(
+		&mut self,
+		block: sc_consensus::BlockCheckParams<Block>,
+	) -> Result<sc_consensus::ImportResult, Self::Error> {
+		match self {
+			Self::Grandpa(inner) => inner.check_block(block).await,
+			Self::Wrapped(inner) => inner.lock().await.check_block(block).await,
+		}
+	}
+
+	async fn import_block(
+		&mut self,
+		block: sc_consensus::BlockImportParams<Block, Self::Transaction>,
+		cache: std::collections::HashMap<sp_consensus::CacheKeyId, Vec<u8>>,
+	) -> Result<sc_consensus::ImportResult, Self::Error> {
+		match self {
+			Self::Grandpa(inner) => inner.import_block(block, cache).await,
+			Self::Wrapped(inner) => inner.lock().await.import_block(block, cache).await,
+		}
+	}
+}
+
+/// Decides whether a transaction is admitted into the transaction pool, before it is validated
+/// by the runtime and gossiped.
+#[derive(Clone)]
+pub struct AdmissionFilter(
+	pub Arc<dyn Fn(&node_runtime::UncheckedExtrinsic) -> bool + Send + Sync>,
+);
+
+impl fmt::Debug for AdmissionFilter {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("AdmissionFilter")
+	}
+}
+
+impl AdmissionFilter {
+	/// The filter of the transaction pool, decoding the opaque extrinsics of the pool.
+	fn into_pool_filter(self) -> sc_transaction_pool::AdmissionFilter<Block> {
+		Arc::new(move |xt| {
+			// Extrinsics that don't decode are left to the runtime to reject.
+			node_runtime::UncheckedExtrinsic::decode(&mut &xt.encode()[..])
+				.map_or(true, |xt| (self.0)(&xt))
+		})
+	}
+}
+
+/// Called with every new BABE epoch the best block enters, starting with the epoch of the best
+/// block when the node starts.
+#[derive(Clone)]
+pub struct EpochChangeHook(pub Arc<dyn Fn(&sc_consensus_babe::Epoch) + Send + Sync>);
+
+impl fmt::Debug for EpochChangeHook {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("EpochChangeHook")
+	}
+}
+
+impl EpochChangeHook {
+	/// Call the hook with `epoch` in a task of its own, so that a slow hook doesn't delay the
+	/// following epochs.
+	fn spawn(&self, spawner: &sc_service::SpawnTaskHandle, epoch: &sc_consensus_babe::Epoch) {
+		let hook = self.0.clone();
+		let epoch = epoch.clone();
+		spawner.spawn_blocking("epoch-change-hook", async move { hook(&epoch) });
+	}
+}
+
+/// The inherent data providers checking the inherents of imported blocks.
+///
+/// BABE verifies the slot of imported blocks with the timestamp and slot providers, the last
+/// provider checks the other inherents of the runtime.
+pub type ImportInherentDataProviders = (
+	sp_timestamp::InherentDataProvider,
+	sp_consensus_babe::inherents::InherentDataProvider,
+	Box<dyn InherentDataProvider>,
+);
+
+/// Creates the [`ImportInherentDataProviders`] at the parent of every imported block.
+pub type CreateImportInherentDataProviders = Box<
+	dyn CreateInherentDataProviders<Block, (), InherentDataProviders = ImportInherentDataProviders>,
+>;
+
+/// Builds the inherent data providers of the import queue from the client and the BABE slot
+/// duration, allowing the inherents of imported blocks to be checked differently than the ones
+/// of authored blocks.
+#[derive(Clone)]
+pub struct ImportInherentDataProvidersBuilder(
+	pub Arc<dyn Fn(Arc<FullClient>, Duration) -> CreateImportInherentDataProviders + Send + Sync>,
+);
+
+impl fmt::Debug for ImportInherentDataProvidersBuilder {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("ImportInherentDataProvidersBuilder")
+	}
+}
+
+/// The inherent data providers of the import queue, unless `options` has an
+/// [`ImportInherentDataProvidersBuilder`].
+pub fn import_inherent_data_providers(
+	client: Arc<FullClient>,
+	slot_duration: Duration,
+	options: &NodeOptions,
+) -> CreateImportInherentDataProviders {
+	let historical_slot_duration = options.historical_slot_duration;
+	let max_timestamp_drift = options.max_timestamp_drift;
+	let disable_uncles = options.disable_uncles;
+
+	Box::new(move |parent, ()| {
+		let client = client.clone();
+		async move {
+			// chains that changed their slot duration need blocks to be checked against the
+			// duration that was in effect when they were authored.
+			let slot_duration = if historical_slot_duration {
+				slot_duration_at(&*client, parent)?
+			} else {
+				slot_duration
+			};
+
+			let mut timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+			if let Some(max_drift) = max_timestamp_drift {
+				timestamp = timestamp.with_max_drift(max_drift);
+			}
+
+			let slot =
+				sp_consensus_babe::inherents::InherentDataProvider::from_timestamp_and_duration(
+					*timestamp,
+					slot_duration,
+				);
+
+			let uncles = (!disable_uncles).then(
+				sp_authorship::InherentDataProvider::<<Block as BlockT>::Header>::check_inherents,
+			);
+
+			Ok((timestamp, slot, Box::new(uncles) as Box<dyn InherentDataProvider>))
+		}
+	})
+}
+
+/// Node-specific options that aren't part of the generic service [`Configuration`].
+#[derive(Clone, Debug, Default)]
+pub struct NodeOptions {
+	/// Push the Prometheus metrics to a Pushgateway.
+	pub metrics_push: Option<crate::metrics_push::Config>,
+	/// Write the Prometheus metrics to a local file.
+	pub metrics_dump: Option<crate::file_output::Config>,
+	/// Terminate TLS in front of the HTTP and WebSocket RPC servers.
+	pub rpc_tls: Option<crate::rpc_proxy::TlsConfig>,
+	/// Accept at most this many connections to the HTTP and WebSocket RPC servers together.
+	pub rpc_max_connections: Option<usize>,
+	/// Verify imported blocks against the BABE slot duration of their parent's runtime, instead
+	/// of the current one.
+	pub historical_slot_duration: bool,
+	/// Tolerance of the timestamps of the imported blocks in the future, the default of the
+	/// timestamp inherent data provider if `None`.
+	pub max_timestamp_drift: Option<Duration>,
+	/// How the BABE proposer task is spawned.
+	pub proposer_spawn_mode: SpawnMode,
+	/// How the GRANDPA voter task is spawned.
+	pub grandpa_voter_spawn_mode: SpawnMode,
+	/// The rule choosing the chain the node builds on.
+	pub select_chain: crate::arg_enums::SelectChainMode,
+	/// The justification import of the import queue, GRANDPA's if `None`.
+	pub justification_import: Option<JustificationImportBuilder>,
+	/// The block import between BABE and GRANDPA, GRANDPA's own if `None`.
+	pub block_import_wrapper: Option<BlockImportWrapper>,
+	/// The order of the ready transactions in the authored blocks, the pool's if `None`.
+	pub transaction_ordering: Option<TransactionOrdering>,
+	/// Run a light client as a transaction relay, see [`new_tx_relay`].
+	pub tx_relay: bool,
+	/// The inherent data providers of the import queue, [`import_inherent_data_providers`] if
+	/// `None`.
+	pub import_inherent_data_providers: Option<ImportInherentDataProvidersBuilder>,
+	/// Preset of the GRANDPA timings.
+	pub finality_profile: FinalityProfile,
+	/// Leave out the uncles inherent, for runtimes that don't handle uncles.
+	pub disable_uncles: bool,
+	/// Provide the transaction storage proof inherent, for runtimes with the transaction storage
+	/// pallet.
+	pub transaction_storage_proof: bool,
+	/// Post every finalized block to a webhook.
+	pub finalized_webhook: Option<crate::finalized_webhook::Config>,
+	/// Publish every finalized block to a message broker.
+	pub event_sink: Option<crate::event_sink::Config>,
+	/// Log the BABE epochs the best block enters.
+	pub log_babe_epochs: bool,
+	/// Called with the BABE epochs the best block enters.
+	pub epoch_change_hook: Option<EpochChangeHook>,
+	/// Only author BABE blocks with this key.
+	pub babe_authoring_key: Option<sp_consensus_babe::AuthorityId>,
+	/// Restart the authority discovery, telemetry and offchain workers up to this many times in a
+	/// row when they fail. The node keeps running without the workers that failed more often.
+	pub auxiliary_task_restarts: Option<u32>,
+	/// Skip the slots in which the authored block would only contain inherents.
+	pub no_empty_blocks: bool,
+	/// Only author blocks during the windows of this schedule.
+	pub author_schedule: Option<crate::author_schedule::AuthorSchedule>,
+	/// Log the decoded digest items of every imported header.
+	pub log_header_digests: bool,
+	/// Log an error for every reorg retracting more than this many blocks of the best chain.
+	pub max_reorg_depth: Option<u32>,
+	/// Shut down the node after a reorg deeper than `max_reorg_depth`.
+	pub halt_on_deep_reorg: bool,
+	/// Limit the size of the served GRANDPA warp sync proofs, in bytes.
+	pub max_warp_proof_size: Option<usize>,
+	/// Wait a random duration of up to this bound before starting to author BABE blocks.
+	pub author_startup_jitter: Option<Duration>,
+	/// Prefix of the Prometheus metrics, instead of `substrate`.
+	pub metrics_namespace: Option<String>,
+	/// Re-execute every imported block to check its state root.
+	pub verify_state_root: bool,
+	/// Shut down the node after a state root mismatch found by `verify_state_root`.
+	pub halt_on_state_root_mismatch: bool,
+	/// Compare the native runtime with the on-chain runtime of the best block.
+	pub check_runtime_version: bool,
+	/// Ban threshold and decay of the reputation of the peers, the network defaults if `None`.
+	pub reputation: Option<sc_network::config::ReputationConfig>,
+	/// Reject the transactions for which the filter returns `false` from the transaction pool.
+	pub admission_filter: Option<AdmissionFilter>,
+	/// Initialize an empty database from this genesis snapshot. Only applied by the `run`
+	/// command, before the service is built.
+	pub genesis_snapshot: Option<crate::genesis_snapshot::Config>,
+	/// Override these genesis storage values when initializing an empty database, after the
+	/// genesis snapshot. Only applied by the `run` command, before the service is built.
+	pub genesis_overrides: Vec<crate::genesis_overrides::GenesisOverride>,
+	/// Include at most this many transactions in the authored blocks, on top of the inherents.
+	pub max_block_transactions: Option<usize>,
+	/// Keep trying transactions in the authored blocks until this portion of the time left
+	/// elapsed, instead of the default of the proposer.
+	pub proposer_soft_deadline: Option<sp_runtime::Percent>,
+	/// Reject the blocks authored by these authorities.
+	pub banned_authorities: HashSet<sp_consensus_babe::AuthorityId>,
+	/// Hold the GRANDPA voter back until this condition is met, instead of starting it right away.
+	pub grandpa_start: Option<GrandpaStart>,
+	/// Run as a single node without any networking, authoring blocks on its own.
+	pub isolated: bool,
+	/// Only sync and serve the finality proofs, see [`restrict_to_finality_gateway`].
+	pub finality_gateway: bool,
+	/// Peers the node connects to, or refuses to connect to.
+	pub peer_filter: sc_network::config::PeerFilter,
+	/// Periodically write the status of the node to a file.
+	pub status_file: Option<crate::file_output::Config>,
+	/// First block of the chain, imported before the network starts.
+	pub first_block: Option<sp_runtime::generic::SignedBlock<Block>>,
+	/// Refuse to start, and stop, when the filesystem of the database has less free space.
+	pub min_free_disk: Option<crate::disk_space::MinFreeDisk>,
+}
+
+/// When to start the GRANDPA voter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GrandpaStart {
+	/// Once the duration elapsed after the start of the node.
+	Delay(Duration),
+	/// Once the best block reaches the given number.
+	Block(BlockNumber),
+}
+
+/// Wait until the GRANDPA voter can be started according to `start`.
+async fn wait_grandpa_start<C>(client: Arc<C>, start: GrandpaStart)
+where
+	C: BlockchainEvents<Block> + HeaderBackend<Block>,
+{
+	match start {
+		GrandpaStart::Delay(delay) => {
+			log::info!("⏳ Waiting {:?} before starting the GRANDPA voter", delay);
+			futures_timer::Delay::new(delay).await;
+		},
+		GrandpaStart::Block(number) => {
+			log::info!("⏳ Waiting for block #{} before starting the GRANDPA voter", number);
+			let mut imports = client.import_notification_stream();
+			while client.info().best_number < number {
+				if imports.next().await.is_none() {
+					break
+				}
+			}
+		},
+	}
+}
+
+/// Run the GRANDPA voter built by `build` once it can be started according to `start`.
+///
+/// The voter runs as an essential task, so that failing to build it shuts down the node.
+async fn delayed_grandpa_voter<C, V, E>(
+	client: Arc<C>,
+	start: GrandpaStart,
+	build: impl FnOnce() -> Result<V, E>,
+) where
+	C: BlockchainEvents<Block> + HeaderBackend<Block>,
+	V: Future<Output = ()>,
+	E: fmt::Display,
+{
+	wait_grandpa_start(client, start).await;
+	match build() {
+		Ok(voter) => voter.await,
+		Err(e) => log::error!("Failed to start the GRANDPA voter, shutting down: {}", e),
+	}
+}
+
+/// The BABE slot duration configured by the runtime at the given block.
+///
+/// Unlike the value cached in the `BabeLink`, this follows changes of the slot duration over the
+/// lifetime of the chain.
+fn slot_duration_at(client: &FullClient, at: Hash) -> Result<Duration, sp_api::ApiError> {
+	let at = BlockId::Hash(at);
+	let api = client.runtime_api();
+
+	let slot_duration = if api.has_api_with::<dyn BabeApi<Block>, _>(&at, |v| v == 1)? {
+		#[allow(deprecated)]
+		api.configuration_before_version_2(&at)?.slot_duration
+	} else {
+		api.configuration(&at)?.slot_duration
+	};
+
+	Ok(Duration::from_millis(slot_duration))
+}
 
 pub fn new_partial(
 	config: &Configuration,
+	options: &NodeOptions,
 ) -> Result<
 	sc_service::PartialComponents<
 		FullClient,
@@ -57,11 +564,11 @@ pub fn new_partial(
 				sc_rpc::SubscriptionTaskExecutor,
 			) -> Result<node_rpc::IoHandler, sc_service::Error>,
 			(
-				sc_consensus_babe::BabeBlockImport<Block, FullClient, FullGrandpaBlockImport>,
+				sc_consensus_babe::BabeBlockImport<Block, FullClient, InnerBlockImport>,
 				grandpa::LinkHalf<Block, FullClient, FullSelectChain>,
 				sc_consensus_babe::BabeLink<Block>,
 			),
-			grandpa::SharedVoterState,
+			(grandpa::SharedVoterState, node_rpc::AuthoringPause, ConsensusSettings),
 			Option<Telemetry>,
 		),
 	>,
@@ -72,7 +579,8 @@ pub fn new_partial(
 		.clone()
 		.filter(|x| !x.is_empty())
 		.map(|endpoints| -> Result<_, sc_telemetry::Error> {
-			let worker = TelemetryWorker::new(16)?;
+			let worker =
+				TelemetryWorker::new(16)?.with_reconnect_policy(config.telemetry_reconnect);
 			let telemetry = worker.handle().new_telemetry(endpoints);
 			Ok((worker, telemetry))
 		})
@@ -92,19 +600,39 @@ pub fn new_partial(
 		)?;
 	let client = Arc::new(client);
 
+	if config.role.is_authority() {
+		check_keystore_password(&keystore_container, &config.keystore)?;
+	}
+
 	let telemetry = telemetry.map(|(worker, telemetry)| {
-		task_manager.spawn_handle().spawn("telemetry", worker.run());
+		match options.auxiliary_task_restarts {
+			Some(restarts) => {
+				// The restarted worker keeps the telemetries registered so far.
+				let worker = Arc::new(futures::lock::Mutex::new(worker));
+				task_manager.spawn_handle().spawn_supervised(
+					"telemetry",
+					RestartPolicy::new(restarts),
+					move || {
+						let worker = worker.clone();
+						async move { worker.lock().await.run_in_place().await }
+					},
+				)
+			},
+			None => task_manager.spawn_handle().spawn("telemetry", worker.run()),
+		}
 		telemetry
 	});
 
-	let select_chain = sc_consensus::LongestChain::new(backend.clone());
+	let select_chain =
+		crate::select_chain::NodeSelectChain::new(backend.clone(), options.select_chain);
 
-	let transaction_pool = sc_transaction_pool::BasicPool::new_full(
+	let transaction_pool = sc_transaction_pool::BasicPool::new_full_with_admission_filter(
 		config.transaction_pool.clone(),
 		config.role.is_authority().into(),
 		config.prometheus_registry(),
 		task_manager.spawn_essential_handle(),
 		client.clone(),
+		options.admission_filter.clone().map(AdmissionFilter::into_pool_filter),
 	);
 
 	let (grandpa_block_import, grandpa_link) = grandpa::block_import(
@@ -113,39 +641,51 @@ pub fn new_partial(
 		select_chain.clone(),
 		telemetry.as_ref().map(|x| x.handle()),
 	)?;
-	let justification_import = grandpa_block_import.clone();
+	let justification_import: sc_consensus::BoxJustificationImport<Block> =
+		match options.justification_import {
+			Some(ref builder) => (builder.0)(client.clone()),
+			None => Box::new(grandpa_block_import.clone()),
+		};
+
+	let inner_block_import = match options.block_import_wrapper {
+		Some(ref wrapper) => InnerBlockImport::Wrapped(Arc::new(futures::lock::Mutex::new(
+			(wrapper.0)(grandpa_block_import),
+		))),
+		None => InnerBlockImport::Grandpa(grandpa_block_import),
+	};
 
 	let (block_import, babe_link) = sc_consensus_babe::block_import(
 		sc_consensus_babe::Config::get_or_compute(&*client)?,
-		grandpa_block_import,
+		inner_block_import,
 		client.clone(),
 	)?;
 
 	let slot_duration = babe_link.config().slot_duration();
-	let import_queue = sc_consensus_babe::import_queue(
-		babe_link.clone(),
-		block_import.clone(),
-		Some(Box::new(justification_import)),
-		client.clone(),
-		select_chain.clone(),
-		move |_, ()| async move {
-			let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
-
-			let slot =
-				sp_consensus_babe::inherents::InherentDataProvider::from_timestamp_and_duration(
-					*timestamp,
-					slot_duration,
-				);
-
-			let uncles =
-				sp_authorship::InherentDataProvider::<<Block as BlockT>::Header>::check_inherents();
-
-			Ok((timestamp, slot, uncles))
+	let import_inherent_data_providers = match options.import_inherent_data_providers {
+		Some(ref builder) => (builder.0)(client.clone(), slot_duration),
+		None => import_inherent_data_providers(client.clone(), slot_duration, options),
+	};
+	let banned_authorities = options.banned_authorities.clone();
+	let epoch_changes = babe_link.epoch_changes().clone();
+	let babe_config = babe_link.config().clone();
+	let import_queue = sc_consensus_babe::import_queue_with_verifier(
+		sc_consensus_babe::ImportQueueParams {
+			babe_link: babe_link.clone(),
+			block_import: block_import.clone(),
+			justification_import: Some(justification_import),
+			client: client.clone(),
+			select_chain: select_chain.clone(),
+			create_inherent_data_providers: import_inherent_data_providers,
+			spawner: &task_manager.spawn_essential_handle(),
+			registry: config.prometheus_registry(),
+			can_author_with: sp_consensus::CanAuthorWithNativeVersion::new(
+				client.executor().clone(),
+			),
+			telemetry: telemetry.as_ref().map(|x| x.handle()),
+		},
+		|verifier| {
+			BannedAuthoritiesVerifier::new(verifier, banned_authorities, epoch_changes, babe_config)
 		},
-		&task_manager.spawn_essential_handle(),
-		config.prometheus_registry(),
-		sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone()),
-		telemetry.as_ref().map(|x| x.handle()),
 	)?;
 
 	let import_setup = (block_import, grandpa_link, babe_link);
@@ -156,7 +696,10 @@ pub fn new_partial(
 		let justification_stream = grandpa_link.justification_stream();
 		let shared_authority_set = grandpa_link.shared_authority_set().clone();
 		let shared_voter_state = grandpa::SharedVoterState::empty();
-		let rpc_setup = shared_voter_state.clone();
+		let authoring_pause = node_rpc::AuthoringPause::default();
+		let consensus_settings = ConsensusSettings::new(options);
+		let consensus_config = Arc::new(consensus_config(config, options, &consensus_settings));
+		let rpc_setup = (shared_voter_state.clone(), authoring_pause.clone(), consensus_settings);
 
 		let finality_proof_provider = grandpa::FinalityProofProvider::new_for_service(
 			backend.clone(),
@@ -167,18 +710,33 @@ pub fn new_partial(
 		let shared_epoch_changes = babe_link.epoch_changes().clone();
 
 		let client = client.clone();
+		let backend = backend.clone();
 		let pool = transaction_pool.clone();
 		let select_chain = select_chain.clone();
 		let keystore = keystore_container.sync_keystore();
 		let chain_spec = config.chain_spec.cloned_box();
+		let state_blocks_kept = match config.state_pruning {
+			sc_service::PruningMode::Constrained(ref constraints) =>
+				Some(constraints.max_blocks.unwrap_or(0)),
+			sc_service::PruningMode::ArchiveAll | sc_service::PruningMode::ArchiveCanonical => None,
+		};
+		let rpc_max_payload = config.rpc_max_payload;
+		let finality_only = options.finality_gateway;
 
 		let rpc_extensions_builder = move |deny_unsafe, subscription_executor| {
 			let deps = node_rpc::FullDeps {
 				client: client.clone(),
+				backend: backend.clone(),
 				pool: pool.clone(),
 				select_chain: select_chain.clone(),
 				chain_spec: chain_spec.cloned_box(),
 				deny_unsafe,
+				state_blocks_kept,
+				rpc_max_payload,
+				finality_only,
+				key_types: node_runtime::SessionKeys::key_ids().to_vec(),
+				authoring_pause: authoring_pause.clone(),
+				consensus_config: consensus_config.clone(),
 				babe: node_rpc::BabeDeps {
 					babe_config: babe_config.clone(),
 					shared_epoch_changes: shared_epoch_changes.clone(),
@@ -218,33 +776,181 @@ pub struct NewFullBase {
 	pub transaction_pool: Arc<sc_transaction_pool::FullPool<Block, FullClient>>,
 }
 
+/// Make sure that the password of the keystore unlocks the keys used to author blocks and vote
+/// on finality, to fail at startup rather than when the keys are first needed.
+fn check_keystore_password(
+	keystore_container: &sc_service::KeystoreContainer,
+	keystore_config: &KeystoreConfig,
+) -> Result<(), ServiceError> {
+	let keystore = match keystore_container.local_keystore() {
+		Some(keystore) => keystore,
+		None => return Ok(()),
+	};
+
+	let result = keystore
+		.check_password::<sc_consensus_babe::AuthorityPair>()
+		.and_then(|()| keystore.check_password::<grandpa::AuthorityPair>());
+
+	match result {
+		Ok(()) => Ok(()),
+		Err(sc_keystore::Error::InvalidPassword) => Err(ServiceError::Other(
+			if matches!(keystore_config, KeystoreConfig::Path { password: Some(_), .. }) {
+				"The keystore password doesn't unlock the keys of the keystore".into()
+			} else {
+				"The keys of the keystore are protected by a password, provide it with \
+				 `--password-interactive` or `--keystore-password-file`"
+					.into()
+			},
+		)),
+		Err(e) => Err(ServiceError::Other(format!("Failed to check the keystore keys: {}", e))),
+	}
+}
+
+/// Replace the Prometheus registry of `config` by one prefixing the metrics with `namespace`.
+///
+/// This happens before the client is created, so that every metric of the node is registered
+/// with the prefix.
+fn set_metrics_namespace(config: &mut Configuration, namespace: &str) -> Result<(), ServiceError> {
+	if let Some(prometheus) = config.prometheus_config.as_mut() {
+		prometheus.registry = Registry::new_custom(Some(namespace.into()), None)?;
+	}
+	Ok(())
+}
+
+/// Restrict `config` to answering RPC queries, when the database is opened read-only.
+///
+/// The node doesn't connect to any peer, so that no block is imported, and the tasks writing to
+/// the database, like authoring, GRANDPA and the offchain workers, are disabled.
+fn restrict_to_read_only(config: &mut Configuration) {
+	if config.role.is_authority() {
+		log::warn!("The database is opened read-only, not authoring blocks");
+	}
+	config.role = sc_service::Role::Full;
+	config.disable_grandpa = true;
+	config.offchain_worker.enabled = false;
+	config.offchain_worker.indexing_enabled = false;
+	disconnect(&mut config.network);
+}
+
+/// The RPC modules served by a finality gateway.
+const FINALITY_GATEWAY_RPC_MODULES: &[&str] =
+	&["chain", "childstate", "grandpa", "state", "sync", "system"];
+
+/// Restrict `config` to a finality gateway, which syncs and serves the warp sync and finality
+/// proofs to bridges and light clients.
+///
+/// The node doesn't author blocks, gossip transactions nor run the offchain workers, and only
+/// serves the safe methods of [`FINALITY_GATEWAY_RPC_MODULES`], so that no transaction can be
+/// submitted to it. The RPC extensions of the node are restricted to GRANDPA and the sync state
+/// when building them.
+fn restrict_to_finality_gateway(config: &mut Configuration) {
+	if config.role.is_authority() {
+		log::warn!("Running as a finality gateway, not authoring blocks");
+	}
+	config.role = sc_service::Role::Full;
+	config.offchain_worker.enabled = false;
+	config.offchain_worker.indexing_enabled = false;
+	config.network.transaction_propagation = TransactionPropagation::None;
+	config.rpc_methods = sc_service::config::RpcMethods::Safe;
+	config.rpc_modules =
+		Some(FINALITY_GATEWAY_RPC_MODULES.iter().map(|&module| module.into()).collect());
+}
+
+/// Run `config` as an isolated single node, which authors blocks without any peer.
+fn isolate(config: &mut Configuration) {
+	// The slots are skipped while the node has no peers otherwise.
+	config.force_authoring = true;
+	config.network.sync_mode = SyncMode::Full;
+	disconnect(&mut config.network);
+}
+
+/// Keep `network` from connecting to, or being reached by, any peer.
+fn disconnect(network: &mut NetworkConfiguration) {
+	network.listen_addresses.clear();
+	network.boot_nodes.clear();
+	network.default_peers_set = SetConfig {
+		in_peers: 0,
+		out_peers: 0,
+		reserved_nodes: Vec::new(),
+		non_reserved_mode: NonReservedPeerMode::Deny,
+	};
+	network.enable_dht_random_walk = false;
+	if let TransportConfig::Normal { enable_mdns, .. } = &mut network.transport {
+		*enable_mdns = false;
+	}
+}
+
 /// Creates a full service from the configuration.
 pub fn new_full_base(
 	mut config: Configuration,
+	options: NodeOptions,
 	with_startup_data: impl FnOnce(
-		&sc_consensus_babe::BabeBlockImport<Block, FullClient, FullGrandpaBlockImport>,
+		&sc_consensus_babe::BabeBlockImport<Block, FullClient, InnerBlockImport>,
 		&sc_consensus_babe::BabeLink<Block>,
 	),
 ) -> Result<NewFullBase, ServiceError> {
+	if config.database_read_only {
+		restrict_to_read_only(&mut config);
+	}
+	if options.isolated {
+		isolate(&mut config);
+	}
+	if options.finality_gateway {
+		restrict_to_finality_gateway(&mut config);
+	}
+	if options.metrics_dump.is_some() && config.prometheus_config.is_none() {
+		// Nothing serves the metrics, but the dump needs them collected.
+		config.prometheus_config = Some(
+			PrometheusConfig::new_with_default_registry(([127, 0, 0, 1], 0).into())
+				.without_endpoint(),
+		);
+	}
+	if let Some(namespace) = &options.metrics_namespace {
+		set_metrics_namespace(&mut config, namespace)?;
+	}
+	if let Some(reputation) = options.reputation {
+		config.network.reputation = reputation;
+	}
+	config.network.peer_filter = options.peer_filter.clone();
+	config.offchain_worker.restart_policy = options.auxiliary_task_restarts.map(RestartPolicy::new);
+
+	// A read-only database isn't written to.
+	let disk_space_guard = match (options.min_free_disk, config.database.path()) {
+		(Some(min_free), Some(path)) if !config.database_read_only => {
+			min_free.check(path).map_err(ServiceError::Other)?;
+			Some((min_free, path.to_path_buf()))
+		},
+		_ => None,
+	};
+
 	let sc_service::PartialComponents {
 		client,
 		backend,
 		mut task_manager,
-		import_queue,
+		mut import_queue,
 		keystore_container,
 		select_chain,
 		transaction_pool,
 		other: (rpc_extensions_builder, import_setup, rpc_setup, mut telemetry),
-	} = new_partial(&config)?;
+	} = new_partial(&config, &options)?;
+
+	if let Some(first_block) = options.first_block.clone() {
+		crate::first_block::import(&*client, &mut import_queue, first_block)
+			.map_err(ServiceError::Other)?;
+	}
 
-	let shared_voter_state = rpc_setup;
+	let (shared_voter_state, authoring_pause, consensus_settings) = rpc_setup;
 	let auth_disc_publish_non_global_ips = config.network.allow_non_globals_in_dht;
 
 	config.network.extra_sets.push(grandpa::grandpa_peers_set_config());
-	let warp_sync = Arc::new(grandpa::warp_proof::NetworkProvider::new(
+	let warp_sync = grandpa::warp_proof::NetworkProvider::new(
 		backend.clone(),
 		import_setup.1.shared_authority_set().clone(),
-	));
+	);
+	let warp_sync = Arc::new(match options.max_warp_proof_size {
+		Some(max_proof_size) => warp_sync.with_max_proof_size(max_proof_size),
+		None => warp_sync,
+	});
 
 	let (network, system_rpc_tx, network_starter) =
 		sc_service::build_network(sc_service::BuildNetworkParams {
@@ -255,7 +961,7 @@ pub fn new_full_base(
 			import_queue,
 			on_demand: None,
 			block_announce_validator_builder: None,
-			warp_sync: Some(warp_sync),
+			warp_sync: (!options.isolated).then(|| warp_sync as Arc<_>),
 		})?;
 
 	if config.offchain_worker.enabled {
@@ -269,13 +975,136 @@ pub fn new_full_base(
 
 	let role = config.role.clone();
 	let force_authoring = config.force_authoring;
-	let backoff_authoring_blocks =
-		Some(sc_consensus_slots::BackoffAuthoringOnFinalizedHeadLagging::default());
+	let backoff_authoring_blocks = Some(consensus_settings.backoff);
 	let name = config.network.node_name.clone();
 	let enable_grandpa = !config.disable_grandpa;
 	let prometheus_registry = config.prometheus_registry().cloned();
 
-	let _rpc_handlers = sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+	if let Some(metrics_push) = options.metrics_push {
+		match prometheus_registry.clone() {
+			Some(registry) => task_manager
+				.spawn_handle()
+				.spawn("metrics-push", crate::metrics_push::run(metrics_push, registry)),
+			None =>
+				log::warn!("Prometheus is disabled, not pushing metrics to {}", metrics_push.url),
+		}
+	}
+
+	if let Some(metrics_dump) = options.metrics_dump {
+		let registry = prometheus_registry
+			.clone()
+			.expect("a registry is created when the metrics are dumped; qed");
+		task_manager
+			.spawn_handle()
+			.spawn("metrics-dump", crate::metrics_dump::run(metrics_dump, registry));
+	}
+
+	if let Some((min_free, path)) = disk_space_guard {
+		task_manager.spawn_essential_handle().spawn("disk-space", min_free.run(path));
+	}
+
+	if let Some(status_file) = options.status_file {
+		task_manager.spawn_handle().spawn(
+			"status-file",
+			crate::status_file::run(status_file, client.clone(), network.clone()),
+		);
+	}
+
+	if let Some(registry) = prometheus_registry.as_ref() {
+		let metrics = crate::sync_metrics::Metrics::register(registry)?;
+		task_manager.spawn_handle().spawn(
+			"sync-metrics",
+			crate::sync_metrics::run(
+				metrics,
+				client.clone(),
+				network.clone(),
+				system_rpc_tx.clone(),
+			),
+		);
+	}
+
+	if let Some(registry) = prometheus_registry.as_ref() {
+		let metrics = crate::protocol_traffic::Metrics::register(registry)?;
+		task_manager
+			.spawn_handle()
+			.spawn("protocol-traffic", crate::protocol_traffic::run(metrics, network.clone()));
+	}
+
+	if options.log_header_digests {
+		task_manager
+			.spawn_handle()
+			.spawn("header-digests", crate::header_digests::run(client.clone()));
+	}
+
+	if let Some(max_depth) = options.max_reorg_depth {
+		let halt = options.halt_on_deep_reorg;
+		let guard = crate::reorg_guard::run(client.clone(), max_depth, halt);
+		if halt {
+			// The node shuts down once an essential task ends.
+			task_manager.spawn_essential_handle().spawn("reorg-guard", guard);
+		} else {
+			task_manager.spawn_handle().spawn("reorg-guard", guard);
+		}
+	}
+
+	if options.verify_state_root {
+		let metrics = prometheus_registry
+			.as_ref()
+			.map(crate::state_root_check::Metrics::register)
+			.transpose()?;
+		let halt = options.halt_on_state_root_mismatch;
+		let check = crate::state_root_check::run(client.clone(), metrics, halt);
+		if halt {
+			task_manager.spawn_essential_handle().spawn_blocking("state-root-check", check);
+		} else {
+			task_manager.spawn_handle().spawn_blocking("state-root-check", check);
+		}
+	}
+
+	if options.check_runtime_version {
+		let metrics = prometheus_registry
+			.as_ref()
+			.map(crate::runtime_version_check::Metrics::register)
+			.transpose()?;
+		let native = node_runtime::native_version();
+		task_manager.spawn_handle().spawn(
+			"runtime-version-check",
+			crate::runtime_version_check::run(client.clone(), native, metrics),
+		);
+	}
+
+	if let Some(finalized_webhook) = options.finalized_webhook {
+		task_manager.spawn_handle().spawn(
+			"finalized-webhook",
+			crate::finalized_webhook::run(finalized_webhook, client.clone()),
+		);
+	}
+
+	if let Some(event_sink) = options.event_sink {
+		task_manager
+			.spawn_handle()
+			.spawn("event-sink", crate::event_sink::run(event_sink, client.clone()));
+	}
+
+	let keeps_indexed_transactions = matches!(
+		config.transaction_storage,
+		sc_service::config::TransactionStorageMode::StorageChain
+	);
+	if options.transaction_storage_proof && !keeps_indexed_transactions {
+		log::warn!(
+			"Indexed transactions are not kept without `--storage-chain`, \
+			 no transaction storage proof can be provided",
+		);
+	}
+
+	let rpc_proxies = crate::rpc_proxy::prepare(
+		options.rpc_tls.as_ref(),
+		options.rpc_max_connections,
+		&mut config,
+	)
+	.map_err(ServiceError::Other)?;
+
+	let rpc_handlers = sc_service::spawn_tasks(sc_service::SpawnTasksParams {
 		config,
 		backend: backend.clone(),
 		client: client.clone(),
@@ -289,25 +1118,71 @@ pub fn new_full_base(
 		system_rpc_tx,
 		telemetry: telemetry.as_mut(),
 	})?;
+	for proxy in rpc_proxies {
+		let spawn_handle = task_manager.spawn_handle();
+		task_manager
+			.spawn_handle()
+			.spawn("rpc-proxy", proxy.run(rpc_handlers.addresses(), spawn_handle));
+	}
 
 	let (block_import, grandpa_link, babe_link) = import_setup;
 
 	(with_startup_data)(&block_import, &babe_link);
 
+	task_manager.spawn_handle().spawn(
+		"clock-skew",
+		crate::clock_skew::run(client.clone(), network.clone(), babe_link.config().slot_duration()),
+	);
+
+	let epochs_keystore = options.log_babe_epochs.then(|| keystore_container.sync_keystore());
+	let epoch_change_hook = options.epoch_change_hook.clone();
+	if epochs_keystore.is_some() || epoch_change_hook.is_some() {
+		let spawner = task_manager.spawn_handle();
+		task_manager.spawn_handle().spawn(
+			"babe-epochs",
+			crate::babe_epochs::run(client.clone(), babe_link.clone(), move |epoch| {
+				if let Some(keystore) = &epochs_keystore {
+					crate::babe_epochs::log_epoch(keystore, epoch);
+				}
+				if let Some(hook) = &epoch_change_hook {
+					hook.spawn(&spawner, epoch);
+				}
+			}),
+		);
+	}
+
 	if let sc_service::config::Role::Authority { .. } = &role {
-		let proposer = sc_basic_authorship::ProposerFactory::new(
+		let mut proposer = sc_basic_authorship::ProposerFactory::new(
 			task_manager.spawn_handle(),
 			client.clone(),
 			transaction_pool.clone(),
 			prometheus_registry.as_ref(),
 			telemetry.as_ref().map(|x| x.handle()),
 		);
+		if let Some(max) = options.max_block_transactions {
+			proposer.set_max_transactions(max);
+		}
+		if let Some(percent) = options.proposer_soft_deadline {
+			proposer.set_soft_deadline(percent);
+		}
+		if let Some(TransactionOrdering(ordering)) = options.transaction_ordering {
+			proposer.set_transaction_ordering(move |a, b| ordering(a, b));
+		}
 
 		let can_author_with =
 			sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone());
 
 		let client_clone = client.clone();
 		let slot_duration = babe_link.config().slot_duration();
+		let disable_uncles = options.disable_uncles;
+		let no_empty_blocks = options.no_empty_blocks.then(|| {
+			NoEmptyBlocks::new(
+				client.clone(),
+				transaction_pool.clone(),
+				babe_link.config().epoch_length,
+			)
+		});
+		let transaction_storage_proof = options.transaction_storage_proof;
 		let babe_config = sc_consensus_babe::BabeParams {
 			keystore: keystore_container.sync_keystore(),
 			client: client.clone(),
@@ -319,10 +1194,14 @@ pub fn new_full_base(
 			create_inherent_data_providers: move |parent, ()| {
 				let client_clone = client_clone.clone();
 				async move {
-					let uncles = sc_consensus_uncles::create_uncles_inherent_data_provider(
-						&*client_clone,
-						parent,
-					)?;
+					let uncles = if disable_uncles {
+						None
+					} else {
+						Some(sc_consensus_uncles::create_uncles_inherent_data_provider(
+							&*client_clone,
+							parent,
+						)?)
+					};
 
 					let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
 
@@ -332,55 +1211,92 @@ pub fn new_full_base(
 							slot_duration,
 						);
 
-					let storage_proof =
-						sp_transaction_storage_proof::registration::new_data_provider(
+					let storage_proof = if transaction_storage_proof {
+						Some(sp_transaction_storage_proof::registration::new_data_provider(
 							&*client_clone,
 							&parent,
-						)?;
+						)?)
+					} else {
+						None
+					};
 
 					Ok((timestamp, slot, uncles, storage_proof))
 				}
 			},
 			force_authoring,
-			backoff_authoring_blocks,
+			backoff_authoring_blocks: Some(AuthoringBackoff::new(
+				backoff_authoring_blocks,
+				no_empty_blocks,
+				authoring_pause,
+				options.author_schedule.clone(),
+			)),
 			babe_link,
 			can_author_with,
-			block_proposal_slot_portion: SlotProportion::new(0.5),
+			block_proposal_slot_portion: SlotProportion::new(BLOCK_PROPOSAL_SLOT_PORTION),
 			max_block_proposal_slot_portion: None,
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
+			authoring_key: options.babe_authoring_key.clone(),
 		};
 
 		let babe = sc_consensus_babe::start_babe(babe_config)?;
-		task_manager.spawn_essential_handle().spawn_blocking("babe-proposer", babe);
+		let startup_jitter = options.author_startup_jitter.map(|bound| {
+			use rand::Rng;
+			Duration::from_millis(rand::thread_rng().gen_range(0, bound.as_millis() as u64 + 1))
+		});
+		let babe = async move {
+			if let Some(jitter) = startup_jitter {
+				log::info!("⏳ Waiting {:?} before authoring blocks", jitter);
+				futures_timer::Delay::new(jitter).await;
+			}
+			babe.await
+		};
+		options.proposer_spawn_mode.spawn_essential(
+			&task_manager.spawn_essential_handle(),
+			"babe-proposer",
+			babe,
+		);
 	}
 
 	// Spawn authority discovery module.
 	if role.is_authority() {
-		let authority_discovery_role =
-			sc_authority_discovery::Role::PublishAndDiscover(keystore_container.keystore());
-		let dht_event_stream =
-			network.event_stream("authority-discovery").filter_map(|e| async move {
-				match e {
-					Event::Dht(e) => Some(e),
-					_ => None,
-				}
-			});
-		let (authority_discovery_worker, _service) =
-			sc_authority_discovery::new_worker_and_service_with_config(
-				sc_authority_discovery::WorkerConfig {
-					publish_non_global_ips: auth_disc_publish_non_global_ips,
-					..Default::default()
-				},
-				client.clone(),
-				network.clone(),
-				Box::pin(dht_event_stream),
-				authority_discovery_role,
-				prometheus_registry.clone(),
-			);
+		let keystore = keystore_container.keystore();
+		let mut prometheus_registry = prometheus_registry.clone();
+		let client = client.clone();
+		let network = network.clone();
+		let mut authority_discovery_worker = move || {
+			let dht_event_stream =
+				network.event_stream("authority-discovery").filter_map(|e| async move {
+					match e {
+						Event::Dht(e) => Some(e),
+						_ => None,
+					}
+				});
+			let (authority_discovery_worker, _service) =
+				sc_authority_discovery::new_worker_and_service_with_config(
+					sc_authority_discovery::WorkerConfig {
+						publish_non_global_ips: auth_disc_publish_non_global_ips,
+						..Default::default()
+					},
+					client.clone(),
+					network.clone(),
+					Box::pin(dht_event_stream),
+					sc_authority_discovery::Role::PublishAndDiscover(keystore.clone()),
+					// The metrics are registered by the first worker only.
+					prometheus_registry.take(),
+				);
+			authority_discovery_worker.run()
+		};
 
-		task_manager
-			.spawn_handle()
-			.spawn("authority-discovery-worker", authority_discovery_worker.run());
+		match options.auxiliary_task_restarts {
+			Some(restarts) => task_manager.spawn_handle().spawn_supervised(
+				"authority-discovery-worker",
+				RestartPolicy::new(restarts),
+				authority_discovery_worker,
+			),
+			None => task_manager
+				.spawn_handle()
+				.spawn("authority-discovery-worker", authority_discovery_worker()),
+		}
 	}
 
 	// if the node isn't actively participating in consensus then it doesn't
@@ -389,14 +1305,11 @@ pub fn new_full_base(
 		if role.is_authority() { Some(keystore_container.sync_keystore()) } else { None };
 
 	let config = grandpa::Config {
-		// FIXME #1578 make this available through chainspec
-		gossip_duration: std::time::Duration::from_millis(333),
-		justification_period: 512,
 		name: Some(name),
-		observer_enabled: false,
 		keystore,
 		local_role: role,
 		telemetry: telemetry.as_ref().map(|x| x.handle()),
+		..consensus_settings.grandpa
 	};
 
 	if enable_grandpa {
@@ -418,9 +1331,24 @@ pub fn new_full_base(
 
 		// the GRANDPA voter task is considered infallible, i.e.
 		// if it fails we take down the service with it.
-		task_manager
-			.spawn_essential_handle()
-			.spawn_blocking("grandpa-voter", grandpa::run_grandpa_voter(grandpa_config)?);
+		let spawner = task_manager.spawn_essential_handle();
+		let spawn_mode = options.grandpa_voter_spawn_mode;
+		match options.grandpa_start {
+			None => spawn_mode.spawn_essential(
+				&spawner,
+				"grandpa-voter",
+				grandpa::run_grandpa_voter(grandpa_config)?,
+			),
+			// The voter is only built once it starts, so that no GRANDPA messages pile up in the
+			// meantime.
+			Some(start) => spawn_mode.spawn_essential(
+				&spawner,
+				"grandpa-voter",
+				delayed_grandpa_voter(client.clone(), start, move || {
+					grandpa::run_grandpa_voter(grandpa_config)
+				}),
+			),
+		}
 	}
 
 	network_starter.start_network();
@@ -428,30 +1356,38 @@ pub fn new_full_base(
 }
 
 /// Builds a new service for a full client.
-pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
-	new_full_base(config, |_, _| ()).map(|NewFullBase { task_manager, .. }| task_manager)
+pub fn new_full(config: Configuration, options: NodeOptions) -> Result<TaskManager, ServiceError> {
+	new_full_base(config, options, |_, _| ()).map(|NewFullBase { task_manager, .. }| task_manager)
 }
 
 pub fn new_light_base(
+	config: Configuration,
+	options: NodeOptions,
+) -> Result<LightBase, ServiceError> {
+	light_base(config, options, false)
+}
+
+/// The RPC modules served by a transaction relay.
+const TX_RELAY_RPC_MODULES: &[&str] = &["author", "system"];
+
+fn light_base(
 	mut config: Configuration,
-) -> Result<
-	(
-		TaskManager,
-		RpcHandlers,
-		Arc<LightClient>,
-		Arc<NetworkService<Block, <Block as BlockT>::Hash>>,
-		Arc<
-			sc_transaction_pool::LightPool<Block, LightClient, sc_network::config::OnDemand<Block>>,
-		>,
-	),
-	ServiceError,
-> {
+	options: NodeOptions,
+	tx_relay: bool,
+) -> Result<LightBase, ServiceError> {
+	if tx_relay {
+		config.offchain_worker.enabled = false;
+		config.rpc_modules =
+			Some(TX_RELAY_RPC_MODULES.iter().map(|&module| module.into()).collect());
+	}
+
 	let telemetry = config
 		.telemetry_endpoints
 		.clone()
 		.filter(|x| !x.is_empty())
 		.map(|endpoints| -> Result<_, sc_telemetry::Error> {
-			let worker = TelemetryWorker::new(16)?;
+			let worker =
+				TelemetryWorker::new(16)?.with_reconnect_policy(config.telemetry_reconnect);
 			let telemetry = worker.handle().new_telemetry(endpoints);
 			Ok((worker, telemetry))
 		})
@@ -502,6 +1438,7 @@ pub fn new_light_base(
 	)?;
 
 	let slot_duration = babe_link.config().slot_duration();
+	let disable_uncles = options.disable_uncles;
 	let import_queue = sc_consensus_babe::import_queue(
 		babe_link,
 		babe_block_import,
@@ -517,8 +1454,9 @@ pub fn new_light_base(
 					slot_duration,
 				);
 
-			let uncles =
-				sp_authorship::InherentDataProvider::<<Block as BlockT>::Header>::check_inherents();
+			let uncles = (!disable_uncles).then(
+				sp_authorship::InherentDataProvider::<<Block as BlockT>::Header>::check_inherents,
+			);
 
 			Ok((timestamp, slot, uncles))
 		},
@@ -545,18 +1483,17 @@ pub fn new_light_base(
 			warp_sync: Some(warp_sync),
 		})?;
 
-	let enable_grandpa = !config.disable_grandpa;
+	// A transaction relay doesn't follow the finality gossip, it only finalizes the blocks
+	// whose justifications come with them, the ones changing the authority set.
+	let enable_grandpa = !config.disable_grandpa && !tx_relay;
 	if enable_grandpa {
 		let name = config.network.node_name.clone();
 
 		let config = grandpa::Config {
-			gossip_duration: std::time::Duration::from_millis(333),
-			justification_period: 512,
 			name: Some(name),
-			observer_enabled: false,
-			keystore: None,
 			local_role: config.role.clone(),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
+			..grandpa_config_template(FinalityProfile::Standard)
 		};
 
 		task_manager.spawn_handle().spawn_blocking(
@@ -603,14 +1540,35 @@ pub fn new_light_base(
 }
 
 /// Builds a new service for a light client.
-pub fn new_light(config: Configuration) -> Result<TaskManager, ServiceError> {
-	new_light_base(config).map(|(task_manager, _, _, _, _)| task_manager)
+pub fn new_light(config: Configuration, options: NodeOptions) -> Result<TaskManager, ServiceError> {
+	new_light_base(config, options).map(|(task_manager, _, _, _, _)| task_manager)
+}
+
+/// Builds a new service for a transaction relay, a light client which only accepts transactions
+/// over RPC and gossips them to its peers.
+///
+/// The transactions are validated against the state of the full peers, as the light client
+/// keeps none. Only the `author` and `system` RPC methods are served, and neither the GRANDPA
+/// observer nor the offchain workers run. Without the observer the relay's finalized block only
+/// moves at the blocks ending an authority set, as those are imported with a justification.
+pub fn new_tx_relay(
+	config: Configuration,
+	options: NodeOptions,
+) -> Result<TaskManager, ServiceError> {
+	light_base(config, options, true).map(|(task_manager, _, _, _, _)| task_manager)
 }
 
 #[cfg(test)]
 mod tests {
-	use crate::service::{new_full_base, new_light_base, NewFullBase};
+	use crate::{
+		service::{
+			delayed_grandpa_voter, new_full_base, new_light_base, wait_grandpa_start,
+			BlockImportWrapper, EpochChangeHook, GrandpaStart, NewFullBase, NodeOptions,
+		},
+		test_client::TestClient,
+	};
 	use codec::Encode;
+	use futures::FutureExt;
 	use node_primitives::{Block, DigestItem, Signature};
 	use node_runtime::{
 		constants::{currency::CENTS, time::SLOT_DURATION},
@@ -669,6 +1627,7 @@ mod tests {
 				let NewFullBase { task_manager, client, network, transaction_pool, .. } =
 					new_full_base(
 						config,
+						Default::default(),
 						|block_import: &sc_consensus_babe::BabeBlockImport<Block, _, _>,
 						 babe_link: &sc_consensus_babe::BabeLink<Block>| {
 							setup_handles = Some((block_import.clone(), babe_link.clone()));
@@ -684,7 +1643,8 @@ mod tests {
 				Ok((node, setup_handles.unwrap()))
 			},
 			|config| {
-				let (keep_alive, _, client, network, transaction_pool) = new_light_base(config)?;
+				let (keep_alive, _, client, network, transaction_pool) =
+					new_light_base(config, Default::default())?;
 				Ok(sc_service_test::TestNetComponents::new(
 					keep_alive,
 					client,
@@ -847,7 +1807,7 @@ mod tests {
 			crate::chain_spec::tests::integration_test_config_with_two_authorities(),
 			|config| {
 				let NewFullBase { task_manager, client, network, transaction_pool, .. } =
-					new_full_base(config, |_, _| ())?;
+					new_full_base(config, Default::default(), |_, _| ())?;
 				Ok(sc_service_test::TestNetComponents::new(
 					task_manager,
 					client,
@@ -856,7 +1816,8 @@ mod tests {
 				))
 			},
 			|config| {
-				let (keep_alive, _, client, network, transaction_pool) = new_light_base(config)?;
+				let (keep_alive, _, client, network, transaction_pool) =
+					new_light_base(config, Default::default())?;
 				Ok(sc_service_test::TestNetComponents::new(
 					keep_alive,
 					client,
@@ -867,4 +1828,164 @@ mod tests {
 			vec!["//Alice".into(), "//Bob".into()],
 		)
 	}
+
+	/// Records the origin of every block passed on to GRANDPA.
+	struct CountingBlockImport {
+		inner: super::FullGrandpaBlockImport,
+		origins: Arc<std::sync::Mutex<Vec<BlockOrigin>>>,
+	}
+
+	#[async_trait::async_trait]
+	impl BlockImport<Block> for CountingBlockImport {
+		type Error = sp_consensus::Error;
+		type Transaction = sp_api::TransactionFor<super::FullClient, Block>;
+
+		async fn check_block(
+			&mut self,
+			block: sc_consensus::BlockCheckParams<Block>,
+		) -> Result<sc_consensus::ImportResult, Self::Error> {
+			self.inner.check_block(block).await
+		}
+
+		async fn import_block(
+			&mut self,
+			block: BlockImportParams<Block, Self::Transaction>,
+			cache: std::collections::HashMap<sp_consensus::CacheKeyId, Vec<u8>>,
+		) -> Result<sc_consensus::ImportResult, Self::Error> {
+			self.origins.lock().unwrap().push(block.origin);
+			self.inner.import_block(block, cache).await
+		}
+	}
+
+	#[test]
+	#[ignore]
+	fn block_import_wrapper_sees_the_imported_and_authored_blocks() {
+		let origins = Arc::new(std::sync::Mutex::new(Vec::new()));
+		let wrapper = {
+			let origins = origins.clone();
+			BlockImportWrapper(Arc::new(move |inner| {
+				Box::new(CountingBlockImport { inner, origins: origins.clone() })
+			}))
+		};
+
+		sc_service_test::consensus(
+			crate::chain_spec::tests::integration_test_config_with_two_authorities(),
+			|config| {
+				let options = NodeOptions {
+					block_import_wrapper: Some(wrapper.clone()),
+					..Default::default()
+				};
+				let NewFullBase { task_manager, client, network, transaction_pool, .. } =
+					new_full_base(config, options, |_, _| ())?;
+				Ok(sc_service_test::TestNetComponents::new(
+					task_manager,
+					client,
+					network,
+					transaction_pool,
+				))
+			},
+			|config| {
+				let (keep_alive, _, client, network, transaction_pool) =
+					new_light_base(config, Default::default())?;
+				Ok(sc_service_test::TestNetComponents::new(
+					keep_alive,
+					client,
+					network,
+					transaction_pool,
+				))
+			},
+			vec!["//Alice".into(), "//Bob".into()],
+		);
+
+		let origins = origins.lock().unwrap();
+		assert!(origins.contains(&BlockOrigin::Own));
+		assert!(origins.iter().any(|origin| *origin != BlockOrigin::Own));
+	}
+
+	#[test]
+	fn epoch_change_hook_is_spawned() {
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		let handle = runtime.handle().clone();
+		let task_executor: sc_service::TaskExecutor =
+			(move |future, _| handle.spawn(future).map(drop)).into();
+		let task_manager = sc_service::TaskManager::new(task_executor, None).unwrap();
+
+		let (tx, rx) = std::sync::mpsc::channel();
+		let tx = std::sync::Mutex::new(tx);
+		let hook = EpochChangeHook(Arc::new(move |epoch: &sc_consensus_babe::Epoch| {
+			tx.lock()
+				.unwrap()
+				.send((epoch.epoch_index, std::thread::current().id()))
+				.unwrap()
+		}));
+		let epoch = sc_consensus_babe::Epoch {
+			epoch_index: 3,
+			start_slot: 0.into(),
+			duration: 10,
+			authorities: Vec::new(),
+			randomness: [0; 32],
+			config: sp_consensus_babe::BabeEpochConfiguration {
+				c: (1, 4),
+				allowed_slots: sp_consensus_babe::AllowedSlots::PrimaryAndSecondaryPlainSlots,
+			},
+		};
+
+		hook.spawn(&task_manager.spawn_handle(), &epoch);
+		let (index, thread) = rx.recv_timeout(std::time::Duration::from_secs(10)).unwrap();
+		assert_eq!(index, 3);
+		assert_ne!(thread, std::thread::current().id());
+	}
+
+	#[test]
+	fn grandpa_waits_for_the_start_block() {
+		let client = TestClient::new();
+		let genesis = client.genesis();
+		let mut wait = Box::pin(wait_grandpa_start(client.clone(), GrandpaStart::Block(2)));
+		assert!(wait.as_mut().now_or_never().is_none());
+
+		let block1 = client.import_child(&genesis);
+		assert!(wait.as_mut().now_or_never().is_none());
+
+		client.import_child(&block1);
+		assert!(wait.as_mut().now_or_never().is_some());
+
+		// Past the start block, the voter starts right away.
+		assert!(wait_grandpa_start(client, GrandpaStart::Block(1)).now_or_never().is_some());
+	}
+
+	#[test]
+	fn grandpa_waits_for_the_start_delay() {
+		let start = std::time::Instant::now();
+		let delay = std::time::Duration::from_millis(100);
+		futures::executor::block_on(wait_grandpa_start(
+			TestClient::new(),
+			GrandpaStart::Delay(delay),
+		));
+		assert!(start.elapsed() >= delay);
+	}
+
+	#[test]
+	fn grandpa_voter_build_failure_shuts_down_the_node() {
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		let handle = runtime.handle().clone();
+		let task_executor: sc_service::TaskExecutor =
+			(move |future, _| handle.spawn(future).map(drop)).into();
+		let mut task_manager = sc_service::TaskManager::new(task_executor, None).unwrap();
+
+		let voter = delayed_grandpa_voter(
+			TestClient::new(),
+			GrandpaStart::Delay(std::time::Duration::from_millis(10)),
+			|| Err::<futures::future::Pending<()>, _>("no voter"),
+		);
+		task_manager.spawn_essential_handle().spawn("grandpa-voter", voter);
+
+		let stopped = runtime.block_on(async {
+			futures::future::select(
+				task_manager.future(),
+				futures_timer::Delay::new(std::time::Duration::from_secs(10)),
+			)
+			.await
+		});
+		assert!(matches!(stopped, futures::future::Either::Left((Err(_), _))));
+	}
 }
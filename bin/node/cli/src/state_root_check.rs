@@ -0,0 +1,241 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Re-verification of the state root of the imported blocks.
+//!
+//! The import already checks the state root computed by the runtime against the header. This
+//! check executes every imported block a second time, on top of the state of its parent as read
+//! back from the database, which catches a state that got corrupted after it was written. The
+//! blocks of the initial sync aren't checked, and at most [`MAX_QUEUED_BLOCKS`] wait for their
+//! check, the oldest ones being skipped when the check can't keep up with the import.
+
+use futures::prelude::*;
+use node_primitives::{Block, Hash, Header};
+use prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
+use sc_block_builder::{BlockBuilderApi, BlockBuilderProvider};
+use sc_client_api::{backend, BlockBackend, BlockImportNotification, BlockchainEvents};
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_consensus::BlockOrigin;
+use sp_runtime::{
+	generic::{BlockId, Digest},
+	traits::{Block as BlockT, Header as HeaderT},
+};
+use std::{collections::VecDeque, sync::Arc};
+
+const LOG_TARGET: &str = "state-root-check";
+
+/// The maximum number of imported blocks waiting for their check.
+pub const MAX_QUEUED_BLOCKS: usize = 64;
+
+/// Counter of the state root mismatches.
+pub struct Metrics {
+	mismatches: Counter<U64>,
+}
+
+impl Metrics {
+	/// Register the counter in `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			mismatches: register(
+				Counter::new(
+					"state_root_mismatches_total",
+					"Number of imported blocks whose state root differs when re-executing them",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
+/// Re-execute every block imported by `client`, and log an error when the resulting state root
+/// doesn't match the one of the header.
+///
+/// With `halt`, the future returns after the first mismatch, which is meant to shut down the node
+/// when it runs as an essential task. The blocks are executed synchronously, so the future should
+/// be spawned as a blocking task.
+pub async fn run<B, C>(client: Arc<C>, metrics: Option<Metrics>, halt: bool)
+where
+	B: backend::Backend<Block>,
+	C: BlockchainEvents<Block>
+		+ BlockBackend<Block>
+		+ BlockBuilderProvider<B, Block, C>
+		+ ProvideRuntimeApi<Block>,
+	C::Api:
+		BlockBuilderApi<Block> + ApiExt<Block, StateBackend = backend::StateBackendFor<B, Block>>,
+{
+	let mut imports = client.import_notification_stream();
+	let mut queue = Queue::default();
+	loop {
+		// Take the blocks imported while the previous one was executed.
+		while let Some(notification) = imports.next().now_or_never().flatten() {
+			queue.push(notification);
+		}
+		let notification = match queue.pop() {
+			Some(notification) => notification,
+			None => match imports.next().await {
+				Some(notification) => {
+					queue.push(notification);
+					continue
+				},
+				None => return,
+			},
+		};
+
+		let header = &notification.header;
+		let state_root = match execute(&*client, notification.hash, header) {
+			Ok(state_root) => state_root,
+			Err(err) => {
+				log::warn!(
+					target: LOG_TARGET,
+					"Failed to re-execute #{} ({}): {}",
+					header.number(),
+					notification.hash,
+					err,
+				);
+				continue
+			},
+		};
+
+		if state_root == *header.state_root() {
+			continue
+		}
+
+		log::error!(
+			target: LOG_TARGET,
+			"🚨 State root mismatch at #{} ({}): the header has {}, re-executing the block gives {}",
+			header.number(),
+			notification.hash,
+			header.state_root(),
+			state_root,
+		);
+		if let Some(metrics) = &metrics {
+			metrics.mismatches.inc();
+		}
+
+		if halt {
+			log::error!(target: LOG_TARGET, "Halting the node after a state root mismatch");
+			return
+		}
+	}
+}
+
+/// The imported blocks waiting for their check, the oldest first.
+#[derive(Default)]
+struct Queue {
+	blocks: VecDeque<BlockImportNotification<Block>>,
+}
+
+impl Queue {
+	/// Queue the block of `notification`, unless it comes from the initial sync. The oldest
+	/// block is skipped when the queue is full.
+	fn push(&mut self, notification: BlockImportNotification<Block>) {
+		if notification.origin == BlockOrigin::NetworkInitialSync {
+			return
+		}
+		if self.blocks.len() == MAX_QUEUED_BLOCKS {
+			if let Some(skipped) = self.blocks.pop_front() {
+				log::debug!(
+					target: LOG_TARGET,
+					"Skipping the check of #{} ({}), {} blocks are waiting",
+					skipped.header.number(),
+					skipped.hash,
+					MAX_QUEUED_BLOCKS,
+				);
+			}
+		}
+		self.blocks.push_back(notification);
+	}
+
+	fn pop(&mut self) -> Option<BlockImportNotification<Block>> {
+		self.blocks.pop_front()
+	}
+}
+
+/// Execute the block `hash` on top of the state of its parent, returning the state root.
+fn execute<B, C>(client: &C, hash: Hash, header: &Header) -> Result<Hash, String>
+where
+	B: backend::Backend<Block>,
+	C: BlockBackend<Block> + BlockBuilderProvider<B, Block, C> + ProvideRuntimeApi<Block>,
+	C::Api:
+		BlockBuilderApi<Block> + ApiExt<Block, StateBackend = backend::StateBackendFor<B, Block>>,
+{
+	let extrinsics = client
+		.block_body(&BlockId::Hash(hash))
+		.map_err(|e| e.to_string())?
+		.ok_or("The body of the block is missing")?;
+
+	// The seal is added once the block is executed.
+	let logs = header.digest().logs().iter().filter(|item| item.as_seal().is_none());
+	let digest = Digest { logs: logs.cloned().collect() };
+
+	let mut builder = client
+		.new_block_at(&BlockId::Hash(*header.parent_hash()), digest, false)
+		.map_err(|e| e.to_string())?;
+	for extrinsic in extrinsics {
+		builder.push(extrinsic).map_err(|e| e.to_string())?;
+	}
+	let block = builder.build().map_err(|e| e.to_string())?.block;
+
+	Ok(*block.header().state_root())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn notification(number: u32, origin: BlockOrigin) -> BlockImportNotification<Block> {
+		let header = Header::new(
+			number,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		);
+		BlockImportNotification {
+			hash: header.hash(),
+			origin,
+			header,
+			is_new_best: true,
+			tree_route: None,
+		}
+	}
+
+	#[test]
+	fn queue_skips_the_initial_sync() {
+		let mut queue = Queue::default();
+		queue.push(notification(1, BlockOrigin::NetworkInitialSync));
+		queue.push(notification(2, BlockOrigin::NetworkBroadcast));
+		queue.push(notification(3, BlockOrigin::Own));
+
+		let numbers: Vec<_> = std::iter::from_fn(|| queue.pop()).map(|n| n.header.number).collect();
+		assert_eq!(numbers, vec![2, 3]);
+	}
+
+	#[test]
+	fn queue_skips_the_oldest_blocks_when_full() {
+		let mut queue = Queue::default();
+		let count = MAX_QUEUED_BLOCKS as u32 + 3;
+		for number in 1..=count {
+			queue.push(notification(number, BlockOrigin::NetworkBroadcast));
+		}
+
+		assert_eq!(queue.blocks.len(), MAX_QUEUED_BLOCKS);
+		assert_eq!(queue.pop().map(|n| n.header.number), Some(4));
+		assert_eq!(queue.blocks.back().map(|n| n.header.number), Some(count));
+	}
+}
@@ -0,0 +1,122 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Snapshot of the node status in a local file.
+//!
+//! Lets scripts check the health of the node without the RPC or a metrics pipeline. The best and
+//! finalized blocks, the number of peers and whether the node is syncing are written as a single
+//! JSON object at a fixed interval, replacing the file atomically.
+
+use crate::file_output::{replace, Config};
+use futures_timer::Delay;
+use node_primitives::Block;
+use sc_client_api::{blockchain::Info, HeaderBackend};
+use sc_network::{NetworkService, NetworkStatus, SyncState};
+use serde_json::{json, Value};
+use sp_runtime::traits::Block as BlockT;
+use std::sync::Arc;
+
+const LOG_TARGET: &str = "status-file";
+
+/// Write the status of `client` and `network` to the configured file every `config.interval`.
+///
+/// A failed write is logged and retried on the next tick, the task only ends with the network.
+pub async fn run<C>(
+	config: Config,
+	client: Arc<C>,
+	network: Arc<NetworkService<Block, <Block as BlockT>::Hash>>,
+) where
+	C: HeaderBackend<Block>,
+{
+	log::info!(
+		target: LOG_TARGET,
+		"📝 Writing the node status to {} every {:?}",
+		config.path.display(),
+		config.interval,
+	);
+
+	loop {
+		let status = match network.status().await {
+			Ok(status) => status,
+			Err(()) => {
+				log::debug!(target: LOG_TARGET, "The network is gone, stopping");
+				return
+			},
+		};
+
+		let json = status_json(&client.info(), &status).to_string();
+		if let Err(err) = replace(&config.path, json.as_bytes()).await {
+			log::warn!(
+				target: LOG_TARGET,
+				"Failed to write the status to {}: {}",
+				config.path.display(),
+				err,
+			);
+		}
+
+		Delay::new(config.interval).await;
+	}
+}
+
+fn status_json(info: &Info<Block>, status: &NetworkStatus<Block>) -> Value {
+	json!({
+		"best": { "number": info.best_number, "hash": info.best_hash },
+		"finalized": { "number": info.finalized_number, "hash": info.finalized_hash },
+		"peers": status.num_connected_peers,
+		"syncing": status.sync_state == SyncState::Downloading,
+		"syncTarget": status.best_seen_block,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::H256;
+
+	#[test]
+	fn describes_the_status() {
+		let info = Info::<Block> {
+			best_hash: H256::repeat_byte(2),
+			best_number: 12,
+			genesis_hash: H256::repeat_byte(0),
+			finalized_hash: H256::repeat_byte(1),
+			finalized_number: 10,
+			finalized_state: None,
+			number_leaves: 1,
+		};
+		let status = NetworkStatus::<Block> {
+			sync_state: SyncState::Downloading,
+			best_seen_block: Some(20),
+			num_sync_peers: 3,
+			num_connected_peers: 4,
+			num_active_peers: 3,
+			total_bytes_inbound: 0,
+			total_bytes_outbound: 0,
+			state_sync: None,
+			warp_sync: None,
+		};
+
+		let json = status_json(&info, &status);
+		assert_eq!(json["best"]["number"], 12);
+		assert_eq!(json["best"]["hash"], json!(H256::repeat_byte(2)));
+		assert_eq!(json["finalized"]["number"], 10);
+		assert_eq!(json["peers"], 4);
+		assert_eq!(json["syncing"], true);
+		assert_eq!(json["syncTarget"], 20);
+	}
+}
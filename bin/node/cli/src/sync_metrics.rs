@@ -0,0 +1,139 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Prometheus gauges of the sync progress.
+//!
+//! The generic `block_height` metrics don't tell how far behind its peers the node is, which is
+//! what alerting on a stuck sync needs. This task samples the sync status of the network and the
+//! best blocks of the peers at a fixed interval, and exposes the gap as gauges.
+
+use futures::channel::oneshot;
+use futures_timer::Delay;
+use node_primitives::{Block, BlockNumber};
+use prometheus_endpoint::{register, Gauge, PrometheusError, Registry, U64};
+use sc_client_api::HeaderBackend;
+use sc_network::NetworkService;
+use sc_rpc::system::Request;
+use sp_runtime::traits::Block as BlockT;
+use sp_utils::mpsc::TracingUnboundedSender;
+use std::{sync::Arc, time::Duration};
+
+const LOG_TARGET: &str = "sync-metrics";
+
+/// The interval between two samples of the sync status.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Gauges of the sync progress.
+pub struct Metrics {
+	best_peer_block: Gauge<U64>,
+	sync_target: Gauge<U64>,
+	blocks_behind: Gauge<U64>,
+}
+
+impl Metrics {
+	/// Register the gauges in `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			best_peer_block: register(
+				Gauge::new(
+					"sync_best_peer_block",
+					"Highest best block number announced by the connected peers",
+				)?,
+				registry,
+			)?,
+			sync_target: register(
+				Gauge::new(
+					"sync_target_block",
+					"Block number the node syncs to, its own best block when no peer is ahead",
+				)?,
+				registry,
+			)?,
+			blocks_behind: register(
+				Gauge::new(
+					"sync_blocks_behind",
+					"Number of blocks between the best block and the sync target",
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	fn update(&self, best: BlockNumber, best_peer: Option<BlockNumber>, target: BlockNumber) {
+		self.best_peer_block.set(best_peer.unwrap_or_default().into());
+		self.sync_target.set(target.into());
+		self.blocks_behind.set(target.saturating_sub(best).into());
+	}
+}
+
+/// Update `metrics` with the sync status of `network` every [`SAMPLE_INTERVAL`].
+///
+/// The best blocks of the peers are requested through `system_rpc_tx`, like the `system_peers`
+/// RPC does.
+pub async fn run<C>(
+	metrics: Metrics,
+	client: Arc<C>,
+	network: Arc<NetworkService<Block, <Block as BlockT>::Hash>>,
+	system_rpc_tx: TracingUnboundedSender<Request<Block>>,
+) where
+	C: HeaderBackend<Block>,
+{
+	loop {
+		Delay::new(SAMPLE_INTERVAL).await;
+
+		let status = match network.status().await {
+			Ok(status) => status,
+			Err(()) => {
+				log::debug!(target: LOG_TARGET, "The network is gone, stopping");
+				return
+			},
+		};
+
+		let (tx, rx) = oneshot::channel();
+		if system_rpc_tx.unbounded_send(Request::Peers(tx)).is_err() {
+			log::debug!(target: LOG_TARGET, "The network is gone, stopping");
+			return
+		}
+		let best_peer = match rx.await {
+			Ok(peers) => peers.into_iter().map(|peer| peer.best_number).max(),
+			Err(_) => None,
+		};
+
+		let best = client.info().best_number;
+		let target = status.best_seen_block.map_or(best, |seen| seen.max(best));
+		metrics.update(best, best_peer, target);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn blocks_behind_saturates() {
+		let registry = Registry::new();
+		let metrics = Metrics::register(&registry).unwrap();
+
+		metrics.update(10, Some(25), 25);
+		assert_eq!(metrics.best_peer_block.get(), 25);
+		assert_eq!(metrics.blocks_behind.get(), 15);
+
+		metrics.update(30, None, 25);
+		assert_eq!(metrics.best_peer_block.get(), 0);
+		assert_eq!(metrics.blocks_behind.get(), 0);
+	}
+}
@@ -0,0 +1,193 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Generation of a systemd service unit running the node.
+
+use crate::GenSystemdCmd;
+use sc_service::{config::BasePath, Configuration};
+use std::{
+	fmt::Write as _,
+	path::{Path, PathBuf},
+};
+
+/// Render the unit running the current executable with the node arguments of `cmd`.
+///
+/// `config` is the configuration resolved from these arguments. Its base path is passed
+/// explicitly when the arguments don't contain one, as the default base path depends on the home
+/// directory of the user running the node.
+pub fn unit(cmd: &GenSystemdCmd, config: &Configuration) -> Result<String, String> {
+	let base_path = match &config.base_path {
+		Some(BasePath::Permanenent(path)) => absolute(path)?,
+		_ =>
+			return Err("The node would run with a temporary base path, which systemd would \
+			 remove on every restart, use `--base-path` to choose one."
+				.into()),
+	};
+
+	let mut args = Vec::new();
+	if !has_base_path(&cmd.args) {
+		args.push("--base-path".to_owned());
+		args.push(base_path.display().to_string());
+	}
+	args.extend(cmd.args.iter().cloned());
+
+	let mut writable = vec![base_path.clone()];
+	for path in config.keystore.path().into_iter().chain(config.database.path()) {
+		let path = absolute(path)?;
+		if !path.starts_with(&base_path) {
+			writable.push(path);
+		}
+	}
+
+	let exe =
+		std::env::current_exe().map_err(|e| format!("Failed to find the executable: {}", e))?;
+	let working_directory = std::env::current_dir()
+		.map_err(|e| format!("Failed to find the working directory: {}", e))?;
+	let user = cmd.user.clone().or_else(|| std::env::var("USER").ok());
+
+	Ok(render(&Unit {
+		description: format!("{} ({})", config.impl_name, config.chain_spec.name()),
+		exe: &exe,
+		args: &args,
+		user: user.as_deref(),
+		working_directory: &working_directory,
+		writable: &writable,
+	}))
+}
+
+struct Unit<'a> {
+	description: String,
+	exe: &'a Path,
+	args: &'a [String],
+	user: Option<&'a str>,
+	working_directory: &'a Path,
+	writable: &'a [PathBuf],
+}
+
+fn render(unit: &Unit) -> String {
+	let quote_path = |path: &Path| quote(&path.display().to_string());
+	let mut exec_start = quote_path(unit.exe);
+	for arg in unit.args {
+		exec_start.push(' ');
+		exec_start.push_str(&quote(arg));
+	}
+	let writable = unit.writable.iter().map(|path| quote_path(path)).collect::<Vec<_>>();
+
+	let mut out = String::new();
+	// Writing to a `String` can't fail.
+	let _ = writeln!(out, "[Unit]");
+	let _ = writeln!(out, "Description={}", unit.description);
+	let _ = writeln!(out, "Wants=network-online.target");
+	let _ = writeln!(out, "After=network-online.target");
+	let _ = writeln!(out);
+	let _ = writeln!(out, "[Service]");
+	if let Some(user) = unit.user {
+		let _ = writeln!(out, "User={}", user);
+	}
+	let _ = writeln!(out, "WorkingDirectory={}", quote_path(unit.working_directory));
+	let _ = writeln!(out, "ExecStart={}", exec_start);
+	let _ = writeln!(out, "Restart=always");
+	let _ = writeln!(out, "RestartSec=10");
+	// The database and the peer connections need far more files than the default limit.
+	let _ = writeln!(out, "LimitNOFILE=65536");
+	let _ = writeln!(out, "NoNewPrivileges=true");
+	let _ = writeln!(out, "PrivateTmp=true");
+	let _ = writeln!(out, "ProtectSystem=strict");
+	let _ = writeln!(out, "ReadWritePaths={}", writable.join(" "));
+	let _ = writeln!(out);
+	let _ = writeln!(out, "[Install]");
+	let _ = writeln!(out, "WantedBy=multi-user.target");
+	out
+}
+
+fn absolute(path: &Path) -> Result<PathBuf, String> {
+	if path.is_absolute() {
+		return Ok(path.into())
+	}
+	std::env::current_dir()
+		.map(|dir| dir.join(path))
+		.map_err(|e| format!("Failed to find the working directory: {}", e))
+}
+
+fn has_base_path(args: &[String]) -> bool {
+	args.iter().any(|arg| {
+		arg == "--base-path" ||
+			arg.starts_with("--base-path=") ||
+			(arg.starts_with("-d") && !arg.starts_with("--"))
+	})
+}
+
+/// Quote `arg` for a command line of a systemd unit, escaping the specifiers and variables.
+fn quote(arg: &str) -> String {
+	let escaped = arg.replace('%', "%%").replace('$', "$$");
+	let plain = !escaped.is_empty() &&
+		!escaped
+			.chars()
+			.any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '\\' | ';'));
+	if plain {
+		return escaped
+	}
+	format!("\"{}\"", escaped.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn quotes_arguments() {
+		assert_eq!(quote("--name"), "--name");
+		assert_eq!(quote("my node"), "\"my node\"");
+		assert_eq!(quote("100%"), "100%%");
+		assert_eq!(quote("$HOME"), "$$HOME");
+		assert_eq!(quote("a\"b\\"), "\"a\\\"b\\\\\"");
+		assert_eq!(quote(""), "\"\"");
+	}
+
+	#[test]
+	fn renders_unit() {
+		let args = vec!["--chain".to_owned(), "my chain.json".to_owned()];
+		let writable = vec![PathBuf::from("/data")];
+		let unit = render(&Unit {
+			description: "Node (Chain)".into(),
+			exe: Path::new("/usr/bin/substrate"),
+			args: &args,
+			user: Some("substrate"),
+			working_directory: Path::new("/srv"),
+			writable: &writable,
+		});
+
+		assert!(unit.contains("\nUser=substrate\n"), "{}", unit);
+		assert!(
+			unit.contains("\nExecStart=/usr/bin/substrate --chain \"my chain.json\"\n"),
+			"{}",
+			unit
+		);
+		assert!(unit.contains("\nReadWritePaths=/data\n"), "{}", unit);
+		assert!(unit.ends_with("WantedBy=multi-user.target\n"), "{}", unit);
+	}
+
+	#[test]
+	fn detects_base_path() {
+		let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+		assert!(has_base_path(&args(&["--base-path", "/data"])));
+		assert!(has_base_path(&args(&["--base-path=/data"])));
+		assert!(has_base_path(&args(&["-d/data"])));
+		assert!(!has_base_path(&args(&["--dev", "--chain", "local"])));
+	}
+}
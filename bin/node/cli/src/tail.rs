@@ -0,0 +1,194 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Following the finalized blocks of the node on the standard output.
+//!
+//! The `tail` command runs a full node that only syncs, and prints the number and hash of every
+//! finalized block, one per line and in order, from the block finalized at startup. The logs
+//! are written to the standard error, so that the output can be piped to another program.
+
+use crate::service::{self, NewFullBase, NodeOptions};
+use futures::prelude::*;
+use node_primitives::{Block, BlockNumber, Hash};
+use sc_client_api::{BlockchainEvents, HeaderBackend};
+use sc_network::config::TransactionPropagation;
+use sc_service::{Configuration, Error, Role, TaskManager};
+use sp_runtime::{generic::BlockId, traits::Header as HeaderT};
+use std::{
+	io::{self, Write},
+	sync::Arc,
+};
+
+const LOG_TARGET: &str = "tail";
+
+/// Start the node of `config`, printing its finalized blocks.
+///
+/// The node doesn't author blocks, gossip transactions nor run the offchain workers. It stops
+/// once the standard output is closed.
+pub fn new_service(mut config: Configuration, options: NodeOptions) -> Result<TaskManager, Error> {
+	config.role = Role::Full;
+	config.offchain_worker.enabled = false;
+	config.offchain_worker.indexing_enabled = false;
+	config.network.transaction_propagation = TransactionPropagation::None;
+
+	let NewFullBase { task_manager, client, .. } =
+		service::new_full_base(config, options, |_, _| ())?;
+	// Writing to the standard output blocks while the reader lags behind.
+	task_manager
+		.spawn_essential_handle()
+		.spawn_blocking("tail", run(client, io::stdout()));
+
+	Ok(task_manager)
+}
+
+/// Write the number and hash of the blocks finalized by `client` to `output`, from the last
+/// finalized block, until writing fails.
+async fn run<C, W>(client: Arc<C>, mut output: W)
+where
+	C: BlockchainEvents<Block> + HeaderBackend<Block>,
+	W: Write,
+{
+	// Subscribe before reading the last finalized block, to miss none.
+	let mut finality = client.finality_notification_stream();
+	let info = client.info();
+	if let Err(err) = write_block(&mut output, info.finalized_number, info.finalized_hash) {
+		log::error!(target: LOG_TARGET, "Failed to write the finalized block: {}", err);
+		return
+	}
+	let mut last = info.finalized_number;
+
+	while let Some(notification) = finality.next().await {
+		let number = *notification.header.number();
+		if number <= last {
+			continue
+		}
+
+		// A single notification can finalize many blocks, like after importing a justification.
+		let blocks = match finalized_since(&*client, last, number, notification.hash) {
+			Ok(blocks) => blocks,
+			Err(err) => {
+				log::error!(target: LOG_TARGET, "Failed to read the finalized blocks: {}", err);
+				return
+			},
+		};
+		for (number, hash) in blocks {
+			if let Err(err) = write_block(&mut output, number, hash) {
+				log::info!(target: LOG_TARGET, "Stopping after failing to write a block: {}", err);
+				return
+			}
+		}
+		last = number;
+	}
+}
+
+/// The blocks after `last` up to the block `number` with `hash`, in ascending order.
+fn finalized_since<C: HeaderBackend<Block>>(
+	client: &C,
+	last: BlockNumber,
+	number: BlockNumber,
+	hash: Hash,
+) -> Result<Vec<(BlockNumber, Hash)>, String> {
+	let mut blocks = vec![(number, hash)];
+	let mut hash = hash;
+	for number in (last + 1..number).rev() {
+		hash = *client
+			.header(BlockId::Hash(hash))
+			.map_err(|e| e.to_string())?
+			.ok_or_else(|| format!("Missing the header of {}", hash))?
+			.parent_hash();
+		blocks.push((number, hash));
+	}
+	blocks.reverse();
+	Ok(blocks)
+}
+
+fn write_block(output: &mut impl Write, number: BlockNumber, hash: Hash) -> io::Result<()> {
+	writeln!(output, "{} {:?}", number, hash)?;
+	output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_client::TestClient;
+
+	fn lines(blocks: &[&node_primitives::Header]) -> String {
+		blocks
+			.iter()
+			.map(|header| format!("{} {:?}\n", header.number, header.hash()))
+			.collect()
+	}
+
+	#[test]
+	fn finalized_since_returns_the_blocks_in_order() {
+		let client = TestClient::new();
+		let block1 = client.import_child(&client.genesis());
+		let block2 = client.import_child(&block1);
+		let block3 = client.import_child(&block2);
+
+		assert_eq!(
+			finalized_since(&*client, 0, 3, block3.hash()).unwrap(),
+			vec![(1, block1.hash()), (2, block2.hash()), (3, block3.hash())],
+		);
+		assert_eq!(
+			finalized_since(&*client, 2, 3, block3.hash()).unwrap(),
+			vec![(3, block3.hash())]
+		);
+		assert!(finalized_since(&*client, 0, 3, Hash::repeat_byte(1)).is_err());
+	}
+
+	#[test]
+	fn run_writes_every_finalized_block_once() {
+		let client = TestClient::new();
+		let genesis = client.genesis();
+		let mut output = Vec::new();
+		let mut tail = Box::pin(run(client.clone(), &mut output));
+		assert!(tail.as_mut().now_or_never().is_none());
+
+		let block1 = client.import_child(&genesis);
+		let block2 = client.import_child(&block1);
+		let block3 = client.finalize_child(&block2);
+		// A notification for a block already written is skipped.
+		client.notify_finalized(&block2);
+		let block4 = client.finalize_child(&block3);
+		assert!(tail.as_mut().now_or_never().is_none());
+
+		drop(tail);
+		assert_eq!(
+			String::from_utf8(output).unwrap(),
+			lines(&[&genesis, &block1, &block2, &block3, &block4]),
+		);
+	}
+
+	#[test]
+	fn run_stops_once_the_output_is_closed() {
+		struct Closed;
+
+		impl Write for Closed {
+			fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+				Err(io::ErrorKind::BrokenPipe.into())
+			}
+
+			fn flush(&mut self) -> io::Result<()> {
+				Ok(())
+			}
+		}
+
+		assert!(run(TestClient::new(), Closed).now_or_never().is_some());
+	}
+}
@@ -0,0 +1,143 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! An in-memory chain of headers notifying its imports and finalizations, for the tests.
+
+use node_primitives::{Block, BlockNumber, Hash, Header};
+use sc_client_api::{
+	blockchain::{BlockStatus, Info, Result},
+	in_mem::Blockchain,
+	BlockImportNotification, BlockchainEvents, FinalityNotification, FinalityNotifications,
+	HeaderBackend, ImportNotifications, NewBlockState, StorageEventStream, StorageKey,
+};
+use sp_consensus::BlockOrigin;
+use sp_runtime::{generic::BlockId, traits::Header as HeaderT};
+use sp_utils::mpsc::{tracing_unbounded, TracingUnboundedSender};
+use std::sync::{Arc, Mutex};
+
+/// A chain starting from a genesis block.
+#[derive(Default)]
+pub struct TestClient {
+	chain: Blockchain<Block>,
+	imports: Mutex<Vec<TracingUnboundedSender<BlockImportNotification<Block>>>>,
+	finality: Mutex<Vec<TracingUnboundedSender<FinalityNotification<Block>>>>,
+}
+
+impl TestClient {
+	pub fn new() -> Arc<Self> {
+		let client = TestClient::default();
+		let genesis = Header::new(0, Hash::zero(), Hash::zero(), Hash::zero(), Default::default());
+		client
+			.chain
+			.insert(genesis.hash(), genesis, None, None, NewBlockState::Final)
+			.unwrap();
+		Arc::new(client)
+	}
+
+	pub fn genesis(&self) -> Header {
+		self.chain.header(BlockId::Number(0)).unwrap().unwrap()
+	}
+
+	/// Import a new best block on top of `parent`.
+	pub fn import_child(&self, parent: &Header) -> Header {
+		let header = child(parent);
+		self.insert(&header, NewBlockState::Best);
+		let notification = BlockImportNotification {
+			hash: header.hash(),
+			origin: BlockOrigin::NetworkBroadcast,
+			header: header.clone(),
+			is_new_best: true,
+			tree_route: None,
+		};
+		notify(&self.imports, notification);
+		header
+	}
+
+	/// Import and finalize a new block on top of `parent`, finalizing its ancestors with it.
+	pub fn finalize_child(&self, parent: &Header) -> Header {
+		let header = child(parent);
+		self.insert(&header, NewBlockState::Final);
+		self.notify_finalized(&header);
+		header
+	}
+
+	/// Send a finality notification for the block `header`, already imported.
+	pub fn notify_finalized(&self, header: &Header) {
+		let notification = FinalityNotification { hash: header.hash(), header: header.clone() };
+		notify(&self.finality, notification);
+	}
+
+	fn insert(&self, header: &Header, state: NewBlockState) {
+		self.chain.insert(header.hash(), header.clone(), None, None, state).unwrap();
+	}
+}
+
+fn child(parent: &Header) -> Header {
+	Header::new(parent.number + 1, Hash::zero(), Hash::zero(), parent.hash(), Default::default())
+}
+
+fn notify<T: Clone>(subscribers: &Mutex<Vec<TracingUnboundedSender<T>>>, notification: T) {
+	subscribers
+		.lock()
+		.unwrap()
+		.retain(|subscriber| subscriber.unbounded_send(notification.clone()).is_ok());
+}
+
+impl HeaderBackend<Block> for TestClient {
+	fn header(&self, id: BlockId<Block>) -> Result<Option<Header>> {
+		self.chain.header(id)
+	}
+
+	fn info(&self) -> Info<Block> {
+		self.chain.info()
+	}
+
+	fn status(&self, id: BlockId<Block>) -> Result<BlockStatus> {
+		self.chain.status(id)
+	}
+
+	fn number(&self, hash: Hash) -> Result<Option<BlockNumber>> {
+		self.chain.number(hash)
+	}
+
+	fn hash(&self, number: BlockNumber) -> Result<Option<Hash>> {
+		self.chain.hash(number)
+	}
+}
+
+impl BlockchainEvents<Block> for TestClient {
+	fn import_notification_stream(&self) -> ImportNotifications<Block> {
+		let (tx, rx) = tracing_unbounded("test_imports");
+		self.imports.lock().unwrap().push(tx);
+		rx
+	}
+
+	fn finality_notification_stream(&self) -> FinalityNotifications<Block> {
+		let (tx, rx) = tracing_unbounded("test_finality");
+		self.finality.lock().unwrap().push(tx);
+		rx
+	}
+
+	fn storage_changes_notification_stream(
+		&self,
+		_filter_keys: Option<&[StorageKey]>,
+		_child_filter_keys: Option<&[(StorageKey, Option<Vec<StorageKey>>)]>,
+	) -> Result<StorageEventStream<Hash>> {
+		unimplemented!()
+	}
+}
@@ -0,0 +1,245 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tracking the extrinsic throughput of a live network.
+//!
+//! The `throughput` command runs the node like `run` does, and reports at a fixed interval the
+//! number of extrinsics per second of the new best blocks, their average fullness by weight,
+//! and the depth of the transaction pool. The blocks of the initial sync and the ones retracted
+//! by a reorg aren't counted. The reports are logged, and the rates set as Prometheus gauges
+//! when the node exposes its metrics, the pool already having its own.
+
+use crate::service::{self, NewFullBase, NodeOptions};
+use codec::Decode;
+use frame_support::weights::Weight;
+use futures::{prelude::*, select};
+use futures_timer::Delay;
+use node_primitives::{Block, Hash};
+use prometheus_endpoint::{register, Gauge, PrometheusError, Registry, F64};
+use sc_client_api::{BlockBackend, BlockchainEvents, StorageProvider};
+use sc_service::{Configuration, Error, TFullBackend, TaskManager};
+use sc_transaction_pool_api::{PoolStatus, TransactionPool};
+use sp_consensus::BlockOrigin;
+use sp_core::{hashing::twox_128, storage::StorageKey};
+use sp_runtime::generic::BlockId;
+use std::{
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+const LOG_TARGET: &str = "throughput";
+
+/// Gauges of the throughput, as of the last report.
+pub struct Metrics {
+	extrinsics_per_second: Gauge<F64>,
+	block_fullness: Gauge<F64>,
+}
+
+impl Metrics {
+	/// Register the gauges in `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			extrinsics_per_second: register(
+				Gauge::new(
+					"throughput_extrinsics_per_second",
+					"Extrinsics per second included in the new best blocks, inherents included",
+				)?,
+				registry,
+			)?,
+			block_fullness: register(
+				Gauge::new(
+					"throughput_block_fullness",
+					"Average weight of the new best blocks, as a fraction of the maximum",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
+/// Start the node of `config`, reporting its throughput every `interval`.
+pub fn new_service(
+	config: Configuration,
+	options: NodeOptions,
+	interval: Duration,
+) -> Result<TaskManager, Error> {
+	let metrics = match config.prometheus_registry() {
+		Some(registry) => Some(Metrics::register(registry)?),
+		None => None,
+	};
+
+	let NewFullBase { task_manager, client, transaction_pool, .. } =
+		service::new_full_base(config, options, |_, _| ())?;
+	task_manager
+		.spawn_handle()
+		.spawn("throughput", run(client, transaction_pool, interval, metrics));
+
+	Ok(task_manager)
+}
+
+/// A new best block counted in a report.
+#[derive(Debug, PartialEq)]
+struct CountedBlock {
+	hash: Hash,
+	extrinsics: usize,
+	/// The fraction of the maximum weight used by the block, if known.
+	fullness: Option<f64>,
+}
+
+/// The new best blocks since the last report.
+#[derive(Debug, Default, PartialEq)]
+struct Window {
+	blocks: Vec<CountedBlock>,
+}
+
+impl Window {
+	fn add(&mut self, hash: Hash, extrinsics: usize, weight: Option<Weight>) {
+		let max = node_runtime::RuntimeBlockWeights::get().max_block;
+		let fullness = weight.map(|weight| weight as f64 / max as f64);
+		self.blocks.push(CountedBlock { hash, extrinsics, fullness });
+	}
+
+	/// Stop counting the block `hash`, retracted by a reorg.
+	fn retract(&mut self, hash: &Hash) {
+		self.blocks.retain(|block| block.hash != *hash);
+	}
+
+	fn extrinsics_per_second(&self, elapsed: Duration) -> f64 {
+		let extrinsics: usize = self.blocks.iter().map(|block| block.extrinsics).sum();
+		extrinsics as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+	}
+
+	/// The average fullness of the blocks, `None` without any weighed block.
+	fn average_fullness(&self) -> Option<f64> {
+		let weighed: Vec<_> = self.blocks.iter().filter_map(|block| block.fullness).collect();
+		(!weighed.is_empty()).then(|| weighed.iter().sum::<f64>() / weighed.len() as f64)
+	}
+}
+
+async fn run<C, P>(client: Arc<C>, pool: Arc<P>, interval: Duration, metrics: Option<Metrics>)
+where
+	C: BlockchainEvents<Block> + BlockBackend<Block> + StorageProvider<Block, TFullBackend<Block>>,
+	P: TransactionPool<Block = Block>,
+{
+	let mut imports = client.import_notification_stream().fuse();
+	let mut window = Window::default();
+	let mut start = Instant::now();
+	let mut report = Delay::new(interval).fuse();
+
+	loop {
+		select! {
+			notification = imports.next() => match notification {
+				Some(notification) if notification.origin == BlockOrigin::NetworkInitialSync => {},
+				Some(notification) if notification.is_new_best => {
+					for retracted in notification.tree_route.iter().flat_map(|route| route.retracted()) {
+						window.retract(&retracted.hash);
+					}
+					let id = BlockId::Hash(notification.hash);
+					match client.block_body(&id) {
+						Ok(Some(body)) =>
+							window.add(notification.hash, body.len(), block_weight(&*client, &id)),
+						Ok(None) => {},
+						Err(err) => log::debug!(
+							target: LOG_TARGET,
+							"Failed to read the body of {}: {}",
+							notification.hash,
+							err,
+						),
+					}
+				},
+				Some(_) => {},
+				None => return,
+			},
+			() = report => {
+				let now = Instant::now();
+				log_report(&window, now - start, pool.status(), metrics.as_ref());
+				window = Window::default();
+				start = now;
+				report = Delay::new(interval).fuse();
+			},
+		}
+	}
+}
+
+/// The weight of the block `id`, as recorded by the system pallet in the state of the block.
+fn block_weight<C: StorageProvider<Block, TFullBackend<Block>>>(
+	client: &C,
+	id: &BlockId<Block>,
+) -> Option<Weight> {
+	let key = StorageKey([twox_128(b"System"), twox_128(b"BlockWeight")].concat());
+	let weight = client.storage(id, &key).ok()??;
+	frame_system::ConsumedWeight::decode(&mut &weight.0[..])
+		.ok()
+		.map(|weight| weight.total())
+}
+
+fn log_report(window: &Window, elapsed: Duration, pool: PoolStatus, metrics: Option<&Metrics>) {
+	let extrinsics_per_second = window.extrinsics_per_second(elapsed);
+	let fullness = window.average_fullness();
+	log::info!(
+		target: LOG_TARGET,
+		"📈 {:.2} extrinsics/s over {} blocks, {} full on average, pool: {} ready, {} future",
+		extrinsics_per_second,
+		window.blocks.len(),
+		fullness.map_or_else(|| "-".into(), |fullness| format!("{:.1}%", fullness * 100.0)),
+		pool.ready,
+		pool.future,
+	);
+
+	if let Some(metrics) = metrics {
+		metrics.extrinsics_per_second.set(extrinsics_per_second);
+		if let Some(fullness) = fullness {
+			metrics.block_fullness.set(fullness);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn window_averages_the_blocks() {
+		let max = node_runtime::RuntimeBlockWeights::get().max_block;
+		let mut window = Window::default();
+		assert_eq!(window.average_fullness(), None);
+
+		window.add(Hash::repeat_byte(1), 3, Some(max / 2));
+		window.add(Hash::repeat_byte(2), 5, None);
+		window.add(Hash::repeat_byte(3), 2, Some(0));
+
+		assert_eq!(window.blocks.len(), 3);
+		assert_eq!(window.extrinsics_per_second(Duration::from_secs(2)), 5.0);
+		assert_eq!(window.average_fullness(), Some(0.25));
+	}
+
+	#[test]
+	fn window_forgets_the_retracted_blocks() {
+		let max = node_runtime::RuntimeBlockWeights::get().max_block;
+		let mut window = Window::default();
+		window.add(Hash::repeat_byte(1), 4, Some(max));
+		window.add(Hash::repeat_byte(2), 2, Some(0));
+
+		window.retract(&Hash::repeat_byte(1));
+		window.retract(&Hash::repeat_byte(9));
+
+		assert_eq!(window.blocks.len(), 1);
+		assert_eq!(window.extrinsics_per_second(Duration::from_secs(1)), 2.0);
+		assert_eq!(window.average_fullness(), Some(0.0));
+	}
+}
@@ -0,0 +1,88 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `verify-justification` command.
+
+use crate::cli::VerifyJustificationCmd;
+use codec::Decode;
+use grandpa::{GenesisAuthoritySetProvider, GrandpaJustification, SharedAuthoritySet};
+use node_primitives::{Block, BlockNumber, Hash};
+use sc_cli::{CliConfiguration, ImportParams, SharedParams};
+use sc_client_api::{Backend, HeaderBackend};
+
+impl VerifyJustificationCmd {
+	/// Run the verify-justification command.
+	pub fn run<B: Backend<Block>>(
+		&self,
+		backend: &B,
+		authority_set: &SharedAuthoritySet<Hash, BlockNumber>,
+		genesis_authorities: &dyn GenesisAuthoritySetProvider<Block>,
+	) -> sc_cli::Result<()> {
+		let block = self.block.parse::<Block>()?;
+		let (hash, number) = match backend.blockchain().header(block)? {
+			Some(header) => (header.hash(), header.number),
+			None => return Err(format!("Block {} not found", block).into()),
+		};
+
+		let encoded = match self.justification.strip_prefix("0x") {
+			Some(_) => sc_cli::utils::decode_hex(&self.justification)?,
+			None => std::fs::read(&self.justification)?,
+		};
+		let justification = GrandpaJustification::<Block>::decode(&mut &encoded[..])?;
+
+		let (set_id, authorities) = grandpa::warp_proof::authority_set_at(
+			backend,
+			authority_set,
+			genesis_authorities,
+			number,
+		)
+		.map_err(|e| format!("Failed to find the authority set of block #{}: {}", number, e))?;
+
+		let round = justification.round();
+		if justification.target() != (number, hash) {
+			return Err(format!(
+				"Invalid justification: finalizes block #{} ({}) instead of #{} ({})",
+				justification.target().0,
+				justification.target().1,
+				number,
+				hash,
+			)
+			.into())
+		}
+		justification.verify(set_id, &authorities).map_err(|e| {
+			format!("Invalid justification of round {} for authority set {}: {}", round, set_id, e)
+		})?;
+
+		println!(
+			"Valid justification of block #{} ({}): round {}, authority set {}",
+			number, hash, round, set_id,
+		);
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for VerifyJustificationCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
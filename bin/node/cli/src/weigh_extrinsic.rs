@@ -0,0 +1,115 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `weigh-extrinsic` command.
+//!
+//! The extrinsic is applied in a new block on top of the given one, without any inherent, and
+//! its weight is read from the `ExtrinsicSuccess` or `ExtrinsicFailed` event it deposits. That is
+//! the actual weight, after the refunds of the call, so the block is never finalized nor stored.
+
+use crate::cli::WeighExtrinsicCmd;
+use codec::Decode;
+use frame_system::{EventRecord, Phase};
+use node_primitives::{Block, Hash, Header};
+use node_runtime::{Event, Runtime};
+use sc_block_builder::BlockBuilderApi;
+use sc_cli::{CliConfiguration, ImportParams, SharedParams};
+use sc_client_api::{backend, HeaderBackend, UsageProvider};
+use sp_api::{ApiExt, Core, ProvideRuntimeApi};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT},
+};
+use std::sync::Arc;
+
+impl WeighExtrinsicCmd {
+	/// Apply the extrinsic on top of the block and print its weight.
+	pub fn run<B, C>(&self, client: Arc<C>, backend: &B) -> sc_cli::Result<()>
+	where
+		B: backend::Backend<Block>,
+		C: HeaderBackend<Block> + ProvideRuntimeApi<Block> + UsageProvider<Block>,
+		C::Api: BlockBuilderApi<Block>
+			+ ApiExt<Block, StateBackend = backend::StateBackendFor<B, Block>>,
+	{
+		let block = match &self.at {
+			Some(at) => at.parse::<Block>()?,
+			None => BlockId::Hash(client.usage_info().chain.best_hash),
+		};
+		let parent = client.header(block)?.ok_or_else(|| format!("Block {} not found", block))?;
+		let at = BlockId::Hash(parent.hash());
+
+		let encoded = sc_cli::utils::decode_hex(&self.extrinsic)?;
+		let extrinsic = <Block as BlockT>::Extrinsic::decode(&mut &encoded[..])?;
+
+		let header = Header::new(
+			parent.number + 1,
+			Default::default(),
+			Default::default(),
+			parent.hash(),
+			Default::default(),
+		);
+		let api = client.runtime_api();
+		api.initialize_block(&at, &header).map_err(|e| e.to_string())?;
+		let result = api
+			.apply_extrinsic(&at, extrinsic)
+			.map_err(|e| e.to_string())?
+			.map_err(|e| format!("Invalid extrinsic: {:?}", e))?;
+
+		let state = backend.state_at(at)?;
+		let changes = api.into_storage_changes(&state, None, parent.hash())?;
+		let events_key = frame_system::Events::<Runtime>::hashed_key();
+		let events = changes
+			.main_storage_changes
+			.iter()
+			.find(|(key, _)| key[..] == events_key[..])
+			.and_then(|(_, value)| value.as_ref())
+			.ok_or("The extrinsic deposited no event")?;
+		let events = Vec::<EventRecord<Event, Hash>>::decode(&mut &events[..])?;
+
+		let info = events
+			.into_iter()
+			.filter(|record| record.phase == Phase::ApplyExtrinsic(0))
+			.find_map(|record| match record.event {
+				Event::System(frame_system::Event::ExtrinsicSuccess(info)) |
+				Event::System(frame_system::Event::ExtrinsicFailed(_, info)) => Some(info),
+				_ => None,
+			})
+			.ok_or("The extrinsic deposited neither `ExtrinsicSuccess` nor `ExtrinsicFailed`")?;
+
+		match result {
+			Ok(()) => println!("Succeeded on top of #{} ({})", parent.number, parent.hash()),
+			Err(err) =>
+				println!("Failed on top of #{} ({}): {:?}", parent.number, parent.hash(), err),
+		}
+		println!("Weight: {}", info.weight);
+		println!("Class: {:?}", info.class);
+		println!("Pays fee: {:?}", info.pays_fee);
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for WeighExtrinsicCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
@@ -0,0 +1,42 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin;
+use std::process::Command;
+use tempfile::tempdir;
+
+pub mod common;
+
+#[test]
+fn api_call_works() {
+	let base_path = tempdir().expect("could not create a temp dir");
+
+	common::run_dev_node_for_a_while(base_path.path());
+
+	let output = Command::new(cargo_bin("substrate"))
+		.args(&["api-call", "--dev", "--pruning", "archive", "-d"])
+		.arg(base_path.path())
+		.args(&["Core_version", "--at", "1"])
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	let version: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	assert_eq!(version["specName"], "node");
+}
@@ -0,0 +1,51 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use assert_cmd::cargo::cargo_bin;
+use std::process::Command;
+use tempfile::tempdir;
+
+#[test]
+fn check_spec_works() {
+	let output = Command::new(cargo_bin("substrate"))
+		.args(&["check-spec", "--dev", "--json"])
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+
+	let report: serde_json::Value = serde_json::from_slice(output.stdout.as_slice()).unwrap();
+	assert!(report["genesisHash"].is_string());
+	assert_eq!(report["errors"], serde_json::json!([]));
+}
+
+#[test]
+fn check_spec_reports_invalid_spec() {
+	let spec_dir = tempdir().expect("could not create a temp dir");
+	let spec_path = spec_dir.path().join("spec.json");
+	std::fs::write(&spec_path, b"{ \"name\": \"broken\" }").unwrap();
+
+	let output = Command::new(cargo_bin("substrate"))
+		.args(&["check-spec", "--json", "--chain"])
+		.arg(&spec_path)
+		.output()
+		.unwrap();
+	assert!(!output.status.success());
+
+	let report: serde_json::Value = serde_json::from_slice(output.stdout.as_slice()).unwrap();
+	assert_eq!(report["errors"].as_array().map(|e| e.len()), Some(1));
+}
@@ -25,6 +25,8 @@ use nix::{
 };
 use std::{
 	convert::TryInto,
+	io::{Read, Write},
+	net::{TcpListener, TcpStream},
 	path::Path,
 	process::{Child, Command, ExitStatus},
 	thread,
@@ -67,3 +69,46 @@ pub fn run_dev_node_for_a_while(base_path: &Path) {
 	kill(Pid::from_raw(cmd.id().try_into().unwrap()), SIGINT).unwrap();
 	assert!(wait_for(&mut cmd, 40).map(|x| x.success()).unwrap_or_default());
 }
+
+/// A TCP port that is free at the time of the call.
+pub fn free_port() -> String {
+	TcpListener::bind("127.0.0.1:0")
+		.unwrap()
+		.local_addr()
+		.unwrap()
+		.port()
+		.to_string()
+}
+
+/// Call the RPC `method` over HTTP, waiting up to 30 seconds for the server to listen.
+pub fn rpc_call(port: &str, method: &str) -> serde_json::Value {
+	let body = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"{}","params":[]}}"#, method);
+	for _ in 0..30 {
+		let mut stream = match TcpStream::connect(format!("127.0.0.1:{}", port)) {
+			Ok(stream) => stream,
+			Err(_) => {
+				thread::sleep(Duration::from_secs(1));
+				continue
+			},
+		};
+		write!(
+			stream,
+			"POST / HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+			 Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+			body.len(),
+			body,
+		)
+		.unwrap();
+
+		let mut response = String::new();
+		stream.read_to_string(&mut response).unwrap();
+		let json = response.split("\r\n\r\n").nth(1).expect("the response has a body");
+		return serde_json::from_str(json).unwrap()
+	}
+	panic!("the RPC server didn't start listening on port {}", port)
+}
+
+/// The names of the RPC methods served on `port`, as listed by `rpc_methods`.
+pub fn rpc_methods(port: &str) -> Vec<String> {
+	serde_json::from_value(rpc_call(port, "rpc_methods")["result"]["methods"].clone()).unwrap()
+}
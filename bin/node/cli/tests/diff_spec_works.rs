@@ -0,0 +1,39 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use assert_cmd::cargo::cargo_bin;
+use std::process::Command;
+
+fn diff_spec(a: &str, b: &str) -> serde_json::Value {
+	let output = Command::new(cargo_bin("substrate"))
+		.args(&["diff-spec", a, b, "--json"])
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+
+	serde_json::from_slice(output.stdout.as_slice()).unwrap()
+}
+
+#[test]
+fn diff_spec_works() {
+	let diff = diff_spec("dev", "dev");
+	assert_eq!(diff, serde_json::json!({ "added": [], "removed": [], "changed": [] }));
+
+	let diff = diff_spec("dev", "local");
+	assert!(!diff["changed"].as_array().unwrap().is_empty());
+}
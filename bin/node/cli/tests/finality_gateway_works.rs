@@ -0,0 +1,66 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin;
+use nix::{
+	sys::signal::{kill, Signal::SIGINT},
+	unistd::Pid,
+};
+use std::{convert::TryInto, process::Command};
+
+pub mod common;
+
+#[test]
+fn finality_gateway_only_serves_the_finality_rpc() {
+	let port = common::free_port();
+	let mut node = Command::new(cargo_bin("substrate"))
+		.args(&["--dev", "--tmp", "--no-telemetry", "--finality-gateway", "--rpc-port", &port])
+		.args(&["--ws-port", &common::free_port(), "--port", &common::free_port()])
+		.spawn()
+		.unwrap();
+
+	let methods = common::rpc_methods(&port);
+	for served in &["grandpa_proveFinality", "sync_state_genSyncSpec", "chain_getBlock"] {
+		assert!(methods.iter().any(|method| method == served), "{} isn't served", served);
+	}
+	let unexpected = ["author_", "babe_", "contracts_", "payment_", "hoster_", "keystore_"];
+	for method in &methods {
+		assert!(
+			!unexpected.iter().any(|module| method.starts_with(module)),
+			"{} is served",
+			method,
+		);
+	}
+
+	kill(Pid::from_raw(node.id().try_into().unwrap()), SIGINT).unwrap();
+	assert!(common::wait_for(&mut node, 40).map(|x| x.success()).unwrap_or_default());
+}
+
+#[test]
+fn finality_gateway_refuses_the_validator_and_unsafe_flags() {
+	for flags in &[&["--validator"][..], &["--rpc-methods", "Unsafe"][..]] {
+		let status = Command::new(cargo_bin("substrate"))
+			.args(&["--dev", "--tmp", "--finality-gateway"])
+			.args(*flags)
+			.status()
+			.unwrap();
+		assert!(!status.success(), "{:?} was accepted", flags);
+	}
+}
@@ -0,0 +1,68 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin;
+use nix::{
+	sys::signal::{kill, Signal::SIGINT},
+	unistd::Pid,
+};
+use std::{convert::TryInto, net::TcpStream, process::Command, thread, time::Duration};
+
+pub mod common;
+
+#[test]
+fn isolated_node_authors_blocks_without_networking() {
+	let rpc_port = common::free_port();
+	let p2p_port = common::free_port();
+	// Without `--dev`, the node only authors blocks while it has peers, unless it is isolated.
+	let mut node = Command::new(cargo_bin("substrate"))
+		.args(&["--chain", "local", "--alice", "--tmp", "--no-telemetry", "--isolated"])
+		.args(&["--rpc-port", &rpc_port, "--ws-port", &common::free_port(), "--port", &p2p_port])
+		.spawn()
+		.unwrap();
+
+	assert_eq!(
+		common::rpc_call(&rpc_port, "system_localListenAddresses")["result"],
+		serde_json::json!([])
+	);
+	assert!(TcpStream::connect(format!("127.0.0.1:{}", p2p_port)).is_err());
+
+	let best_number = || {
+		let header = common::rpc_call(&rpc_port, "chain_getHeader");
+		u64::from_str_radix(
+			header["result"]["number"].as_str().unwrap().trim_start_matches("0x"),
+			16,
+		)
+		.unwrap()
+	};
+	let mut authored = false;
+	for _ in 0..60 {
+		if best_number() > 1 {
+			authored = true;
+			break
+		}
+		thread::sleep(Duration::from_secs(1));
+	}
+	assert!(authored, "the isolated node doesn't author blocks");
+	assert_eq!(common::rpc_call(&rpc_port, "system_health")["result"]["peers"], 0);
+
+	kill(Pid::from_raw(node.id().try_into().unwrap()), SIGINT).unwrap();
+	assert!(common::wait_for(&mut node, 40).map(|x| x.success()).unwrap_or_default());
+}
@@ -0,0 +1,79 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin;
+use nix::{
+	sys::signal::{kill, Signal::SIGINT},
+	unistd::Pid,
+};
+use std::{
+	convert::TryInto,
+	io::Read,
+	net::TcpStream,
+	process::{Child, Command},
+	thread,
+	time::Duration,
+};
+
+pub mod common;
+
+/// Start a dev node with an exposed HTTP RPC server, going through the RPC proxy.
+fn start_node(port: &str, max_connections: &str) -> Child {
+	Command::new(cargo_bin("substrate"))
+		.args(&["--dev", "--tmp", "--no-telemetry", "--rpc-external", "--rpc-cors", "all"])
+		.args(&["--rpc-max-connections", max_connections, "--rpc-port", port])
+		.args(&["--ws-port", &common::free_port(), "--port", &common::free_port()])
+		.spawn()
+		.unwrap()
+}
+
+fn stop_node(mut node: Child) {
+	kill(Pid::from_raw(node.id().try_into().unwrap()), SIGINT).unwrap();
+	assert!(common::wait_for(&mut node, 40).map(|x| x.success()).unwrap_or_default());
+}
+
+#[test]
+fn unsafe_methods_are_denied_behind_the_proxy() {
+	let port = common::free_port();
+	let node = start_node(&port, "8");
+
+	assert_eq!(common::rpc_call(&port, "system_name")["result"], "Substrate Node");
+	// The unsafe calls are reported as unknown methods.
+	assert_eq!(common::rpc_call(&port, "author_rotateKeys")["error"]["code"], -32601);
+
+	stop_node(node);
+}
+
+#[test]
+fn connections_beyond_the_limit_are_closed() {
+	let port = common::free_port();
+	let node = start_node(&port, "1");
+	assert_eq!(common::rpc_call(&port, "system_name")["result"], "Substrate Node");
+	// Let the proxy release the connection of the call.
+	thread::sleep(Duration::from_secs(1));
+
+	let _open = TcpStream::connect(format!("127.0.0.1:{}", port)).unwrap();
+	let mut rejected = TcpStream::connect(format!("127.0.0.1:{}", port)).unwrap();
+	rejected.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+	let mut response = Vec::new();
+	assert_eq!(rejected.read_to_end(&mut response).unwrap(), 0);
+
+	stop_node(node);
+}
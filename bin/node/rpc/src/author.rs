@@ -0,0 +1,252 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Extrinsic submission waiting for finality.
+//!
+//! `author_submitAndWatchExtrinsic` notifies every status change of an extrinsic, leaving it to
+//! the client to tell when the block including it is finalized. [`AuthorFinality`] follows the
+//! status of the extrinsic together with the finality notifications, and only resolves once the
+//! block including the extrinsic is finalized.
+
+use std::sync::Arc;
+
+use codec::Decode;
+use futures::{future, prelude::*, stream};
+use jsonrpc_core::{BoxFuture, Error as RpcError, ErrorCode, Result as RpcResult};
+use jsonrpc_derive::rpc;
+use node_primitives::{Block, BlockNumber, Hash};
+use sc_client_api::{BlockchainEvents, FinalityNotifications};
+use sc_rpc_api::author::error::Error;
+use sc_transaction_pool_api::{
+	error::IntoPoolError, TransactionFor, TransactionPool, TransactionSource, TransactionStatus,
+};
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::{generic::BlockId, traits::Header};
+
+/// The extrinsic left the pool without being finalized.
+const NOT_FINALIZED: i64 = 1020;
+
+/// Author RPC methods waiting for finality.
+#[rpc]
+pub trait AuthorFinalityApi<BlockHash> {
+	/// Submit a SCALE encoded extrinsic and wait for the block including it to be finalized.
+	///
+	/// Returns the hash of that block, or an error if the extrinsic is dropped from the pool
+	/// before.
+	#[rpc(name = "author_submitAndWaitFinalized")]
+	fn submit_and_wait_finalized(&self, extrinsic: Bytes) -> BoxFuture<RpcResult<BlockHash>>;
+}
+
+/// Implements [`AuthorFinalityApi`] on top of the transaction pool.
+pub struct AuthorFinality<C, P> {
+	client: Arc<C>,
+	pool: Arc<P>,
+}
+
+impl<C, P> AuthorFinality<C, P> {
+	/// Create a new handler submitting the extrinsics to `pool`.
+	pub fn new(client: Arc<C>, pool: Arc<P>) -> Self {
+		Self { client, pool }
+	}
+}
+
+impl<C, P> AuthorFinalityApi<Hash> for AuthorFinality<C, P>
+where
+	C: HeaderBackend<Block> + BlockchainEvents<Block> + Send + Sync + 'static,
+	P: TransactionPool<Block = Block> + 'static,
+{
+	fn submit_and_wait_finalized(&self, extrinsic: Bytes) -> BoxFuture<RpcResult<Hash>> {
+		let xt = match TransactionFor::<P>::decode(&mut &extrinsic[..]) {
+			Ok(xt) => xt,
+			Err(err) => return future::err(Error::from(err).into()).boxed(),
+		};
+
+		let client = self.client.clone();
+		// Subscribed before submitting, so that no finalization is missed.
+		let finality = client.finality_notification_stream();
+		let submit = self.pool.submit_and_watch(
+			&BlockId::hash(client.info().best_hash),
+			TransactionSource::External,
+			xt,
+		);
+
+		async move {
+			let status = submit.await.map_err(|e| {
+				e.into_pool_error()
+					.map(Error::from)
+					.unwrap_or_else(|e| Error::Verification(Box::new(e)))
+			})?;
+			wait_finalized(&*client, status, finality).await
+		}
+		.boxed()
+	}
+}
+
+enum Event<TxHash> {
+	Status(TransactionStatus<TxHash, Hash>),
+	Finalized,
+}
+
+/// Follow the `status` of an extrinsic until the block including it is finalized.
+async fn wait_finalized<C, TxHash>(
+	client: &C,
+	status: impl Stream<Item = TransactionStatus<TxHash, Hash>> + Unpin,
+	finality: FinalityNotifications<Block>,
+) -> RpcResult<Hash>
+where
+	C: HeaderBackend<Block>,
+{
+	let mut events = stream::select(status.map(Event::Status), finality.map(|_| Event::Finalized));
+	let mut in_block = None;
+
+	while let Some(event) = events.next().await {
+		match event {
+			Event::Status(TransactionStatus::InBlock(hash)) => in_block = Some(hash),
+			Event::Status(TransactionStatus::Retracted(_)) => in_block = None,
+			Event::Status(TransactionStatus::Finalized(hash)) => return Ok(hash),
+			Event::Status(TransactionStatus::FinalityTimeout(_)) =>
+				return Err(not_finalized("was included in a block that was not finalized in time")),
+			Event::Status(TransactionStatus::Usurped(_)) =>
+				return Err(not_finalized("was replaced by another extrinsic")),
+			Event::Status(TransactionStatus::Dropped) =>
+				return Err(not_finalized("was dropped from the pool")),
+			Event::Status(TransactionStatus::Invalid) =>
+				return Err(not_finalized("became invalid")),
+			Event::Status(_) | Event::Finalized => {},
+		}
+
+		if let Some(hash) = in_block {
+			if is_finalized(client, hash).map_err(|e| Error::Client(Box::new(e)))? {
+				return Ok(hash)
+			}
+		}
+	}
+
+	Err(not_finalized("was not finalized before the node shut down"))
+}
+
+/// Whether the block `hash` is finalized, which also requires it to be in the canonical chain.
+fn is_finalized<C: HeaderBackend<Block>>(client: &C, hash: Hash) -> sp_blockchain::Result<bool> {
+	let number: BlockNumber = match client.header(BlockId::hash(hash))? {
+		Some(header) => *header.number(),
+		None => return Ok(false),
+	};
+
+	Ok(number <= client.info().finalized_number && client.hash(number)? == Some(hash))
+}
+
+fn not_finalized(reason: &str) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(NOT_FINALIZED),
+		message: format!("The extrinsic {}", reason),
+		data: None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::channel::mpsc;
+	use node_primitives::Header;
+	use sc_client_api::{in_mem::Blockchain, FinalityNotification, NewBlockState};
+	use sp_runtime::traits::Header as _;
+	use sp_utils::mpsc::{tracing_unbounded, TracingUnboundedSender};
+
+	type Status = TransactionStatus<Hash, Hash>;
+
+	fn insert(client: &Blockchain<Block>, number: BlockNumber, parent_hash: Hash) -> Header {
+		let header = Header::new(
+			number,
+			Default::default(),
+			Default::default(),
+			parent_hash,
+			Default::default(),
+		);
+		let state = if number == 0 { NewBlockState::Final } else { NewBlockState::Best };
+		client.insert(header.hash(), header.clone(), None, None, state).unwrap();
+		header
+	}
+
+	/// Finalize a new child of `parent`, which finalizes `parent` as well.
+	fn finalize_child(
+		client: &Blockchain<Block>,
+		parent: &Header,
+		finality: &TracingUnboundedSender<FinalityNotification<Block>>,
+	) {
+		let header = Header::new(
+			parent.number + 1,
+			Default::default(),
+			Default::default(),
+			parent.hash(),
+			Default::default(),
+		);
+		let hash = header.hash();
+		client.insert(hash, header.clone(), None, None, NewBlockState::Final).unwrap();
+		finality.unbounded_send(FinalityNotification { hash, header }).unwrap();
+	}
+
+	#[test]
+	fn resolves_once_the_including_block_is_finalized() {
+		let client = Blockchain::<Block>::new();
+		let genesis = insert(&client, 0, Default::default());
+		let block1 = insert(&client, 1, genesis.hash());
+		let (status, status_rx) = mpsc::unbounded::<Status>();
+		let (finality, finality_rx) = tracing_unbounded("test_finality");
+		let mut wait = Box::pin(wait_finalized(&client, status_rx, finality_rx));
+
+		status.unbounded_send(TransactionStatus::InBlock(block1.hash())).unwrap();
+		assert!(wait.as_mut().now_or_never().is_none());
+
+		finalize_child(&client, &block1, &finality);
+		assert_eq!(wait.as_mut().now_or_never().unwrap().unwrap(), block1.hash());
+	}
+
+	#[test]
+	fn forgets_the_retracted_blocks() {
+		let client = Blockchain::<Block>::new();
+		let genesis = insert(&client, 0, Default::default());
+		let block1 = insert(&client, 1, genesis.hash());
+		let (status, status_rx) = mpsc::unbounded::<Status>();
+		let (finality, finality_rx) = tracing_unbounded("test_finality");
+		let mut wait = Box::pin(wait_finalized(&client, status_rx, finality_rx));
+
+		status.unbounded_send(TransactionStatus::InBlock(block1.hash())).unwrap();
+		status.unbounded_send(TransactionStatus::Retracted(block1.hash())).unwrap();
+		assert!(wait.as_mut().now_or_never().is_none());
+
+		finalize_child(&client, &block1, &finality);
+		assert!(wait.as_mut().now_or_never().is_none());
+
+		status.unbounded_send(TransactionStatus::Dropped).unwrap();
+		let err = wait.as_mut().now_or_never().unwrap().unwrap_err();
+		assert_eq!(err.code, ErrorCode::ServerError(NOT_FINALIZED));
+		assert_eq!(err.message, "The extrinsic was dropped from the pool");
+	}
+
+	#[test]
+	fn fails_when_the_streams_end() {
+		let client = Blockchain::<Block>::new();
+		let (status, status_rx) = mpsc::unbounded::<Status>();
+		let (finality, finality_rx) = tracing_unbounded("test_finality");
+		drop((status, finality));
+
+		let err = futures::executor::block_on(wait_finalized(&client, status_rx, finality_rx))
+			.unwrap_err();
+		assert_eq!(err.message, "The extrinsic was not finalized before the node shut down");
+	}
+}
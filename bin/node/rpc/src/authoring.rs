@@ -0,0 +1,117 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Pausing the authoring of blocks.
+//!
+//! Lets operators stop signing blocks before a maintenance, without restarting the node. The slots
+//! are still followed while the authoring is paused, only the blocks aren't proposed.
+
+use std::sync::{
+	atomic::{AtomicBool, Ordering},
+	Arc,
+};
+
+use jsonrpc_core::Result as RpcResult;
+use jsonrpc_derive::rpc;
+use sc_rpc_api::DenyUnsafe;
+
+/// Whether the authoring of blocks is paused, shared between the RPC handler and the authoring
+/// task.
+#[derive(Clone, Debug, Default)]
+pub struct AuthoringPause(Arc<AtomicBool>);
+
+impl AuthoringPause {
+	/// Whether the authoring is currently paused.
+	pub fn is_paused(&self) -> bool {
+		self.0.load(Ordering::Relaxed)
+	}
+
+	/// Pause or resume the authoring, returning whether it was paused before.
+	pub fn set_paused(&self, paused: bool) -> bool {
+		self.0.swap(paused, Ordering::Relaxed)
+	}
+}
+
+/// Authoring control RPC methods.
+#[rpc]
+pub trait AuthoringApi {
+	/// Stop authoring blocks, until `author_resumeAuthoring` is called.
+	///
+	/// Returns `false` if the authoring was already paused.
+	#[rpc(name = "author_pauseAuthoring")]
+	fn pause_authoring(&self) -> RpcResult<bool>;
+
+	/// Resume authoring blocks after `author_pauseAuthoring`.
+	///
+	/// Returns `false` if the authoring wasn't paused.
+	#[rpc(name = "author_resumeAuthoring")]
+	fn resume_authoring(&self) -> RpcResult<bool>;
+}
+
+/// Implements [`AuthoringApi`] by toggling an [`AuthoringPause`].
+pub struct Authoring {
+	pause: AuthoringPause,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl Authoring {
+	/// Create a new handler toggling `pause`.
+	pub fn new(pause: AuthoringPause, deny_unsafe: DenyUnsafe) -> Self {
+		Self { pause, deny_unsafe }
+	}
+}
+
+impl AuthoringApi for Authoring {
+	fn pause_authoring(&self) -> RpcResult<bool> {
+		self.deny_unsafe.check_if_safe()?;
+		Ok(!self.pause.set_paused(true))
+	}
+
+	fn resume_authoring(&self) -> RpcResult<bool> {
+		self.deny_unsafe.check_if_safe()?;
+		Ok(self.pause.set_paused(false))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn toggles_the_pause() {
+		let pause = AuthoringPause::default();
+		let authoring = Authoring::new(pause.clone(), DenyUnsafe::No);
+
+		assert_eq!(authoring.pause_authoring(), Ok(true));
+		assert!(pause.is_paused());
+		assert_eq!(authoring.pause_authoring(), Ok(false));
+
+		assert_eq!(authoring.resume_authoring(), Ok(true));
+		assert!(!pause.is_paused());
+		assert_eq!(authoring.resume_authoring(), Ok(false));
+	}
+
+	#[test]
+	fn is_unsafe() {
+		let pause = AuthoringPause::default();
+		let authoring = Authoring::new(pause.clone(), DenyUnsafe::Yes);
+
+		assert!(authoring.pause_authoring().is_err());
+		assert!(!pause.is_paused());
+	}
+}
@@ -0,0 +1,149 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Reporting of the consensus configuration of the node.
+//!
+//! The consensus settings are resolved from many command line flags and presets, this lets
+//! operators check the effective values of a running node.
+
+use std::sync::Arc;
+
+use jsonrpc_core::Result as RpcResult;
+use jsonrpc_derive::rpc;
+use serde::{Deserialize, Serialize};
+
+/// The effective consensus configuration of the node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsensusConfig {
+	/// The role of the node, e.g. `AUTHORITY`.
+	pub role: String,
+	/// The BABE authoring settings.
+	pub babe: BabeConfig,
+	/// The settings of the authoring backoff.
+	pub backoff: BackoffConfig,
+	/// The GRANDPA settings.
+	pub grandpa: GrandpaConfig,
+}
+
+/// The BABE authoring settings.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BabeConfig {
+	/// Author blocks even while the node is offline or syncing.
+	pub force_authoring: bool,
+	/// The portion of the slot the proposer may spend building a block.
+	pub block_proposal_slot_portion: f32,
+	/// The portion of the slot the proposer may spend building a block after missed slots.
+	pub max_block_proposal_slot_portion: Option<f32>,
+	/// The maximum number of transactions in the authored blocks.
+	pub max_block_transactions: Option<usize>,
+	/// The soft deadline of the proposer, in percent of the time left to build the block.
+	pub proposer_soft_deadline: Option<u8>,
+}
+
+/// The settings of the authoring backoff.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackoffConfig {
+	/// The maximum number of slots skipped while finality lags.
+	pub max_interval: u32,
+	/// The number of unfinalized blocks allowed before backing off.
+	pub unfinalized_slack: u32,
+	/// The higher this value, the slower the backoff grows with the unfinalized blocks.
+	pub authoring_bias: u32,
+	/// Skip the slots in which the block would only contain inherents.
+	pub no_empty_blocks: bool,
+}
+
+/// The GRANDPA settings.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrandpaConfig {
+	/// Whether the node runs GRANDPA.
+	pub enabled: bool,
+	/// The preset of the timings.
+	pub finality_profile: String,
+	/// The interval between two gossip rounds, in milliseconds.
+	pub gossip_duration_ms: u64,
+	/// The number of blocks between two stored justifications.
+	pub justification_period: u32,
+}
+
+/// Consensus configuration RPC methods.
+#[rpc]
+pub trait ConsensusConfigApi {
+	/// The effective consensus configuration of the node.
+	#[rpc(name = "hoster_consensusConfig")]
+	fn consensus_config(&self) -> RpcResult<ConsensusConfig>;
+}
+
+/// Implements [`ConsensusConfigApi`] by returning the configuration resolved by the service.
+pub struct ConsensusConfigHandler(Arc<ConsensusConfig>);
+
+impl ConsensusConfigHandler {
+	/// Create a new handler returning `config`.
+	pub fn new(config: Arc<ConsensusConfig>) -> Self {
+		Self(config)
+	}
+}
+
+impl ConsensusConfigApi for ConsensusConfigHandler {
+	fn consensus_config(&self) -> RpcResult<ConsensusConfig> {
+		Ok((*self.0).clone())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn serializes_in_camel_case() {
+		let config = ConsensusConfig {
+			role: "AUTHORITY".into(),
+			babe: BabeConfig {
+				force_authoring: false,
+				block_proposal_slot_portion: 0.5,
+				max_block_proposal_slot_portion: None,
+				max_block_transactions: Some(100),
+				proposer_soft_deadline: None,
+			},
+			backoff: BackoffConfig {
+				max_interval: 100,
+				unfinalized_slack: 50,
+				authoring_bias: 2,
+				no_empty_blocks: true,
+			},
+			grandpa: GrandpaConfig {
+				enabled: true,
+				finality_profile: "Standard".into(),
+				gossip_duration_ms: 333,
+				justification_period: 512,
+			},
+		};
+		let handler = ConsensusConfigHandler::new(Arc::new(config));
+
+		let json = serde_json::to_value(handler.consensus_config().unwrap()).unwrap();
+		assert_eq!(json["role"], "AUTHORITY");
+		assert_eq!(json["babe"]["blockProposalSlotPortion"], 0.5);
+		assert_eq!(json["babe"]["maxBlockTransactions"], 100);
+		assert_eq!(json["backoff"]["noEmptyBlocks"], true);
+		assert_eq!(json["grandpa"]["gossipDurationMs"], 333);
+	}
+}
@@ -0,0 +1,133 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Listing of the public keys held by the keystore.
+//!
+//! Complements `author_rotateKeys` by letting operators audit the keys of a node. Only the public
+//! keys are ever read from the keystore.
+
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	convert::TryFrom,
+};
+
+use jsonrpc_core::{Error as RpcError, Result as RpcResult};
+use jsonrpc_derive::rpc;
+use sc_rpc_api::DenyUnsafe;
+use sp_core::{crypto::KeyTypeId, Bytes};
+use sp_keystore::{SyncCryptoStore, SyncCryptoStorePtr};
+
+/// Keystore RPC methods.
+#[rpc]
+pub trait KeystoreApi {
+	/// The public keys held by the keystore, by key type.
+	///
+	/// `key_types` are four character key type ids like `babe` or `gran`, by default the session
+	/// key types of the runtime are listed.
+	#[rpc(name = "keystore_publicKeys")]
+	fn public_keys(
+		&self,
+		key_types: Option<Vec<String>>,
+	) -> RpcResult<BTreeMap<String, Vec<Bytes>>>;
+}
+
+/// Implements [`KeystoreApi`] on top of the keystore of the node.
+pub struct Keystore {
+	keystore: SyncCryptoStorePtr,
+	key_types: Vec<KeyTypeId>,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl Keystore {
+	/// Create a new handler, listing the `key_types` when the caller doesn't give any.
+	pub fn new(
+		keystore: SyncCryptoStorePtr,
+		key_types: Vec<KeyTypeId>,
+		deny_unsafe: DenyUnsafe,
+	) -> Self {
+		Self { keystore, key_types, deny_unsafe }
+	}
+}
+
+impl KeystoreApi for Keystore {
+	fn public_keys(
+		&self,
+		key_types: Option<Vec<String>>,
+	) -> RpcResult<BTreeMap<String, Vec<Bytes>>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let key_types = match key_types {
+			Some(key_types) => key_types
+				.iter()
+				.map(|key_type| {
+					KeyTypeId::try_from(key_type.as_str()).map_err(|()| {
+						RpcError::invalid_params(format!("Invalid key type `{}`", key_type))
+					})
+				})
+				.collect::<RpcResult<Vec<_>>>()?,
+			None => self.key_types.clone(),
+		};
+
+		key_types
+			.into_iter()
+			.map(|key_type| {
+				let keys =
+					SyncCryptoStore::keys(&*self.keystore, key_type).map_err(|e| RpcError {
+						message: format!("Failed to read the keystore: {}", e),
+						..RpcError::internal_error()
+					})?;
+				// The keystore may report the same public key once per crypto type.
+				let keys: BTreeSet<_> = keys.into_iter().map(|pair| pair.1).collect();
+				Ok((
+					String::from_utf8_lossy(&key_type.0).into_owned(),
+					keys.into_iter().map(Bytes).collect(),
+				))
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::crypto::key_types::{BABE, GRANDPA};
+	use sp_keystore::testing::KeyStore;
+	use std::sync::Arc;
+
+	#[test]
+	fn lists_public_keys_by_key_type() {
+		let keystore = Arc::new(KeyStore::new());
+		let babe = SyncCryptoStore::sr25519_generate_new(&*keystore, BABE, None).unwrap();
+		let keystore = Keystore::new(keystore, vec![BABE, GRANDPA], DenyUnsafe::No);
+
+		let keys = keystore.public_keys(None).unwrap();
+		assert_eq!(keys["babe"], vec![Bytes(babe.0.to_vec())]);
+		assert!(keys["gran"].is_empty());
+
+		let keys = keystore.public_keys(Some(vec!["gran".into()])).unwrap();
+		assert_eq!(keys.keys().collect::<Vec<_>>(), vec!["gran"]);
+
+		assert!(keystore.public_keys(Some(vec!["toolong".into()])).is_err());
+	}
+
+	#[test]
+	fn is_unsafe() {
+		let keystore = Keystore::new(Arc::new(KeyStore::new()), vec![BABE], DenyUnsafe::Yes);
+		assert!(keystore.public_keys(None).is_err());
+	}
+}
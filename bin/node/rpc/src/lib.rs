@@ -30,10 +30,21 @@
 
 #![warn(missing_docs)]
 
+mod author;
+mod authoring;
+mod consensus_config;
+mod keystore;
+mod state;
+
 use std::sync::Arc;
 
+pub use authoring::AuthoringPause;
+pub use consensus_config::{BabeConfig, BackoffConfig, ConsensusConfig, GrandpaConfig};
+use jsonrpc_pubsub::manager::SubscriptionManager;
 use node_primitives::{AccountId, Balance, Block, BlockNumber, Hash, Index};
-use sc_client_api::AuxStore;
+use sc_client_api::{
+	AuxStore, BlockBackend, BlockchainEvents, ExecutorProvider, ProofProvider, StorageProvider,
+};
 use sc_consensus_babe::{Config, Epoch};
 use sc_consensus_babe_rpc::BabeRpcHandler;
 use sc_consensus_epochs::SharedEpochChanges;
@@ -41,14 +52,15 @@ use sc_finality_grandpa::{
 	FinalityProofProvider, GrandpaJustificationStream, SharedAuthoritySet, SharedVoterState,
 };
 use sc_finality_grandpa_rpc::GrandpaRpcHandler;
-use sc_rpc::SubscriptionTaskExecutor;
+use sc_rpc::{state::StateApi, SubscriptionTaskExecutor};
 pub use sc_rpc_api::DenyUnsafe;
 use sc_transaction_pool_api::TransactionPool;
-use sp_api::ProvideRuntimeApi;
+use sp_api::{CallApiAt, Metadata, ProvideRuntimeApi};
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
 use sp_consensus::SelectChain;
 use sp_consensus_babe::BabeApi;
+use sp_core::crypto::KeyTypeId;
 use sp_keystore::SyncCryptoStorePtr;
 
 /// Light client extra dependencies.
@@ -91,6 +103,8 @@ pub struct GrandpaDeps<B> {
 pub struct FullDeps<C, P, SC, B> {
 	/// The client instance to use.
 	pub client: Arc<C>,
+	/// The backend instance to use.
+	pub backend: Arc<B>,
 	/// Transaction pool instance.
 	pub pool: Arc<P>,
 	/// The SelectChain Strategy
@@ -99,6 +113,20 @@ pub struct FullDeps<C, P, SC, B> {
 	pub chain_spec: Box<dyn sc_chain_spec::ChainSpec>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// Number of blocks behind the last finalized one whose state is kept, `None` if the state
+	/// is never pruned.
+	pub state_blocks_kept: Option<u32>,
+	/// Only add the GRANDPA and sync state RPC extensions, for a finality gateway.
+	pub finality_only: bool,
+	/// Maximum payload of a state RPC response, in megabytes.
+	pub rpc_max_payload: Option<usize>,
+	/// Key types listed by `keystore_publicKeys` when the caller doesn't give any, usually the
+	/// session key types of the runtime.
+	pub key_types: Vec<KeyTypeId>,
+	/// Pause of the authoring toggled by `author_pauseAuthoring` and `author_resumeAuthoring`.
+	pub authoring_pause: AuthoringPause,
+	/// The consensus configuration returned by `hoster_consensusConfig`.
+	pub consensus_config: Arc<ConsensusConfig>,
 	/// BABE specific dependencies.
 	pub babe: BabeDeps,
 	/// GRANDPA specific dependencies.
@@ -117,16 +145,23 @@ where
 		+ HeaderBackend<Block>
 		+ AuxStore
 		+ HeaderMetadata<Block, Error = BlockChainError>
+		+ ExecutorProvider<Block>
+		+ StorageProvider<Block, B>
+		+ ProofProvider<Block>
+		+ BlockchainEvents<Block>
+		+ CallApiAt<Block>
+		+ BlockBackend<Block>
 		+ Sync
 		+ Send
 		+ 'static,
+	C::Api: Metadata<Block>,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_contracts_rpc::ContractsRuntimeApi<Block, AccountId, Balance, BlockNumber, Hash>,
 	C::Api: pallet_mmr_rpc::MmrRuntimeApi<Block, <Block as sp_runtime::traits::Block>::Hash>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
 	C::Api: BabeApi<Block>,
 	C::Api: BlockBuilder<Block>,
-	P: TransactionPool + 'static,
+	P: TransactionPool<Block = Block> + 'static,
 	SC: SelectChain<Block> + 'static,
 	B: sc_client_api::Backend<Block> + Send + Sync + 'static,
 	B::State: sc_client_api::backend::StateBackend<sp_runtime::traits::HashFor<Block>>,
@@ -137,7 +172,22 @@ where
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
 
 	let mut io = jsonrpc_core::IoHandler::default();
-	let FullDeps { client, pool, select_chain, chain_spec, deny_unsafe, babe, grandpa } = deps;
+	let FullDeps {
+		client,
+		backend,
+		pool,
+		select_chain,
+		chain_spec,
+		deny_unsafe,
+		state_blocks_kept,
+		finality_only,
+		rpc_max_payload,
+		key_types,
+		authoring_pause,
+		consensus_config,
+		babe,
+		grandpa,
+	} = deps;
 
 	let BabeDeps { keystore, babe_config, shared_epoch_changes } = babe;
 	let GrandpaDeps {
@@ -148,39 +198,77 @@ where
 		finality_provider,
 	} = grandpa;
 
-	io.extend_with(SystemApi::to_delegate(FullSystem::new(client.clone(), pool, deny_unsafe)));
-	// Making synchronous calls in light client freezes the browser currently,
-	// more context: https://github.com/paritytech/substrate/pull/3480
-	// These RPCs should use an asynchronous caller instead.
-	io.extend_with(ContractsApi::to_delegate(Contracts::new(client.clone())));
-	io.extend_with(MmrApi::to_delegate(Mmr::new(client.clone())));
-	io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone())));
-	io.extend_with(sc_consensus_babe_rpc::BabeApi::to_delegate(BabeRpcHandler::new(
-		client.clone(),
-		shared_epoch_changes.clone(),
-		keystore,
-		babe_config,
-		select_chain,
-		deny_unsafe,
-	)));
 	io.extend_with(sc_finality_grandpa_rpc::GrandpaApi::to_delegate(GrandpaRpcHandler::new(
 		shared_authority_set.clone(),
 		shared_voter_state,
 		justification_stream,
-		subscription_executor,
+		subscription_executor.clone(),
 		finality_provider,
+		deny_unsafe,
 	)));
 
 	io.extend_with(sc_sync_state_rpc::SyncStateRpcApi::to_delegate(
 		sc_sync_state_rpc::SyncStateRpcHandler::new(
 			chain_spec,
-			client,
+			client.clone(),
 			shared_authority_set,
-			shared_epoch_changes,
+			shared_epoch_changes.clone(),
 			deny_unsafe,
 		)?,
 	));
 
+	if finality_only {
+		return Ok(io)
+	}
+
+	io.extend_with(SystemApi::to_delegate(FullSystem::new(
+		client.clone(),
+		pool.clone(),
+		deny_unsafe,
+	)));
+	io.extend_with(author::AuthorFinalityApi::to_delegate(author::AuthorFinality::new(
+		client.clone(),
+		pool,
+	)));
+	// Making synchronous calls in light client freezes the browser currently,
+	// more context: https://github.com/paritytech/substrate/pull/3480
+	// These RPCs should use an asynchronous caller instead.
+	io.extend_with(ContractsApi::to_delegate(Contracts::new(client.clone())));
+	io.extend_with(MmrApi::to_delegate(Mmr::new(client.clone())));
+	io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone())));
+	io.extend_with(sc_consensus_babe_rpc::BabeApi::to_delegate(BabeRpcHandler::new(
+		client.clone(),
+		shared_epoch_changes,
+		keystore.clone(),
+		babe_config,
+		select_chain,
+		deny_unsafe,
+	)));
+	io.extend_with(authoring::AuthoringApi::to_delegate(authoring::Authoring::new(
+		authoring_pause,
+		deny_unsafe,
+	)));
+	io.extend_with(consensus_config::ConsensusConfigApi::to_delegate(
+		consensus_config::ConsensusConfigHandler::new(consensus_config),
+	));
+	io.extend_with(keystore::KeystoreApi::to_delegate(keystore::Keystore::new(
+		keystore,
+		key_types,
+		deny_unsafe,
+	)));
+	// Overrides the default state RPC so that queries against pruned blocks fail with a
+	// meaningful error.
+	if let Some(blocks_kept) = state_blocks_kept {
+		let subscriptions = SubscriptionManager::new(Arc::new(subscription_executor));
+		let (state, _) =
+			sc_rpc::state::new_full(client.clone(), subscriptions, deny_unsafe, rpc_max_payload);
+		io.extend_with(StateApi::to_delegate(state::PruningAwareState::new(
+			state,
+			client.clone(),
+			backend,
+			blocks_kept,
+		)));
+	}
 	Ok(io)
 }
 
@@ -0,0 +1,279 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! State RPC that is aware of state pruning.
+//!
+//! On a pruned node, querying the state of an old block fails deep inside the state backend
+//! with a generic client error. The [`PruningAwareState`] wrapper checks whether the state of the
+//! requested block is still available before forwarding the call, and otherwise returns
+//! [`Error::StateUnavailable`] together with the oldest block that can be queried.
+
+use std::sync::Arc;
+
+use jsonrpc_core::Result as RpcResult;
+use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId};
+use node_primitives::{Block, Hash};
+use sc_client_api::Backend;
+use sc_rpc_api::state::{
+	error::{Error, FutureResult},
+	ReadProof, StateApi,
+};
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	storage::{StorageChangeSet, StorageData, StorageKey},
+	Bytes,
+};
+use sp_rpc::tracing::TraceBlockResponse;
+use sp_version::RuntimeVersion;
+
+/// State RPC handler that rejects queries against pruned blocks.
+pub struct PruningAwareState<C, B> {
+	inner: sc_rpc::state::State<Block, C>,
+	client: Arc<C>,
+	backend: Arc<B>,
+	blocks_kept: u32,
+}
+
+impl<C, B> PruningAwareState<C, B> {
+	/// Wrap `inner`, `blocks_kept` being the number of blocks behind the last finalized one whose
+	/// state is kept by the backend.
+	pub fn new(
+		inner: sc_rpc::state::State<Block, C>,
+		client: Arc<C>,
+		backend: Arc<B>,
+		blocks_kept: u32,
+	) -> Self {
+		Self { inner, client, backend, blocks_kept }
+	}
+}
+
+/// Check that the state of the block `hash` has not been pruned.
+///
+/// Unknown blocks are let through, the inner handler reports them as usual.
+fn ensure_state_available<C, B>(
+	client: &C,
+	backend: &B,
+	blocks_kept: u32,
+	hash: Option<Hash>,
+) -> Result<(), Error>
+where
+	C: HeaderBackend<Block>,
+	B: Backend<Block>,
+{
+	let hash = match hash {
+		Some(hash) => hash,
+		None => return Ok(()),
+	};
+
+	let number = match client.number(hash) {
+		Ok(Some(number)) => number,
+		_ => return Ok(()),
+	};
+
+	if backend.have_state_at(&hash, number) {
+		return Ok(())
+	}
+
+	let oldest = client.info().finalized_number.saturating_sub(blocks_kept);
+	Err(Error::StateUnavailable { block: number.into(), oldest: oldest.into() })
+}
+
+impl<C, B> PruningAwareState<C, B>
+where
+	C: HeaderBackend<Block>,
+	B: Backend<Block>,
+{
+	fn checked<T: Send + 'static>(
+		&self,
+		hashes: &[Option<Hash>],
+		call: impl FnOnce() -> FutureResult<T>,
+	) -> FutureResult<T> {
+		let available = hashes.iter().try_for_each(|hash| {
+			ensure_state_available(&*self.client, &*self.backend, self.blocks_kept, *hash)
+		});
+		match available {
+			Ok(()) => call(),
+			Err(err) => Box::pin(std::future::ready(Err(err))),
+		}
+	}
+}
+
+impl<C, B> StateApi<Hash> for PruningAwareState<C, B>
+where
+	C: HeaderBackend<Block> + Send + Sync + 'static,
+	B: Backend<Block> + Send + Sync + 'static,
+{
+	type Metadata = sc_rpc::Metadata;
+
+	fn call(&self, name: String, bytes: Bytes, hash: Option<Hash>) -> FutureResult<Bytes> {
+		self.checked(&[hash], || self.inner.call(name, bytes, hash))
+	}
+
+	fn storage_keys(
+		&self,
+		prefix: StorageKey,
+		hash: Option<Hash>,
+	) -> FutureResult<Vec<StorageKey>> {
+		self.checked(&[hash], || self.inner.storage_keys(prefix, hash))
+	}
+
+	fn storage_pairs(
+		&self,
+		prefix: StorageKey,
+		hash: Option<Hash>,
+	) -> FutureResult<Vec<(StorageKey, StorageData)>> {
+		self.checked(&[hash], || self.inner.storage_pairs(prefix, hash))
+	}
+
+	fn storage_keys_paged(
+		&self,
+		prefix: Option<StorageKey>,
+		count: u32,
+		start_key: Option<StorageKey>,
+		hash: Option<Hash>,
+	) -> FutureResult<Vec<StorageKey>> {
+		self.checked(&[hash], || self.inner.storage_keys_paged(prefix, count, start_key, hash))
+	}
+
+	fn storage(&self, key: StorageKey, hash: Option<Hash>) -> FutureResult<Option<StorageData>> {
+		self.checked(&[hash], || self.inner.storage(key, hash))
+	}
+
+	fn storage_hash(&self, key: StorageKey, hash: Option<Hash>) -> FutureResult<Option<Hash>> {
+		self.checked(&[hash], || self.inner.storage_hash(key, hash))
+	}
+
+	fn storage_size(&self, key: StorageKey, hash: Option<Hash>) -> FutureResult<Option<u64>> {
+		self.checked(&[hash], || self.inner.storage_size(key, hash))
+	}
+
+	fn metadata(&self, hash: Option<Hash>) -> FutureResult<Bytes> {
+		self.checked(&[hash], || self.inner.metadata(hash))
+	}
+
+	fn runtime_version(&self, hash: Option<Hash>) -> FutureResult<RuntimeVersion> {
+		self.checked(&[hash], || self.inner.runtime_version(hash))
+	}
+
+	fn query_storage(
+		&self,
+		keys: Vec<StorageKey>,
+		block: Hash,
+		hash: Option<Hash>,
+	) -> FutureResult<Vec<StorageChangeSet<Hash>>> {
+		self.checked(&[Some(block), hash], || self.inner.query_storage(keys, block, hash))
+	}
+
+	fn query_storage_at(
+		&self,
+		keys: Vec<StorageKey>,
+		at: Option<Hash>,
+	) -> FutureResult<Vec<StorageChangeSet<Hash>>> {
+		self.checked(&[at], || self.inner.query_storage_at(keys, at))
+	}
+
+	fn read_proof(
+		&self,
+		keys: Vec<StorageKey>,
+		hash: Option<Hash>,
+	) -> FutureResult<ReadProof<Hash>> {
+		self.checked(&[hash], || self.inner.read_proof(keys, hash))
+	}
+
+	fn subscribe_runtime_version(
+		&self,
+		meta: Self::Metadata,
+		subscriber: Subscriber<RuntimeVersion>,
+	) {
+		self.inner.subscribe_runtime_version(meta, subscriber)
+	}
+
+	fn unsubscribe_runtime_version(
+		&self,
+		meta: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> RpcResult<bool> {
+		self.inner.unsubscribe_runtime_version(meta, id)
+	}
+
+	fn subscribe_storage(
+		&self,
+		meta: Self::Metadata,
+		subscriber: Subscriber<StorageChangeSet<Hash>>,
+		keys: Option<Vec<StorageKey>>,
+	) {
+		self.inner.subscribe_storage(meta, subscriber, keys)
+	}
+
+	fn unsubscribe_storage(
+		&self,
+		meta: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> RpcResult<bool> {
+		self.inner.unsubscribe_storage(meta, id)
+	}
+
+	fn trace_block(
+		&self,
+		block: Hash,
+		targets: Option<String>,
+		storage_keys: Option<String>,
+	) -> FutureResult<TraceBlockResponse> {
+		self.checked(&[Some(block)], || self.inner.trace_block(block, targets, storage_keys))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use node_primitives::Header;
+	use sc_client_api::{in_mem, BlockImportOperation, NewBlockState};
+	use sp_core::H256;
+	use sp_runtime::traits::Header as _;
+
+	fn header(number: u32, parent_hash: Hash) -> Header {
+		Header::new(number, Default::default(), Default::default(), parent_hash, Default::default())
+	}
+
+	#[test]
+	fn pruned_blocks_are_reported_with_the_oldest_available_block() {
+		let backend = in_mem::Backend::<Block>::new();
+		// The genesis block is imported without its state, as if it had been pruned.
+		let genesis = header(0, Default::default());
+		let genesis_hash = genesis.hash();
+		backend
+			.blockchain()
+			.insert(genesis_hash, genesis, None, None, NewBlockState::Final)
+			.unwrap();
+
+		let block1 = header(1, genesis_hash);
+		let block1_hash = block1.hash();
+		let mut op = backend.begin_operation().unwrap();
+		op.set_block_data(block1, None, None, None, NewBlockState::Final).unwrap();
+		backend.commit_operation(op).unwrap();
+
+		let check = |hash| ensure_state_available(backend.blockchain(), &backend, 0, hash);
+		assert!(matches!(
+			check(Some(genesis_hash)),
+			Err(Error::StateUnavailable { block: 0, oldest: 1 })
+		));
+		assert!(check(Some(block1_hash)).is_ok());
+		// The best block and the unknown blocks are left to the inner handler.
+		assert!(check(None).is_ok());
+		assert!(check(Some(H256::repeat_byte(1))).is_ok());
+	}
+}
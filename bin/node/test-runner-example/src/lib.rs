@@ -88,7 +88,10 @@ mod tests {
 	use node_cli::chain_spec::development_config;
 	use sp_keyring::sr25519::Keyring::Alice;
 	use sp_runtime::{traits::IdentifyAccount, MultiSigner};
-	use test_runner::{build_runtime, client_parts, task_executor, ConfigOrChainSpec, Node};
+	use test_runner::{
+		build_runtime, client_parts, default_config_with_backend, task_executor, Backend,
+		ConfigOrChainSpec, Node,
+	};
 
 	#[test]
 	fn test_runner() {
@@ -129,4 +132,34 @@ mod tests {
 			let _client = node.client();
 		})
 	}
+
+	#[test]
+	fn test_runner_in_memory() {
+		let tokio_runtime = build_runtime().unwrap();
+		let task_executor = task_executor(tokio_runtime.handle().clone());
+		let config = default_config_with_backend(
+			task_executor,
+			Box::new(development_config()),
+			Backend::InMemory,
+		);
+		assert!(config.base_path.is_none());
+
+		let (rpc, task_manager, client, pool, command_sink, backend) =
+			client_parts::<NodeTemplateChainInfo>(ConfigOrChainSpec::Config(config)).unwrap();
+		let node = Node::<NodeTemplateChainInfo>::new(
+			rpc,
+			task_manager,
+			client,
+			pool,
+			command_sink,
+			backend,
+		);
+
+		tokio_runtime.block_on(async {
+			node.seal_blocks(1).await;
+			let block_number =
+				node.with_state(|| frame_system::Pallet::<node_runtime::Runtime>::block_number());
+			assert_eq!(block_number, 1);
+		})
+	}
 }
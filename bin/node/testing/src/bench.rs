@@ -384,6 +384,8 @@ impl BenchDb {
 			source: database_type.into_settings(dir.into()),
 			keep_blocks: sc_client_db::KeepBlocks::All,
 			transaction_storage: sc_client_db::TransactionStorageMode::BlockBody,
+			disable_state_cache_layer: false,
+			read_only: false,
 		};
 		let task_executor = TaskExecutor::new();
 
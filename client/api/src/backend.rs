@@ -531,6 +531,8 @@ pub trait Backend<Block: BlockT>: AuxStore + Send + Sync {
 	) -> sp_blockchain::Result<(NumberFor<Block>, HashSet<Block::Hash>)>;
 
 	/// Discard non-best, unfinalized leaf block.
+	///
+	/// The parent of the block becomes a leaf again, unless it has other children.
 	fn remove_leaf_block(&self, hash: &Block::Hash) -> sp_blockchain::Result<()>;
 
 	/// Insert auxiliary data into key-value store.
@@ -189,6 +189,26 @@ where
 		}
 	}
 
+	/// Remove the leaf `hash` at `number`, making `parent_hash` a leaf in its place if given.
+	/// Returns `false` if `hash` isn't a leaf.
+	pub fn remove(&mut self, hash: H, number: N, parent_hash: Option<H>) -> bool {
+		let number = Reverse(number);
+		if !self.remove_leaf(&number, &hash) {
+			return false
+		}
+		self.pending_removed.push(hash);
+
+		if let Some(parent_hash) = parent_hash {
+			if !number.0.is_zero() {
+				let parent_number = number.0 - N::one();
+				self.insert_leaf(Reverse(parent_number.clone()), parent_hash.clone());
+				self.pending_added.push((parent_hash, parent_number));
+			}
+		}
+
+		true
+	}
+
 	/// returns an iterator over all hashes in the leaf set
 	/// ordered by their block number descending.
 	pub fn hashes(&self) -> Vec<H> {
@@ -387,4 +407,20 @@ mod tests {
 		set.undo().undo_finalization(displaced);
 		assert!(set.contains(10, 10_1));
 	}
+
+	#[test]
+	fn remove_restores_the_parent() {
+		let mut set = LeafSet::new();
+		set.import(10_1u32, 10u32, 0u32);
+		set.import(11_1, 11, 10_1);
+		set.import(11_2, 11, 10_1);
+
+		assert!(set.remove(11_1, 11, None));
+		assert!(!set.contains(11, 11_1));
+		assert!(!set.contains(10, 10_1));
+
+		assert!(set.remove(11_2, 11, Some(10_1)));
+		assert_eq!(set.hashes(), vec![10_1]);
+		assert!(!set.remove(11_2, 11, Some(10_1)));
+	}
 }
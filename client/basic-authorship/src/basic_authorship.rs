@@ -42,8 +42,17 @@ use sp_inherents::InherentData;
 use sp_runtime::{
 	generic::BlockId,
 	traits::{BlakeTwo256, Block as BlockT, DigestFor, Hash as HashT, Header as HeaderT},
+	transaction_validity::TransactionTag,
+	Percent, SaturatedConversion,
+};
+use std::{
+	cmp,
+	collections::{BinaryHeap, HashMap, HashSet},
+	marker::PhantomData,
+	pin::Pin,
+	sync::Arc,
+	time,
 };
-use std::{marker::PhantomData, pin::Pin, sync::Arc, time};
 
 use prometheus_endpoint::Registry as PrometheusRegistry;
 use sc_proposer_metrics::MetricsLink as PrometheusMetrics;
@@ -57,6 +66,46 @@ use sc_proposer_metrics::MetricsLink as PrometheusMetrics;
 /// transferred to other nodes.
 pub const DEFAULT_BLOCK_SIZE_LIMIT: usize = 4 * 1024 * 1024 + 512;
 
+/// Default soft deadline used by [`Proposer`], see [`ProposerFactory::set_soft_deadline`].
+///
+/// The proposer stops as soon as it skipped [`MAX_SKIPPED_TRANSACTIONS`] transactions.
+pub const DEFAULT_SOFT_DEADLINE_PERCENT: Percent = Percent::from_percent(0);
+
+/// If the block is full we will attempt to push at most
+/// this number of transactions before quitting for real.
+/// It allows us to increase block utilization.
+pub const MAX_SKIPPED_TRANSACTIONS: usize = 8;
+
+/// Order of the ready transactions in the blocks, see
+/// [`ProposerFactory::set_transaction_ordering`].
+///
+/// Returns [`cmp::Ordering::Less`] when the first transaction should be included before the
+/// second one.
+pub type TransactionOrdering<T> = Arc<dyn Fn(&T, &T) -> cmp::Ordering + Send + Sync>;
+
+/// The ready transactions of the pool `A`.
+type ReadyIterator<A> =
+	Box<dyn Iterator<Item = Arc<<A as TransactionPool>::InPoolTransaction>> + Send>;
+
+/// Reorders the ready transactions of the pool `A`.
+///
+/// The bound on the pool is on the method, so that [`ProposerFactory`] can hold one without it.
+trait Reorder<A>: Send + Sync {
+	fn reorder(&self, ready: ReadyIterator<A>) -> ReadyIterator<A>
+	where
+		A: TransactionPool;
+}
+
+impl<A> Reorder<A> for TransactionOrdering<A::InPoolTransaction>
+where
+	A: TransactionPool + 'static,
+	A::InPoolTransaction: Send + Sync,
+{
+	fn reorder(&self, ready: ReadyIterator<A>) -> ReadyIterator<A> {
+		Box::new(OrderedTransactions::new(ready, self.clone()))
+	}
+}
+
 /// [`Proposer`] factory.
 pub struct ProposerFactory<A, B, C, PR> {
 	spawn_handle: Box<dyn SpawnNamed>,
@@ -71,6 +120,12 @@ pub struct ProposerFactory<A, B, C, PR> {
 	/// If no `block_size_limit` is passed to [`sp_consensus::Proposer::propose`], this block size
 	/// limit will be used.
 	default_block_size_limit: usize,
+	/// The maximum number of transactions included in a block, on top of the inherents.
+	max_transactions: Option<usize>,
+	/// The soft deadline, as a percentage of the time left to build the block.
+	soft_deadline_percent: Percent,
+	/// The [`Reorder`] of the ready transactions, the order of the pool if `None`.
+	transaction_ordering: Option<Arc<dyn Reorder<A>>>,
 	telemetry: Option<TelemetryHandle>,
 	/// When estimating the block size, should the proof be included?
 	include_proof_in_block_size_estimation: bool,
@@ -95,6 +150,9 @@ impl<A, B, C> ProposerFactory<A, B, C, DisableProofRecording> {
 			transaction_pool,
 			metrics: PrometheusMetrics::new(prometheus),
 			default_block_size_limit: DEFAULT_BLOCK_SIZE_LIMIT,
+			max_transactions: None,
+			soft_deadline_percent: DEFAULT_SOFT_DEADLINE_PERCENT,
+			transaction_ordering: None,
 			telemetry,
 			client,
 			include_proof_in_block_size_estimation: false,
@@ -123,6 +181,9 @@ impl<A, B, C> ProposerFactory<A, B, C, EnableProofRecording> {
 			transaction_pool,
 			metrics: PrometheusMetrics::new(prometheus),
 			default_block_size_limit: DEFAULT_BLOCK_SIZE_LIMIT,
+			max_transactions: None,
+			soft_deadline_percent: DEFAULT_SOFT_DEADLINE_PERCENT,
+			transaction_ordering: None,
 			telemetry,
 			include_proof_in_block_size_estimation: true,
 			_phantom: PhantomData,
@@ -146,6 +207,45 @@ impl<A, B, C, PR> ProposerFactory<A, B, C, PR> {
 	pub fn set_default_block_size_limit(&mut self, limit: usize) {
 		self.default_block_size_limit = limit;
 	}
+
+	/// Set the maximum number of transactions included in a block.
+	///
+	/// The inherents don't count towards the limit and are always included. By default, the
+	/// number of transactions is only limited by the block size and weight.
+	pub fn set_max_transactions(&mut self, limit: usize) {
+		self.max_transactions = Some(limit);
+	}
+
+	/// Set the soft deadline, as a percentage of the time left to build the block.
+	///
+	/// Until the soft deadline, the proposer keeps trying the next transactions when the block
+	/// seems full, instead of stopping after [`MAX_SKIPPED_TRANSACTIONS`] transactions didn't fit.
+	/// A higher value fills the blocks better, at the cost of slot time spent on transactions that
+	/// may not fit in the end.
+	///
+	/// The default value is [`DEFAULT_SOFT_DEADLINE_PERCENT`].
+	pub fn set_soft_deadline(&mut self, percent: Percent) {
+		self.soft_deadline_percent = percent;
+	}
+
+	/// Set the order in which the ready transactions are included in the blocks.
+	///
+	/// The transactions are still included after the ones providing the tags they require, the
+	/// ordering only picks the next transaction among the ones whose requirements are met. By
+	/// default, the transactions are included in the order of the pool, by priority.
+	pub fn set_transaction_ordering(
+		&mut self,
+		ordering: impl Fn(&A::InPoolTransaction, &A::InPoolTransaction) -> cmp::Ordering
+			+ Send
+			+ Sync
+			+ 'static,
+	) where
+		A: TransactionPool + 'static,
+		A::InPoolTransaction: Send + Sync,
+	{
+		let ordering: TransactionOrdering<A::InPoolTransaction> = Arc::new(ordering);
+		self.transaction_ordering = Some(Arc::new(ordering));
+	}
 }
 
 impl<B, Block, C, A, PR> ProposerFactory<A, B, C, PR>
@@ -183,6 +283,9 @@ where
 			now,
 			metrics: self.metrics.clone(),
 			default_block_size_limit: self.default_block_size_limit,
+			max_transactions: self.max_transactions,
+			soft_deadline_percent: self.soft_deadline_percent,
+			transaction_ordering: self.transaction_ordering.clone(),
 			telemetry: self.telemetry.clone(),
 			_phantom: PhantomData,
 			include_proof_in_block_size_estimation: self.include_proof_in_block_size_estimation,
@@ -227,6 +330,9 @@ pub struct Proposer<B, Block: BlockT, C, A: TransactionPool, PR> {
 	now: Box<dyn Fn() -> time::Instant + Send + Sync>,
 	metrics: PrometheusMetrics,
 	default_block_size_limit: usize,
+	max_transactions: Option<usize>,
+	soft_deadline_percent: Percent,
+	transaction_ordering: Option<Arc<dyn Reorder<A>>>,
 	include_proof_in_block_size_estimation: bool,
 	telemetry: Option<TelemetryHandle>,
 	_phantom: PhantomData<(B, PR)>,
@@ -309,11 +415,6 @@ where
 		block_size_limit: Option<usize>,
 	) -> Result<Proposal<Block, backend::TransactionFor<B, Block>, PR::Proof>, sp_blockchain::Error>
 	{
-		/// If the block is full we will attempt to push at most
-		/// this number of transactions before quitting for real.
-		/// It allows us to increase block utilization.
-		const MAX_SKIPPED_TRANSACTIONS: usize = 8;
-
 		let mut block_builder =
 			self.client.new_block_at(&self.parent_id, inherent_digests, PR::ENABLED)?;
 
@@ -355,13 +456,23 @@ where
 				self.transaction_pool.ready()
 			},
 		};
+		let pending_iterator = match &self.transaction_ordering {
+			Some(reorder) => reorder.reorder(pending_iterator),
+			None => pending_iterator,
+		};
 
 		let block_size_limit = block_size_limit.unwrap_or(self.default_block_size_limit);
 
+		let now = (self.now)();
+		let left_micros: u64 = deadline.saturating_duration_since(now).as_micros().saturated_into();
+		let soft_deadline =
+			now + time::Duration::from_micros(self.soft_deadline_percent.mul_floor(left_micros));
+
 		debug!("Attempting to push transactions from the pool.");
 		debug!("Pool status: {:?}", self.transaction_pool.status());
 		let mut transaction_pushed = false;
 		let mut hit_block_size_limit = false;
+		let mut transactions = 0;
 
 		for pending_tx in pending_iterator {
 			if (self.now)() > deadline {
@@ -372,6 +483,14 @@ where
 				break
 			}
 
+			if self.max_transactions.map_or(false, |max| transactions >= max) {
+				debug!(
+					"Reached the limit of {} transactions, proceeding with proposing.",
+					transactions
+				);
+				break
+			}
+
 			let pending_tx_data = pending_tx.data().clone();
 			let pending_tx_hash = pending_tx.hash().clone();
 
@@ -386,6 +505,12 @@ where
 						MAX_SKIPPED_TRANSACTIONS - skipped,
 					);
 					continue
+				} else if (self.now)() < soft_deadline {
+					debug!(
+						"Transaction would overflow the block size limit, \
+						 but will keep trying until the soft deadline."
+					);
+					continue
 				} else {
 					debug!("Reached block size limit, proceeding with proposing.");
 					hit_block_size_limit = true;
@@ -397,6 +522,7 @@ where
 			match sc_block_builder::BlockBuilder::push(&mut block_builder, pending_tx_data) {
 				Ok(()) => {
 					transaction_pushed = true;
+					transactions += 1;
 					debug!("[{:?}] Pushed to the block.", pending_tx_hash);
 				},
 				Err(ApplyExtrinsicFailed(Validity(e))) if e.exhausted_resources() => {
@@ -406,6 +532,8 @@ where
 							"Block seems full, but will try {} more transactions before quitting.",
 							MAX_SKIPPED_TRANSACTIONS - skipped,
 						);
+					} else if (self.now)() < soft_deadline {
+						debug!("Block seems full, but will keep trying until the soft deadline.");
 					} else {
 						debug!("Block is full, proceed with proposing.");
 						break
@@ -477,6 +605,120 @@ where
 	}
 }
 
+/// The ready transactions in the order of a [`TransactionOrdering`], every transaction coming
+/// after the ones providing the tags it requires.
+///
+/// The transactions are only heapified when the iterator is created, and are popped one by one
+/// as the block builder asks for them.
+struct OrderedTransactions<T: InPoolTransaction> {
+	transactions: Vec<Arc<T>>,
+	ordering: TransactionOrdering<T>,
+	/// The number of unsatisfied tags required by every transaction.
+	missing: Vec<usize>,
+	/// The transactions requiring every unsatisfied tag.
+	dependents: HashMap<TransactionTag, Vec<usize>>,
+	/// The transactions whose requirements are met.
+	candidates: BinaryHeap<Candidate<T>>,
+	/// The number of transactions that weren't returned yet.
+	left: usize,
+}
+
+impl<T: InPoolTransaction> OrderedTransactions<T> {
+	fn new(transactions: impl Iterator<Item = Arc<T>>, ordering: TransactionOrdering<T>) -> Self {
+		let transactions: Vec<_> = transactions.collect();
+
+		// Only the tags provided by the ready transactions are waited for, the others are already
+		// provided by the chain.
+		let provided: HashSet<_> = transactions.iter().flat_map(|tx| tx.provides()).collect();
+		let mut missing = Vec::with_capacity(transactions.len());
+		let mut dependents = HashMap::<_, Vec<_>>::new();
+		for (index, tx) in transactions.iter().enumerate() {
+			let requires: Vec<_> =
+				tx.requires().iter().filter(|tag| provided.contains(tag)).collect();
+			missing.push(requires.len());
+			for tag in requires {
+				dependents.entry(tag.clone()).or_default().push(index);
+			}
+		}
+
+		let candidates = (0..transactions.len())
+			.filter(|&index| missing[index] == 0)
+			.map(|index| Candidate::new(index, &transactions, &ordering))
+			.collect::<Vec<_>>()
+			.into();
+		let left = transactions.len();
+		Self { transactions, ordering, missing, dependents, candidates, left }
+	}
+}
+
+impl<T: InPoolTransaction> Iterator for OrderedTransactions<T> {
+	type Item = Arc<T>;
+
+	fn next(&mut self) -> Option<Arc<T>> {
+		let Candidate { transaction, .. } = match self.candidates.pop() {
+			Some(candidate) => candidate,
+			None => {
+				if self.left > 0 {
+					// The pool never returns circular requirements.
+					warn!(
+						"{} ready transactions with unmet requirements left out of the block.",
+						self.left,
+					);
+					self.left = 0;
+				}
+				return None
+			},
+		};
+		self.left -= 1;
+
+		for tag in transaction.provides() {
+			for dependent in self.dependents.remove(tag).into_iter().flatten() {
+				self.missing[dependent] -= 1;
+				if self.missing[dependent] == 0 {
+					let candidate = Candidate::new(dependent, &self.transactions, &self.ordering);
+					self.candidates.push(candidate);
+				}
+			}
+		}
+		Some(transaction)
+	}
+}
+
+/// A transaction whose requirements are met, the best one first in a [`BinaryHeap`].
+struct Candidate<T> {
+	index: usize,
+	transaction: Arc<T>,
+	ordering: TransactionOrdering<T>,
+}
+
+impl<T> Candidate<T> {
+	fn new(index: usize, transactions: &[Arc<T>], ordering: &TransactionOrdering<T>) -> Self {
+		Self { index, transaction: transactions[index].clone(), ordering: ordering.clone() }
+	}
+}
+
+impl<T> PartialEq for Candidate<T> {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == cmp::Ordering::Equal
+	}
+}
+
+impl<T> Eq for Candidate<T> {}
+
+impl<T> PartialOrd for Candidate<T> {
+	fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<T> Ord for Candidate<T> {
+	fn cmp(&self, other: &Self) -> cmp::Ordering {
+		// Reversed, as the greatest candidate is popped first. The order of the pool breaks ties.
+		(self.ordering)(&other.transaction, &self.transaction)
+			.then_with(|| other.index.cmp(&self.index))
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -845,4 +1087,187 @@ mod tests {
 		// block size and thus, one less transaction should fit into the limit.
 		assert_eq!(block.extrinsics().len(), extrinsics_num - 2);
 	}
+
+	#[test]
+	fn should_cease_building_block_when_transaction_limit_is_reached() {
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let txpool = BasicPool::new_full(
+			Default::default(),
+			true.into(),
+			None,
+			spawner.clone(),
+			client.clone(),
+		);
+		let genesis_header = client
+			.header(&BlockId::Number(0u64))
+			.expect("header get error")
+			.expect("there should be header");
+
+		block_on(txpool.submit_at(&BlockId::number(0), SOURCE, (0..4).map(extrinsic).collect()))
+			.unwrap();
+		block_on(txpool.maintain(chain_event(genesis_header.clone())));
+
+		let mut proposer_factory =
+			ProposerFactory::new(spawner.clone(), client.clone(), txpool.clone(), None, None);
+		proposer_factory.set_max_transactions(3);
+
+		let proposer = block_on(proposer_factory.init(&genesis_header)).unwrap();
+		let deadline = time::Duration::from_secs(300);
+		let block =
+			block_on(proposer.propose(Default::default(), Default::default(), deadline, None))
+				.map(|r| r.block)
+				.unwrap();
+
+		assert_eq!(block.extrinsics().len(), 3);
+	}
+
+	#[test]
+	fn should_keep_trying_transactions_until_soft_deadline() {
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let txpool = BasicPool::new_full(
+			Default::default(),
+			true.into(),
+			None,
+			spawner.clone(),
+			client.clone(),
+		);
+		let genesis_header = client
+			.header(&BlockId::Number(0u64))
+			.expect("header get error")
+			.expect("there should be header");
+
+		// Only the first transaction exhausting the resources fits in the block, they are followed
+		// by more of them than the proposer skips by default, and then by transactions that fit.
+		let exhausting = (0..=2 * MAX_SKIPPED_TRANSACTIONS as u64).map(|nonce| {
+			Transfer {
+				amount: Default::default(),
+				nonce,
+				from: AccountKeyring::Alice.into(),
+				to: Default::default(),
+			}
+			.into_resources_exhausting_tx()
+		});
+		let fitting = (0..4).map(|nonce| {
+			Transfer {
+				amount: Default::default(),
+				nonce,
+				from: AccountKeyring::Bob.into(),
+				to: Default::default(),
+			}
+			.into_signed_tx()
+		});
+		block_on(txpool.submit_at(
+			&BlockId::number(0),
+			SOURCE,
+			exhausting.chain(fitting).collect(),
+		))
+		.unwrap();
+		block_on(txpool.maintain(chain_event(genesis_header.clone())));
+
+		let mut proposer_factory =
+			ProposerFactory::new(spawner.clone(), client.clone(), txpool.clone(), None, None);
+		let propose = |proposer_factory: &mut ProposerFactory<_, _, _, _>| {
+			let proposer = block_on(proposer_factory.init(&genesis_header)).unwrap();
+			let deadline = time::Duration::from_secs(300);
+			block_on(proposer.propose(Default::default(), Default::default(), deadline, None))
+				.map(|r| r.block)
+				.unwrap()
+		};
+
+		assert_eq!(propose(&mut proposer_factory).extrinsics().len(), 1);
+
+		proposer_factory.set_soft_deadline(Percent::from_percent(50));
+		assert_eq!(propose(&mut proposer_factory).extrinsics().len(), 5);
+	}
+
+	#[test]
+	fn should_include_transactions_in_the_given_order() {
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let txpool = BasicPool::new_full(
+			Default::default(),
+			true.into(),
+			None,
+			spawner.clone(),
+			client.clone(),
+		);
+		let genesis_header = client
+			.header(&BlockId::Number(0u64))
+			.expect("header get error")
+			.expect("there should be header");
+
+		let transfer = |from: AccountKeyring, nonce| {
+			Transfer {
+				amount: Default::default(),
+				nonce,
+				from: from.into(),
+				to: Default::default(),
+			}
+			.into_signed_tx()
+		};
+		let transfers = (0..3)
+			.map(|nonce| transfer(AccountKeyring::Alice, nonce))
+			.chain((0..3).map(|nonce| transfer(AccountKeyring::Bob, nonce)));
+		block_on(txpool.submit_at(&BlockId::number(0), SOURCE, transfers.collect())).unwrap();
+		block_on(txpool.maintain(chain_event(genesis_header.clone())));
+
+		// Bob first, and the highest nonces first, which can't come before the lower ones.
+		let mut proposer_factory =
+			ProposerFactory::new(spawner.clone(), client.clone(), txpool.clone(), None, None);
+		proposer_factory.set_transaction_ordering(|a, b| {
+			let (a, b) = (a.data().transfer(), b.data().transfer());
+			let is_alice = |transfer: &Transfer| transfer.from == AccountKeyring::Alice.into();
+			is_alice(a).cmp(&is_alice(b)).then(b.nonce.cmp(&a.nonce))
+		});
+
+		let proposer = block_on(proposer_factory.init(&genesis_header)).unwrap();
+		let deadline = time::Duration::from_secs(300);
+		let block =
+			block_on(proposer.propose(Default::default(), Default::default(), deadline, None))
+				.map(|r| r.block)
+				.unwrap();
+
+		let expected: Vec<_> = (0..3)
+			.map(|nonce| transfer(AccountKeyring::Bob, nonce))
+			.chain((0..3).map(|nonce| transfer(AccountKeyring::Alice, nonce)))
+			.collect();
+		assert_eq!(block.extrinsics(), &expected[..]);
+	}
+
+	#[test]
+	fn ordered_transactions_wait_for_their_requirements() {
+		let transaction = |hash: u8, priority, requires: &[u8], provides: &[u8]| {
+			Arc::new(sc_transaction_pool::Transaction {
+				data: (),
+				bytes: 0,
+				hash,
+				priority,
+				valid_till: 0,
+				requires: requires.iter().map(|&tag| vec![tag]).collect(),
+				provides: provides.iter().map(|&tag| vec![tag]).collect(),
+				propagate: true,
+				source: SOURCE,
+			})
+		};
+		// `3` requires the tag of `1` and one already provided by the chain, `4` and `5` require
+		// each other.
+		let ready = vec![
+			transaction(1, 1, &[], &[1]),
+			transaction(2, 2, &[], &[2]),
+			transaction(3, 3, &[1, 9], &[3]),
+			transaction(4, 4, &[5], &[4]),
+			transaction(5, 5, &[4], &[5]),
+		];
+		let ordering: TransactionOrdering<_> = Arc::new(|a: &_, b: &_| {
+			let priority = |tx: &sc_transaction_pool::Transaction<u8, ()>| tx.priority;
+			priority(b).cmp(&priority(a))
+		});
+
+		let order: Vec<_> = OrderedTransactions::new(ready.into_iter(), ordering)
+			.map(|tx| tx.hash)
+			.collect();
+		assert_eq!(order, vec![2, 1, 3]);
+	}
 }
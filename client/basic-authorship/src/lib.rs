@@ -72,4 +72,7 @@
 
 mod basic_authorship;
 
-pub use crate::basic_authorship::{Proposer, ProposerFactory, DEFAULT_BLOCK_SIZE_LIMIT};
+pub use crate::basic_authorship::{
+	Proposer, ProposerFactory, TransactionOrdering, DEFAULT_BLOCK_SIZE_LIMIT,
+	DEFAULT_SOFT_DEADLINE_PERCENT, MAX_SKIPPED_TRANSACTIONS,
+};
@@ -127,6 +127,18 @@ arg_enum! {
 	}
 }
 
+arg_enum! {
+	/// Format of exported blocks.
+	#[allow(missing_docs)]
+	#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+	pub enum ExportFormat {
+		// One JSON object per line, with decoded headers.
+		Json,
+		// SCALE encoded blocks, as read by `import-blocks --binary`.
+		Scale,
+	}
+}
+
 arg_enum! {
 	/// How to execute blocks
 	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
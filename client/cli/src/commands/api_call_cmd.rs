@@ -0,0 +1,116 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	commands::utils::decode_hex,
+	error,
+	params::{BlockNumberOrHash, ImportParams, SharedParams},
+	CliConfiguration,
+};
+use parity_scale_codec::Decode;
+use sc_client_api::{CallExecutor, ExecutionStrategy, ExecutorProvider, UsageProvider};
+use sp_core::{hexdisplay::HexDisplay, OpaqueMetadata};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT},
+};
+use sp_version::RuntimeVersion;
+use std::{fmt::Debug, str::FromStr, sync::Arc};
+use structopt::StructOpt;
+
+/// The `api-call` command used to call a runtime API function at a block.
+///
+/// The result is printed as SCALE encoded hex, except for the results of `Core_version` and
+/// `Metadata_metadata` which are decoded.
+#[derive(Debug, StructOpt, Clone)]
+pub struct ApiCallCmd {
+	/// Name of the runtime API function, e.g. `Core_version` or `Metadata_metadata`.
+	#[structopt(value_name = "METHOD")]
+	pub method: String,
+
+	/// SCALE encoded arguments of the function, as hex.
+	#[structopt(long, value_name = "HEX")]
+	pub args: Option<String>,
+
+	/// Block hash or number to call the function at. Default is the best block.
+	#[structopt(long, value_name = "HASH or NUMBER")]
+	pub at: Option<BlockNumberOrHash>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: ImportParams,
+}
+
+impl ApiCallCmd {
+	/// Run the api-call command
+	pub fn run<B, C>(&self, client: Arc<C>) -> error::Result<()>
+	where
+		B: BlockT,
+		C: ExecutorProvider<B> + UsageProvider<B>,
+		B::Hash: FromStr,
+		<B::Hash as FromStr>::Err: Debug,
+		<<B::Header as HeaderT>::Number as FromStr>::Err: Debug,
+	{
+		let at = match &self.at {
+			Some(at) => at.parse()?,
+			None => BlockId::Hash(client.usage_info().chain.best_hash),
+		};
+		let args = match &self.args {
+			Some(args) => decode_hex(args)?,
+			None => Vec::new(),
+		};
+
+		let result = client.executor().call(
+			&at,
+			&self.method,
+			&args,
+			ExecutionStrategy::NativeElseWasm,
+			None,
+		)?;
+
+		match self.method.as_str() {
+			"Core_version" => {
+				let version = RuntimeVersion::decode(&mut &result[..])?;
+				let json = serde_json::to_string_pretty(&version)
+					.map_err(|e| format!("Error encoding the runtime version: {}", e))?;
+				println!("{}", json);
+			},
+			"Metadata_metadata" => {
+				let metadata = OpaqueMetadata::decode(&mut &result[..])?;
+				println!("0x{}", HexDisplay::from(&*metadata));
+			},
+			_ => println!("0x{}", HexDisplay::from(&result)),
+		}
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ApiCallCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
@@ -0,0 +1,135 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{error, params::SharedParams, SubstrateCli};
+use sc_service::ChainSpec;
+use serde::Serialize;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use std::collections::HashSet;
+use structopt::StructOpt;
+
+/// The `check-spec` command used to validate a chain specification without starting the node.
+#[derive(Debug, StructOpt, Clone)]
+pub struct CheckSpecCmd {
+	/// Print the findings as JSON.
+	#[structopt(long)]
+	pub json: bool,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+/// The findings of the `check-spec` command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CheckSpecReport {
+	/// The chain that was checked, as passed on the command line.
+	chain: String,
+	/// The name of the chain spec, if it could be loaded.
+	name: Option<String>,
+	/// The id of the chain spec, if it could be loaded.
+	id: Option<String>,
+	/// The number of boot nodes in the chain spec.
+	boot_nodes: usize,
+	/// The hash of the genesis block built from the chain spec.
+	genesis_hash: Option<String>,
+	/// The state root of the genesis block built from the chain spec.
+	genesis_state_root: Option<String>,
+	/// Problems that make the chain spec unusable.
+	errors: Vec<String>,
+	/// Problems that don't prevent using the chain spec.
+	warnings: Vec<String>,
+}
+
+impl CheckSpecCmd {
+	/// Run the `check-spec` command.
+	///
+	/// Fails if the chain spec can't be used to start a node.
+	pub fn run<B: BlockT, C: SubstrateCli>(&self, cli: &C) -> error::Result<()> {
+		let chain = self.shared_params.chain_id(self.shared_params.is_dev());
+		let mut report = CheckSpecReport { chain, ..Default::default() };
+
+		// The extension and the boot nodes are deserialized together with the spec, so loading
+		// the spec is enough to validate them.
+		match cli.load_spec(&report.chain) {
+			Ok(spec) => check_spec::<B>(&*spec, &mut report),
+			Err(e) => report.errors.push(format!("Failed to load the chain spec: {}", e)),
+		}
+
+		if self.json {
+			let json = serde_json::to_string_pretty(&report)
+				.map_err(|e| format!("Failed to serialize the findings: {}", e))?;
+			println!("{}", json);
+		} else {
+			print_report(&report);
+		}
+
+		if report.errors.is_empty() {
+			Ok(())
+		} else {
+			Err(error::Error::Input(format!("Chain spec `{}` is invalid", report.chain)))
+		}
+	}
+}
+
+fn check_spec<B: BlockT>(spec: &dyn ChainSpec, report: &mut CheckSpecReport) {
+	report.name = Some(spec.name().into());
+	report.id = Some(spec.id().into());
+	report.boot_nodes = spec.boot_nodes().len();
+
+	let mut seen = HashSet::new();
+	for boot_node in spec.boot_nodes() {
+		if !seen.insert(boot_node.to_string()) {
+			report.warnings.push(format!("Duplicate boot node `{}`", boot_node));
+		}
+	}
+
+	if spec.boot_nodes().is_empty() {
+		report.warnings.push("The chain spec has no boot nodes".into());
+	}
+
+	match sc_service::chain_ops::build_genesis_storage(spec) {
+		Ok(storage) => {
+			let genesis = sc_service::chain_ops::genesis_block::<B>(storage);
+			report.genesis_hash = Some(format!("{:?}", genesis.hash()));
+			report.genesis_state_root = Some(format!("{:?}", genesis.header().state_root()));
+		},
+		Err(e) => report.errors.push(format!("Failed to build the genesis storage: {}", e)),
+	}
+}
+
+fn print_report(report: &CheckSpecReport) {
+	println!("Chain spec: {}", report.chain);
+	if let (Some(name), Some(id)) = (&report.name, &report.id) {
+		println!("Name: {} ({})", name, id);
+		println!("Boot nodes: {}", report.boot_nodes);
+	}
+	if let Some(genesis_hash) = &report.genesis_hash {
+		println!("Genesis hash: {}", genesis_hash);
+	}
+	if let Some(genesis_state_root) = &report.genesis_state_root {
+		println!("Genesis state root: {}", genesis_state_root);
+	}
+	for warning in &report.warnings {
+		println!("Warning: {}", warning);
+	}
+	for error in &report.errors {
+		println!("Error: {}", error);
+	}
+}
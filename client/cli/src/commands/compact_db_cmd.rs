@@ -0,0 +1,64 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{DatabaseParams, SharedParams},
+	CliConfiguration,
+};
+use sc_service::{chain_ops::compact_db, DatabaseSource};
+use structopt::StructOpt;
+
+/// The `compact-db` command used to compact the database offline.
+#[derive(Debug, StructOpt, Clone)]
+pub struct CompactDbCmd {
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl CompactDbCmd {
+	/// Run the compact-db command
+	pub fn run(&self, database_config: DatabaseSource) -> error::Result<()> {
+		let (size_before, size_after) = compact_db(&database_config)?;
+
+		let mib = |bytes: u64| bytes as f64 / (1024 * 1024) as f64;
+		println!(
+			"Compacted the database from {:.1} MiB to {:.1} MiB, reclaiming {:.1} MiB.",
+			mib(size_before),
+			mib(size_after),
+			mib(size_before.saturating_sub(size_after)),
+		);
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for CompactDbCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
@@ -0,0 +1,145 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{error, SubstrateCli};
+use serde::Serialize;
+use sp_core::{bytes::to_hex, storage::Storage};
+use std::collections::{BTreeMap, BTreeSet};
+use structopt::StructOpt;
+
+/// The `diff-spec` command used to compare the genesis storage of two chain specifications.
+#[derive(Debug, StructOpt, Clone)]
+pub struct DiffSpecCmd {
+	/// The chain spec to compare from, as passed to `--chain`.
+	#[structopt(value_name = "A")]
+	pub a: String,
+
+	/// The chain spec to compare to, as passed to `--chain`.
+	#[structopt(value_name = "B")]
+	pub b: String,
+
+	/// Print the differences as JSON.
+	#[structopt(long)]
+	pub json: bool,
+}
+
+/// A storage key of the genesis storage.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StorageKey {
+	/// The storage key of the child trie holding the key, if it isn't in the main trie.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	child: Option<String>,
+	/// The hex encoded key.
+	key: String,
+}
+
+/// The differences found by the `diff-spec` command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StorageDiff {
+	/// Keys only in the genesis storage of `B`.
+	added: Vec<StorageKey>,
+	/// Keys only in the genesis storage of `A`.
+	removed: Vec<StorageKey>,
+	/// Keys with a different value in the genesis storage of `B`.
+	changed: Vec<StorageKey>,
+}
+
+impl DiffSpecCmd {
+	/// Run the `diff-spec` command.
+	pub fn run<C: SubstrateCli>(&self, cli: &C) -> error::Result<()> {
+		let a = self.genesis_storage(cli, &self.a)?;
+		let b = self.genesis_storage(cli, &self.b)?;
+		let diff = diff_storage(&a, &b);
+
+		if self.json {
+			let json = serde_json::to_string_pretty(&diff)
+				.map_err(|e| format!("Failed to serialize the differences: {}", e))?;
+			println!("{}", json);
+		} else {
+			print_diff(&diff);
+		}
+
+		Ok(())
+	}
+
+	fn genesis_storage<C: SubstrateCli>(&self, cli: &C, chain: &str) -> error::Result<Storage> {
+		let spec = cli.load_spec(chain)?;
+		sc_service::chain_ops::build_genesis_storage(&*spec).map_err(|e| {
+			error::Error::Input(format!(
+				"Failed to build the genesis storage of `{}`: {}",
+				chain, e
+			))
+		})
+	}
+}
+
+/// Compare the main trie and the child tries of the genesis storages `a` and `b`.
+fn diff_storage(a: &Storage, b: &Storage) -> StorageDiff {
+	let mut diff = StorageDiff::default();
+	diff_trie(None, &a.top, &b.top, &mut diff);
+
+	let empty = BTreeMap::new();
+	let children: BTreeSet<_> =
+		a.children_default.keys().chain(b.children_default.keys()).collect();
+	for child in children {
+		let a = a.children_default.get(child).map_or(&empty, |child| &child.data);
+		let b = b.children_default.get(child).map_or(&empty, |child| &child.data);
+		diff_trie(Some(child), a, b, &mut diff);
+	}
+
+	diff
+}
+
+fn diff_trie(
+	child: Option<&[u8]>,
+	a: &BTreeMap<Vec<u8>, Vec<u8>>,
+	b: &BTreeMap<Vec<u8>, Vec<u8>>,
+	diff: &mut StorageDiff,
+) {
+	let storage_key =
+		|key: &[u8]| StorageKey { child: child.map(|c| to_hex(c, false)), key: to_hex(key, false) };
+
+	for (key, value) in a {
+		match b.get(key) {
+			None => diff.removed.push(storage_key(key)),
+			Some(other) if other != value => diff.changed.push(storage_key(key)),
+			Some(_) => {},
+		}
+	}
+	diff.added
+		.extend(b.keys().filter(|key| !a.contains_key(*key)).map(|key| storage_key(key)));
+}
+
+fn print_diff(diff: &StorageDiff) {
+	let print = |sign: char, key: &StorageKey| match &key.child {
+		Some(child) => println!("{} {} (child {})", sign, key.key, child),
+		None => println!("{} {}", sign, key.key),
+	};
+
+	diff.added.iter().for_each(|key| print('+', key));
+	diff.removed.iter().for_each(|key| print('-', key));
+	diff.changed.iter().for_each(|key| print('~', key));
+	println!(
+		"{} added, {} removed, {} changed",
+		diff.added.len(),
+		diff.removed.len(),
+		diff.changed.len(),
+	);
+}
@@ -0,0 +1,116 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	arg_enums::ExportFormat,
+	error,
+	params::{DatabaseParams, GenericNumber, PruningParams, SharedParams},
+	CliConfiguration,
+};
+use log::info;
+use sc_client_api::{BlockBackend, UsageProvider};
+use sc_service::{chain_ops::export_finalized_blocks, config::DatabaseSource};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use std::{fmt::Debug, io, str::FromStr, sync::Arc};
+use structopt::StructOpt;
+
+/// The `export-finalized` command used to stream the finalized blocks to stdout.
+#[derive(Debug, StructOpt, Clone)]
+pub struct ExportFinalizedCmd {
+	/// Output format of the blocks.
+	///
+	/// `json` writes one object per line, with the header fields and the digest logs decoded.
+	/// `scale` writes the same format as `export-blocks --binary`.
+	#[structopt(
+		long,
+		value_name = "FORMAT",
+		possible_values = &ExportFormat::variants(),
+		case_insensitive = true,
+		default_value = "Json"
+	)]
+	pub format: ExportFormat,
+
+	/// Specify starting block number.
+	///
+	/// Default is 1.
+	#[structopt(long = "from", value_name = "BLOCK")]
+	pub from: Option<GenericNumber>,
+
+	/// Specify last block number, which must be finalized.
+	///
+	/// Default is the last finalized block.
+	#[structopt(long = "to", value_name = "BLOCK")]
+	pub to: Option<GenericNumber>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl ExportFinalizedCmd {
+	/// Run the export-finalized command
+	pub async fn run<B, C>(
+		&self,
+		client: Arc<C>,
+		database_config: DatabaseSource,
+	) -> error::Result<()>
+	where
+		B: BlockT,
+		C: BlockBackend<B> + UsageProvider<B> + 'static,
+		<<B::Header as HeaderT>::Number as FromStr>::Err: Debug,
+	{
+		if let DatabaseSource::RocksDb { ref path, .. } = database_config {
+			info!("DB path: {}", path.display());
+		}
+
+		let from = self
+			.from
+			.as_ref()
+			.map(|f| f.parse())
+			.transpose()?
+			.unwrap_or_else(|| 1u32.into());
+		let to = self.to.as_ref().map(|t| t.parse()).transpose()?;
+		let scale = self.format == ExportFormat::Scale;
+
+		export_finalized_blocks(client, io::stdout(), from, to, scale)
+			.await
+			.map_err(Into::into)
+	}
+}
+
+impl CliConfiguration for ExportFinalizedCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
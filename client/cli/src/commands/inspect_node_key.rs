@@ -17,9 +17,10 @@
 
 //! Implementation of the `inspect-node-key` subcommand
 
+use super::peer_id_cmd::peer_id;
 use crate::{Error, NetworkSchemeFlag};
-use libp2p::identity::{ed25519, PublicKey};
-use std::{fs, path::PathBuf};
+use sc_network::config::{NodeKeyConfig, Secret};
+use std::path::PathBuf;
 use structopt::StructOpt;
 
 /// The `inspect-node-key` command
@@ -41,15 +42,12 @@ pub struct InspectNodeKeyCmd {
 impl InspectNodeKeyCmd {
 	/// runs the command
 	pub fn run(&self) -> Result<(), Error> {
-		let mut file_content =
-			hex::decode(fs::read(&self.file)?).map_err(|_| "failed to decode secret as hex")?;
-		let secret =
-			ed25519::SecretKey::from_bytes(&mut file_content).map_err(|_| "Bad node key file")?;
+		// The node generates the missing node key files, which are only read here.
+		if !self.file.is_file() {
+			return Err(format!("The node key file {} doesn't exist", self.file.display()).into())
+		}
 
-		let keypair = ed25519::Keypair::from(secret);
-		let peer_id = PublicKey::Ed25519(keypair.public()).into_peer_id();
-
-		println!("{}", peer_id);
+		println!("{}", peer_id(NodeKeyConfig::Ed25519(Secret::File(self.file.clone())))?);
 
 		Ok(())
 	}
@@ -70,4 +68,15 @@ mod tests {
 		let cmd = InspectNodeKeyCmd::from_iter(&["inspect-node-key", "--file", path]);
 		assert!(cmd.run().is_ok());
 	}
+
+	#[test]
+	fn inspect_node_key_does_not_generate_the_file() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("node-id");
+
+		let cmd =
+			InspectNodeKeyCmd::from_iter(&["inspect-node-key", "--file", path.to_str().unwrap()]);
+		assert!(cmd.run().is_err());
+		assert!(!path.exists());
+	}
 }
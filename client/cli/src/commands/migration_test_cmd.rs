@@ -0,0 +1,112 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{GenericNumber, ImportParams, SharedParams},
+	CliConfiguration,
+};
+use sc_client_api::{backend::Backend, BlockBackend, ExecutorProvider, UsageProvider};
+use sc_service::chain_ops::migration_test;
+use sp_core::traits::{CodeExecutor, SpawnNamed};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use std::{fmt::Debug, fs, path::PathBuf, str::FromStr, sync::Arc};
+use structopt::StructOpt;
+
+/// The `migration-test` command used to re-execute imported blocks with a new runtime.
+#[derive(Debug, StructOpt, Clone)]
+pub struct MigrationTestCmd {
+	/// Path of the wasm runtime to re-execute the blocks with.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	pub runtime_blob: PathBuf,
+
+	/// Specify starting block number.
+	///
+	/// Default is 1.
+	#[structopt(long = "from", value_name = "BLOCK")]
+	pub from: Option<GenericNumber>,
+
+	/// Specify last block number.
+	///
+	/// Default is the best block.
+	#[structopt(long = "to", value_name = "BLOCK")]
+	pub to: Option<GenericNumber>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: ImportParams,
+}
+
+impl MigrationTestCmd {
+	/// Run the migration-test command
+	pub fn run<B, BA, C, E>(
+		&self,
+		client: Arc<C>,
+		backend: Arc<BA>,
+		executor: E,
+		spawn_handle: impl SpawnNamed + Clone + Send + 'static,
+	) -> error::Result<()>
+	where
+		B: BlockT,
+		BA: Backend<B>,
+		C: BlockBackend<B> + ExecutorProvider<B> + UsageProvider<B>,
+		E: CodeExecutor + Clone + 'static,
+		<<B::Header as HeaderT>::Number as FromStr>::Err: Debug,
+	{
+		let code = fs::read(&self.runtime_blob).map_err(|e| {
+			format!("Failed to read the runtime blob `{}`: {}", self.runtime_blob.display(), e)
+		})?;
+		let from = self
+			.from
+			.as_ref()
+			.map(|f| f.parse())
+			.transpose()?
+			.unwrap_or_else(|| 1u32.into());
+		let to = match self.to.as_ref() {
+			Some(to) => to.parse()?,
+			None => client.usage_info().chain.best_number,
+		};
+
+		let divergences =
+			migration_test(&*client, &*backend, &executor, &code, from, to, spawn_handle)?;
+
+		if divergences.is_empty() {
+			println!("All the blocks from #{} to #{} execute with the new runtime.", from, to);
+			return Ok(())
+		}
+
+		for divergence in &divergences {
+			println!("#{} ({:?}): {}", divergence.number, divergence.hash, divergence.error);
+		}
+		Err(format!("{} blocks diverge with the new runtime", divergences.len()).into())
+	}
+}
+
+impl CliConfiguration for MigrationTestCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
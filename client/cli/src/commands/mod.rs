@@ -15,9 +15,14 @@
 
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
+mod api_call_cmd;
 mod build_spec_cmd;
 mod check_block_cmd;
+mod check_spec_cmd;
+mod compact_db_cmd;
+mod diff_spec_cmd;
 mod export_blocks_cmd;
+mod export_finalized_cmd;
 mod export_state_cmd;
 mod generate;
 mod generate_node_key;
@@ -26,6 +31,9 @@ mod insert_key;
 mod inspect_key;
 mod inspect_node_key;
 mod key;
+mod migration_test_cmd;
+mod peer_id_cmd;
+mod prune_forks_cmd;
 mod purge_chain_cmd;
 mod revert_cmd;
 mod run_cmd;
@@ -35,10 +43,12 @@ mod vanity;
 mod verify;
 
 pub use self::{
-	build_spec_cmd::BuildSpecCmd, check_block_cmd::CheckBlockCmd,
-	export_blocks_cmd::ExportBlocksCmd, export_state_cmd::ExportStateCmd, generate::GenerateCmd,
-	generate_node_key::GenerateNodeKeyCmd, import_blocks_cmd::ImportBlocksCmd,
-	insert_key::InsertKeyCmd, inspect_key::InspectKeyCmd, inspect_node_key::InspectNodeKeyCmd,
-	key::KeySubcommand, purge_chain_cmd::PurgeChainCmd, revert_cmd::RevertCmd, run_cmd::RunCmd,
-	sign::SignCmd, vanity::VanityCmd, verify::VerifyCmd,
+	api_call_cmd::ApiCallCmd, build_spec_cmd::BuildSpecCmd, check_block_cmd::CheckBlockCmd,
+	check_spec_cmd::CheckSpecCmd, compact_db_cmd::CompactDbCmd, diff_spec_cmd::DiffSpecCmd,
+	export_blocks_cmd::ExportBlocksCmd, export_finalized_cmd::ExportFinalizedCmd,
+	export_state_cmd::ExportStateCmd, generate::GenerateCmd, generate_node_key::GenerateNodeKeyCmd,
+	import_blocks_cmd::ImportBlocksCmd, insert_key::InsertKeyCmd, inspect_key::InspectKeyCmd,
+	inspect_node_key::InspectNodeKeyCmd, key::KeySubcommand, migration_test_cmd::MigrationTestCmd,
+	peer_id_cmd::PeerIdCmd, prune_forks_cmd::PruneForksCmd, purge_chain_cmd::PurgeChainCmd,
+	revert_cmd::RevertCmd, run_cmd::RunCmd, sign::SignCmd, vanity::VanityCmd, verify::VerifyCmd,
 };
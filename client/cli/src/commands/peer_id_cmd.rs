@@ -0,0 +1,93 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Implementation of the `peer-id` subcommand
+
+use crate::{
+	error,
+	params::{NodeKeyParams, SharedParams},
+	CliConfiguration,
+};
+use sc_network::{config::NodeKeyConfig, PeerId};
+use sc_service::config::NetworkConfiguration;
+use structopt::StructOpt;
+
+/// The `peer-id` command used to print the peer ID of the node key, without starting the node.
+///
+/// Unlike `key inspect-node-key`, which reads a given file, the node key is found like when
+/// starting the node, from the node key parameters and the base path.
+#[derive(Debug, StructOpt, Clone)]
+pub struct PeerIdCmd {
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub node_key_params: NodeKeyParams,
+}
+
+impl PeerIdCmd {
+	/// Run the `peer-id` command.
+	///
+	/// The node key is obtained like when starting the node, so a missing node key file is
+	/// generated.
+	pub fn run(&self, network_config: NetworkConfiguration) -> error::Result<()> {
+		println!("{}", peer_id(network_config.node_key)?);
+		Ok(())
+	}
+}
+
+impl CliConfiguration for PeerIdCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn node_key_params(&self) -> Option<&NodeKeyParams> {
+		Some(&self.node_key_params)
+	}
+}
+
+/// The peer ID of `node_key`.
+pub(crate) fn peer_id(node_key: NodeKeyConfig) -> error::Result<PeerId> {
+	Ok(node_key.into_keypair()?.public().into_peer_id())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sc_network::config::identity::{ed25519, PublicKey};
+	use std::fs;
+
+	#[test]
+	fn reads_or_generates_the_node_key_file() {
+		let dir = tempfile::tempdir().unwrap();
+		let file = dir.path().join("node-key");
+		let keypair = ed25519::Keypair::generate();
+		fs::write(&file, hex::encode(keypair.secret().as_ref())).unwrap();
+
+		let cmd = PeerIdCmd::from_iter(&["peer-id", "--node-key-file", file.to_str().unwrap()]);
+		let node_key = cmd.node_key_params.node_key(&dir.path().into()).unwrap();
+		assert_eq!(peer_id(node_key).unwrap(), PublicKey::Ed25519(keypair.public()).into_peer_id());
+
+		let cmd = PeerIdCmd::from_iter(&["peer-id"]);
+		let generated = peer_id(cmd.node_key_params.node_key(&dir.path().into()).unwrap()).unwrap();
+		let read = peer_id(cmd.node_key_params.node_key(&dir.path().into()).unwrap()).unwrap();
+		assert_eq!(generated, read);
+		assert!(dir.path().join("secret_ed25519").exists());
+	}
+}
@@ -0,0 +1,91 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{PruningParams, SharedParams},
+	CliConfiguration,
+};
+use sc_client_api::{Backend, HeaderBackend};
+use sc_service::chain_ops::{list_forks, prune_forks};
+use sp_runtime::traits::Block as BlockT;
+use std::sync::Arc;
+use structopt::StructOpt;
+
+/// The `prune-forks` command used to list the leaves of the chain and remove the stale forks.
+#[derive(Debug, StructOpt)]
+pub struct PruneForksCmd {
+	/// Remove the forks leaving the best chain below the finalized block, instead of only
+	/// listing the leaves.
+	#[structopt(long)]
+	pub prune: bool,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+}
+
+impl PruneForksCmd {
+	/// Run the prune-forks command
+	pub async fn run<B, BA>(&self, backend: Arc<BA>) -> error::Result<()>
+	where
+		B: BlockT,
+		BA: Backend<B>,
+	{
+		let finalized = backend.blockchain().info().finalized_number;
+
+		for fork in list_forks(&*backend)? {
+			let kind = if fork.is_canonical() {
+				"best"
+			} else if fork.is_stale(finalized) {
+				"stale"
+			} else {
+				"fork"
+			};
+			println!(
+				"#{} ({}): {}, fork point #{} ({})",
+				fork.leaf_number,
+				fork.leaf_hash,
+				kind,
+				fork.fork_point_number,
+				fork.fork_point_hash,
+			);
+		}
+
+		if self.prune {
+			let pruned = prune_forks(&*backend)?;
+			println!("Pruned {} blocks", pruned);
+		}
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for PruneForksCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+}
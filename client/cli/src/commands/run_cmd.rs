@@ -27,11 +27,16 @@ use crate::{
 };
 use regex::Regex;
 use sc_service::{
-	config::{BasePath, PrometheusConfig, TransactionPoolOptions},
+	config::{BasePath, PrometheusConfig, TelemetryReconnectPolicy, TransactionPoolOptions},
 	ChainSpec, Role,
 };
 use sc_telemetry::TelemetryEndpoints;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::{
+	net::{IpAddr, Ipv4Addr, SocketAddr},
+	num::NonZeroU32,
+	path::PathBuf,
+	time::Duration,
+};
 use structopt::StructOpt;
 
 /// The `run` command used to run a node.
@@ -105,6 +110,52 @@ pub struct RunCmd {
 	#[structopt(long = "rpc-max-payload")]
 	pub rpc_max_payload: Option<usize>,
 
+	/// Set the maximum size of the HTTP and WS RPC requests, in megabytes, instead of
+	/// `--rpc-max-payload`.
+	///
+	/// Larger requests are rejected by the server before being parsed, with a
+	/// `413 Payload Too Large` status over HTTP.
+	#[structopt(long = "rpc-max-request-size", value_name = "MB")]
+	pub rpc_max_request_size: Option<usize>,
+
+	/// Set the maximum size of the responses to HTTP and WS RPC calls, in megabytes.
+	///
+	/// Calls whose response is larger get a JSON-RPC error instead, as do all the calls of a
+	/// batch whose whole response is larger. This also lets WS responses grow beyond the default
+	/// buffer size of the server.
+	#[structopt(long = "rpc-max-response-size", value_name = "MB")]
+	pub rpc_max_response_size: Option<usize>,
+
+	/// Limit the number of HTTP and WS RPC calls per second, for each peer.
+	///
+	/// The HTTP calls are limited per IP address of the peer. The WS server doesn't expose the
+	/// address of the peers, every WS connection has its own limit. Calls exceeding the limit are
+	/// rejected with a JSON-RPC error.
+	#[structopt(long = "rpc-rate-limit", value_name = "CALLS_PER_SECOND")]
+	pub rpc_rate_limit: Option<NonZeroU32>,
+
+	/// Limit the number of active subscriptions of every WS RPC connection.
+	///
+	/// Subscriptions beyond the limit are rejected with a JSON-RPC error until some of the
+	/// connection's subscriptions are cancelled.
+	#[structopt(long = "rpc-max-subscriptions-per-connection", value_name = "COUNT")]
+	pub rpc_max_subscriptions_per_connection: Option<NonZeroU32>,
+
+	/// Default depth of the `chain_subscribeConfirmedHeads` RPC subscriptions.
+	///
+	/// These subscriptions are notified about the blocks of the best chain once they are this
+	/// many blocks deep. Without it, every subscription has to give its own depth.
+	#[structopt(long = "confirmation-depth", value_name = "BLOCKS")]
+	pub confirmation_depth: Option<u32>,
+
+	/// Report the node as not ready for the given number of seconds after it started.
+	///
+	/// The `system_health` RPC, and so the HTTP `/health` endpoint, fail until the warm-up is
+	/// over and the node isn't doing a major sync, which keeps load balancers from routing
+	/// requests to a node that just started.
+	#[structopt(long, value_name = "SECONDS")]
+	pub health_warmup: Option<u64>,
+
 	/// Expose Prometheus exporter on all interfaces.
 	///
 	/// Default is local.
@@ -112,7 +163,10 @@ pub struct RunCmd {
 	pub prometheus_external: bool,
 
 	/// Specify IPC RPC server path
-	#[structopt(long = "ipc-path", value_name = "PATH")]
+	///
+	/// On Unix, the server listens on a Unix domain socket at this path. A stale socket left
+	/// behind by a node that didn't shut down cleanly is removed on startup.
+	#[structopt(long = "ipc-path", visible_alias = "rpc-unix-socket", value_name = "PATH")]
 	pub ipc_path: Option<String>,
 
 	/// Specify HTTP RPC server TCP port.
@@ -144,6 +198,16 @@ pub struct RunCmd {
 	#[structopt(long = "prometheus-port", value_name = "PORT")]
 	pub prometheus_port: Option<u16>,
 
+	/// Expose the Prometheus exporter on a Unix domain socket instead of a TCP port.
+	///
+	/// A stale socket left behind by a node that didn't shut down cleanly is removed on startup.
+	#[structopt(
+		long = "prometheus-unix-socket",
+		value_name = "PATH",
+		conflicts_with_all = &["prometheus-port", "prometheus-external"]
+	)]
+	pub prometheus_unix_socket: Option<PathBuf>,
+
 	/// Do not expose a Prometheus exporter endpoint.
 	///
 	/// Prometheus metric endpoint is enabled by default.
@@ -171,6 +235,18 @@ pub struct RunCmd {
 	#[structopt(long = "telemetry-url", value_name = "URL VERBOSITY", parse(try_from_str = parse_telemetry_endpoints))]
 	pub telemetry_endpoints: Vec<(String, u8)>,
 
+	/// Minimum number of seconds between two attempts to connect to a telemetry server.
+	///
+	/// The actual pause is randomly up to twice as long. Default is 10 seconds.
+	#[structopt(long, value_name = "SECONDS")]
+	pub telemetry_reconnect_interval: Option<u64>,
+
+	/// Stop connecting to a telemetry server after the given number of failed attempts in a row.
+	///
+	/// Default is to try forever.
+	#[structopt(long, value_name = "COUNT")]
+	pub telemetry_max_retries: Option<u32>,
+
 	#[allow(missing_docs)]
 	#[structopt(flatten)]
 	pub offchain_worker_params: OffchainWorkerParams,
@@ -340,6 +416,16 @@ impl CliConfiguration for RunCmd {
 		})
 	}
 
+	fn telemetry_reconnect(&self) -> Result<TelemetryReconnectPolicy> {
+		let interval = match self.telemetry_reconnect_interval {
+			Some(0) =>
+				return Err(Error::Input("--telemetry-reconnect-interval must be at least 1".into())),
+			Some(secs) => Duration::from_secs(secs),
+			None => TelemetryReconnectPolicy::default().interval,
+		};
+		Ok(TelemetryReconnectPolicy { interval, max_retries: self.telemetry_max_retries })
+	}
+
 	fn role(&self, is_dev: bool) -> Result<Role> {
 		let keyring = self.get_keyring();
 		let is_light = self.light;
@@ -366,10 +452,15 @@ impl CliConfiguration for RunCmd {
 			let interface =
 				if self.prometheus_external { Ipv4Addr::UNSPECIFIED } else { Ipv4Addr::LOCALHOST };
 
-			Some(PrometheusConfig::new_with_default_registry(SocketAddr::new(
+			let config = PrometheusConfig::new_with_default_registry(SocketAddr::new(
 				interface.into(),
 				self.prometheus_port.unwrap_or(default_listen_port),
-			)))
+			));
+
+			Some(match &self.prometheus_unix_socket {
+				Some(path) => config.with_unix_socket(path.clone()),
+				None => config,
+			})
 		})
 	}
 
@@ -440,6 +531,33 @@ impl CliConfiguration for RunCmd {
 		Ok(self.rpc_max_payload)
 	}
 
+	fn rpc_max_request_size(&self) -> Result<Option<usize>> {
+		Ok(self.rpc_max_request_size)
+	}
+
+	fn rpc_max_response_size(&self) -> Result<Option<usize>> {
+		Ok(self.rpc_max_response_size)
+	}
+
+	fn rpc_rate_limit(&self) -> Result<Option<NonZeroU32>> {
+		Ok(self.rpc_rate_limit)
+	}
+
+	fn rpc_max_subscriptions_per_connection(&self) -> Result<Option<NonZeroU32>> {
+		Ok(self.rpc_max_subscriptions_per_connection)
+	}
+
+	fn rpc_confirmation_depth(&self) -> Result<Option<u32>> {
+		Ok(self.confirmation_depth)
+	}
+
+	fn health_warmup(&self) -> Result<Option<Duration>> {
+		match self.health_warmup {
+			Some(0) => Err(Error::Input("--health-warmup must be at least 1".into())),
+			secs => Ok(secs.map(Duration::from_secs)),
+		}
+	}
+
 	fn transaction_pool(&self) -> Result<TransactionPoolOptions> {
 		Ok(self.pool_config.transaction_pool())
 	}
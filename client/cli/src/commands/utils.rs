@@ -265,11 +265,8 @@ where
 
 /// helper method for decoding hex
 pub fn decode_hex<T: AsRef<[u8]>>(message: T) -> Result<Vec<u8>, Error> {
-	let mut message = message.as_ref();
-	if message[..2] == [b'0', b'x'] {
-		message = &message[2..]
-	}
-	Ok(hex::decode(message)?)
+	let message = message.as_ref();
+	Ok(hex::decode(message.strip_prefix(b"0x").unwrap_or(message))?)
 }
 
 /// checks if message is Some, otherwise reads message from stdin and optionally decodes hex
@@ -29,12 +29,13 @@ use sc_service::{
 	config::{
 		BasePath, Configuration, DatabaseSource, KeystoreConfig, NetworkConfiguration,
 		NodeKeyConfig, OffchainWorkerConfig, PrometheusConfig, PruningMode, Role, RpcMethods,
-		TaskExecutor, TelemetryEndpoints, TransactionPoolOptions, WasmExecutionMethod,
+		TaskExecutor, TelemetryEndpoints, TelemetryReconnectPolicy, TransactionPoolOptions,
+		WasmExecutionMethod,
 	},
 	ChainSpec, KeepBlocks, TracingReceiver, TransactionStorageMode,
 };
 use sc_tracing::logging::LoggerBuilder;
-use std::{net::SocketAddr, path::PathBuf};
+use std::{net::SocketAddr, num::NonZeroU32, path::PathBuf, time::Duration};
 
 /// The maximum number of characters for a node name.
 pub(crate) const NODE_NAME_MAX_LENGTH: usize = 64;
@@ -229,6 +230,14 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		})
 	}
 
+	/// Returns `true` if the database should be opened read-only.
+	///
+	/// By default this is retrieved from `DatabaseParams` if it is available. Otherwise its
+	/// `false`.
+	fn database_read_only(&self) -> Result<bool> {
+		Ok(self.database_params().map(|x| x.read_only()).unwrap_or_default())
+	}
+
 	/// Get the state cache size.
 	///
 	/// By default this is retrieved from `ImportParams` if it is available. Otherwise its `0`.
@@ -243,6 +252,13 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(Default::default())
 	}
 
+	/// Returns `true` if the state cache layer should be bypassed.
+	///
+	/// By default this is retrieved from `ImportParams` if it is available. Otherwise its `false`.
+	fn disable_state_cache_layer(&self) -> Result<bool> {
+		Ok(self.import_params().map(|x| x.no_state_cache_layer).unwrap_or_default())
+	}
+
 	/// Get the state pruning mode.
 	///
 	/// By default this is retrieved from `PruningMode` if it is available. Otherwise its
@@ -362,6 +378,53 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(None)
 	}
 
+	/// Get the maximum size of the RPC requests in megabytes (`None` if limited by
+	/// `rpc_max_payload`).
+	///
+	/// By default this is `None`.
+	fn rpc_max_request_size(&self) -> Result<Option<usize>> {
+		Ok(None)
+	}
+
+	/// Get the maximum size of the RPC responses in megabytes (`None` if limited by
+	/// `rpc_max_payload`).
+	///
+	/// By default this is `None`.
+	fn rpc_max_response_size(&self) -> Result<Option<usize>> {
+		Ok(None)
+	}
+
+	/// Get the maximum number of RPC calls per second and connection (`None` if unlimited).
+	///
+	/// By default this is `None`.
+	fn rpc_rate_limit(&self) -> Result<Option<NonZeroU32>> {
+		Ok(None)
+	}
+
+	/// Get the maximum number of active subscriptions per WS RPC connection (`None` if
+	/// unlimited).
+	///
+	/// By default this is `None`.
+	fn rpc_max_subscriptions_per_connection(&self) -> Result<Option<NonZeroU32>> {
+		Ok(None)
+	}
+
+	/// Get the default depth of the confirmed head RPC subscriptions (`None` if they have to
+	/// give one).
+	///
+	/// By default this is `None`.
+	fn rpc_confirmation_depth(&self) -> Result<Option<u32>> {
+		Ok(None)
+	}
+
+	/// Get how long the node is reported as not ready after it started (`None` if it is ready
+	/// right away).
+	///
+	/// By default this is `None`.
+	fn health_warmup(&self) -> Result<Option<Duration>> {
+		Ok(None)
+	}
+
 	/// Get the prometheus configuration (`None` if disabled)
 	///
 	/// By default this is `None`.
@@ -379,6 +442,13 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(chain_spec.telemetry_endpoints().clone())
 	}
 
+	/// Get how the telemetry reconnects to the endpoints.
+	///
+	/// By default this is `TelemetryReconnectPolicy::default()`.
+	fn telemetry_reconnect(&self) -> Result<TelemetryReconnectPolicy> {
+		Ok(Default::default())
+	}
+
 	/// Get the default value for heap pages
 	///
 	/// By default this is `None`.
@@ -471,8 +541,12 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		let config_dir = base_path.config_dir(chain_spec.id());
 		let net_config_dir = config_dir.join(DEFAULT_NETWORK_CONFIG_PATH);
 		let client_id = C::client_id();
-		let database_cache_size = self.database_cache_size()?.unwrap_or(128);
+		let database_cache_size = self.database_cache_size()?;
 		let database = self.database()?.unwrap_or(Database::RocksDb);
+		if let Some(cache_size) = database_cache_size {
+			crate::params::check_database_cache_size(cache_size, database);
+		}
+		let database_cache_size = database_cache_size.unwrap_or(128);
 		let node_key = self.node_key(&net_config_dir)?;
 		let role = self.role(is_dev)?;
 		let max_runtime_instances = self.max_runtime_instances()?.unwrap_or(8);
@@ -481,6 +555,9 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		let telemetry_endpoints = self.telemetry_endpoints(&chain_spec)?;
 
 		let unsafe_pruning = self.import_params().map(|p| p.unsafe_pruning).unwrap_or(false);
+		let state_pruning = self.state_pruning(unsafe_pruning, &role)?;
+		let keep_blocks = self.keep_blocks()?;
+		crate::params::check_keep_blocks(keep_blocks, &state_pruning)?;
 
 		Ok(Configuration {
 			impl_name: C::impl_name(),
@@ -500,10 +577,12 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			keystore_remote,
 			keystore,
 			database: self.database_config(&config_dir, database_cache_size, database)?,
+			database_read_only: self.database_read_only()?,
 			state_cache_size: self.state_cache_size()?,
 			state_cache_child_ratio: self.state_cache_child_ratio()?,
-			state_pruning: self.state_pruning(unsafe_pruning, &role)?,
-			keep_blocks: self.keep_blocks()?,
+			disable_state_cache_layer: self.disable_state_cache_layer()?,
+			state_pruning,
+			keep_blocks,
 			transaction_storage: self.database_transaction_storage()?,
 			wasm_method: self.wasm_method()?,
 			wasm_runtime_overrides: self.wasm_runtime_overrides(),
@@ -515,9 +594,18 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			rpc_ws_max_connections: self.rpc_ws_max_connections()?,
 			rpc_http_threads: self.rpc_http_threads()?,
 			rpc_cors: self.rpc_cors(is_dev)?,
+			rpc_allowed_hosts: Vec::new(),
 			rpc_max_payload: self.rpc_max_payload()?,
+			rpc_max_request_size: self.rpc_max_request_size()?,
+			rpc_max_response_size: self.rpc_max_response_size()?,
+			rpc_rate_limit: self.rpc_rate_limit()?,
+			rpc_max_subscriptions_per_connection: self.rpc_max_subscriptions_per_connection()?,
+			rpc_confirmation_depth: self.rpc_confirmation_depth()?,
+			rpc_modules: None,
+			health_warmup: self.health_warmup()?,
 			prometheus_config: self.prometheus_config(DCV::prometheus_listen_port())?,
 			telemetry_endpoints,
+			telemetry_reconnect: self.telemetry_reconnect()?,
 			default_heap_pages: self.default_heap_pages()?,
 			offchain_worker: self.offchain_worker(&role)?,
 			force_authoring: self.force_authoring()?,
@@ -563,6 +651,16 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 	/// 2. Initializes the logger
 	/// 3. Raises the FD limit
 	fn init<C: SubstrateCli>(&self) -> Result<()> {
+		self.init_with_logger_hook::<C, _>(|_| {})
+	}
+
+	/// Initialize substrate like [`Self::init`], calling `logger_hook` on the [`LoggerBuilder`]
+	/// right before the logger is initialized, e.g. to add a custom tracing layer.
+	fn init_with_logger_hook<C, F>(&self, logger_hook: F) -> Result<()>
+	where
+		C: SubstrateCli,
+		F: FnOnce(&mut LoggerBuilder),
+	{
 		sp_panic_handler::set(&C::support_url(), &C::impl_version());
 
 		let mut logger = LoggerBuilder::new(self.log_filters()?);
@@ -577,6 +675,7 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			logger.with_colors(false);
 		}
 
+		logger_hook(&mut logger);
 		logger.init()?;
 
 		if let Some(new_limit) = fdlimit::raise_fd_limit() {
@@ -228,6 +228,41 @@ pub trait SubstrateCli: Sized {
 		Runner::new(self, command)
 	}
 
+	/// Create a runner for the command provided in argument, like [`Self::create_runner`], but
+	/// calling `logger_hook` on the [`LoggerBuilder`] before the logger is initialized.
+	///
+	/// This is the place to add a custom tracing layer with [`LoggerBuilder::with_custom_layer`],
+	/// as the logger can't be changed after the runner is created.
+	fn create_runner_with_logger_hook<T, F>(
+		&self,
+		command: &T,
+		logger_hook: F,
+	) -> error::Result<Runner<Self>>
+	where
+		T: CliConfiguration,
+		F: FnOnce(&mut LoggerBuilder),
+	{
+		command.init_with_logger_hook::<Self, _>(logger_hook)?;
+		Runner::new(self, command)
+	}
+
+	/// Create a runner for the command provided in argument, like
+	/// [`Self::create_runner_with_logger_hook`], but running on the given tokio runtime instead of
+	/// a default one.
+	fn create_runner_with_tokio_runtime<T, F>(
+		&self,
+		command: &T,
+		tokio_runtime: tokio::runtime::Runtime,
+		logger_hook: F,
+	) -> error::Result<Runner<Self>>
+	where
+		T: CliConfiguration,
+		F: FnOnce(&mut LoggerBuilder),
+	{
+		command.init_with_logger_hook::<Self, _>(logger_hook)?;
+		Runner::with_tokio_runtime(self, command, tokio_runtime)
+	}
+
 	/// Native runtime version.
 	fn native_runtime_version(chain_spec: &Box<dyn ChainSpec>) -> &'static RuntimeVersion;
 }
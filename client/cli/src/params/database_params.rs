@@ -34,7 +34,11 @@ pub struct DatabaseParams {
 	pub database: Option<Database>,
 
 	/// Limit the memory the database cache can use.
-	#[structopt(long = "db-cache", value_name = "MiB")]
+	///
+	/// This is the block cache of RocksDB, which keeps the recently read database blocks in
+	/// memory, separately from the state cache of `--state-cache-size`. ParityDb has no such
+	/// cache. Default is 128 MiB.
+	#[structopt(long = "db-cache", alias = "db-cache-size", value_name = "MiB")]
 	pub database_cache_size: Option<usize>,
 
 	/// Enable storage chain mode
@@ -45,6 +49,14 @@ pub struct DatabaseParams {
 	/// in the block body column.
 	#[structopt(long)]
 	pub storage_chain: bool,
+
+	/// Open the database read-only.
+	///
+	/// The database isn't locked, so this works on the database of a running node, as it was at
+	/// startup. No block is imported or authored, the node only answers RPC queries. Only
+	/// supported by RocksDB.
+	#[structopt(long)]
+	pub read_only: bool,
 }
 
 impl DatabaseParams {
@@ -58,6 +70,11 @@ impl DatabaseParams {
 		self.database_cache_size
 	}
 
+	/// Open the database read-only.
+	pub fn read_only(&self) -> bool {
+		self.read_only
+	}
+
 	/// Transaction storage scheme.
 	pub fn transaction_storage(&self) -> TransactionStorageMode {
 		if self.storage_chain {
@@ -67,3 +84,55 @@ impl DatabaseParams {
 		}
 	}
 }
+
+/// Warn about a `--db-cache` ignored by the database, or larger than the available memory.
+pub(crate) fn check_database_cache_size(cache_size: usize, database: Database) {
+	if let Database::ParityDb = database {
+		log::warn!("ParityDb has no database cache, --db-cache is ignored");
+		return
+	}
+
+	match available_memory() {
+		Some(available) if cache_size as u64 * 1024 * 1024 > available => log::warn!(
+			"The database cache of {} MiB is larger than the {} MiB of available memory",
+			cache_size,
+			available / 1024 / 1024,
+		),
+		_ => {},
+	}
+}
+
+/// The memory available for starting new applications in bytes, as reported by Linux.
+///
+/// `None` where the `/proc/meminfo` file doesn't exist or doesn't report it.
+pub fn available_memory() -> Option<u64> {
+	std::fs::read_to_string("/proc/meminfo")
+		.ok()
+		.and_then(|meminfo| mem_available(&meminfo))
+}
+
+/// The available memory in bytes, from the content of the `/proc/meminfo` file.
+fn mem_available(meminfo: &str) -> Option<u64> {
+	let kib = meminfo
+		.lines()
+		.find_map(|line| line.strip_prefix("MemAvailable:"))?
+		.trim()
+		.strip_suffix("kB")?
+		.trim()
+		.parse::<u64>()
+		.ok()?;
+	Some(kib * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn reads_the_available_memory() {
+		let meminfo = "MemTotal:       16314500 kB\nMemFree:         1214416 kB\n\
+		               MemAvailable:    9508280 kB\nBuffers:          633184 kB\n";
+		assert_eq!(mem_available(meminfo), Some(9508280 * 1024));
+		assert_eq!(mem_available("MemTotal:       16314500 kB\n"), None);
+	}
+}
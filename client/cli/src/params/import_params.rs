@@ -76,6 +76,13 @@ pub struct ImportParams {
 	/// Specify the state cache size.
 	#[structopt(long = "state-cache-size", value_name = "Bytes", default_value = "67108864")]
 	pub state_cache_size: usize,
+
+	/// Read the state straight from the database, bypassing the state cache.
+	///
+	/// Slows down block import and RPC queries, only meant for ruling out the cache when
+	/// debugging state inconsistencies.
+	#[structopt(long = "no-state-cache-layer")]
+	pub no_state_cache_layer: bool,
 }
 
 impl ImportParams {
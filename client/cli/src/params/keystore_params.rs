@@ -57,6 +57,7 @@ pub struct KeystoreParams {
 	/// File that contains the password used by the keystore.
 	#[structopt(
 		long = "password-filename",
+		alias = "keystore-password-file",
 		value_name = "PATH",
 		parse(from_os_str),
 		conflicts_with_all = &[ "password-interactive", "password" ]
@@ -83,7 +84,9 @@ impl KeystoreParams {
 			#[cfg(target_os = "unknown")]
 			None
 		} else if let Some(ref file) = self.password_filename {
-			let password = fs::read_to_string(file).map_err(|e| format!("{}", e))?;
+			let password = fs::read_to_string(file).map_err(|e| {
+				format!("Failed to read the keystore password file `{}`: {}", file.display(), e)
+			})?;
 			Some(SecretString::new(password))
 		} else {
 			self.password.clone()
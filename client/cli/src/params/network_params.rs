@@ -19,7 +19,8 @@
 use crate::{arg_enums::SyncMode, params::node_key_params::NodeKeyParams};
 use sc_network::{
 	config::{
-		NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode, SetConfig, TransportConfig,
+		NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode, SetConfig,
+		TransactionPropagation, TransportConfig, DEFAULT_FAST_SYNC_PIVOT_OFFSET,
 	},
 	multiaddr::Protocol,
 };
@@ -104,6 +105,18 @@ pub struct NetworkParams {
 	#[structopt(long = "max-parallel-downloads", value_name = "COUNT", default_value = "5")]
 	pub max_parallel_downloads: u32,
 
+	/// Maximum number of warp sync proofs generated at a time for the peers.
+	///
+	/// Up to 16 requests above the limit wait for a proof to be generated, the next ones are
+	/// refused and lower the reputation of their peer, which protects the nodes serving many
+	/// joining peers. By default, the proofs are generated one at a time.
+	#[structopt(
+		long,
+		value_name = "COUNT",
+		parse(try_from_str = parse_max_concurrent_warp_requests)
+	)]
+	pub max_concurrent_warp_requests: Option<usize>,
+
 	#[allow(missing_docs)]
 	#[structopt(flatten)]
 	pub node_key_params: NodeKeyParams,
@@ -136,6 +149,48 @@ pub struct NetworkParams {
 	/// - `FastUnsafe`: Same as `Fast`, but skip downloading state proofs.
 	#[structopt(long, value_name = "SYNC_MODE", default_value = "Full")]
 	pub sync: SyncMode,
+
+	/// In fast sync, download the state of the first finalized block that is at most this many
+	/// blocks behind the median of the best blocks of the peers.
+	///
+	/// Finalized blocks lag behind the best block, so this has to cover the usual finality
+	/// distance of the chain for the state sync to start. It can't exceed 256, as the peers
+	/// prune the state of older blocks by default. Defaults to 8.
+	#[structopt(long, value_name = "BLOCKS", parse(try_from_str = parse_pivot_offset))]
+	pub fast_sync_pivot_offset: Option<u32>,
+
+	/// Don't gossip any transaction to the peers.
+	///
+	/// The transactions received from the peers are still imported into the pool.
+	#[structopt(long)]
+	pub no_tx_gossip: bool,
+
+	/// Only gossip the transactions that weren't received from the peers, e.g. the ones
+	/// submitted through the RPC.
+	#[structopt(long, conflicts_with = "no-tx-gossip")]
+	pub tx_gossip_local_only: bool,
+}
+
+/// The largest `--fast-sync-pivot-offset`, the number of blocks whose state is kept by default.
+const MAX_FAST_SYNC_PIVOT_OFFSET: u32 = 256;
+
+fn parse_pivot_offset(s: &str) -> Result<u32, String> {
+	match s.parse::<u32>().map_err(|e| e.to_string())? {
+		0 => Err("Finalized blocks lag behind the best block, the offset can't be 0".into()),
+		offset if offset > MAX_FAST_SYNC_PIVOT_OFFSET => Err(format!(
+			"The peers only keep the state of their last {} blocks by default, the offset can't \
+			 exceed it",
+			MAX_FAST_SYNC_PIVOT_OFFSET,
+		)),
+		offset => Ok(offset),
+	}
+}
+
+fn parse_max_concurrent_warp_requests(s: &str) -> Result<usize, String> {
+	match s.parse::<usize>().map_err(|e| e.to_string())? {
+		0 => Err("At least one warp sync proof must be generated at a time".into()),
+		limit => Ok(limit),
+	}
 }
 
 impl NetworkParams {
@@ -226,10 +281,42 @@ impl NetworkParams {
 			max_parallel_downloads: self.max_parallel_downloads,
 			enable_dht_random_walk: !self.reserved_only,
 			allow_non_globals_in_dht,
+			reputation: Default::default(),
+			peer_filter: Default::default(),
+			transaction_propagation: if self.no_tx_gossip {
+				TransactionPropagation::None
+			} else if self.tx_gossip_local_only {
+				TransactionPropagation::Local
+			} else {
+				TransactionPropagation::All
+			},
 			kademlia_disjoint_query_paths: self.kademlia_disjoint_query_paths,
+			max_concurrent_warp_requests: self.max_concurrent_warp_requests,
 			yamux_window_size: None,
 			ipfs_server: self.ipfs_server,
 			sync_mode: self.sync.into(),
+			fast_sync_pivot_offset: self
+				.fast_sync_pivot_offset
+				.unwrap_or(DEFAULT_FAST_SYNC_PIVOT_OFFSET),
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fast_sync_pivot_offset_is_bounded() {
+		let parse = |args: &[&str]| {
+			NetworkParams::from_iter_safe(std::iter::once("test").chain(args.iter().copied()))
+				.map(|params| params.fast_sync_pivot_offset)
+		};
+
+		assert_eq!(parse(&[]).unwrap(), None);
+		assert_eq!(parse(&["--fast-sync-pivot-offset", "1"]).unwrap(), Some(1));
+		assert_eq!(parse(&["--fast-sync-pivot-offset", "256"]).unwrap(), Some(256));
+		assert!(parse(&["--fast-sync-pivot-offset", "0"]).is_err());
+		assert!(parse(&["--fast-sync-pivot-offset", "257"]).is_err());
+	}
+}
@@ -50,6 +50,20 @@ pub struct OffchainWorkerParams {
 	/// DB during block import.
 	#[structopt(long = "enable-offchain-indexing", value_name = "ENABLE_OFFCHAIN_INDEXING")]
 	pub indexing_enabled: bool,
+
+	/// Number of threads running the offchain workers.
+	///
+	/// The offchain workers run on a thread pool of their own, default is one thread per CPU.
+	/// Fewer threads keep offchain-heavy runtimes from competing with the rest of the node.
+	#[structopt(long, value_name = "COUNT")]
+	pub offchain_worker_threads: Option<usize>,
+
+	/// Maximum number of offchain HTTP requests in progress at the same time.
+	///
+	/// The requests above the limit wait for a previous one to get a response instead of failing,
+	/// which keeps offchain workers from overwhelming external services. Unlimited by default.
+	#[structopt(long, value_name = "COUNT")]
+	pub offchain_http_concurrency: Option<usize>,
 }
 
 impl OffchainWorkerParams {
@@ -62,7 +76,20 @@ impl OffchainWorkerParams {
 			(OffchainWorkerEnabled::WhenValidating, _) => false,
 		};
 
+		if self.offchain_worker_threads == Some(0) {
+			return Err(error::Error::Input("--offchain-worker-threads must be at least 1".into()))
+		}
+		if self.offchain_http_concurrency == Some(0) {
+			return Err(error::Error::Input("--offchain-http-concurrency must be at least 1".into()))
+		}
+
 		let indexing_enabled = self.indexing_enabled;
-		Ok(OffchainWorkerConfig { enabled, indexing_enabled })
+		Ok(OffchainWorkerConfig {
+			enabled,
+			indexing_enabled,
+			threads: self.offchain_worker_threads,
+			http_concurrency: self.offchain_http_concurrency,
+			restart_policy: None,
+		})
 	}
 }
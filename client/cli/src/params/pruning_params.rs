@@ -32,7 +32,9 @@ pub struct PruningParams {
 	pub pruning: Option<String>,
 	/// Specify the number of finalized blocks to keep in the database.
 	///
-	/// Default is to keep all blocks.
+	/// Only the bodies of the finalized blocks below the last `COUNT` ones are pruned, the headers
+	/// are always kept. It can't be less than the number of blocks whose state is kept by
+	/// `--pruning`. Default is to keep all blocks.
 	#[structopt(long, value_name = "COUNT")]
 	pub keep_blocks: Option<u32>,
 }
@@ -67,8 +69,53 @@ impl PruningParams {
 	/// Get the block pruning value from the parameters
 	pub fn keep_blocks(&self) -> error::Result<KeepBlocks> {
 		Ok(match self.keep_blocks {
+			Some(0) =>
+				return Err(error::Error::Input("--keep-blocks must be at least 1".to_string())),
 			Some(n) => KeepBlocks::Some(n),
 			None => KeepBlocks::All,
 		})
 	}
 }
+
+/// Check that the bodies of the blocks whose state is kept by `state_pruning` aren't pruned.
+///
+/// Those are the blocks the node can still re-execute or serve to its peers, which needs their
+/// bodies.
+pub(crate) fn check_keep_blocks(
+	keep_blocks: KeepBlocks,
+	state_pruning: &PruningMode,
+) -> error::Result<()> {
+	let state_blocks = match state_pruning {
+		PruningMode::Constrained(constraints) => constraints.max_blocks.unwrap_or(0),
+		PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => return Ok(()),
+	};
+
+	match keep_blocks {
+		KeepBlocks::Some(n) if n < state_blocks => Err(error::Error::Input(format!(
+			"--keep-blocks {} is less than the {} blocks whose state is kept, keep at least as \
+			many blocks or lower --pruning",
+			n, state_blocks,
+		))),
+		_ => Ok(()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn keep_blocks_covers_the_state_pruning_window() {
+		let params = |keep_blocks| PruningParams { pruning: None, keep_blocks };
+
+		assert!(params(Some(0)).keep_blocks().is_err());
+		assert!(matches!(params(Some(1)).keep_blocks(), Ok(KeepBlocks::Some(1))));
+		assert!(matches!(params(None).keep_blocks(), Ok(KeepBlocks::All)));
+
+		let state_pruning = PruningMode::keep_blocks(256);
+		assert!(check_keep_blocks(KeepBlocks::Some(255), &state_pruning).is_err());
+		assert!(check_keep_blocks(KeepBlocks::Some(256), &state_pruning).is_ok());
+		assert!(check_keep_blocks(KeepBlocks::All, &state_pruning).is_ok());
+		assert!(check_keep_blocks(KeepBlocks::Some(1), &PruningMode::ArchiveAll).is_ok());
+	}
+}
@@ -73,7 +73,14 @@ where
 
 /// Build a tokio runtime with all features
 pub fn build_runtime() -> std::result::Result<tokio::runtime::Runtime, std::io::Error> {
-	tokio::runtime::Builder::new_multi_thread()
+	runtime_builder().build()
+}
+
+/// Create the builder of a tokio runtime with all features, which can be customized further (e.g.
+/// to size its thread pools) before building the runtime.
+pub fn runtime_builder() -> tokio::runtime::Builder {
+	let mut builder = tokio::runtime::Builder::new_multi_thread();
+	builder
 		.on_thread_start(|| {
 			TOKIO_THREADS_ALIVE.inc();
 			TOKIO_THREADS_TOTAL.inc();
@@ -81,8 +88,8 @@ pub fn build_runtime() -> std::result::Result<tokio::runtime::Runtime, std::io::
 		.on_thread_stop(|| {
 			TOKIO_THREADS_ALIVE.dec();
 		})
-		.enable_all()
-		.build()
+		.enable_all();
+	builder
 }
 
 fn run_until_exit<F, E>(
@@ -113,7 +120,16 @@ pub struct Runner<C: SubstrateCli> {
 impl<C: SubstrateCli> Runner<C> {
 	/// Create a new runtime with the command provided in argument
 	pub fn new<T: CliConfiguration>(cli: &C, command: &T) -> Result<Runner<C>> {
-		let tokio_runtime = build_runtime()?;
+		Self::with_tokio_runtime(cli, command, build_runtime()?)
+	}
+
+	/// Create a new runtime with the command provided in argument, running on the given tokio
+	/// runtime.
+	pub fn with_tokio_runtime<T: CliConfiguration>(
+		cli: &C,
+		command: &T,
+		tokio_runtime: tokio::runtime::Runtime,
+	) -> Result<Runner<C>> {
 		let runtime_handle = tokio_runtime.handle().clone();
 
 		let task_executor = move |fut, task_type| match task_type {
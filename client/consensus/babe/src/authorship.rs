@@ -194,6 +194,24 @@ pub fn claim_slot(
 	claim_slot_using_keys(slot, epoch, keystore, &authorities)
 }
 
+/// Like `claim_slot`, but only tries to claim the slot with `key`, and fails if `key` isn't one
+/// of the authorities of the epoch.
+pub fn claim_slot_with_key(
+	slot: Slot,
+	epoch: &Epoch,
+	keystore: &SyncCryptoStorePtr,
+	key: &AuthorityId,
+) -> Option<(PreDigest, AuthorityId)> {
+	let authorities = epoch
+		.authorities
+		.iter()
+		.enumerate()
+		.filter(|(_, a)| a.0 == *key)
+		.map(|(index, a)| (a.0.clone(), index))
+		.collect::<Vec<_>>();
+	claim_slot_using_keys(slot, epoch, keystore, &authorities)
+}
+
 /// Like `claim_slot`, but allows passing an explicit set of key pairs. Useful if we intend
 /// to make repeated calls for different slots using the same key pairs.
 pub fn claim_slot_using_keys(
@@ -309,4 +327,38 @@ mod tests {
 		epoch.authorities.push((valid_public_key.clone().into(), 10));
 		assert_eq!(claim_slot(10.into(), &epoch, &keystore).unwrap().1, valid_public_key.into());
 	}
+
+	#[test]
+	fn claim_slot_with_key_only_uses_that_key() {
+		let keystore: SyncCryptoStorePtr = Arc::new(LocalKeystore::in_memory());
+		let mut keys = (0..2).map(|_| {
+			AuthorityId::from(
+				SyncCryptoStore::sr25519_generate_new(&*keystore, AuthorityId::ID, None).unwrap(),
+			)
+		});
+		let (other, key) = (keys.next().unwrap(), keys.next().unwrap());
+
+		let mut epoch = Epoch {
+			epoch_index: 10,
+			start_slot: 0.into(),
+			duration: 20,
+			authorities: vec![(other.clone(), 1), (key.clone(), 1)],
+			randomness: Default::default(),
+			config: BabeEpochConfiguration {
+				c: (0, 10),
+				allowed_slots: AllowedSlots::PrimaryAndSecondaryPlainSlots,
+			},
+		};
+
+		let claims = (0..20u64)
+			.filter_map(|slot| claim_slot_with_key(slot.into(), &epoch, &keystore, &key))
+			.collect::<Vec<_>>();
+		assert!(!claims.is_empty());
+		assert!(claims.iter().all(|(_, claimed)| *claimed == key));
+		// The slots assigned to the other local key aren't claimed.
+		assert!(claims.len() < 20);
+
+		epoch.authorities.pop();
+		assert!(claim_slot_with_key(10.into(), &epoch, &keystore, &key).is_none());
+	}
 }
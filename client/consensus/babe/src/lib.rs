@@ -440,6 +440,11 @@ pub struct BabeParams<B: BlockT, C, SC, E, I, SO, L, CIDP, BS, CAW> {
 
 	/// Handle use to report telemetries.
 	pub telemetry: Option<TelemetryHandle>,
+
+	/// Only author blocks with this key, instead of any authority key in the keystore.
+	///
+	/// Starting the worker fails if the key isn't in the keystore.
+	pub authoring_key: Option<AuthorityId>,
 }
 
 /// Start the babe worker.
@@ -460,6 +465,7 @@ pub fn start_babe<B, C, SC, E, I, SO, CIDP, BS, CAW, L, Error>(
 		block_proposal_slot_portion,
 		max_block_proposal_slot_portion,
 		telemetry,
+		authoring_key,
 	}: BabeParams<B, C, SC, E, I, SO, L, CIDP, BS, CAW>,
 ) -> Result<BabeWorker<B>, sp_consensus::Error>
 where
@@ -491,6 +497,16 @@ where
 {
 	const HANDLE_BUFFER_SIZE: usize = 1024;
 
+	if let Some(key) = &authoring_key {
+		if !SyncCryptoStore::has_keys(&*keystore, &[(key.to_raw_vec(), AuthorityId::ID)]) {
+			return Err(sp_consensus::Error::CannotSign(
+				key.to_raw_vec(),
+				"BABE authoring key not found in keystore".into(),
+			))
+		}
+		info!(target: "babe", "👶 Authoring blocks with key {}", key);
+	}
+
 	let config = babe_link.config;
 	let slot_notification_sinks = Arc::new(Mutex::new(Vec::new()));
 
@@ -509,6 +525,7 @@ where
 		block_proposal_slot_portion,
 		max_block_proposal_slot_portion,
 		telemetry,
+		authoring_key,
 	};
 
 	info!(target: "babe", "👶 Starting BABE Authorship worker");
@@ -670,6 +687,7 @@ struct BabeSlotWorker<B: BlockT, C, E, I, SO, L, BS> {
 	block_proposal_slot_portion: SlotProportion,
 	max_block_proposal_slot_portion: Option<SlotProportion>,
 	telemetry: Option<TelemetryHandle>,
+	authoring_key: Option<AuthorityId>,
 }
 
 impl<B, C, E, I, Error, SO, L, BS> sc_consensus_slots::SimpleSlotWorker<B>
@@ -737,14 +755,13 @@ where
 		epoch_descriptor: &ViableEpochDescriptor<B::Hash, NumberFor<B>, Epoch>,
 	) -> Option<Self::Claim> {
 		debug!(target: "babe", "Attempting to claim slot {}", slot);
-		let s = authorship::claim_slot(
-			slot,
-			self.epoch_changes
-				.shared_data()
-				.viable_epoch(&epoch_descriptor, |slot| Epoch::genesis(&self.config, slot))?
-				.as_ref(),
-			&self.keystore,
-		);
+		let epoch_changes = self.epoch_changes.shared_data();
+		let epoch = epoch_changes
+			.viable_epoch(&epoch_descriptor, |slot| Epoch::genesis(&self.config, slot))?;
+		let s = match &self.authoring_key {
+			Some(key) => authorship::claim_slot_with_key(slot, epoch.as_ref(), &self.keystore, key),
+			None => authorship::claim_slot(slot, epoch.as_ref(), &self.keystore),
+		};
 
 		if s.is_some() {
 			debug!(target: "babe", "Claimed slot {}", slot);
@@ -1760,6 +1777,88 @@ where
 	CAW: CanAuthorWith<Block> + Send + Sync + 'static,
 	CIDP: CreateInherentDataProviders<Block, ()> + Send + Sync + 'static,
 	CIDP::InherentDataProviders: InherentDataProviderExt + Send + Sync,
+{
+	import_queue_with_verifier(
+		ImportQueueParams {
+			babe_link,
+			block_import,
+			justification_import,
+			client,
+			select_chain,
+			create_inherent_data_providers,
+			spawner,
+			registry,
+			can_author_with,
+			telemetry,
+		},
+		|verifier| verifier,
+	)
+}
+
+/// Parameters of [`import_queue_with_verifier`], the ones of [`import_queue`].
+pub struct ImportQueueParams<'a, Block: BlockT, Inner, Client, SelectChain, CAW, CIDP, Spawn> {
+	/// The BABE link, from [`block_import`].
+	pub babe_link: BabeLink<Block>,
+	/// The block import the verified blocks are passed to.
+	pub block_import: Inner,
+	/// The import of the justifications.
+	pub justification_import: Option<BoxJustificationImport<Block>>,
+	/// The client.
+	pub client: Arc<Client>,
+	/// The SelectChain Strategy.
+	pub select_chain: SelectChain,
+	/// Something that can create the inherent data providers checking the blocks.
+	pub create_inherent_data_providers: CIDP,
+	/// The spawner of the import queue's essential task.
+	pub spawner: &'a Spawn,
+	/// The registry of the import queue's metrics.
+	pub registry: Option<&'a Registry>,
+	/// Checks if the current native implementation can author with a runtime at a given block.
+	pub can_author_with: CAW,
+	/// Handle use to report telemetries.
+	pub telemetry: Option<TelemetryHandle>,
+}
+
+/// Like [`import_queue`], but passing the [`BabeVerifier`] through `wrap_verifier`, which lets the
+/// caller add its own checks to the verification of the blocks.
+pub fn import_queue_with_verifier<Block: BlockT, Client, SelectChain, Inner, CAW, CIDP, Spawn, V>(
+	ImportQueueParams {
+		babe_link,
+		block_import,
+		justification_import,
+		client,
+		select_chain,
+		create_inherent_data_providers,
+		spawner,
+		registry,
+		can_author_with,
+		telemetry,
+	}: ImportQueueParams<'_, Block, Inner, Client, SelectChain, CAW, CIDP, Spawn>,
+	wrap_verifier: impl FnOnce(BabeVerifier<Block, Client, SelectChain, CAW, CIDP>) -> V,
+) -> ClientResult<DefaultImportQueue<Block, Client>>
+where
+	Spawn: sp_core::traits::SpawnEssentialNamed,
+	Inner: BlockImport<
+			Block,
+			Error = ConsensusError,
+			Transaction = sp_api::TransactionFor<Client, Block>,
+		> + Send
+		+ Sync
+		+ 'static,
+	Client: ProvideRuntimeApi<Block>
+		+ ProvideCache<Block>
+		+ HeaderBackend<Block>
+		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
+		+ AuxStore
+		+ Send
+		+ Sync
+		+ 'static,
+	Client::Api: BlockBuilderApi<Block> + BabeApi<Block> + ApiExt<Block>,
+	SelectChain: sp_consensus::SelectChain<Block> + 'static,
+	CAW: CanAuthorWith<Block> + Send + Sync + 'static,
+	CIDP: CreateInherentDataProviders<Block, ()> + Send + Sync + 'static,
+	CIDP::InherentDataProviders: InherentDataProviderExt + Send + Sync,
+	V: Verifier<Block> + 'static,
 {
 	let verifier = BabeVerifier {
 		select_chain,
@@ -1771,5 +1870,11 @@ where
 		client,
 	};
 
-	Ok(BasicQueue::new(verifier, Box::new(block_import), justification_import, spawner, registry))
+	Ok(BasicQueue::new(
+		wrap_verifier(verifier),
+		Box::new(block_import),
+		justification_import,
+		spawner,
+		registry,
+	))
 }
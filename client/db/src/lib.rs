@@ -100,6 +100,7 @@ use sp_trie::{prefixed_key, MemoryDB, PrefixedMemoryDB};
 // Re-export the Database trait so that one can pass an implementation of it.
 pub use sc_state_db::PruningMode;
 pub use sp_database::Database;
+pub use utils::compact_database;
 
 #[cfg(any(feature = "with-kvdb-rocksdb", test))]
 pub use bench::BenchmarkingState;
@@ -302,6 +303,10 @@ pub struct DatabaseSettings {
 	pub keep_blocks: KeepBlocks,
 	/// Block body/Transaction storage scheme.
 	pub transaction_storage: TransactionStorageMode,
+	/// Read the state straight from the database, bypassing the state cache.
+	pub disable_state_cache_layer: bool,
+	/// Open the database read-only, without locking it and rejecting every change.
+	pub read_only: bool,
 }
 
 /// Block pruning settings.
@@ -355,6 +360,18 @@ pub enum DatabaseSource {
 }
 
 impl DatabaseSource {
+	/// A database kept in memory, for tests that don't need anything written to disk.
+	#[cfg(any(
+		feature = "with-kvdb-rocksdb",
+		feature = "with-parity-db",
+		feature = "test-helpers",
+		test
+	))]
+	pub fn in_memory() -> Self {
+		let db = kvdb_memorydb::create(crate::utils::NUM_COLUMNS);
+		DatabaseSource::Custom(sp_database::as_database(db))
+	}
+
 	/// Return dabase path for databases that are on the disk.
 	pub fn path(&self) -> Option<&Path> {
 		match self {
@@ -1076,6 +1093,7 @@ pub struct Backend<Block: BlockT> {
 	blockchain: BlockchainDb<Block>,
 	canonicalization_delay: u64,
 	shared_cache: SharedCache<Block>,
+	disable_state_cache_layer: bool,
 	import_lock: Arc<RwLock<()>>,
 	is_archive: bool,
 	keep_blocks: KeepBlocks,
@@ -1120,6 +1138,8 @@ impl<Block: BlockT> Backend<Block> {
 			source: DatabaseSource::Custom(db),
 			keep_blocks: KeepBlocks::Some(keep_blocks),
 			transaction_storage,
+			disable_state_cache_layer: false,
+			read_only: false,
 		};
 
 		Self::new(db_setting, canonicalization_delay).expect("failed to create test-db")
@@ -1165,6 +1185,7 @@ impl<Block: BlockT> Backend<Block> {
 				config.state_cache_size,
 				config.state_cache_child_ratio.unwrap_or(DEFAULT_CHILD_RATIO),
 			),
+			disable_state_cache_layer: config.disable_state_cache_layer,
 			import_lock: Default::default(),
 			is_archive: is_archive_pruning,
 			io_stats: FrozenForDuration::new(std::time::Duration::from_secs(1)),
@@ -1837,11 +1858,25 @@ impl<Block: BlockT> Backend<Block> {
 		Ok(())
 	}
 
+	fn caching_state(
+		&self,
+		state: RefTrackingState<Block>,
+		parent_hash: Option<Block::Hash>,
+	) -> CachingState<RefTrackingState<Block>, Block> {
+		if self.disable_state_cache_layer {
+			let mut caching_state = CachingState::new(state, self.shared_cache.clone(), None);
+			caching_state.disable_cache();
+			caching_state
+		} else {
+			CachingState::new(state, self.shared_cache.clone(), parent_hash)
+		}
+	}
+
 	fn empty_state(&self) -> ClientResult<SyncingCachingState<RefTrackingState<Block>, Block>> {
 		let root = EmptyStorage::<Block>::new().0; // Empty trie
 		let db_state = DbState::<Block>::new(self.storage.clone(), root);
 		let state = RefTrackingState::new(db_state, self.storage.clone(), None);
-		let caching_state = CachingState::new(state, self.shared_cache.clone(), None);
+		let caching_state = self.caching_state(state, None);
 		Ok(SyncingCachingState::new(
 			caching_state,
 			self.state_usage.clone(),
@@ -2258,19 +2293,13 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 	}
 
 	fn remove_leaf_block(&self, hash: &Block::Hash) -> ClientResult<()> {
-		let best_hash = self.blockchain.info().best_hash;
+		let info = self.blockchain.info();
 
-		if best_hash == *hash {
+		if info.best_hash == *hash {
 			return Err(sp_blockchain::Error::Backend(format!("Can't remove best block {:?}", hash)))
 		}
 
 		let hdr = self.blockchain.header_metadata(hash.clone())?;
-		if !self.have_state_at(&hash, hdr.number) {
-			return Err(sp_blockchain::Error::UnknownBlock(format!(
-				"State already discarded for {:?}",
-				hash
-			)))
-		}
 
 		let mut leaves = self.blockchain.leaves.write();
 		if !leaves.contains(hdr.number, *hash) {
@@ -2281,16 +2310,61 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 		}
 
 		let mut transaction = Transaction::new();
+		// The state of blocks on stale forks is discarded by the state db once a sibling is
+		// finalized, there is nothing left to remove then.
 		if let Some(commit) = self.storage.state_db.remove(hash) {
 			apply_state_commit(&mut transaction, commit);
 		}
+		let id = BlockId::hash(*hash);
+		self.prune_block(&mut transaction, id)?;
+		for column in [columns::HEADER, columns::JUSTIFICATIONS] {
+			utils::remove_from_db(
+				&mut transaction,
+				&*self.storage.db,
+				columns::KEY_LOOKUP,
+				column,
+				id,
+			)?;
+		}
 		transaction.remove(columns::KEY_LOOKUP, hash.as_ref());
-		let changes_trie_cache_ops = self
-			.changes_tries_storage
-			.revert(&mut transaction, &cache::ComplexBlockId::new(*hash, hdr.number))?;
+		// The changes trie cache abandoned the forks below the finalized block when finalizing it.
+		let changes_trie_cache_ops = if hdr.number > info.finalized_number {
+			Some(
+				self.changes_tries_storage
+					.revert(&mut transaction, &cache::ComplexBlockId::new(*hash, hdr.number))?,
+			)
+		} else {
+			None
+		};
 
-		self.changes_tries_storage.post_commit(Some(changes_trie_cache_ops));
-		leaves.revert(hash.clone(), hdr.number);
+		// The parent becomes a leaf again, unless it has other children.
+		let siblings: Vec<_> = self
+			.blockchain
+			.children(hdr.parent)?
+			.into_iter()
+			.filter(|child| child != hash)
+			.collect();
+		let parent_leaf = if siblings.is_empty() {
+			children::remove_children(
+				&mut transaction,
+				columns::META,
+				meta_keys::CHILDREN_PREFIX,
+				hdr.parent,
+			);
+			Some(hdr.parent)
+		} else {
+			children::write_children(
+				&mut transaction,
+				columns::META,
+				meta_keys::CHILDREN_PREFIX,
+				hdr.parent,
+				siblings,
+			);
+			None
+		};
+
+		self.changes_tries_storage.post_commit(changes_trie_cache_ops);
+		leaves.remove(*hash, hdr.number, parent_leaf);
 		leaves.prepare_transaction(&mut transaction, columns::META, meta_keys::LEAF_PREFIX);
 		self.storage.db.commit(transaction)?;
 		self.blockchain().remove_header_metadata(*hash);
@@ -2314,7 +2388,7 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 				let root = genesis_state.root.clone();
 				let db_state = DbState::<Block>::new(genesis_state.clone(), root);
 				let state = RefTrackingState::new(db_state, self.storage.clone(), None);
-				let caching_state = CachingState::new(state, self.shared_cache.clone(), None);
+				let caching_state = self.caching_state(state, None);
 				let mut state = SyncingCachingState::new(
 					caching_state,
 					self.state_usage.clone(),
@@ -2346,8 +2420,7 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 					let db_state = DbState::<Block>::new(self.storage.clone(), root);
 					let state =
 						RefTrackingState::new(db_state, self.storage.clone(), Some(hash.clone()));
-					let caching_state =
-						CachingState::new(state, self.shared_cache.clone(), Some(hash));
+					let caching_state = self.caching_state(state, Some(hash));
 					Ok(SyncingCachingState::new(
 						caching_state,
 						self.state_usage.clone(),
@@ -2528,6 +2601,8 @@ pub(crate) mod tests {
 				source: DatabaseSource::Custom(backing),
 				keep_blocks: KeepBlocks::All,
 				transaction_storage: TransactionStorageMode::BlockBody,
+				disable_state_cache_layer: false,
+				read_only: false,
 			},
 			0,
 		)
@@ -3391,6 +3466,43 @@ pub(crate) mod tests {
 		backend.remove_leaf_block(&prev_hash).unwrap();
 		assert_eq!(None, backend.blockchain().header(BlockId::hash(prev_hash.clone())).unwrap());
 		assert!(!backend.have_state_at(&prev_hash, 1));
+		assert_eq!(backend.blockchain().leaves().unwrap(), vec![best_hash]);
+		assert_eq!(backend.blockchain().children(blocks[0]).unwrap(), vec![best_hash]);
+	}
+
+	#[test]
+	fn remove_leaf_block_makes_the_parent_a_leaf() {
+		let backend: Backend<Block> = Backend::new_test(10, 10);
+		let block0 = insert_header(&backend, 0, Default::default(), None, Default::default());
+		let block1 = insert_header(&backend, 1, block0, None, Default::default());
+		let fork2 = insert_header(&backend, 2, block1, None, H256::from_low_u64_be(1));
+		let fork3 = insert_header(&backend, 3, fork2, None, H256::from_low_u64_be(1));
+		let block2 = insert_header(&backend, 2, block1, None, H256::from_low_u64_be(2));
+		let block3 = insert_header(&backend, 3, block2, None, H256::from_low_u64_be(2));
+		let block4 = insert_header(&backend, 4, block3, None, H256::from_low_u64_be(2));
+		assert_eq!(backend.blockchain().info().best_hash, block4);
+
+		// Only the leaves can be removed.
+		assert!(backend.remove_leaf_block(&fork2).is_err());
+
+		backend.remove_leaf_block(&fork3).unwrap();
+		assert_eq!(backend.blockchain().header(BlockId::hash(fork3)).unwrap(), None);
+		assert_eq!(backend.blockchain().leaves().unwrap(), vec![block4, fork2]);
+		assert!(backend.blockchain().children(fork2).unwrap().is_empty());
+
+		backend.remove_leaf_block(&fork2).unwrap();
+		assert_eq!(backend.blockchain().header(BlockId::hash(fork2)).unwrap(), None);
+		assert_eq!(backend.blockchain().leaves().unwrap(), vec![block4]);
+		assert_eq!(backend.blockchain().children(block1).unwrap(), vec![block2]);
+
+		// The removal survives a restart, the leaves are read back from the database.
+		let leaves = LeafSet::<H256, u64>::read_from_db(
+			&*backend.storage.db,
+			columns::META,
+			meta_keys::LEAF_PREFIX,
+		)
+		.unwrap();
+		assert_eq!(leaves.hashes(), vec![block4]);
 	}
 
 	#[test]
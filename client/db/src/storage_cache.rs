@@ -308,6 +308,8 @@ pub struct CachingState<S, B: BlockT> {
 	state: S,
 	/// Cache data.
 	cache: CacheChanges<B>,
+	/// Read straight from the backing state, see [`CachingState::disable_cache`].
+	disabled: bool,
 }
 
 impl<S, B: BlockT> std::fmt::Debug for CachingState<S, B> {
@@ -460,9 +462,18 @@ impl<S: StateBackend<HashFor<B>>, B: BlockT> CachingState<S, B> {
 				}),
 				parent_hash,
 			},
+			disabled: false,
 		}
 	}
 
+	/// Read straight from the backing state, without looking up or filling the local and shared
+	/// caches.
+	///
+	/// Meant for ruling out the cache when debugging state inconsistencies.
+	pub(crate) fn disable_cache(&mut self) {
+		self.disabled = true;
+	}
+
 	/// Check if the key can be returned from cache by matching current block parent hash against
 	/// canonical state and filtering out entries modified in later blocks.
 	fn is_allowed(
@@ -523,6 +534,11 @@ impl<S: StateBackend<HashFor<B>>, B: BlockT> StateBackend<HashFor<B>> for Cachin
 	type TrieBackendStorage = S::TrieBackendStorage;
 
 	fn storage(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+		if self.disabled {
+			let value = self.state.storage(key)?;
+			self.usage.tally_key_read(key, value.as_ref(), false);
+			return Ok(value)
+		}
 		let local_cache = self.cache.local_cache.upgradable_read();
 		// Note that local cache makes that lru is not refreshed
 		if let Some(entry) = local_cache.storage.get(key).cloned() {
@@ -552,6 +568,9 @@ impl<S: StateBackend<HashFor<B>>, B: BlockT> StateBackend<HashFor<B>> for Cachin
 	}
 
 	fn storage_hash(&self, key: &[u8]) -> Result<Option<B::Hash>, Self::Error> {
+		if self.disabled {
+			return self.state.storage_hash(key)
+		}
 		let local_cache = self.cache.local_cache.upgradable_read();
 		if let Some(entry) = local_cache.hashes.get(key).cloned() {
 			trace!("Found hash in local cache: {:?}", HexDisplay::from(&key));
@@ -581,6 +600,10 @@ impl<S: StateBackend<HashFor<B>>, B: BlockT> StateBackend<HashFor<B>> for Cachin
 		key: &[u8],
 	) -> Result<Option<Vec<u8>>, Self::Error> {
 		let key = (child_info.storage_key().to_vec(), key.to_vec());
+		if self.disabled {
+			let value = self.state.child_storage(child_info, &key.1[..])?;
+			return Ok(self.usage.tally_child_key_read(&key, value, false))
+		}
 		let local_cache = self.cache.local_cache.upgradable_read();
 		if let Some(entry) = local_cache.child_storage.get(&key).cloned() {
 			trace!("Found in local cache: {:?}", key);
@@ -1242,6 +1265,39 @@ mod tests {
 		assert_eq!(shared.read().used_storage_cache_size(), 101 /* bytes */);
 	}
 
+	#[test]
+	fn disabled_cache_reads_from_state() {
+		let root_parent = H256::random();
+		let key = H256::random()[..].to_vec();
+		let shared = new_shared_cache::<Block>(256 * 1024, (0, 1));
+		let h0 = H256::random();
+
+		let mut s = CachingState::new(
+			InMemoryBackend::<BlakeTwo256>::default(),
+			shared.clone(),
+			Some(root_parent),
+		);
+		s.cache.sync_cache(
+			&[],
+			&[],
+			vec![(key.clone(), Some(vec![2]))],
+			vec![],
+			Some(h0),
+			Some(0),
+			true,
+		);
+
+		let s =
+			CachingState::new(InMemoryBackend::<BlakeTwo256>::default(), shared.clone(), Some(h0));
+		assert_eq!(s.storage(&key).unwrap(), Some(vec![2]));
+
+		let mut s =
+			CachingState::new(InMemoryBackend::<BlakeTwo256>::default(), shared.clone(), Some(h0));
+		s.disable_cache();
+		assert_eq!(s.storage(&key).unwrap(), None);
+		assert!(s.cache.local_cache.read().storage.is_empty());
+	}
+
 	#[test]
 	fn should_remove_lru_items_based_on_tracking_used_size() {
 		let root_parent = H256::random();
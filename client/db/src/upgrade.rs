@@ -50,6 +50,8 @@ pub enum UpgradeError {
 	UnsupportedVersion(u32),
 	/// Database version comes from future version of the client.
 	FutureDatabaseVersion(u32),
+	/// Database has to be upgraded, but is opened read-only.
+	ReadOnlyUpgrade(u32),
 	/// Invalid justification block.
 	DecodingJustificationBlock,
 	/// Common io error.
@@ -77,6 +79,13 @@ impl fmt::Display for UpgradeError {
 			UpgradeError::FutureDatabaseVersion(version) => {
 				write!(f, "Database version comes from future version of the client: {}", version)
 			},
+			UpgradeError::ReadOnlyUpgrade(version) => {
+				write!(
+					f,
+					"Database version {} has to be upgraded, but is opened read-only",
+					version
+				)
+			},
 			UpgradeError::DecodingJustificationBlock => {
 				write!(f, "Decodoning justification block failed")
 			},
@@ -102,6 +111,16 @@ pub fn upgrade_db<Block: BlockT>(db_path: &Path, db_type: DatabaseType) -> Upgra
 	Ok(())
 }
 
+/// Check that the database has the current version, without upgrading it.
+pub fn check_version(db_path: &Path) -> UpgradeResult<()> {
+	match current_version(db_path)? {
+		CURRENT_VERSION => Ok(()),
+		db_version if db_version > CURRENT_VERSION =>
+			Err(UpgradeError::FutureDatabaseVersion(db_version)),
+		db_version => Err(UpgradeError::ReadOnlyUpgrade(db_version)),
+	}
+}
+
 /// Migration from version1 to version2:
 /// 1) the number of columns has changed from 11 to 12;
 /// 2) transactions column is added;
@@ -195,6 +214,8 @@ mod tests {
 				source: DatabaseSource::RocksDb { path: db_path.to_owned(), cache_size: 128 },
 				keep_blocks: KeepBlocks::All,
 				transaction_storage: TransactionStorageMode::BlockBody,
+				disable_state_cache_layer: false,
+				read_only: false,
 			},
 			DatabaseType::Full,
 		)
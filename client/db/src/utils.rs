@@ -213,26 +213,70 @@ pub fn open_database<Block: BlockT>(
 	config: &DatabaseSettings,
 	db_type: DatabaseType,
 ) -> sp_blockchain::Result<Arc<dyn Database<DbHash>>> {
+	let read_only = config.read_only;
 	let db: Arc<dyn Database<DbHash>> = match &config.source {
+		DatabaseSource::ParityDb { .. } if read_only =>
+			return Err(backend_err("only RocksDB databases can be opened read-only")),
 		DatabaseSource::ParityDb { path } => open_parity_db::<Block>(&path, db_type, true)?,
 		DatabaseSource::RocksDb { path, cache_size } =>
-			open_kvdb_rocksdb::<Block>(&path, db_type, true, *cache_size)?,
+			open_kvdb_rocksdb::<Block>(path, db_type, !read_only, *cache_size, read_only)?,
 		DatabaseSource::Custom(db) => db.clone(),
 		DatabaseSource::Auto { paritydb_path, rocksdb_path, cache_size } => {
 			// check if rocksdb exists first, if not, open paritydb
-			match open_kvdb_rocksdb::<Block>(&rocksdb_path, db_type, false, *cache_size) {
+			match open_kvdb_rocksdb::<Block>(&rocksdb_path, db_type, false, *cache_size, read_only)
+			{
 				Ok(db) => db,
+				Err(OpenDbError::NotEnabled(_)) | Err(OpenDbError::DoesNotExist) if read_only =>
+					return Err(backend_err("only RocksDB databases can be opened read-only")),
 				Err(OpenDbError::NotEnabled(_)) | Err(OpenDbError::DoesNotExist) =>
 					open_parity_db::<Block>(&paritydb_path, db_type, true)?,
 				Err(_) => return Err(backend_err("cannot open rocksdb. corrupted database")),
 			}
 		},
 	};
+	let db: Arc<dyn Database<DbHash>> = if read_only { Arc::new(ReadOnlyDatabase(db)) } else { db };
 
 	check_database_type(&*db, db_type)?;
 	Ok(db)
 }
 
+/// A database rejecting every change, for the databases opened read-only.
+///
+/// Empty transactions are accepted, as the backend commits some when opening the database.
+struct ReadOnlyDatabase(Arc<dyn Database<DbHash>>);
+
+impl Database<DbHash> for ReadOnlyDatabase {
+	fn commit(&self, transaction: Transaction<DbHash>) -> sp_database::error::Result<()> {
+		if transaction.0.is_empty() {
+			return Ok(())
+		}
+		Err(sp_database::error::DatabaseError(Box::new(io::Error::new(
+			io::ErrorKind::PermissionDenied,
+			"the database is opened read-only",
+		))))
+	}
+
+	fn get(&self, col: u32, key: &[u8]) -> Option<Vec<u8>> {
+		self.0.get(col, key)
+	}
+
+	fn contains(&self, col: u32, key: &[u8]) -> bool {
+		self.0.contains(col, key)
+	}
+
+	fn value_size(&self, col: u32, key: &[u8]) -> Option<usize> {
+		self.0.value_size(col, key)
+	}
+
+	fn with_get(&self, col: u32, key: &[u8], f: &mut dyn FnMut(&[u8])) {
+		self.0.with_get(col, key, f)
+	}
+
+	fn supports_ref_counting(&self) -> bool {
+		self.0.supports_ref_counting()
+	}
+}
+
 #[derive(Debug)]
 enum OpenDbError {
 	// constructed only when rocksdb and paritydb are disabled
@@ -304,9 +348,15 @@ fn open_kvdb_rocksdb<Block: BlockT>(
 	db_type: DatabaseType,
 	create: bool,
 	cache_size: usize,
+	read_only: bool,
 ) -> OpenDbResult {
 	// first upgrade database to required version
-	match crate::upgrade::upgrade_db::<Block>(&path, db_type) {
+	let upgrade = if read_only {
+		crate::upgrade::check_version(path)
+	} else {
+		crate::upgrade::upgrade_db::<Block>(&path, db_type)
+	};
+	match upgrade {
 		// in case of missing version file, assume that database simply does not exist at given
 		// location
 		Ok(_) | Err(crate::upgrade::UpgradeError::MissingDatabaseVersionFile) => (),
@@ -316,6 +366,13 @@ fn open_kvdb_rocksdb<Block: BlockT>(
 	// and now open database assuming that it has the latest version
 	let mut db_config = kvdb_rocksdb::DatabaseConfig::with_columns(NUM_COLUMNS);
 	db_config.create_if_missing = create;
+	if read_only {
+		// A secondary instance doesn't take the lock of the database, and sees the database as
+		// it was when opened. It needs a directory of its own for its logs.
+		let mut secondary = path.as_os_str().to_owned();
+		secondary.push("-read-only");
+		db_config.secondary = Some(secondary.into());
+	}
 
 	let mut memory_budget = std::collections::HashMap::new();
 	match db_type {
@@ -356,7 +413,9 @@ fn open_kvdb_rocksdb<Block: BlockT>(
 
 	let db = kvdb_rocksdb::Database::open(&db_config, path)?;
 	// write database version only after the database is succesfully opened
-	crate::upgrade::update_version(path)?;
+	if !read_only {
+		crate::upgrade::update_version(path)?;
+	}
 	Ok(sp_database::as_database(db))
 }
 
@@ -366,10 +425,87 @@ fn open_kvdb_rocksdb<Block: BlockT>(
 	_db_type: DatabaseType,
 	_create: bool,
 	_cache_size: usize,
+	_read_only: bool,
 ) -> OpenDbResult {
 	Err(OpenDbError::NotEnabled("with-kvdb-rocksdb"))
 }
 
+/// Compacts the configured database offline.
+///
+/// Only RocksDB databases can be compacted. Returns the size of the database in bytes before and
+/// after the compaction.
+pub fn compact_database(source: &DatabaseSource) -> sp_blockchain::Result<(u64, u64)> {
+	let path = match source {
+		DatabaseSource::RocksDb { path, .. } => path,
+		DatabaseSource::Auto { rocksdb_path, .. } if rocksdb_path.exists() => rocksdb_path,
+		DatabaseSource::Auto { .. } | DatabaseSource::ParityDb { .. } =>
+			return Err(backend_err("ParityDb databases can not be compacted")),
+		DatabaseSource::Custom(_) => return Err(backend_err("cannot compact custom database")),
+	};
+
+	if !path.exists() {
+		return Err(OpenDbError::DoesNotExist.into())
+	}
+
+	let size = |path| {
+		directory_size(path).map_err(|e| {
+			sp_blockchain::Error::Backend(format!("cannot get the size of the database: {}", e))
+		})
+	};
+
+	let size_before = size(path)?;
+	compact_rocksdb(path)?;
+	let size_after = size(path)?;
+
+	Ok((size_before, size_after))
+}
+
+#[cfg(feature = "with-kvdb-rocksdb")]
+fn compact_rocksdb(path: &Path) -> Result<(), OpenDbError> {
+	let rocksdb_err = |e: rocksdb::Error| {
+		if e.to_string().contains("lock file") {
+			OpenDbError::Internal(format!(
+				"database at {} is locked, stop the node using it first: {}",
+				path.display(),
+				e,
+			))
+		} else {
+			OpenDbError::Internal(e.to_string())
+		}
+	};
+
+	let mut options = rocksdb::Options::default();
+	// Like `kvdb-rocksdb`, which the database was created with.
+	options.set_level_compaction_dynamic_level_bytes(true);
+
+	let columns = rocksdb::DB::list_cf(&options, path).map_err(rocksdb_err)?;
+	let db = rocksdb::DB::open_cf(&options, path, &columns).map_err(rocksdb_err)?;
+
+	for column in &columns {
+		if let Some(handle) = db.cf_handle(column) {
+			log::info!(target: "db", "Compacting column {}", column);
+			db.compact_range_cf(handle, None::<&[u8]>, None::<&[u8]>);
+		}
+	}
+
+	Ok(())
+}
+
+#[cfg(not(feature = "with-kvdb-rocksdb"))]
+fn compact_rocksdb(_path: &Path) -> Result<(), OpenDbError> {
+	Err(OpenDbError::NotEnabled("with-kvdb-rocksdb"))
+}
+
+fn directory_size(path: &Path) -> io::Result<u64> {
+	let mut size = 0;
+	for entry in std::fs::read_dir(path)? {
+		let entry = entry?;
+		let metadata = entry.metadata()?;
+		size += if metadata.is_dir() { directory_size(&entry.path())? } else { metadata.len() };
+	}
+	Ok(size)
+}
+
 /// Check database type.
 pub fn check_database_type(
 	db: &dyn Database<DbHash>,
@@ -616,7 +752,55 @@ mod tests {
 			source,
 			keep_blocks: KeepBlocks::All,
 			transaction_storage: TransactionStorageMode::BlockBody,
+			disable_state_cache_layer: false,
+			read_only: false,
+		}
+	}
+
+	#[cfg(feature = "with-kvdb-rocksdb")]
+	#[test]
+	fn compact_database_works() {
+		let db_dir = tempfile::TempDir::new().unwrap();
+		let source =
+			DatabaseSource::RocksDb { path: db_dir.path().join("rocksdb_path"), cache_size: 128 };
+		let settings = db_settings(source.clone());
+
+		// a database opened by a node can't be compacted
+		{
+			let _db = open_database::<Block>(&settings, DatabaseType::Full).unwrap();
+			let err = compact_database(&source).unwrap_err();
+			assert!(err.to_string().contains("locked"), "{}", err);
 		}
+
+		let (size_before, size_after) = compact_database(&source).unwrap();
+		assert!(size_before > 0 && size_after > 0);
+	}
+
+	#[cfg(feature = "with-kvdb-rocksdb")]
+	#[test]
+	fn read_only_database_rejects_changes() {
+		let db_dir = tempfile::TempDir::new().unwrap();
+		let source =
+			DatabaseSource::RocksDb { path: db_dir.path().join("rocksdb_path"), cache_size: 128 };
+		let mut settings = db_settings(source);
+
+		settings.read_only = true;
+		assert!(open_database::<Block>(&settings, DatabaseType::Full).is_err());
+
+		// the primary instance stays writable while the database is opened read-only
+		settings.read_only = false;
+		let db = open_database::<Block>(&settings, DatabaseType::Full).unwrap();
+		let mut transaction = Transaction::new();
+		transaction.set(COLUMN_META, b"key", b"value");
+		db.commit(transaction).unwrap();
+
+		settings.read_only = true;
+		let read_only_db = open_database::<Block>(&settings, DatabaseType::Full).unwrap();
+		assert_eq!(read_only_db.get(COLUMN_META, b"key"), Some(b"value".to_vec()));
+		let mut transaction = Transaction::new();
+		transaction.remove(COLUMN_META, b"key");
+		assert!(read_only_db.commit(transaction).is_err());
+		drop(db);
 	}
 
 	#[cfg(feature = "with-parity-db")]
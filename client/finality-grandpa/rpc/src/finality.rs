@@ -32,6 +32,12 @@ pub trait RpcFinalityProofProvider<Block: BlockT> {
 		&self,
 		block: NumberFor<Block>,
 	) -> Result<Option<EncodedFinalityProof>, sc_finality_grandpa::FinalityProofError>;
+
+	/// Prove finality for the given block hash, as `rpc_prove_finality` does for its number.
+	fn rpc_prove_finality_of(
+		&self,
+		block: Block::Hash,
+	) -> Result<Option<EncodedFinalityProof>, sc_finality_grandpa::FinalityProofError>;
 }
 
 impl<B, Block> RpcFinalityProofProvider<Block> for FinalityProofProvider<B, Block>
@@ -46,4 +52,11 @@ where
 	) -> Result<Option<EncodedFinalityProof>, sc_finality_grandpa::FinalityProofError> {
 		self.prove_finality(block).map(|x| x.map(|y| EncodedFinalityProof(y.into())))
 	}
+
+	fn rpc_prove_finality_of(
+		&self,
+		block: Block::Hash,
+	) -> Result<Option<EncodedFinalityProof>, sc_finality_grandpa::FinalityProofError> {
+		self.prove_finality_of(block).map(|x| x.map(|y| EncodedFinalityProof(y.into())))
+	}
 }
@@ -36,6 +36,7 @@ use sp_runtime::traits::{Block as BlockT, NumberFor};
 use finality::{EncodedFinalityProof, RpcFinalityProofProvider};
 use notification::JustificationNotification;
 use report::{ReportAuthoritySet, ReportVoterState, ReportedRoundStates};
+use sc_rpc::DenyUnsafe;
 
 type FutureResult<T> = jsonrpc_core::BoxFuture<Result<T, jsonrpc_core::Error>>;
 
@@ -79,6 +80,15 @@ pub trait GrandpaApi<Notification, Hash, Number> {
 	/// in the set and all the intermediary headers to link them together.
 	#[rpc(name = "grandpa_proveFinality")]
 	fn prove_finality(&self, block: Number) -> FutureResult<Option<EncodedFinalityProof>>;
+
+	/// Export the finality proof of the given block hash, for relayers needing to prove the
+	/// finality of that exact block.
+	///
+	/// Like `grandpa_proveFinality`, the proof holds the justification of the block or of one of
+	/// its descendants, and the headers linking them. Fails for the blocks on a fork of the
+	/// finalized chain.
+	#[rpc(name = "grandpa_exportJustification")]
+	fn export_justification(&self, block: Hash) -> FutureResult<Option<EncodedFinalityProof>>;
 }
 
 /// Implements the GrandpaApi RPC trait for interacting with GRANDPA.
@@ -88,6 +98,7 @@ pub struct GrandpaRpcHandler<AuthoritySet, VoterState, Block: BlockT, ProofProvi
 	justification_stream: GrandpaJustificationStream<Block>,
 	manager: SubscriptionManager,
 	finality_proof_provider: Arc<ProofProvider>,
+	deny_unsafe: DenyUnsafe,
 }
 
 impl<AuthoritySet, VoterState, Block: BlockT, ProofProvider>
@@ -100,12 +111,20 @@ impl<AuthoritySet, VoterState, Block: BlockT, ProofProvider>
 		justification_stream: GrandpaJustificationStream<Block>,
 		executor: E,
 		finality_proof_provider: Arc<ProofProvider>,
+		deny_unsafe: DenyUnsafe,
 	) -> Self
 	where
 		E: Spawn + Sync + Send + 'static,
 	{
 		let manager = SubscriptionManager::new(Arc::new(executor));
-		Self { authority_set, voter_state, justification_stream, manager, finality_proof_provider }
+		Self {
+			authority_set,
+			voter_state,
+			justification_stream,
+			manager,
+			finality_proof_provider,
+			deny_unsafe,
+		}
 	}
 }
 
@@ -165,6 +184,21 @@ where
 			.map_err(jsonrpc_core::Error::from)
 			.boxed()
 	}
+
+	fn export_justification(
+		&self,
+		block: Block::Hash,
+	) -> FutureResult<Option<EncodedFinalityProof>> {
+		if let Err(err) = self.deny_unsafe.check_if_safe() {
+			return async move { Err(err.into()) }.boxed()
+		}
+
+		let result = self.finality_proof_provider.rpc_prove_finality_of(block).map_err(|e| {
+			warn!("Error exporting justification: {}", e);
+			jsonrpc_core::Error::from(error::Error::ExportJustificationFailed(e))
+		});
+		async move { result }.boxed()
+	}
 }
 
 #[cfg(test)]
@@ -241,6 +275,16 @@ mod tests {
 					.into(),
 			)))
 		}
+
+		fn rpc_prove_finality_of(
+			&self,
+			_block: Block::Hash,
+		) -> Result<Option<EncodedFinalityProof>, sc_finality_grandpa::FinalityProofError> {
+			Ok(self
+				.finality_proof
+				.as_ref()
+				.map(|proof| EncodedFinalityProof(proof.encode().into())))
+		}
 	}
 
 	impl ReportVoterState for TestVoterState {
@@ -285,6 +329,17 @@ mod tests {
 		voter_state: VoterState,
 		finality_proof: Option<FinalityProof<Header>>,
 	) -> (jsonrpc_core::MetaIoHandler<sc_rpc::Metadata>, GrandpaJustificationSender<Block>)
+	where
+		VoterState: ReportVoterState + Send + Sync + 'static,
+	{
+		setup_io_handler_with_deny_unsafe(voter_state, finality_proof, DenyUnsafe::No)
+	}
+
+	fn setup_io_handler_with_deny_unsafe<VoterState>(
+		voter_state: VoterState,
+		finality_proof: Option<FinalityProof<Header>>,
+		deny_unsafe: DenyUnsafe,
+	) -> (jsonrpc_core::MetaIoHandler<sc_rpc::Metadata>, GrandpaJustificationSender<Block>)
 	where
 		VoterState: ReportVoterState + Send + Sync + 'static,
 	{
@@ -297,6 +352,7 @@ mod tests {
 			justification_stream,
 			sc_rpc::testing::TaskExecutor,
 			finality_proof_provider,
+			deny_unsafe,
 		);
 
 		let mut io = jsonrpc_core::MetaIoHandler::default();
@@ -510,4 +566,37 @@ mod tests {
 		let finality_proof_rpc: FinalityProof<Header> = Decode::decode(&mut &result[..]).unwrap();
 		assert_eq!(finality_proof_rpc, finality_proof);
 	}
+
+	#[test]
+	fn export_justification_is_unsafe() {
+		let finality_proof = FinalityProof {
+			block: header(42).hash(),
+			justification: vec![1, 2, 3],
+			unknown_headers: vec![],
+		};
+		let request = format!(
+			"{{\"jsonrpc\":\"2.0\",\"method\":\"grandpa_exportJustification\",\"params\":[\"{:?}\"],\"id\":1}}",
+			header(42).hash(),
+		);
+
+		let (io, _) = setup_io_handler_with_deny_unsafe(
+			TestVoterState,
+			Some(finality_proof.clone()),
+			DenyUnsafe::No,
+		);
+		let resp = io.handle_request_sync(&request, sc_rpc::Metadata::default());
+		let mut resp: serde_json::Value = serde_json::from_str(&resp.unwrap()).unwrap();
+		let result: sp_core::Bytes = serde_json::from_value(resp["result"].take()).unwrap();
+		let finality_proof_rpc: FinalityProof<Header> = Decode::decode(&mut &result[..]).unwrap();
+		assert_eq!(finality_proof_rpc, finality_proof);
+
+		let (io, _) = setup_io_handler_with_deny_unsafe(
+			TestVoterState,
+			Some(finality_proof),
+			DenyUnsafe::Yes,
+		);
+		let resp = io.handle_request_sync(&request, sc_rpc::Metadata::default());
+		let resp: serde_json::Value = serde_json::from_str(&resp.unwrap()).unwrap();
+		assert_eq!(resp["error"]["message"], "Method not found");
+	}
 }
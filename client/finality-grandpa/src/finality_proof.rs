@@ -116,6 +116,34 @@ where
 
 		prove_finality(&*self.backend, authority_set_changes, block)
 	}
+
+	/// Prove finality for the block `hash`, as [`Self::prove_finality`] does for its number.
+	///
+	/// Fails if the block is on a fork of the finalized chain.
+	pub fn prove_finality_of(
+		&self,
+		hash: Block::Hash,
+	) -> Result<Option<Vec<u8>>, FinalityProofError> {
+		let block = canonical_number(&*self.backend, hash)?;
+		self.prove_finality(block)
+	}
+}
+
+/// The number of the block `hash`, checking that it isn't on a fork of the finalized chain.
+fn canonical_number<Block, B>(
+	backend: &B,
+	hash: Block::Hash,
+) -> Result<NumberFor<Block>, FinalityProofError>
+where
+	Block: BlockT,
+	B: Backend<Block>,
+{
+	let blockchain = backend.blockchain();
+	let number = blockchain.expect_block_number_from_id(&BlockId::Hash(hash))?;
+	if number <= blockchain.info().finalized_number && blockchain.hash(number)? != Some(hash) {
+		return Err(FinalityProofError::BlockNotInFinalizedChain)
+	}
+	Ok(number)
 }
 
 /// Finality for block B is proved by providing:
@@ -137,6 +165,9 @@ pub enum FinalityProofError {
 	/// The requested block has not yet been finalized.
 	#[display(fmt = "Block not yet finalized")]
 	BlockNotYetFinalized,
+	/// The requested block is on a fork of the finalized chain, and is never finalized.
+	#[display(fmt = "Block not in the finalized chain")]
+	BlockNotInFinalizedChain,
 	/// The requested block is not covered by authority set changes. Likely this means the block is
 	/// in the latest authority set, and the subscription API is more appropriate.
 	#[display(fmt = "Block not covered by authority set changes")]
@@ -248,8 +279,8 @@ pub(crate) mod tests {
 	use sp_keyring::Ed25519Keyring;
 	use substrate_test_runtime_client::{
 		runtime::{Block, Header, H256},
-		Backend as TestBackend, ClientBlockImportExt, ClientExt, DefaultTestClientBuilderExt,
-		TestClient, TestClientBuilder, TestClientBuilderExt,
+		Backend as TestBackend, BlockBuilderExt, ClientBlockImportExt, ClientExt,
+		DefaultTestClientBuilderExt, TestClient, TestClientBuilder, TestClientBuilderExt,
 	};
 
 	/// Check GRANDPA proof-of-finality for the given block.
@@ -332,6 +363,27 @@ pub(crate) mod tests {
 		assert!(matches!(proof_of_5, Err(FinalityProofError::BlockNotYetFinalized)));
 	}
 
+	#[test]
+	fn finality_proof_of_a_hash_fails_on_forks() {
+		let (mut client, backend, blocks) = test_blockchain(6, &[4]);
+		let mut fork = client.new_block_at(&BlockId::Number(2), Default::default(), false).unwrap();
+		fork.push_storage_change(vec![1], None).unwrap();
+		let fork = fork.build().unwrap().block;
+		block_on(client.import(BlockOrigin::Own, fork.clone())).unwrap();
+
+		assert_eq!(canonical_number(&*backend, blocks[2].hash()).unwrap(), 3);
+		// Not finalized yet, `prove_finality` fails.
+		assert_eq!(canonical_number(&*backend, blocks[5].hash()).unwrap(), 6);
+		assert!(matches!(
+			canonical_number(&*backend, fork.hash()),
+			Err(FinalityProofError::BlockNotInFinalizedChain)
+		));
+		assert!(matches!(
+			canonical_number(&*backend, H256::repeat_byte(1)),
+			Err(FinalityProofError::Client(_))
+		));
+	}
+
 	#[test]
 	fn finality_proof_is_none_if_no_justification_known() {
 		let (_, backend, _) = test_blockchain(6, &[4]);
@@ -198,6 +198,11 @@ impl<Block: BlockT> GrandpaJustification<Block> {
 		Ok(())
 	}
 
+	/// The round of the commit of this justification.
+	pub fn round(&self) -> u64 {
+		self.round
+	}
+
 	/// The target block number and hash that this justifications proves finality for.
 	pub fn target(&self) -> (NumberFor<Block>, Block::Hash) {
 		(self.commit.target_number, self.commit.target_hash)
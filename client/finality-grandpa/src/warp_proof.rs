@@ -19,8 +19,9 @@
 use sp_runtime::codec::{self, Decode, Encode};
 
 use crate::{
-	best_justification, find_scheduled_change, AuthoritySetChanges, BlockNumberOps,
-	GrandpaJustification, SharedAuthoritySet,
+	authorities::AuthoritySetChangeId, best_justification, find_scheduled_change,
+	AuthoritySetChanges, BlockNumberOps, GenesisAuthoritySetProvider, GrandpaJustification,
+	SharedAuthoritySet,
 };
 use sc_client_api::Backend as ClientBackend;
 use sc_network::warp_request_handler::{EncodedProof, VerificationResult, WarpSyncProvider};
@@ -28,7 +29,7 @@ use sp_blockchain::{Backend as BlockchainBackend, HeaderBackend};
 use sp_finality_grandpa::{AuthorityList, SetId, GRANDPA_ENGINE_ID};
 use sp_runtime::{
 	generic::BlockId,
-	traits::{Block as BlockT, Header as HeaderT, NumberFor, One},
+	traits::{Block as BlockT, Header as HeaderT, NumberFor, One, Zero},
 };
 
 use std::sync::Arc;
@@ -55,7 +56,7 @@ pub enum Error {
 impl std::error::Error for Error {}
 
 /// The maximum size in bytes of the `WarpSyncProof`.
-pub(super) const MAX_WARP_SYNC_PROOF_SIZE: usize = 8 * 1024 * 1024;
+pub const MAX_WARP_SYNC_PROOF_SIZE: usize = 8 * 1024 * 1024;
 
 /// A proof of an authority set change.
 #[derive(Decode, Encode, Debug)]
@@ -78,11 +79,15 @@ pub struct WarpSyncProof<Block: BlockT> {
 impl<Block: BlockT> WarpSyncProof<Block> {
 	/// Generates a warp sync proof starting at the given block. It will generate authority set
 	/// change proofs for all changes that happened from `begin` until the current authority set
-	/// (capped by MAX_WARP_SYNC_PROOF_SIZE).
+	/// (capped by `max_proof_size`, itself capped by MAX_WARP_SYNC_PROOF_SIZE).
+	///
+	/// The first authority set change proof is always included if it fits in
+	/// MAX_WARP_SYNC_PROOF_SIZE, so that the requester makes progress with any `max_proof_size`.
 	fn generate<Backend>(
 		backend: &Backend,
 		begin: Block::Hash,
 		set_changes: &AuthoritySetChanges<NumberFor<Block>>,
+		max_proof_size: usize,
 	) -> Result<WarpSyncProof<Block>, Error>
 	where
 		Backend: ClientBackend<Block>,
@@ -147,7 +152,12 @@ impl<Block: BlockT> WarpSyncProof<Block> {
 			// Check for the limit. We remove some bytes from the maximum size, because we're only
 			// counting the size of the `WarpSyncFragment`s. The extra margin is here to leave
 			// room for rest of the data (the size of the `Vec` and the boolean).
-			if proofs_encoded_len + proof_size >= MAX_WARP_SYNC_PROOF_SIZE - 50 {
+			let limit = if proofs.is_empty() {
+				MAX_WARP_SYNC_PROOF_SIZE
+			} else {
+				max_proof_size.min(MAX_WARP_SYNC_PROOF_SIZE)
+			};
+			if proofs_encoded_len + proof_size >= limit.saturating_sub(50) {
 				proof_limit_reached = true;
 				break
 			}
@@ -228,6 +238,60 @@ impl<Block: BlockT> WarpSyncProof<Block> {
 	}
 }
 
+/// The id and the authorities of the set that finalizes the block `number`.
+///
+/// Like for warp sync proofs, the authorities of past sets are read from the standard change
+/// signaled by the last block of the previous set. Sets that started with a forced change can't
+/// be recovered this way.
+pub fn authority_set_at<Block, Backend>(
+	backend: &Backend,
+	authority_set: &SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
+	genesis_authorities: &dyn GenesisAuthoritySetProvider<Block>,
+	number: NumberFor<Block>,
+) -> Result<(SetId, AuthorityList), Error>
+where
+	Block: BlockT,
+	Backend: ClientBackend<Block>,
+{
+	let set_changes = authority_set.authority_set_changes();
+	let set_id = match set_changes.get_set_id(number) {
+		AuthoritySetChangeId::Latest => {
+			let set = authority_set.inner();
+			return Ok((set.set_id, set.current_authorities.clone()))
+		},
+		// Without any change the block is necessarily in the first set.
+		AuthoritySetChangeId::Unknown if authority_set.set_id() == 0 =>
+			return Ok((0, authority_set.inner().current_authorities.clone())),
+		AuthoritySetChangeId::Set(set_id, _) => set_id,
+		AuthoritySetChangeId::Unknown => return Err(Error::MissingData),
+	};
+
+	if set_id == 0 {
+		return Ok((0, genesis_authorities.get()?))
+	}
+
+	let last_block = set_changes
+		.iter_from(Zero::zero())
+		.into_iter()
+		.flatten()
+		.find(|(id, _)| *id == set_id - 1)
+		.map(|(_, last_block)| *last_block)
+		.ok_or(Error::MissingData)?;
+	let header = backend
+		.blockchain()
+		.header(BlockId::Number(last_block))?
+		.ok_or(Error::MissingData)?;
+
+	let change = find_scheduled_change::<Block>(&header).ok_or_else(|| {
+		Error::InvalidRequest(format!(
+			"Authority set {} was not signaled by a standard change at block #{}",
+			set_id, last_block,
+		))
+	})?;
+
+	Ok((set_id, change.next_authorities))
+}
+
 /// Implements network API for warp sync.
 pub struct NetworkProvider<Block: BlockT, Backend: ClientBackend<Block>>
 where
@@ -235,6 +299,7 @@ where
 {
 	backend: Arc<Backend>,
 	authority_set: SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
+	max_proof_size: usize,
 }
 
 impl<Block: BlockT, Backend: ClientBackend<Block>> NetworkProvider<Block, Backend>
@@ -246,7 +311,17 @@ where
 		backend: Arc<Backend>,
 		authority_set: SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
 	) -> Self {
-		NetworkProvider { backend, authority_set }
+		NetworkProvider { backend, authority_set, max_proof_size: MAX_WARP_SYNC_PROOF_SIZE }
+	}
+
+	/// Limit the size of the served proofs to `max_proof_size` bytes, instead of
+	/// [`MAX_WARP_SYNC_PROOF_SIZE`].
+	///
+	/// Larger proofs are truncated to the authority set changes that fit, the requester asks for
+	/// the rest of them in the following requests.
+	pub fn with_max_proof_size(mut self, max_proof_size: usize) -> Self {
+		self.max_proof_size = max_proof_size;
+		self
 	}
 }
 
@@ -263,6 +338,7 @@ where
 			&*self.backend,
 			start,
 			&self.authority_set.authority_set_changes(),
+			self.max_proof_size,
 		)
 		.map_err(Box::new)?;
 		Ok(EncodedProof(proof.encode()))
@@ -302,13 +378,21 @@ where
 
 #[cfg(test)]
 mod tests {
-	use super::{codec::Encode, WarpSyncProof};
-	use crate::{AuthoritySetChanges, GrandpaJustification};
+	use super::{
+		authority_set_at,
+		codec::{Decode, Encode},
+		WarpSyncProof, MAX_WARP_SYNC_PROOF_SIZE,
+	};
+	use crate::{
+		AuthoritySet, AuthoritySetChanges, GenesisAuthoritySetProvider, GrandpaJustification,
+	};
+	use fork_tree::ForkTree;
 	use rand::prelude::*;
 	use sc_block_builder::BlockBuilderProvider;
-	use sp_blockchain::HeaderBackend;
+	use sc_client_api::Backend as _;
+	use sp_blockchain::{Backend as _, Error as ClientError, HeaderBackend};
 	use sp_consensus::BlockOrigin;
-	use sp_finality_grandpa::GRANDPA_ENGINE_ID;
+	use sp_finality_grandpa::{AuthorityList, SetId, GRANDPA_ENGINE_ID};
 	use sp_keyring::Ed25519Keyring;
 	use sp_runtime::{generic::BlockId, traits::Header as _};
 	use std::sync::Arc;
@@ -317,8 +401,24 @@ mod tests {
 		TestClientBuilderExt,
 	};
 
-	#[test]
-	fn warp_sync_proof_generate_verify() {
+	struct Genesis(AuthorityList);
+
+	impl GenesisAuthoritySetProvider<substrate_test_runtime_client::runtime::Block> for Genesis {
+		fn get(&self) -> Result<AuthorityList, ClientError> {
+			Ok(self.0.clone())
+		}
+	}
+
+	/// A chain of 100 blocks, with an authority set change every 10 blocks.
+	struct TestChain {
+		backend: Arc<substrate_test_runtime_client::Backend>,
+		genesis_authorities: AuthorityList,
+		set_id: SetId,
+		authorities: AuthorityList,
+		set_changes: AuthoritySetChanges<u64>,
+	}
+
+	fn test_chain() -> TestChain {
 		let mut rng = rand::rngs::StdRng::from_seed([0; 32]);
 		let builder = TestClientBuilder::new();
 		let backend = builder.backend();
@@ -408,23 +508,100 @@ mod tests {
 			}
 		}
 
-		let authority_set_changes = AuthoritySetChanges::from(authority_set_changes);
+		TestChain {
+			backend,
+			genesis_authorities,
+			set_id: current_set_id,
+			authorities: current_authorities
+				.iter()
+				.map(|keyring| (keyring.public().into(), 1))
+				.collect(),
+			set_changes: AuthoritySetChanges::from(authority_set_changes),
+		}
+	}
+
+	#[test]
+	fn warp_sync_proof_generate_verify() {
+		let chain = test_chain();
 
 		// generate a warp sync proof
-		let genesis_hash = client.hash(0).unwrap().unwrap();
+		let genesis_hash = chain.backend.blockchain().hash(0).unwrap().unwrap();
 
-		let warp_sync_proof =
-			WarpSyncProof::generate(&*backend, genesis_hash, &authority_set_changes).unwrap();
+		let warp_sync_proof = WarpSyncProof::generate(
+			&*chain.backend,
+			genesis_hash,
+			&chain.set_changes,
+			MAX_WARP_SYNC_PROOF_SIZE,
+		)
+		.unwrap();
 
 		// verifying the proof should yield the last set id and authorities
-		let (new_set_id, new_authorities) = warp_sync_proof.verify(0, genesis_authorities).unwrap();
+		let (new_set_id, new_authorities) =
+			warp_sync_proof.verify(0, chain.genesis_authorities).unwrap();
 
-		let expected_authorities = current_authorities
-			.iter()
-			.map(|keyring| (keyring.public().into(), 1))
-			.collect::<Vec<_>>();
+		assert_eq!(new_set_id, chain.set_id);
+		assert_eq!(new_authorities, chain.authorities);
+	}
+
+	#[test]
+	fn warp_sync_proof_respects_the_size_limit() {
+		let chain = test_chain();
+		let genesis_hash = chain.backend.blockchain().hash(0).unwrap().unwrap();
+		let generate = |max_proof_size| {
+			WarpSyncProof::generate(
+				&*chain.backend,
+				genesis_hash,
+				&chain.set_changes,
+				max_proof_size,
+			)
+			.unwrap()
+		};
+
+		let full_proof = generate(MAX_WARP_SYNC_PROOF_SIZE);
+		assert_eq!(full_proof.proofs.len(), 10);
+		assert!(full_proof.is_finished);
 
-		assert_eq!(new_set_id, current_set_id);
-		assert_eq!(new_authorities, expected_authorities);
+		// a size limit truncates the proof, but keeps the first set change
+		let truncated_proof = generate(1);
+		assert_eq!(truncated_proof.proofs.len(), 1);
+		assert!(!truncated_proof.is_finished);
+	}
+
+	#[test]
+	fn authority_set_at_recovers_the_past_sets() {
+		let TestChain { backend, genesis_authorities, set_id, authorities, set_changes } =
+			test_chain();
+		let authority_set = AuthoritySet::new(
+			authorities.clone(),
+			set_id,
+			ForkTree::new(),
+			Vec::new(),
+			set_changes,
+		)
+		.unwrap()
+		.into();
+		let genesis = Genesis(genesis_authorities.clone());
+		let set_at =
+			|number| authority_set_at(&*backend, &authority_set, &genesis, number).unwrap();
+
+		assert_eq!(set_at(5), (0, genesis_authorities));
+		assert_eq!(set_at(100), (9, set_at(95).1));
+		assert_eq!(set_at(101), (set_id, authorities));
+
+		// the set is the one whose signatures verify the justifications
+		let justification = backend
+			.blockchain()
+			.justifications(BlockId::Number(30))
+			.unwrap()
+			.and_then(|just| just.into_justification(GRANDPA_ENGINE_ID))
+			.unwrap();
+		let justification =
+			GrandpaJustification::<substrate_test_runtime_client::runtime::Block>::decode(
+				&mut &justification[..],
+			)
+			.unwrap();
+		let (set_id, authorities) = set_at(30);
+		assert_eq!(set_id, 2);
+		justification.verify(set_id, &authorities).unwrap();
 	}
 }
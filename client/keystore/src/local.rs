@@ -31,6 +31,7 @@ use sp_keystore::{
 };
 use std::{
 	collections::{HashMap, HashSet},
+	convert::TryFrom,
 	fs::{self, File},
 	io::Write,
 	path::PathBuf,
@@ -65,6 +66,13 @@ impl LocalKeystore {
 	) -> Result<Option<Pair>> {
 		self.0.read().key_pair::<Pair>(public)
 	}
+
+	/// Check that all the keys of the given `Pair` can be unlocked with the keystore password.
+	///
+	/// Returns `Err(Error::InvalidPassword)` for the first key that can't.
+	pub fn check_password<Pair: AppPair>(&self) -> Result<()> {
+		self.0.read().check_password::<Pair>()
+	}
 }
 
 #[async_trait]
@@ -530,6 +538,18 @@ impl KeystoreInner {
 		self.key_pair_by_type::<Pair::Generic>(IsWrappedBy::from_ref(public), Pair::ID)
 			.map(|v| v.map(Into::into))
 	}
+
+	/// Check that all the keys of the given `Pair` can be unlocked with the keystore password.
+	fn check_password<Pair: AppPair>(&self) -> Result<()> {
+		for public in self.raw_public_keys(Pair::ID)? {
+			// Skip the keys of another crypto that share the key type.
+			if let Ok(public) = <Pair::Generic as PairT>::Public::try_from(public.as_slice()) {
+				self.key_pair_by_type::<Pair::Generic>(&public, Pair::ID)?;
+			}
+		}
+
+		Ok(())
+	}
 }
 
 #[cfg(test)]
@@ -652,6 +672,19 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn check_password_fails_with_wrong_password() {
+		let temp_dir = TempDir::new().unwrap();
+		let store =
+			LocalKeystore::open(temp_dir.path(), Some(FromStr::from_str("password").unwrap()))
+				.unwrap();
+		SyncCryptoStore::ed25519_generate_new(&store, ed25519::AppPair::ID, None).unwrap();
+		assert!(store.check_password::<ed25519::AppPair>().is_ok());
+
+		let store = LocalKeystore::open(temp_dir.path(), None).unwrap();
+		assert!(matches!(store.check_password::<ed25519::AppPair>(), Err(Error::InvalidPassword)));
+	}
+
 	#[test]
 	fn public_keys_are_returned() {
 		let temp_dir = TempDir::new().unwrap();
@@ -60,6 +60,12 @@
 //! opens the door for neighbor status packets to be baked into the gossip protocol.
 //! These status packets will typically contain light pieces of information
 //! used to inform peers of a current view of protocol state.
+//!
+//! # Typed channels
+//!
+//! For gossiping the values of a SCALE encodable type, the [`typed::channel`] sets up the
+//! `GossipEngine` and a validator accepting every message that decodes, and returns a typed
+//! sender and receiver.
 
 pub use self::{
 	bridge::GossipEngine,
@@ -74,6 +80,7 @@ use std::{borrow::Cow, iter, pin::Pin, sync::Arc};
 
 mod bridge;
 mod state_machine;
+pub mod typed;
 mod validator;
 
 /// Abstraction over a network.
@@ -0,0 +1,245 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Typed gossip channels.
+//!
+//! A [`channel`] gossips the values of a SCALE encodable type over a notifications protocol of
+//! its own, without having to implement a [`Validator`]. Every message that decodes is relayed to
+//! the other peers during [`MESSAGE_LIFETIME`], then dropped.
+
+use crate::{
+	GossipEngine, Network, TopicNotification, ValidationResult, Validator, ValidatorContext,
+};
+use codec::{Decode, DecodeAll, Encode};
+use futures::{channel::mpsc, prelude::*};
+use libp2p::PeerId;
+use parking_lot::Mutex;
+use prometheus_endpoint::Registry;
+use sc_network::config::{NonDefaultSetConfig, NonReservedPeerMode, SetConfig};
+use sp_runtime::traits::{Block as BlockT, Hash, HashFor};
+use std::{
+	borrow::Cow,
+	collections::HashMap,
+	marker::PhantomData,
+	pin::Pin,
+	sync::Arc,
+	task::{Context, Poll},
+	time::{Duration, Instant},
+};
+
+/// How long a message is relayed to the peers.
+pub const MESSAGE_LIFETIME: Duration = Duration::from_secs(30);
+
+/// The maximum size of an encoded message.
+const MAX_MESSAGE_SIZE: u64 = 1024 * 1024;
+
+/// The configuration of the peer set of `protocol`.
+///
+/// It has to be added to the `extra_sets` of the network configuration before building the
+/// network. Like for the other gossip protocols, the peers are the peers the node syncs with.
+pub fn set_config(protocol: impl Into<Cow<'static, str>>) -> NonDefaultSetConfig {
+	NonDefaultSetConfig {
+		notifications_protocol: protocol.into(),
+		fallback_names: Vec::new(),
+		max_notification_size: MAX_MESSAGE_SIZE,
+		set_config: SetConfig {
+			in_peers: 0,
+			out_peers: 0,
+			reserved_nodes: Vec::new(),
+			non_reserved_mode: NonReservedPeerMode::Deny,
+		},
+	}
+}
+
+/// Create a gossip channel of `T` messages over `protocol`.
+///
+/// The protocol has to be registered with [`set_config`]. The returned future drives the gossip
+/// and has to be spawned, it ends when the network does.
+pub fn channel<B, N, T>(
+	network: N,
+	protocol: impl Into<Cow<'static, str>>,
+	metrics_registry: Option<&Registry>,
+) -> (Sender<B, T>, Receiver<T>, impl Future<Output = ()> + Send)
+where
+	B: BlockT + 'static,
+	N: Network<B> + Send + Clone + 'static,
+	T: Encode + Decode + 'static,
+{
+	let protocol = protocol.into();
+	// All the messages of the channel share the same topic.
+	let topic = HashFor::<B>::hash(protocol.as_bytes());
+	let validator = Arc::new(TypedValidator::<B, T>::new(topic));
+	let engine = Arc::new(Mutex::new(GossipEngine::new(
+		network,
+		protocol,
+		validator.clone(),
+		metrics_registry,
+	)));
+	let messages = engine.lock().messages_for(topic);
+
+	let worker = {
+		let engine = engine.clone();
+		future::poll_fn(move |cx| engine.lock().poll_unpin(cx))
+	};
+
+	(Sender { engine, validator, topic }, Receiver { messages, _marker: PhantomData }, worker)
+}
+
+/// Sends messages to the peers of a gossip [`channel`].
+pub struct Sender<B: BlockT, T> {
+	engine: Arc<Mutex<GossipEngine<B>>>,
+	validator: Arc<TypedValidator<B, T>>,
+	topic: B::Hash,
+}
+
+impl<B: BlockT, T> Clone for Sender<B, T> {
+	fn clone(&self) -> Self {
+		Self { engine: self.engine.clone(), validator: self.validator.clone(), topic: self.topic }
+	}
+}
+
+impl<B: BlockT, T: Encode> Sender<B, T> {
+	/// Gossip `message` to the peers, which relay it to their own peers.
+	pub fn send(&self, message: &T) {
+		let message = message.encode();
+		self.validator.note_message(&message);
+		self.engine.lock().gossip_message(self.topic, message, false);
+	}
+}
+
+/// A message received on a gossip [`channel`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification<T> {
+	/// The decoded message.
+	pub message: T,
+	/// The peer the message was received from, if known.
+	pub sender: Option<PeerId>,
+}
+
+/// Receives the messages that the peers send on a gossip [`channel`].
+///
+/// The messages sent by the local node aren't received.
+pub struct Receiver<T> {
+	messages: mpsc::Receiver<TopicNotification>,
+	_marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Decode> Stream for Receiver<T> {
+	type Item = Notification<T>;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		loop {
+			match futures::ready!(self.messages.poll_next_unpin(cx)) {
+				Some(TopicNotification { message, sender }) =>
+					if let Ok(message) = T::decode_all(&message) {
+						return Poll::Ready(Some(Notification { message, sender }))
+					},
+				None => return Poll::Ready(None),
+			}
+		}
+	}
+}
+
+/// Keeps the messages that decode as `T`, until they are older than [`MESSAGE_LIFETIME`].
+struct TypedValidator<B: BlockT, T> {
+	topic: B::Hash,
+	received: Mutex<HashMap<B::Hash, Instant>>,
+	_marker: PhantomData<fn() -> T>,
+}
+
+impl<B: BlockT, T> TypedValidator<B, T> {
+	fn new(topic: B::Hash) -> Self {
+		Self { topic, received: Default::default(), _marker: PhantomData }
+	}
+
+	fn note_message(&self, message: &[u8]) {
+		self.received.lock().insert(HashFor::<B>::hash(message), Instant::now());
+	}
+}
+
+impl<B: BlockT, T: Decode> Validator<B> for TypedValidator<B, T> {
+	fn validate(
+		&self,
+		_context: &mut dyn ValidatorContext<B>,
+		_sender: &PeerId,
+		data: &[u8],
+	) -> ValidationResult<B::Hash> {
+		if T::decode_all(data).is_ok() {
+			self.note_message(data);
+			ValidationResult::ProcessAndKeep(self.topic)
+		} else {
+			ValidationResult::Discard
+		}
+	}
+
+	fn message_expired<'a>(&'a self) -> Box<dyn FnMut(B::Hash, &[u8]) -> bool + 'a> {
+		let mut received = self.received.lock();
+		let now = Instant::now();
+		received.retain(|_, at| now.duration_since(*at) < MESSAGE_LIFETIME);
+		Box::new(move |_topic, data| !received.contains_key(&HashFor::<B>::hash(data)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use substrate_test_runtime_client::runtime::Block;
+
+	struct NoOpContext;
+
+	impl ValidatorContext<Block> for NoOpContext {
+		fn broadcast_topic(&mut self, _topic: <Block as BlockT>::Hash, _force: bool) {}
+		fn broadcast_message(
+			&mut self,
+			_topic: <Block as BlockT>::Hash,
+			_message: Vec<u8>,
+			_force: bool,
+		) {
+		}
+		fn send_message(&mut self, _who: &PeerId, _message: Vec<u8>) {}
+		fn send_topic(&mut self, _who: &PeerId, _topic: <Block as BlockT>::Hash, _force: bool) {}
+	}
+
+	#[test]
+	fn keeps_the_messages_that_decode() {
+		let topic = Default::default();
+		let validator = TypedValidator::<Block, (u32, u32)>::new(topic);
+		let sender = PeerId::random();
+
+		let message = (7u32, 8u32).encode();
+		assert!(matches!(
+			validator.validate(&mut NoOpContext, &sender, &message),
+			ValidationResult::ProcessAndKeep(t) if t == topic
+		));
+		assert!(matches!(
+			validator.validate(&mut NoOpContext, &sender, &[1, 2, 3]),
+			ValidationResult::Discard
+		));
+		// Trailing bytes don't decode either.
+		let mut trailing = message.clone();
+		trailing.push(0);
+		assert!(matches!(
+			validator.validate(&mut NoOpContext, &sender, &trailing),
+			ValidationResult::Discard
+		));
+
+		let mut expired = validator.message_expired();
+		assert!(!expired(topic, &message));
+		assert!(expired(topic, &(9u32, 10u32).encode()));
+	}
+}
@@ -30,6 +30,7 @@ pub use crate::{
 	warp_request_handler::WarpSyncProvider,
 };
 pub use libp2p::{build_multiaddr, core::PublicKey, identity};
+pub use sc_peerset::{PeerFilter, ReputationConfig};
 
 // Note: this re-export shouldn't be part of the public API of the crate and will be removed in
 // the future.
@@ -383,6 +384,18 @@ impl From<multiaddr::Error> for ParseErr {
 	}
 }
 
+/// Which transactions of the pool are gossiped to the peers.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TransactionPropagation {
+	/// Gossip all the propagable transactions.
+	All,
+	/// Only gossip the transactions that weren't received from the network, e.g. the ones
+	/// submitted through the RPC.
+	Local,
+	/// Never gossip transactions.
+	None,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 /// Sync operation mode.
 pub enum SyncMode {
@@ -399,6 +412,9 @@ pub enum SyncMode {
 	Warp,
 }
 
+/// Default value of [`NetworkConfiguration::fast_sync_pivot_offset`].
+pub const DEFAULT_FAST_SYNC_PIVOT_OFFSET: u32 = 8;
+
 impl Default for SyncMode {
 	fn default() -> Self {
 		SyncMode::Full
@@ -434,6 +450,9 @@ pub struct NetworkConfiguration {
 	pub max_parallel_downloads: u32,
 	/// Initial syncing mode.
 	pub sync_mode: SyncMode,
+	/// In fast sync, the state is downloaded at the first finalized block that is at most this
+	/// many blocks behind the median best block of the peers.
+	pub fast_sync_pivot_offset: u32,
 
 	/// True if Kademlia random discovery should be enabled.
 	///
@@ -443,12 +462,25 @@ pub struct NetworkConfiguration {
 	/// Should we insert non-global addresses into the DHT?
 	pub allow_non_globals_in_dht: bool,
 
+	/// Ban threshold and decay of the reputation of the peers.
+	pub reputation: ReputationConfig,
+
+	/// Nodes the peer set connects to, or refuses to connect to.
+	pub peer_filter: PeerFilter,
+
+	/// Which transactions of the pool are gossiped to the peers.
+	pub transaction_propagation: TransactionPropagation,
+
 	/// Require iterative Kademlia DHT queries to use disjoint paths for increased resiliency in
 	/// the presence of potentially adversarial nodes.
 	pub kademlia_disjoint_query_paths: bool,
 	/// Enable serving block data over IPFS bitswap.
 	pub ipfs_server: bool,
 
+	/// Maximum number of warp sync proofs generated at a time for the peers, `None` to generate
+	/// them one at a time on the task of the request handler.
+	pub max_concurrent_warp_requests: Option<usize>,
+
 	/// Size of Yamux receive window of all substreams. `None` for the default (256kiB).
 	/// Any value less than 256kiB is invalid.
 	///
@@ -493,9 +525,14 @@ impl NetworkConfiguration {
 			transport: TransportConfig::Normal { enable_mdns: false, allow_private_ipv4: true },
 			max_parallel_downloads: 5,
 			sync_mode: SyncMode::Full,
+			fast_sync_pivot_offset: DEFAULT_FAST_SYNC_PIVOT_OFFSET,
 			enable_dht_random_walk: true,
 			allow_non_globals_in_dht: false,
+			reputation: Default::default(),
+			peer_filter: Default::default(),
+			transaction_propagation: TransactionPropagation::All,
 			kademlia_disjoint_query_paths: false,
+			max_concurrent_warp_requests: None,
 			yamux_window_size: None,
 			ipfs_server: false,
 		}
@@ -275,7 +275,7 @@ pub use protocol::{
 };
 pub use service::{
 	IfDisconnected, NetworkService, NetworkWorker, NotificationSender, NotificationSenderReady,
-	OutboundFailure, RequestFailure,
+	NotificationsTraffic, OutboundFailure, RequestFailure,
 };
 
 pub use sc_peerset::ReputationChange;
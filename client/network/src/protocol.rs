@@ -22,6 +22,7 @@ use crate::{
 	error,
 	request_responses::RequestFailure,
 	schema::v1::StateResponse,
+	service::traffic::TrafficCounters,
 	utils::{interval, LruHashSet},
 	warp_request_handler::EncodedProof,
 };
@@ -191,6 +192,10 @@ pub struct Protocol<B: BlockT> {
 	boot_node_ids: HashSet<PeerId>,
 	/// A cache for the data that was associated to a block announcement.
 	block_announce_data_cache: lru::LruCache<B::Hash, Vec<u8>>,
+	/// Name of the block announces protocol, under which its traffic is counted.
+	block_announces_protocol: Cow<'static, str>,
+	/// Bytes of the notifications sent and received, by protocol.
+	notifications_traffic: TrafficCounters,
 }
 
 #[derive(Debug)]
@@ -230,6 +235,8 @@ pub struct ProtocolConfig {
 	pub max_parallel_downloads: u32,
 	/// Enable state sync.
 	pub sync_mode: config::SyncMode,
+	/// Distance to the median best block of the peers under which state sync starts.
+	pub fast_sync_pivot_offset: u32,
 }
 
 impl ProtocolConfig {
@@ -240,7 +247,11 @@ impl ProtocolConfig {
 			match self.sync_mode {
 				config::SyncMode::Full => sync::SyncMode::Full,
 				config::SyncMode::Fast { skip_proofs, storage_chain_mode } =>
-					sync::SyncMode::LightState { skip_proofs, storage_chain_mode },
+					sync::SyncMode::LightState {
+						skip_proofs,
+						storage_chain_mode,
+						pivot_offset: self.fast_sync_pivot_offset,
+					},
 				config::SyncMode::Warp => sync::SyncMode::Warp,
 			}
 		}
@@ -253,6 +264,7 @@ impl Default for ProtocolConfig {
 			roles: Roles::FULL,
 			max_parallel_downloads: 5,
 			sync_mode: config::SyncMode::Full,
+			fast_sync_pivot_offset: config::DEFAULT_FAST_SYNC_PIVOT_OFFSET,
 		}
 	}
 }
@@ -297,6 +309,7 @@ impl<B: BlockT> Protocol<B> {
 		block_announce_validator: Box<dyn BlockAnnounceValidator<B> + Send>,
 		metrics_registry: Option<&Registry>,
 		warp_sync_provider: Option<Arc<dyn WarpSyncProvider<B>>>,
+		notifications_traffic: TrafficCounters,
 	) -> error::Result<(Protocol<B>, sc_peerset::PeersetHandle, Vec<(PeerId, Multiaddr)>)> {
 		let info = chain.info();
 		let sync = ChainSync::new(
@@ -380,7 +393,11 @@ impl<B: BlockT> Protocol<B> {
 				});
 			}
 
-			sc_peerset::Peerset::from_config(sc_peerset::PeersetConfig { sets })
+			sc_peerset::Peerset::from_config(sc_peerset::PeersetConfig {
+				sets,
+				reputation: network_config.reputation,
+				peer_filter: network_config.peer_filter.clone(),
+			})
 		};
 
 		let block_announces_protocol: Cow<'static, str> = Cow::from({
@@ -401,7 +418,7 @@ impl<B: BlockT> Protocol<B> {
 					.encode();
 
 			let sync_protocol_config = notifications::ProtocolConfig {
-				name: block_announces_protocol,
+				name: block_announces_protocol.clone(),
 				fallback_names: Vec::new(),
 				handshake: block_announces_handshake,
 				max_notification_size: MAX_BLOCK_ANNOUNCE_SIZE,
@@ -451,6 +468,8 @@ impl<B: BlockT> Protocol<B> {
 			},
 			boot_node_ids,
 			block_announce_data_cache,
+			block_announces_protocol,
+			notifications_traffic,
 		};
 
 		Ok((protocol, peerset_handle, known_addresses))
@@ -902,8 +921,9 @@ impl<B: BlockT> Protocol<B> {
 					data: Some(data.clone()),
 				};
 
-				self.behaviour
-					.write_notification(who, HARDCODED_PEERSETS_SYNC, message.encode());
+				let message = message.encode();
+				self.notifications_traffic.sent(&self.block_announces_protocol, message.len());
+				self.behaviour.write_notification(who, HARDCODED_PEERSETS_SYNC, message);
 			}
 		}
 	}
@@ -1730,6 +1750,8 @@ impl<B: BlockT> NetworkBehaviour for Protocol<B> {
 			},
 			NotificationsOut::Notification { peer_id, set_id, message } => match set_id {
 				HARDCODED_PEERSETS_SYNC if self.peers.contains_key(&peer_id) => {
+					self.notifications_traffic
+						.received(&self.block_announces_protocol, message.len());
 					if let Ok(announce) = message::BlockAnnounce::decode(&mut message.as_ref()) {
 						self.push_block_announce_validation(peer_id, announce);
 
@@ -75,6 +75,8 @@ fn build_nodes() -> (Swarm<CustomProtoWithAddr>, Swarm<CustomProtoWithAddr>) {
 				reserved_nodes: Default::default(),
 				reserved_only: false,
 			}],
+			reputation: Default::default(),
+			peer_filter: Default::default(),
 		});
 
 		let behaviour = CustomProtoWithAddr {
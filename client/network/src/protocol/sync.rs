@@ -93,9 +93,6 @@ const MAX_CONCURRENT_BLOCK_ANNOUNCE_VALIDATIONS: usize = 256;
 /// See [`MAX_CONCURRENT_BLOCK_ANNOUNCE_VALIDATIONS`] for more information.
 const MAX_CONCURRENT_BLOCK_ANNOUNCE_VALIDATIONS_PER_PEER: usize = 4;
 
-/// Pick the state to sync as the latest finalized number minus this.
-const STATE_SYNC_FINALITY_THRESHOLD: u32 = 8;
-
 /// We use a heuristic that with a high likelihood, by the time
 /// `MAJOR_SYNC_BLOCKS` have been imported we'll be on the same
 /// chain as (or at least closer to) the peer so we want to delay
@@ -513,8 +510,9 @@ pub enum SyncMode {
 	Light,
 	// Sync headers and block bodies
 	Full,
-	// Sync headers and the last finalied state
-	LightState { storage_chain_mode: bool, skip_proofs: bool },
+	// Sync headers and the last finalied state, picked once it is at most `pivot_offset` blocks
+	// behind the peers.
+	LightState { storage_chain_mode: bool, skip_proofs: bool, pivot_offset: u32 },
 	// Warp sync mode.
 	Warp,
 }
@@ -1558,14 +1556,14 @@ impl<B: BlockT> ChainSync<B> {
 			is_descendent_of(&**client, base, block)
 		});
 
-		if let SyncMode::LightState { skip_proofs, .. } = &self.mode {
+		if let SyncMode::LightState { skip_proofs, pivot_offset, .. } = &self.mode {
 			if self.state_sync.is_none() && !self.peers.is_empty() && self.queue_blocks.is_empty() {
 				// Finalized a recent block.
 				let mut heads: Vec<_> =
 					self.peers.iter().map(|(_, peer)| peer.best_number).collect();
 				heads.sort();
 				let median = heads[heads.len() / 2];
-				if number + STATE_SYNC_FINALITY_THRESHOLD.saturated_into() >= median {
+				if number + (*pivot_offset).saturated_into() >= median {
 					if let Ok(Some(header)) = self.client.header(BlockId::hash(hash.clone())) {
 						log::debug!(
 							target: "sync",
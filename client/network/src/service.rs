@@ -30,7 +30,7 @@
 use crate::{
 	behaviour::{self, Behaviour, BehaviourOut},
 	bitswap::Bitswap,
-	config::{parse_str_addr, Params, TransportConfig},
+	config::{parse_str_addr, Params, PeerFilter, TransportConfig},
 	discovery::DiscoveryConfig,
 	error::Error,
 	light_client_requests,
@@ -88,15 +88,18 @@ use std::{
 	},
 	task::Poll,
 };
+use traffic::TrafficCounters;
 
 pub use behaviour::{
 	IfDisconnected, InboundFailure, OutboundFailure, RequestFailure, ResponseFailure,
 };
+pub use traffic::NotificationsTraffic;
 
 mod metrics;
 mod out_events;
 #[cfg(test)]
 mod tests;
+pub(crate) mod traffic;
 
 /// Substrate network service. Handles network IO and manages connectivity.
 pub struct NetworkService<B: BlockT + 'static, H: ExHashT> {
@@ -121,6 +124,8 @@ pub struct NetworkService<B: BlockT + 'static, H: ExHashT> {
 	/// Field extracted from the [`Metrics`] struct and necessary to report the
 	/// notifications-related metrics.
 	notifications_sizes_metric: Option<HistogramVec>,
+	/// Bytes of the notifications sent and received, by protocol.
+	notifications_traffic: TrafficCounters,
 	/// Marker to pin the `H` generic. Serves no purpose except to not break backwards
 	/// compatibility.
 	_marker: PhantomData<H>,
@@ -192,11 +197,13 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			None => (None, None),
 		};
 
+		let notifications_traffic = TrafficCounters::default();
 		let (protocol, peerset_handle, mut known_addresses) = Protocol::new(
 			protocol::ProtocolConfig {
 				roles: From::from(&params.role),
 				max_parallel_downloads: params.network_config.max_parallel_downloads,
 				sync_mode: params.network_config.sync_mode.clone(),
+				fast_sync_pivot_offset: params.network_config.fast_sync_pivot_offset,
 			},
 			params.chain.clone(),
 			params.protocol_id.clone(),
@@ -210,6 +217,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			params.block_announce_validator,
 			params.metrics_registry.as_ref(),
 			warp_sync_provider,
+			notifications_traffic.clone(),
 		)?;
 
 		// List of multiaddresses that we know in the network.
@@ -412,6 +420,13 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			Swarm::<B>::add_external_address(&mut swarm, addr.clone(), AddressScore::Infinite);
 		}
 
+		// The peerset only filters the peers it gives slots to, the denied peers are banned so
+		// that no other protocol connects to them either.
+		let peer_filter = params.network_config.peer_filter.clone();
+		for peer_id in &peer_filter.denied {
+			Swarm::<B>::ban_peer_id(&mut swarm, *peer_id);
+		}
+
 		let external_addresses = Arc::new(Mutex::new(Vec::new()));
 		let peers_notifications_sinks = Arc::new(Mutex::new(HashMap::new()));
 
@@ -427,6 +442,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			notifications_sizes_metric: metrics
 				.as_ref()
 				.map(|metrics| metrics.notifications_sizes.clone()),
+			notifications_traffic,
 			_marker: PhantomData,
 		});
 
@@ -452,6 +468,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			tx_handler_controller,
 			metrics,
 			boot_node_ids,
+			peer_filter,
 		})
 	}
 
@@ -757,6 +774,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 				.with_label_values(&["out", &protocol])
 				.observe(message.len() as f64);
 		}
+		self.notifications_traffic.sent(&protocol, message.len());
 
 		// Sending is communicated to the `NotificationsSink`.
 		trace!(
@@ -859,7 +877,12 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 			.as_ref()
 			.map(|histogram| histogram.with_label_values(&["out", &protocol]));
 
-		Ok(NotificationSender { sink, protocol_name: protocol, notification_size_metric })
+		Ok(NotificationSender {
+			sink,
+			protocol_name: protocol,
+			notification_size_metric,
+			notifications_traffic: self.notifications_traffic.clone(),
+		})
 	}
 
 	/// Returns a stream containing the events that happen on the network.
@@ -1222,6 +1245,12 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 		self.num_connected.load(Ordering::Relaxed)
 	}
 
+	/// Returns the number of bytes of notifications sent and received so far, by protocol,
+	/// including the block announces.
+	pub fn notifications_traffic(&self) -> HashMap<Cow<'static, str>, NotificationsTraffic> {
+		self.notifications_traffic.get()
+	}
+
 	/// Inform the network service about new best imported block.
 	pub fn new_best_block_imported(&self, hash: B::Hash, number: NumberFor<B>) {
 		let _ = self
@@ -1315,6 +1344,9 @@ pub struct NotificationSender {
 	/// Field extracted from the [`Metrics`] struct and necessary to report the
 	/// notifications-related metrics.
 	notification_size_metric: Option<Histogram>,
+
+	/// Bytes of the notifications sent and received, by protocol.
+	notifications_traffic: TrafficCounters,
 }
 
 impl NotificationSender {
@@ -1331,6 +1363,7 @@ impl NotificationSender {
 			peer_id: self.sink.peer_id(),
 			protocol_name: &self.protocol_name,
 			notification_size_metric: self.notification_size_metric.clone(),
+			notifications_traffic: &self.notifications_traffic,
 		})
 	}
 }
@@ -1349,6 +1382,9 @@ pub struct NotificationSenderReady<'a> {
 	/// Field extracted from the [`Metrics`] struct and necessary to report the
 	/// notifications-related metrics.
 	notification_size_metric: Option<Histogram>,
+
+	/// Bytes of the notifications sent and received, by protocol.
+	notifications_traffic: &'a TrafficCounters,
 }
 
 impl<'a> NotificationSenderReady<'a> {
@@ -1359,6 +1395,7 @@ impl<'a> NotificationSenderReady<'a> {
 		if let Some(notification_size_metric) = &self.notification_size_metric {
 			notification_size_metric.observe(notification.len() as f64);
 		}
+		self.notifications_traffic.sent(self.protocol_name, notification.len());
 
 		trace!(
 			target: "sub-libp2p",
@@ -1458,6 +1495,8 @@ pub struct NetworkWorker<B: BlockT + 'static, H: ExHashT> {
 	peers_notifications_sinks: Arc<Mutex<HashMap<(PeerId, Cow<'static, str>), NotificationsSink>>>,
 	/// Controller for the handler of incoming and outgoing transactions.
 	tx_handler_controller: transactions::TransactionsHandlerController<H>,
+	/// The peers that are connected to, the other ones are banned once they connect.
+	peer_filter: PeerFilter,
 }
 
 impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
@@ -1832,6 +1871,9 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 								.observe(message.len() as f64);
 						}
 					}
+					for (protocol, message) in &messages {
+						this.service.notifications_traffic.received(protocol, message.len());
+					}
 					this.event_streams.send(Event::NotificationsReceived { remote, messages });
 				},
 				Poll::Ready(SwarmEvent::Behaviour(BehaviourOut::SyncConnected(remote))) => {
@@ -1863,6 +1905,11 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 				}) => {
 					debug!(target: "sub-libp2p", "Libp2p => Connected({:?})", peer_id);
 
+					if !this.peer_filter.is_allowed(&peer_id) {
+						debug!(target: "sub-libp2p", "Libp2p => Banning filtered out {:?}", peer_id);
+						Swarm::<B>::ban_peer_id(&mut this.network_service, peer_id);
+					}
+
 					if let Some(metrics) = this.metrics.as_ref() {
 						let direction = match endpoint {
 							ConnectedPoint::Dialer { .. } => "out",
@@ -661,3 +661,51 @@ fn ensure_public_addresses_consistent_with_transport_not_memory() {
 		..config::NetworkConfiguration::new("test-node", "test-client", Default::default(), None)
 	});
 }
+
+#[test]
+fn denied_peers_are_never_connected() {
+	let listen_addr = config::build_multiaddr![Memory(rand::random::<u64>())];
+	let denied_key = libp2p::identity::ed25519::SecretKey::generate();
+	let denied_id = PeerId::from_public_key(
+		libp2p::identity::Keypair::Ed25519(denied_key.clone().into()).public(),
+	);
+
+	let (node1, _) = build_test_full_node(config::NetworkConfiguration {
+		listen_addresses: vec![listen_addr.clone()],
+		transport: config::TransportConfig::MemoryOnly,
+		peer_filter: config::PeerFilter {
+			allowed: None,
+			denied: std::iter::once(denied_id.clone()).collect(),
+		},
+		..config::NetworkConfiguration::new_local()
+	});
+
+	// The denied node dials the other one, which only the network can refuse.
+	let (node2, _) = build_test_full_node(config::NetworkConfiguration {
+		listen_addresses: vec![],
+		transport: config::TransportConfig::MemoryOnly,
+		node_key: config::NodeKeyConfig::Ed25519(config::Secret::Input(denied_key)),
+		default_peers_set: config::SetConfig {
+			reserved_nodes: vec![config::MultiaddrWithPeerId {
+				multiaddr: listen_addr,
+				peer_id: node1.local_peer_id().clone(),
+			}],
+			..Default::default()
+		},
+		..config::NetworkConfiguration::new_local()
+	});
+	assert_eq!(node2.local_peer_id(), &denied_id);
+
+	async_std::task::block_on(async move {
+		futures_timer::Delay::new(Duration::from_secs(5)).await;
+
+		// The client version would be known from the identify protocol if the nodes had connected.
+		let state = node1.network_state().await.unwrap();
+		let denied_id = denied_id.to_base58();
+		assert!(!state.connected_peers.contains_key(&denied_id));
+		assert!(state
+			.not_connected_peers
+			.get(&denied_id)
+			.map_or(true, |peer| peer.version_string.is_none()));
+	});
+}
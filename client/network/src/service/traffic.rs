@@ -0,0 +1,93 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Accounting of the bytes of notifications, by protocol.
+//!
+//! Unlike the `sub_libp2p_notifications_sizes` histogram, the counters are kept whether or not
+//! the network has a Prometheus registry, and can be read through the service.
+
+use parking_lot::Mutex;
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
+
+/// Number of bytes of the notifications sent and received on a protocol.
+///
+/// Only the payload of the notifications is counted, not the framing and the encryption overhead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NotificationsTraffic {
+	/// Bytes of the notifications received from the peers.
+	pub bytes_received: u64,
+	/// Bytes of the notifications sent to the peers.
+	pub bytes_sent: u64,
+}
+
+/// The traffic of every protocol, shared between the service, its worker and the notification
+/// senders.
+#[derive(Clone, Default)]
+pub struct TrafficCounters(Arc<Mutex<HashMap<Cow<'static, str>, NotificationsTraffic>>>);
+
+impl TrafficCounters {
+	pub(crate) fn received(&self, protocol: &str, bytes: usize) {
+		self.update(protocol, |traffic| {
+			traffic.bytes_received = traffic.bytes_received.saturating_add(bytes as u64)
+		});
+	}
+
+	pub(crate) fn sent(&self, protocol: &str, bytes: usize) {
+		self.update(protocol, |traffic| {
+			traffic.bytes_sent = traffic.bytes_sent.saturating_add(bytes as u64)
+		});
+	}
+
+	/// Only copies the name of the protocol the first time, as this is called for every
+	/// notification.
+	fn update(&self, protocol: &str, f: impl FnOnce(&mut NotificationsTraffic)) {
+		let mut counters = self.0.lock();
+		match counters.get_mut(protocol) {
+			Some(traffic) => f(traffic),
+			None => f(counters.entry(Cow::Owned(protocol.to_owned())).or_default()),
+		}
+	}
+
+	pub(crate) fn get(&self) -> HashMap<Cow<'static, str>, NotificationsTraffic> {
+		self.0.lock().clone()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn counts_by_protocol() {
+		let counters = TrafficCounters::default();
+		let grandpa = Cow::Borrowed("/paritytech/grandpa/1");
+		let transactions = Cow::Borrowed("/sup/transactions/1");
+
+		counters.sent(&grandpa, 10);
+		counters.received(&grandpa, 3);
+		counters.sent(&grandpa, 5);
+		counters.received(&transactions, 7);
+
+		let traffic = counters.clone().get();
+		assert_eq!(traffic[&grandpa], NotificationsTraffic { bytes_received: 3, bytes_sent: 15 });
+		assert_eq!(
+			traffic[&transactions],
+			NotificationsTraffic { bytes_received: 7, bytes_sent: 0 }
+		);
+	}
+}
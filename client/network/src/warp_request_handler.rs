@@ -16,16 +16,20 @@
 
 //! Helper for handling (i.e. answering) grandpa warp sync requests from a remote peer.
 
-use crate::config::{IncomingRequest, OutgoingResponse, ProtocolId, RequestResponseConfig};
+use crate::{
+	config::{IncomingRequest, OutgoingResponse, ProtocolId, RequestResponseConfig},
+	ReputationChange,
+};
 use codec::{Decode, Encode};
 use futures::{
 	channel::{mpsc, oneshot},
-	stream::StreamExt,
+	stream::{FuturesUnordered, StreamExt},
 };
 use log::debug;
+use sp_core::traits::SpawnNamed;
 use sp_finality_grandpa::{AuthorityList, SetId};
 use sp_runtime::traits::Block as BlockT;
-use std::{sync::Arc, time::Duration};
+use std::{collections::VecDeque, sync::Arc, time::Duration};
 
 /// Scale-encoded warp sync proof response.
 pub struct EncodedProof(pub Vec<u8>);
@@ -39,6 +43,16 @@ pub struct Request<B: BlockT> {
 
 const MAX_RESPONSE_SIZE: u64 = 16 * 1024 * 1024;
 
+/// Number of requests waiting for the proofs being generated, with a limit of concurrent requests.
+const MAX_QUEUED_REQUESTS: usize = 16;
+
+mod rep {
+	use super::ReputationChange as Rep;
+
+	/// Reputation change when a peer sent a request while too many proofs were being generated.
+	pub const TOO_MANY_REQUESTS: Rep = Rep::new(-(1 << 10), "Too many warp sync requests");
+}
+
 /// Proof verification result.
 pub enum VerificationResult<Block: BlockT> {
 	/// Proof is valid, but the target was not reached.
@@ -92,6 +106,8 @@ fn generate_protocol_name(protocol_id: ProtocolId) -> String {
 pub struct RequestHandler<TBlock: BlockT> {
 	backend: Arc<dyn WarpSyncProvider<TBlock>>,
 	request_receiver: mpsc::Receiver<IncomingRequest>,
+	/// Maximum number of proofs generated at a time, and the spawner of their generation.
+	concurrency: Option<(usize, Box<dyn SpawnNamed>)>,
 }
 
 impl<TBlock: BlockT> RequestHandler<TBlock> {
@@ -105,7 +121,21 @@ impl<TBlock: BlockT> RequestHandler<TBlock> {
 		let mut request_response_config = generate_request_response_config(protocol_id);
 		request_response_config.inbound_queue = Some(tx);
 
-		(Self { backend, request_receiver }, request_response_config)
+		(Self { backend, request_receiver, concurrency: None }, request_response_config)
+	}
+
+	/// Generate up to `limit` proofs at a time, on blocking tasks of `spawner`.
+	///
+	/// Up to 16 requests above the limit wait for a proof to be generated, the next ones are
+	/// refused with a reputation change of their peer. By default, the proofs are generated one at
+	/// a time by [`Self::run`].
+	pub fn with_max_concurrent_requests(
+		mut self,
+		limit: usize,
+		spawner: Box<dyn SpawnNamed>,
+	) -> Self {
+		self.concurrency = Some((limit, spawner));
+		self
 	}
 
 	fn handle_request(
@@ -115,39 +145,114 @@ impl<TBlock: BlockT> RequestHandler<TBlock> {
 	) -> Result<(), HandleRequestError> {
 		let request = Request::<TBlock>::decode(&mut &payload[..])?;
 
-		let EncodedProof(proof) = self
-			.backend
-			.generate(request.begin)
-			.map_err(HandleRequestError::InvalidRequest)?;
-
-		pending_response
-			.send(OutgoingResponse {
-				result: Ok(proof),
-				reputation_changes: Vec::new(),
-				sent_feedback: None,
-			})
-			.map_err(|_| HandleRequestError::SendResponse)
+		let proof = self.backend.generate(request.begin);
+		send_proof(proof, pending_response)
 	}
 
 	/// Run [`RequestHandler`].
 	pub async fn run(mut self) {
-		while let Some(request) = self.request_receiver.next().await {
-			let IncomingRequest { peer, payload, pending_response } = request;
+		let (limit, spawner) = match self.concurrency.take() {
+			Some(concurrency) => concurrency,
+			None => {
+				while let Some(request) = self.request_receiver.next().await {
+					let IncomingRequest { peer, payload, pending_response } = request;
+					log_result(&peer, self.handle_request(payload, pending_response));
+				}
+				return
+			},
+		};
 
-			match self.handle_request(payload, pending_response) {
-				Ok(()) => {
-					debug!(target: "sync", "Handled grandpa warp sync request from {}.", peer)
+		let backend = self.backend;
+		let handle = |request: IncomingRequest| {
+			handle_request_concurrently(backend.clone(), spawner.clone(), request)
+		};
+		let mut requests = self.request_receiver.fuse();
+		let mut in_flight = FuturesUnordered::new();
+		let mut queued = VecDeque::new();
+		loop {
+			futures::select! {
+				request = requests.next() => match request {
+					Some(request) if in_flight.len() < limit => in_flight.push(handle(request)),
+					Some(request) if queued.len() < MAX_QUEUED_REQUESTS =>
+						queued.push_back(request),
+					Some(request) => refuse(request),
+					None => break,
 				},
-				Err(e) => debug!(
-					target: "sync",
-					"Failed to handle grandpa warp sync request from {}: {}",
-					peer, e,
-				),
+				() = in_flight.select_next_some() =>
+					if let Some(request) = queued.pop_front() {
+						in_flight.push(handle(request));
+					},
 			}
 		}
 	}
 }
 
+/// Refuse a request over the limit of queued requests.
+fn refuse(request: IncomingRequest) {
+	debug!(target: "sync", "Refusing grandpa warp sync request from {}.", request.peer);
+	let _ = request.pending_response.send(OutgoingResponse {
+		result: Err(()),
+		reputation_changes: vec![rep::TOO_MANY_REQUESTS],
+		sent_feedback: None,
+	});
+}
+
+/// Handle a request, generating the proof on a blocking task of `spawner`.
+async fn handle_request_concurrently<TBlock: BlockT>(
+	backend: Arc<dyn WarpSyncProvider<TBlock>>,
+	spawner: Box<dyn SpawnNamed>,
+	request: IncomingRequest,
+) {
+	let IncomingRequest { peer, payload, pending_response } = request;
+	let result = generate_concurrently(backend, spawner, payload, pending_response).await;
+	log_result(&peer, result);
+}
+
+async fn generate_concurrently<TBlock: BlockT>(
+	backend: Arc<dyn WarpSyncProvider<TBlock>>,
+	spawner: Box<dyn SpawnNamed>,
+	payload: Vec<u8>,
+	pending_response: oneshot::Sender<OutgoingResponse>,
+) -> Result<(), HandleRequestError> {
+	let request = Request::<TBlock>::decode(&mut &payload[..])?;
+
+	let (tx, rx) = oneshot::channel();
+	spawner.spawn_blocking(
+		"warp-sync-proof",
+		Box::pin(async move {
+			let _ = tx.send(backend.generate(request.begin));
+		}),
+	);
+	let proof = rx.await.map_err(|_| HandleRequestError::Cancelled)?;
+	send_proof(proof, pending_response)
+}
+
+fn send_proof(
+	proof: Result<EncodedProof, Box<dyn std::error::Error + Send + Sync>>,
+	pending_response: oneshot::Sender<OutgoingResponse>,
+) -> Result<(), HandleRequestError> {
+	let EncodedProof(proof) = proof.map_err(HandleRequestError::InvalidRequest)?;
+
+	pending_response
+		.send(OutgoingResponse {
+			result: Ok(proof),
+			reputation_changes: Vec::new(),
+			sent_feedback: None,
+		})
+		.map_err(|_| HandleRequestError::SendResponse)
+}
+
+fn log_result(peer: &libp2p::PeerId, result: Result<(), HandleRequestError>) {
+	match result {
+		Ok(()) => debug!(target: "sync", "Handled grandpa warp sync request from {}.", peer),
+		Err(e) => debug!(
+			target: "sync",
+			"Failed to handle grandpa warp sync request from {}: {}",
+			peer, e,
+		),
+	}
+}
+
 #[derive(Debug, derive_more::Display, derive_more::From)]
 enum HandleRequestError {
 	#[display(fmt = "Failed to decode request: {}.", _0)]
@@ -162,4 +267,79 @@ enum HandleRequestError {
 	InvalidRequest(Box<dyn std::error::Error + Send + Sync>),
 	#[display(fmt = "Failed to send response.")]
 	SendResponse,
+	#[display(fmt = "The proof generation was cancelled.")]
+	Cancelled,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::executor::block_on;
+	use std::sync::{mpsc as std_mpsc, Mutex};
+	use substrate_test_runtime_client::runtime::Block;
+
+	/// Generates an empty proof for every message of `release`.
+	struct BlockingProvider {
+		release: Mutex<std_mpsc::Receiver<()>>,
+	}
+
+	impl WarpSyncProvider<Block> for BlockingProvider {
+		fn generate(
+			&self,
+			_start: <Block as BlockT>::Hash,
+		) -> Result<EncodedProof, Box<dyn std::error::Error + Send + Sync>> {
+			self.release.lock().unwrap().recv()?;
+			Ok(EncodedProof(Vec::new()))
+		}
+
+		fn verify(
+			&self,
+			_proof: &EncodedProof,
+			_set_id: SetId,
+			_authorities: AuthorityList,
+		) -> Result<VerificationResult<Block>, Box<dyn std::error::Error + Send + Sync>> {
+			unimplemented!()
+		}
+
+		fn current_authorities(&self) -> AuthorityList {
+			unimplemented!()
+		}
+	}
+
+	#[test]
+	fn refuses_the_requests_beyond_the_queue() {
+		let (release, release_rx) = std_mpsc::channel();
+		let provider = Arc::new(BlockingProvider { release: Mutex::new(release_rx) });
+		let (handler, config) = RequestHandler::<Block>::new(ProtocolId::from("dot"), provider);
+		let handler = handler
+			.with_max_concurrent_requests(1, Box::new(sp_core::testing::TaskExecutor::new()));
+		std::thread::spawn(move || block_on(handler.run()));
+
+		let mut inbound_queue = config.inbound_queue.unwrap();
+		let payload = Request::<Block> { begin: Default::default() }.encode();
+		let mut responses = Vec::new();
+		// One request being handled, the queued ones, and one more.
+		for _ in 0..MAX_QUEUED_REQUESTS + 2 {
+			let (pending_response, response) = oneshot::channel();
+			inbound_queue
+				.try_send(IncomingRequest {
+					peer: libp2p::PeerId::random(),
+					payload: payload.clone(),
+					pending_response,
+				})
+				.unwrap();
+			responses.push(response);
+		}
+
+		let refused = block_on(responses.pop().unwrap()).unwrap();
+		assert_eq!(refused.result, Err(()));
+		assert_eq!(refused.reputation_changes, vec![rep::TOO_MANY_REQUESTS]);
+
+		for response in responses {
+			release.send(()).unwrap();
+			let response = block_on(response).unwrap();
+			assert_eq!(response.result, Ok(Vec::new()));
+			assert!(response.reputation_changes.is_empty());
+		}
+	}
 }
@@ -44,14 +44,29 @@ use std::{
 	sync::Arc,
 	task::{Context, Poll},
 };
+use tokio::sync::{AcquireError, OwnedSemaphorePermit, Semaphore};
 
 /// Wrapper struct used for keeping the hyper_rustls client running.
 #[derive(Clone)]
-pub struct SharedClient(Arc<HyperClient<HttpsConnector<client::HttpConnector>, Body>>);
+pub struct SharedClient {
+	client: Arc<HyperClient<HttpsConnector<client::HttpConnector>, Body>>,
+	/// Permits of the requests in progress, if their number is limited.
+	concurrency: Option<Arc<Semaphore>>,
+}
 
 impl SharedClient {
 	pub fn new() -> Self {
-		Self(Arc::new(HyperClient::builder().build(HttpsConnector::with_native_roots())))
+		Self {
+			client: Arc::new(HyperClient::builder().build(HttpsConnector::with_native_roots())),
+			concurrency: None,
+		}
+	}
+
+	/// Limit the number of requests in progress at the same time to `limit`, across all the
+	/// offchain workers. The requests above the limit wait for a previous one to receive the
+	/// headers of its response, its body being read at the pace of the worker.
+	pub fn with_concurrency(limit: usize) -> Self {
+		Self { concurrency: Some(Arc::new(Semaphore::new(limit))), ..Self::new() }
 	}
 }
 
@@ -69,8 +84,13 @@ pub fn http(shared_client: SharedClient) -> (HttpApi, HttpWorker) {
 		requests: FnvHashMap::default(),
 	};
 
-	let engine =
-		HttpWorker { to_api, from_api, http_client: shared_client.0, requests: Vec::new() };
+	let engine = HttpWorker {
+		to_api,
+		from_api,
+		http_client: shared_client.client,
+		concurrency: shared_client.concurrency,
+		requests: Vec::new(),
+	};
 
 	(api, engine)
 }
@@ -568,14 +588,26 @@ pub struct HttpWorker {
 	from_api: TracingUnboundedReceiver<ApiToWorker>,
 	/// The engine that runs HTTP requests.
 	http_client: Arc<HyperClient<HttpsConnector<client::HttpConnector>, Body>>,
+	/// Permits of the requests in progress, shared with the other workers.
+	concurrency: Option<Arc<Semaphore>>,
 	/// HTTP requests that are being worked on by the engine.
 	requests: Vec<(HttpRequestId, HttpWorkerRequest)>,
 }
 
+/// Permit of a request in progress, released once the response headers arrive.
+type Permit = Option<OwnedSemaphorePermit>;
+
 /// HTTP request being processed by the worker.
 enum HttpWorkerRequest {
+	/// Request is waiting for one of the requests in progress to finish before being dispatched.
+	Queued {
+		/// Resolves once the request may be dispatched.
+		permit: Pin<Box<dyn Future<Output = Result<OwnedSemaphorePermit, AcquireError>> + Send>>,
+		/// The request to dispatch.
+		request: hyper::Request<hyper::Body>,
+	},
 	/// Request has been dispatched and is waiting for a response from the Internet.
-	Dispatched(hyper::client::ResponseFuture),
+	Dispatched(hyper::client::ResponseFuture, Permit),
 	/// Progressively reading the body of the response and sending it to the channel.
 	ReadBody {
 		/// Body to read `Chunk`s from. Only used if the channel is ready to accept data.
@@ -599,11 +631,26 @@ impl Future for HttpWorker {
 		for n in (0..me.requests.len()).rev() {
 			let (id, request) = me.requests.swap_remove(n);
 			match request {
-				HttpWorkerRequest::Dispatched(mut future) => {
+				HttpWorkerRequest::Queued { mut permit, request } => {
+					let permit = match Future::poll(permit.as_mut(), cx) {
+						Poll::Pending => {
+							me.requests.push((id, HttpWorkerRequest::Queued { permit, request }));
+							continue
+						},
+						Poll::Ready(permit) => permit.ok(),
+					};
+
+					let future = me.http_client.request(request);
+					me.requests.push((id, HttpWorkerRequest::Dispatched(future, permit)));
+					cx.waker().wake_by_ref(); // reschedule the task to poll the request
+					continue
+				},
+
+				HttpWorkerRequest::Dispatched(mut future, permit) => {
 					// Check for an HTTP response from the Internet.
 					let response = match Future::poll(Pin::new(&mut future), cx) {
 						Poll::Pending => {
-							me.requests.push((id, HttpWorkerRequest::Dispatched(future)));
+							me.requests.push((id, HttpWorkerRequest::Dispatched(future, permit)));
 							continue
 						},
 						Poll::Ready(Ok(response)) => response,
@@ -613,6 +660,10 @@ impl Future for HttpWorker {
 						},
 					};
 
+					// The body is read at the pace of the offchain worker, which may first write
+					// the body of a queued request.
+					drop(permit);
+
 					// We received a response! Decompose it into its parts.
 					let (head, body) = response.into_parts();
 					let (status_code, headers) = (head.status, head.headers);
@@ -667,9 +718,15 @@ impl Future for HttpWorker {
 			Poll::Pending => {},
 			Poll::Ready(None) => return Poll::Ready(()), // stops the worker
 			Poll::Ready(Some(ApiToWorker::Dispatch { id, request })) => {
-				let future = me.http_client.request(request);
 				debug_assert!(me.requests.iter().all(|(i, _)| *i != id));
-				me.requests.push((id, HttpWorkerRequest::Dispatched(future)));
+				let request = match &me.concurrency {
+					Some(concurrency) => HttpWorkerRequest::Queued {
+						permit: Box::pin(concurrency.clone().acquire_owned()),
+						request,
+					},
+					None => HttpWorkerRequest::Dispatched(me.http_client.request(request), None),
+				};
+				me.requests.push((id, request));
 				cx.waker().wake_by_ref(); // reschedule the task to poll the request
 			},
 		}
@@ -687,7 +744,8 @@ impl fmt::Debug for HttpWorker {
 impl fmt::Debug for HttpWorkerRequest {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match self {
-			HttpWorkerRequest::Dispatched(_) =>
+			HttpWorkerRequest::Queued { .. } => f.debug_tuple("HttpWorkerRequest::Queued").finish(),
+			HttpWorkerRequest::Dispatched(..) =>
 				f.debug_tuple("HttpWorkerRequest::Dispatched").finish(),
 			HttpWorkerRequest::ReadBody { .. } =>
 				f.debug_tuple("HttpWorkerRequest::Response").finish(),
@@ -713,9 +771,14 @@ mod tests {
 	// Returns an `HttpApi` whose worker is ran in the background, and a `SocketAddr` to an HTTP
 	// server that runs in the background as well.
 	macro_rules! build_api_server {
-		() => {{
-			let hyper_client = SHARED_CLIENT.clone();
-			let (api, worker) = http(hyper_client.clone());
+		() => {
+			build_api_server!(SHARED_CLIENT.clone())
+		};
+		($client:expr) => {
+			build_api_server!($client, || hyper::Body::from("Hello World!"))
+		};
+		($client:expr, $body:expr) => {{
+			let (api, worker) = http($client);
 
 			let (addr_tx, addr_rx) = std::sync::mpsc::channel();
 			std::thread::spawn(move || {
@@ -726,9 +789,7 @@ mod tests {
 						hyper::service::make_service_fn(|_| async move {
 							Ok::<_, Infallible>(hyper::service::service_fn(
 								move |_req| async move {
-									Ok::<_, Infallible>(hyper::Response::new(hyper::Body::from(
-										"Hello World!",
-									)))
+									Ok::<_, Infallible>(hyper::Response::new($body()))
 								},
 							))
 						}),
@@ -766,6 +827,75 @@ mod tests {
 		assert_eq!(&buf[..n], b"Hello World!");
 	}
 
+	#[test]
+	fn queues_requests_above_the_concurrency() {
+		let deadline = timestamp::now().add(Duration::from_millis(10_000));
+
+		let (mut api, addr) = build_api_server!(SharedClient::with_concurrency(1));
+
+		let ids: Vec<_> = (0..3)
+			.map(|_| {
+				let id = api.request_start("POST", &format!("http://{}", addr)).unwrap();
+				api.request_write_body(id, &[], Some(deadline)).unwrap();
+				id
+			})
+			.collect();
+
+		for status in api.response_wait(&ids, Some(deadline)) {
+			assert_eq!(status, HttpRequestStatus::Finished(200));
+		}
+		for id in ids {
+			let mut buf = vec![0; 2048];
+			let n = api.response_read_body(id, &mut buf, Some(deadline)).unwrap();
+			assert_eq!(&buf[..n], b"Hello World!");
+		}
+	}
+
+	#[test]
+	fn writes_queued_bodies_while_responses_are_unread() {
+		let deadline = timestamp::now().add(Duration::from_millis(10_000));
+
+		// Responses of many chunks, which fill the buffers of the worker.
+		let (mut api, addr) = build_api_server!(SharedClient::with_concurrency(1), || {
+			let (mut sender, body) = hyper::Body::channel();
+			tokio::spawn(async move {
+				for _ in 0..16 {
+					if sender.send_data("Hello World!".into()).await.is_err() {
+						break
+					}
+				}
+			});
+			body
+		});
+
+		// None of the responses is read before the bodies of all the requests are written.
+		let ids: Vec<_> = (0..3)
+			.map(|_| {
+				let id = api.request_start("POST", &format!("http://{}", addr)).unwrap();
+				for chunk in &[&b"Hello"[..], b", ", b"World", b"!"] {
+					api.request_write_body(id, chunk, Some(deadline)).unwrap();
+				}
+				api.request_write_body(id, &[], Some(deadline)).unwrap();
+				id
+			})
+			.collect();
+
+		for status in api.response_wait(&ids, Some(deadline)) {
+			assert_eq!(status, HttpRequestStatus::Finished(200));
+		}
+		for id in ids {
+			let mut body = Vec::new();
+			let mut buf = vec![0; 2048];
+			loop {
+				match api.response_read_body(id, &mut buf, Some(deadline)).unwrap() {
+					0 => break,
+					n => body.extend_from_slice(&buf[..n]),
+				}
+			}
+			assert_eq!(body, b"Hello World!".repeat(16));
+		}
+	}
+
 	#[test]
 	fn request_start_invalid_call() {
 		let (mut api, addr) = build_api_server!();
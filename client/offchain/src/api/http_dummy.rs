@@ -34,6 +34,10 @@ impl SharedClient {
 	pub fn new() -> Self {
 		Self
 	}
+
+	pub fn with_concurrency(_: usize) -> Self {
+		Self
+	}
 }
 
 /// Creates a pair of [`HttpApi`] and [`HttpWorker`].
@@ -90,19 +90,29 @@ pub struct OffchainWorkers<Client, Block: traits::Block> {
 }
 
 impl<Client, Block: traits::Block> OffchainWorkers<Client, Block> {
-	/// Creates new `OffchainWorkers`.
+	/// Creates new `OffchainWorkers`, running on a thread pool of one thread per CPU.
 	pub fn new(client: Arc<Client>) -> Self {
+		Self::with_threads(client, num_cpus::get())
+	}
+
+	/// Creates new `OffchainWorkers`, running on a thread pool of `threads` threads.
+	pub fn with_threads(client: Arc<Client>, threads: usize) -> Self {
 		let shared_client = api::SharedClient::new();
 		Self {
 			client,
 			_block: PhantomData,
-			thread_pool: Mutex::new(ThreadPool::with_name(
-				"offchain-worker".into(),
-				num_cpus::get(),
-			)),
+			thread_pool: Mutex::new(ThreadPool::with_name("offchain-worker".into(), threads)),
 			shared_client,
 		}
 	}
+
+	/// Limit the number of offchain HTTP requests in progress at the same time to `limit`.
+	///
+	/// The requests above the limit are queued until a previous one receives its response.
+	pub fn with_http_concurrency(mut self, limit: usize) -> Self {
+		self.shared_client = api::SharedClient::with_concurrency(limit);
+		self
+	}
 }
 
 impl<Client, Block: traits::Block> fmt::Debug for OffchainWorkers<Client, Block> {
@@ -48,8 +48,10 @@ use wasm_timer::Delay;
 
 pub use libp2p::PeerId;
 
-/// We don't accept nodes whose reputation is under this value.
+/// We don't accept nodes whose reputation is under this value, unless configured otherwise.
 const BANNED_THRESHOLD: i32 = 82 * (i32::MIN / 100);
+/// Default of [`ReputationConfig::decay_divisor`].
+const DECAY_DIVISOR: u32 = 50;
 /// Reputation change for a node when we get disconnected from it.
 const DISCONNECT_REPUTATION_CHANGE: i32 = -256;
 /// Amount of time between the moment we disconnect from a node and the moment we remove it from
@@ -207,6 +209,51 @@ impl From<u64> for IncomingIndex {
 pub struct PeersetConfig {
 	/// List of sets of nodes the peerset manages.
 	pub sets: Vec<SetConfig>,
+
+	/// Ban threshold and decay of the reputations.
+	pub reputation: ReputationConfig,
+
+	/// Nodes that are never connected to, whatever their reputation.
+	pub peer_filter: PeerFilter,
+}
+
+/// Filter of the nodes the peerset connects to, which is common to all the sets.
+///
+/// The filter also applies to the reserved nodes and the bootnodes. The network bans the nodes
+/// that aren't allowed, so that they aren't connected to by the other protocols either.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PeerFilter {
+	/// If `Some`, only these nodes are connected to.
+	pub allowed: Option<HashSet<PeerId>>,
+
+	/// Nodes that are never connected to.
+	pub denied: HashSet<PeerId>,
+}
+
+impl PeerFilter {
+	/// Whether connecting to `peer_id` is allowed.
+	pub fn is_allowed(&self, peer_id: &PeerId) -> bool {
+		!self.denied.contains(peer_id) &&
+			self.allowed.as_ref().map_or(true, |allowed| allowed.contains(peer_id))
+	}
+}
+
+/// Configuration of the reputation system, which is common to all the sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReputationConfig {
+	/// Nodes whose reputation is under this value are disconnected, and no connection to them
+	/// is accepted or attempted.
+	pub banned_threshold: i32,
+
+	/// Every second, the reputation of each node moves towards zero by `1 / decay_divisor` of
+	/// its value. The default of `50` halves the reputations in about 34 seconds.
+	pub decay_divisor: u32,
+}
+
+impl Default for ReputationConfig {
+	fn default() -> Self {
+		ReputationConfig { banned_threshold: BANNED_THRESHOLD, decay_divisor: DECAY_DIVISOR }
+	}
 }
 
 /// Configuration for a single set of nodes.
@@ -259,6 +306,10 @@ pub struct Peerset {
 	/// Next time to do a periodic call to `alloc_slots` with all sets. This is done once per
 	/// second, to match the period of the reputation updates.
 	next_periodic_alloc_slots: Delay,
+	/// Ban threshold and decay of the reputations.
+	reputation: ReputationConfig,
+	/// Nodes that are never connected to.
+	peer_filter: PeerFilter,
 }
 
 impl Peerset {
@@ -286,6 +337,8 @@ impl Peerset {
 				created: now,
 				latest_time_update: now,
 				next_periodic_alloc_slots: Delay::new(Duration::new(0, 0)),
+				reputation: config.reputation,
+				peer_filter: config.peer_filter,
 			}
 		};
 
@@ -295,6 +348,9 @@ impl Peerset {
 			}
 
 			for peer_id in set_config.bootnodes {
+				if !peerset.peer_filter.is_allowed(&peer_id) {
+					continue
+				}
 				if let peersstate::Peer::Unknown(entry) = peerset.data.peer(set, &peer_id) {
 					entry.discover();
 				} else {
@@ -397,6 +453,11 @@ impl Peerset {
 	///
 	/// > **Note**: This has the same effect as [`PeersetHandle::add_to_peers_set`].
 	pub fn add_to_peers_set(&mut self, set_id: SetId, peer_id: PeerId) {
+		if !self.peer_filter.is_allowed(&peer_id) {
+			trace!(target: "peerset", "Ignoring filtered out node {}", peer_id);
+			return
+		}
+
 		if let peersstate::Peer::Unknown(entry) = self.data.peer(set_id.0, &peer_id) {
 			entry.discover();
 			self.alloc_slots(set_id);
@@ -428,7 +489,7 @@ impl Peerset {
 
 		let mut reputation = self.data.peer_reputation(peer_id.clone());
 		reputation.add_reputation(change.value);
-		if reputation.reputation() >= BANNED_THRESHOLD {
+		if reputation.reputation() >= self.reputation.banned_threshold {
 			trace!(target: "peerset", "Report {}: {:+} to {}. Reason: {}",
 				peer_id, change.value, reputation.reputation(), change.reason
 			);
@@ -472,19 +533,20 @@ impl Peerset {
 		// If we multiply each second the reputation by `k` (where `k` is between 0 and 1), it
 		// takes `ln(0.5) / ln(k)` seconds to reduce the reputation by half. Use this formula to
 		// empirically determine a value of `k` that looks correct.
+		let divisor = self.reputation.decay_divisor.max(1).min(i32::MAX as u32) as i32;
 		for _ in 0..secs_diff {
 			for peer_id in self.data.peers().cloned().collect::<Vec<_>>() {
-				// We use `k = 0.98`, so we divide by `50`. With that value, it takes 34.3 seconds
-				// to reduce the reputation by half.
-				fn reput_tick(reput: i32) -> i32 {
-					let mut diff = reput / 50;
+				// By default we use `k = 0.98`, so we divide by `50`. With that value, it takes
+				// 34.3 seconds to reduce the reputation by half.
+				let reput_tick = |reput: i32| {
+					let mut diff = reput / divisor;
 					if diff == 0 && reput < 0 {
 						diff = -1;
 					} else if diff == 0 && reput > 0 {
 						diff = 1;
 					}
 					reput.saturating_sub(diff)
-				}
+				};
 
 				let mut peer_reputation = self.data.peer_reputation(peer_id.clone());
 
@@ -524,6 +586,10 @@ impl Peerset {
 
 		// Try to connect to all the reserved nodes that we are not connected to.
 		for reserved_node in &self.reserved_nodes[set_id.0].0 {
+			if !self.peer_filter.is_allowed(reserved_node) {
+				continue
+			}
+
 			let entry = match self.data.peer(set_id.0, reserved_node) {
 				peersstate::Peer::Unknown(n) => n.discover(),
 				peersstate::Peer::NotConnected(n) => n,
@@ -534,7 +600,7 @@ impl Peerset {
 			// This is a rather opinionated behaviour, and it wouldn't be fundamentally wrong to
 			// remove that check. If necessary, the peerset should be refactored to give more
 			// control over what happens in that situation.
-			if entry.reputation() < BANNED_THRESHOLD {
+			if entry.reputation() < self.reputation.banned_threshold {
 				break
 			}
 
@@ -572,7 +638,7 @@ impl Peerset {
 			};
 
 			// Don't connect to nodes with an abysmal reputation.
-			if next.reputation() < BANNED_THRESHOLD {
+			if next.reputation() < self.reputation.banned_threshold {
 				break
 			}
 
@@ -604,6 +670,11 @@ impl Peerset {
 
 		self.update_time();
 
+		if !self.peer_filter.is_allowed(&peer_id) {
+			self.message_queue.push_back(Message::Reject(index));
+			return
+		}
+
 		if self.reserved_nodes[set_id.0].1 {
 			if !self.reserved_nodes[set_id.0].0.contains(&peer_id) {
 				self.message_queue.push_back(Message::Reject(index));
@@ -621,7 +692,7 @@ impl Peerset {
 			peersstate::Peer::Unknown(entry) => entry.discover(),
 		};
 
-		if not_connected.reputation() < BANNED_THRESHOLD {
+		if not_connected.reputation() < self.reputation.banned_threshold {
 			self.message_queue.push_back(Message::Reject(index));
 			return
 		}
@@ -763,8 +834,8 @@ pub enum DropReason {
 #[cfg(test)]
 mod tests {
 	use super::{
-		IncomingIndex, Message, Peerset, PeersetConfig, ReputationChange, SetConfig, SetId,
-		BANNED_THRESHOLD,
+		IncomingIndex, Message, PeerFilter, Peerset, PeersetConfig, ReputationChange,
+		ReputationConfig, SetConfig, SetId, BANNED_THRESHOLD,
 	};
 	use futures::prelude::*;
 	use libp2p::PeerId;
@@ -798,6 +869,8 @@ mod tests {
 				reserved_nodes: Default::default(),
 				reserved_only: true,
 			}],
+			reputation: Default::default(),
+			peer_filter: Default::default(),
 		};
 
 		let (peerset, handle) = Peerset::from_config(config);
@@ -831,6 +904,8 @@ mod tests {
 				reserved_nodes: Default::default(),
 				reserved_only: false,
 			}],
+			reputation: Default::default(),
+			peer_filter: Default::default(),
 		};
 
 		let (mut peerset, _handle) = Peerset::from_config(config);
@@ -862,6 +937,8 @@ mod tests {
 				reserved_nodes: Default::default(),
 				reserved_only: true,
 			}],
+			reputation: Default::default(),
+			peer_filter: Default::default(),
 		};
 
 		let (mut peerset, _) = Peerset::from_config(config);
@@ -870,6 +947,41 @@ mod tests {
 		assert_messages(peerset, vec![Message::Reject(ii)]);
 	}
 
+	#[test]
+	fn test_peerset_filter() {
+		let allowed = PeerId::random();
+		let denied = PeerId::random();
+		let unknown = PeerId::random();
+		let config = PeersetConfig {
+			sets: vec![SetConfig {
+				in_peers: 50,
+				out_peers: 50,
+				bootnodes: vec![denied.clone(), allowed.clone()],
+				reserved_nodes: vec![unknown.clone()].into_iter().collect(),
+				reserved_only: false,
+			}],
+			reputation: Default::default(),
+			peer_filter: PeerFilter {
+				allowed: Some(vec![allowed.clone(), denied.clone()].into_iter().collect()),
+				denied: vec![denied.clone()].into_iter().collect(),
+			},
+		};
+
+		let (mut peerset, _) = Peerset::from_config(config);
+		peerset.add_to_peers_set(SetId::from(0), unknown.clone());
+		peerset.incoming(SetId::from(0), denied, IncomingIndex(1));
+		peerset.incoming(SetId::from(0), unknown, IncomingIndex(2));
+
+		assert_messages(
+			peerset,
+			vec![
+				Message::Connect { set_id: SetId::from(0), peer_id: allowed },
+				Message::Reject(IncomingIndex(1)),
+				Message::Reject(IncomingIndex(2)),
+			],
+		);
+	}
+
 	#[test]
 	fn test_peerset_discovered() {
 		let bootnode = PeerId::random();
@@ -883,6 +995,8 @@ mod tests {
 				reserved_nodes: Default::default(),
 				reserved_only: false,
 			}],
+			reputation: Default::default(),
+			peer_filter: Default::default(),
 		};
 
 		let (mut peerset, _handle) = Peerset::from_config(config);
@@ -909,6 +1023,8 @@ mod tests {
 				reserved_nodes: Default::default(),
 				reserved_only: false,
 			}],
+			reputation: Default::default(),
+			peer_filter: Default::default(),
 		});
 
 		// We ban a node by setting its reputation under the threshold.
@@ -942,6 +1058,41 @@ mod tests {
 		futures::executor::block_on(fut);
 	}
 
+	#[test]
+	fn test_peerset_banned_with_custom_threshold() {
+		let (mut peerset, handle) = Peerset::from_config(PeersetConfig {
+			sets: vec![SetConfig {
+				in_peers: 25,
+				out_peers: 25,
+				bootnodes: vec![],
+				reserved_nodes: Default::default(),
+				reserved_only: false,
+			}],
+			reputation: ReputationConfig { banned_threshold: -1000, decay_divisor: 50 },
+			peer_filter: Default::default(),
+		});
+
+		// A report that is far from the default threshold bans the node.
+		let peer_id = PeerId::random();
+		handle.report_peer(peer_id.clone(), ReputationChange::new(-1001, ""));
+
+		let fut = futures::future::poll_fn(move |cx| {
+			// We need one polling for the message to be processed.
+			assert_eq!(Stream::poll_next(Pin::new(&mut peerset), cx), Poll::Pending);
+
+			peerset.incoming(SetId::from(0), peer_id.clone(), IncomingIndex(1));
+			if let Poll::Ready(msg) = Stream::poll_next(Pin::new(&mut peerset), cx) {
+				assert_eq!(msg.unwrap(), Message::Reject(IncomingIndex(1)));
+			} else {
+				panic!()
+			}
+
+			Poll::Ready(())
+		});
+
+		futures::executor::block_on(fut);
+	}
+
 	#[test]
 	fn test_relloc_after_banned() {
 		let (mut peerset, handle) = Peerset::from_config(PeersetConfig {
@@ -952,6 +1103,8 @@ mod tests {
 				reserved_nodes: Default::default(),
 				reserved_only: false,
 			}],
+			reputation: Default::default(),
+			peer_filter: Default::default(),
 		});
 
 		// We ban a node by setting its reputation under the threshold.
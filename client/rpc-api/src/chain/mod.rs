@@ -110,4 +110,32 @@ pub trait ChainApi<Number, Hash, Header, SignedBlock> {
 		metadata: Option<Self::Metadata>,
 		id: SubscriptionId,
 	) -> RpcResult<bool>;
+
+	/// Confirmed head subscription.
+	///
+	/// Notifies about the blocks of the best chain once they are `depth` blocks deep, `depth`
+	/// defaulting to the confirmation depth the node is configured with.
+	#[pubsub(
+		subscription = "chain_confirmedHead",
+		subscribe,
+		name = "chain_subscribeConfirmedHeads"
+	)]
+	fn subscribe_confirmed_heads(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<Header>,
+		depth: Option<u32>,
+	);
+
+	/// Unsubscribe from confirmed head subscription.
+	#[pubsub(
+		subscription = "chain_confirmedHead",
+		unsubscribe,
+		name = "chain_unsubscribeConfirmedHeads"
+	)]
+	fn unsubscribe_confirmed_heads(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> RpcResult<bool>;
 }
@@ -17,10 +17,11 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 //! RPC Metadata
-use std::sync::Arc;
+use std::{net::IpAddr, sync::Arc};
 
 use futures::channel::mpsc;
 use jsonrpc_pubsub::{PubSubMetadata, Session};
+use sc_rpc_server::{ConnectionSubscriptions, RemoteMetadata, SubscriptionMetadata};
 
 /// RPC Metadata.
 ///
@@ -30,6 +31,8 @@ use jsonrpc_pubsub::{PubSubMetadata, Session};
 #[derive(Default, Clone)]
 pub struct Metadata {
 	session: Option<Arc<Session>>,
+	subscriptions: Option<ConnectionSubscriptions>,
+	remote_ip: Option<IpAddr>,
 }
 
 impl jsonrpc_core::Metadata for Metadata {}
@@ -42,7 +45,14 @@ impl PubSubMetadata for Metadata {
 impl Metadata {
 	/// Create new `Metadata` with session (Pub/Sub) support.
 	pub fn new(transport: mpsc::UnboundedSender<String>) -> Self {
-		Metadata { session: Some(Arc::new(Session::new(transport))) }
+		Metadata { session: Some(Arc::new(Session::new(transport))), ..Default::default() }
+	}
+
+	/// The subscriptions of the connection, when they are limited.
+	///
+	/// Subscriptions that end without being cancelled by the client should be reported to it.
+	pub fn subscriptions(&self) -> Option<ConnectionSubscriptions> {
+		self.subscriptions.clone()
 	}
 
 	/// Create new `Metadata` for tests.
@@ -53,6 +63,22 @@ impl Metadata {
 	}
 }
 
+impl SubscriptionMetadata for Metadata {
+	fn with_subscriptions(self, subscriptions: ConnectionSubscriptions) -> Self {
+		Metadata { subscriptions: Some(subscriptions), ..self }
+	}
+}
+
+impl RemoteMetadata for Metadata {
+	fn with_remote_ip(self, remote_ip: IpAddr) -> Self {
+		Metadata { remote_ip: Some(remote_ip), ..self }
+	}
+
+	fn remote_ip(&self) -> Option<IpAddr> {
+		self.remote_ip
+	}
+}
+
 impl From<mpsc::UnboundedSender<String>> for Metadata {
 	fn from(sender: mpsc::UnboundedSender<String>) -> Self {
 		Self::new(sender)
@@ -51,6 +51,18 @@ pub enum Error {
 		/// Maximum allowed value
 		max: u32,
 	},
+	/// The state of the requested block has been pruned.
+	#[display(
+		fmt = "State unavailable at pruned block #{}, the oldest block with state is #{}",
+		block,
+		oldest
+	)]
+	StateUnavailable {
+		/// Number of the requested block.
+		block: u64,
+		/// Number of the oldest block whose state is still available.
+		oldest: u64,
+	},
 	/// Call to an unsafe RPC was denied.
 	UnsafeRpcCalled(crate::policy::UnsafeRpcError),
 }
@@ -80,6 +92,11 @@ impl From<Error> for rpc::Error {
 				message: format!("{}", e),
 				data: None,
 			},
+			Error::StateUnavailable { oldest, .. } => rpc::Error {
+				code: rpc::ErrorCode::ServerError(BASE_ERROR + 3),
+				message: format!("{}", e),
+				data: Some(serde_json::json!({ "oldestAvailableBlock": oldest })),
+			},
 			e => errors::internal(e),
 		}
 	}
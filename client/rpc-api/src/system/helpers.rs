@@ -49,6 +49,30 @@ pub struct Health {
 	///
 	/// Might be false for local chains or when running without discovery.
 	pub should_have_peers: bool,
+	/// Is the node still warming up after its start
+	///
+	/// Only ever true with `--health-warmup`, the health RPC fails while it is.
+	#[serde(default, skip_serializing_if = "is_false")]
+	pub is_warming_up: bool,
+	/// Version of the runtime at the last finalized block, if known.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub runtime: Option<HealthRuntime>,
+}
+
+/// Version of the runtime reported by the health RPC.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthRuntime {
+	/// Name of the runtime specification.
+	pub spec_name: String,
+	/// Version of the runtime specification.
+	pub spec_version: u32,
+	/// Version of the implementation of the specification.
+	pub impl_version: u32,
+}
+
+fn is_false(value: &bool) -> bool {
+	!value
 }
 
 impl fmt::Display for Health {
@@ -105,10 +129,38 @@ mod tests {
 				peers: 1,
 				is_syncing: false,
 				should_have_peers: true,
+				is_warming_up: false,
+				runtime: None,
 			})
 			.unwrap(),
 			r#"{"peers":1,"isSyncing":false,"shouldHavePeers":true}"#,
 		);
+		assert_eq!(
+			::serde_json::to_string(&Health {
+				peers: 1,
+				is_syncing: false,
+				should_have_peers: true,
+				is_warming_up: true,
+				runtime: None,
+			})
+			.unwrap(),
+			r#"{"peers":1,"isSyncing":false,"shouldHavePeers":true,"isWarmingUp":true}"#,
+		);
+		assert_eq!(
+			::serde_json::to_string(&Health {
+				peers: 1,
+				is_syncing: false,
+				should_have_peers: true,
+				is_warming_up: false,
+				runtime: Some(HealthRuntime {
+					spec_name: "node".into(),
+					spec_version: 267,
+					impl_version: 1,
+				}),
+			})
+			.unwrap(),
+			r#"{"peers":1,"isSyncing":false,"shouldHavePeers":true,"runtime":{"specName":"node","specVersion":267,"implVersion":1}}"#,
+		);
 	}
 
 	#[test]
@@ -29,7 +29,7 @@ use self::error::Result as SystemResult;
 
 pub use self::{
 	gen_client::Client as SystemClient,
-	helpers::{Health, NodeRole, PeerInfo, SyncState, SystemInfo},
+	helpers::{Health, HealthRuntime, NodeRole, PeerInfo, SyncState, SystemInfo},
 };
 
 /// Substrate system RPC API
@@ -60,8 +60,14 @@ pub trait SystemApi<Hash, Number> {
 	/// Node is considered healthy if it is:
 	/// - connected to some peers (unless running in dev mode)
 	/// - not performing a major sync
+	///
+	/// The runtime version reported is the one of the last finalized block, which lags behind
+	/// the best block after a runtime upgrade until the upgrade is finalized.
+	///
+	/// Fails with the health as error data while the node is warming up, which makes the HTTP
+	/// `/health` endpoint answer with `503 Service Unavailable`.
 	#[rpc(name = "system_health", returns = "Health")]
-	fn system_health(&self) -> Receiver<Health>;
+	fn system_health(&self) -> BoxFuture<jsonrpc_core::Result<Health>>;
 
 	/// Returns the base58-encoded PeerId of the node.
 	#[rpc(name = "system_localPeerId", returns = "String")]
@@ -0,0 +1,224 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! HTTP server passing the address of its peers to the metadata of their calls.
+//!
+//! The server of `jsonrpc-http-server` doesn't give the address of the peers to the metadata
+//! extractors, so the connections are accepted here and handed to its request handler, with an
+//! extractor per connection.
+
+use std::{convert::Infallible, io, net::SocketAddr, sync::Arc};
+
+use http::{
+	hyper::{self, server::conn::AddrStream, service::Service},
+	tokio, AccessControlAllowOrigin, Host, RequestMiddlewareAction, RestApi, ServerHandler,
+};
+use jsonrpc_core::MetaIoHandler;
+use log::error;
+
+use crate::{RemoteMetadata, RpcHandler, RpcMiddleware, SubscriptionMetadata};
+
+/// HTTP RPC server, stopped when closed or dropped.
+pub struct HttpServer {
+	address: SocketAddr,
+	runtime: Option<tokio::runtime::Runtime>,
+}
+
+impl HttpServer {
+	/// Start the server on `addr`, handling the requests on `threads` threads.
+	///
+	/// `allowed_hosts` are completed with the listening address.
+	pub(crate) fn start<M>(
+		addr: &SocketAddr,
+		threads: usize,
+		cors_domains: Option<Vec<AccessControlAllowOrigin>>,
+		allowed_hosts: Option<Vec<Host>>,
+		rest_api: RestApi,
+		io: RpcHandler<M>,
+		max_request_body_size: usize,
+	) -> io::Result<Self>
+	where
+		M: pubsub::PubSubMetadata + SubscriptionMetadata + RemoteMetadata + Default + Unpin,
+	{
+		let runtime = tokio::runtime::Builder::new_multi_thread()
+			.worker_threads(threads)
+			.thread_name("http.worker")
+			.enable_all()
+			.build()?;
+
+		let listener = std::net::TcpListener::bind(addr)?;
+		let address = listener.local_addr()?;
+		let builder = {
+			let _runtime = runtime.enter();
+			hyper::Server::from_tcp(listener)
+				.map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+		};
+		let allowed_hosts = jsonrpc_server_utils::hosts::update(allowed_hosts, &address);
+
+		let handler: Arc<MetaIoHandler<M, RpcMiddleware>> = Arc::new(io.into());
+		let make_service = hyper::service::make_service_fn(move |socket: &AddrStream| {
+			let remote_ip = socket.remote_addr().ip();
+			// The request handler only keeps a weak reference to it, the connection owns it.
+			let rpc = http::Rpc {
+				handler: handler.clone(),
+				extractor: Arc::new(move |_: &hyper::Request<hyper::Body>| {
+					M::default().with_remote_ip(remote_ip)
+				}),
+			};
+			let mut server_handler = ServerHandler::new(
+				rpc.downgrade(),
+				cors_domains.clone(),
+				None,
+				http::cors::AccessControlAllowHeaders::Any,
+				allowed_hosts.clone(),
+				Arc::new(|request| RequestMiddlewareAction::Proceed {
+					should_continue_on_invalid_cors: false,
+					request,
+				}),
+				rest_api,
+				Some(("/health".into(), "system_health".into())),
+				max_request_body_size,
+				true,
+			);
+			let service = hyper::service::service_fn(move |request| {
+				let _rpc = &rpc;
+				server_handler.call(request)
+			});
+			async { Ok::<_, Infallible>(service) }
+		});
+
+		let server = builder
+			.http1_keepalive(true)
+			.tcp_nodelay(true)
+			// Recover from the accept errors, like too many open files, instead of stopping.
+			.tcp_sleep_on_accept_errors(true)
+			.serve(make_service);
+		runtime.spawn(async {
+			if let Err(err) = server.await {
+				error!("Error running HTTP server: {:?}", err);
+			}
+		});
+
+		Ok(Self { address, runtime: Some(runtime) })
+	}
+
+	/// Returns the address the server listens on.
+	pub fn address(&self) -> &SocketAddr {
+		&self.address
+	}
+
+	/// Stop the server, waiting for its threads to finish.
+	pub fn close(mut self) {
+		self.stop();
+	}
+
+	fn stop(&mut self) {
+		if let Some(runtime) = self.runtime.take() {
+			// Dropping the runtime blocks until its threads finish, which panics in an async
+			// context, the one of the node for instance.
+			let _ = std::thread::spawn(move || drop(runtime)).join();
+		}
+	}
+}
+
+impl Drop for HttpServer {
+	fn drop(&mut self) {
+		self.stop();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{
+		collections::HashSet,
+		io::{Read, Write},
+		net::{IpAddr, TcpStream},
+		num::NonZeroU32,
+	};
+
+	#[derive(Clone, Default)]
+	struct Metadata(Option<IpAddr>);
+
+	impl jsonrpc_core::Metadata for Metadata {}
+
+	impl pubsub::PubSubMetadata for Metadata {
+		fn session(&self) -> Option<Arc<pubsub::Session>> {
+			None
+		}
+	}
+
+	impl SubscriptionMetadata for Metadata {
+		fn with_subscriptions(self, _: crate::ConnectionSubscriptions) -> Self {
+			self
+		}
+	}
+
+	impl RemoteMetadata for Metadata {
+		fn with_remote_ip(self, remote_ip: IpAddr) -> Self {
+			Metadata(Some(remote_ip))
+		}
+
+		fn remote_ip(&self) -> Option<IpAddr> {
+			self.0
+		}
+	}
+
+	fn call(server: &HttpServer) -> String {
+		let body = r#"{"jsonrpc":"2.0","method":"remote_ip","id":1}"#;
+		let mut stream = TcpStream::connect(server.address()).unwrap();
+		write!(
+			stream,
+			"POST / HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+			 Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+			server.address(),
+			body.len(),
+			body,
+		)
+		.unwrap();
+		let mut response = String::new();
+		stream.read_to_string(&mut response).unwrap();
+		response
+	}
+
+	#[test]
+	fn passes_the_address_of_the_peer_to_the_calls() {
+		let limiter = crate::RateLimiter::new(NonZeroU32::new(1).unwrap());
+		let middleware =
+			RpcMiddleware::new(None, HashSet::new(), "http", Some(Arc::new(limiter)), None, None);
+		let mut io = pubsub::PubSubHandler::new(MetaIoHandler::with_middleware(middleware));
+		io.add_method_with_meta("remote_ip", |_, meta: Metadata| async move {
+			Ok(jsonrpc_core::Value::from(meta.0.map(|ip| ip.to_string()).unwrap_or_default()))
+		});
+		let server = HttpServer::start(
+			&"127.0.0.1:0".parse().unwrap(),
+			1,
+			None,
+			None,
+			RestApi::Unsecure,
+			io,
+			crate::RPC_MAX_PAYLOAD_DEFAULT,
+		)
+		.unwrap();
+
+		assert!(call(&server).contains(r#""result":"127.0.0.1""#));
+		// The second connection of the peer shares its limit.
+		assert!(call(&server).contains("Rate limit of 1 calls per second exceeded"));
+		server.close();
+	}
+}
@@ -20,7 +20,11 @@
 
 #![warn(missing_docs)]
 
+#[cfg(not(target_os = "unknown"))]
+mod http_server;
 mod middleware;
+mod rate_limit;
+mod subscription_limit;
 
 use jsonrpc_core::{IoHandlerExtension, MetaIoHandler};
 use log::error;
@@ -28,7 +32,8 @@ use prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
 use pubsub::PubSubMetadata;
 use std::io;
 
-const MEGABYTE: usize = 1024 * 1024;
+/// Number of bytes in a megabyte, the unit of the RPC payload limits.
+pub const MEGABYTE: usize = 1024 * 1024;
 
 /// Maximal payload accepted by RPC servers.
 pub const RPC_MAX_PAYLOAD_DEFAULT: usize = 15 * MEGABYTE;
@@ -44,9 +49,11 @@ pub type RpcHandler<T> = pubsub::PubSubHandler<T, RpcMiddleware>;
 
 pub use self::inner::*;
 pub use middleware::{method_names, RpcMetrics, RpcMiddleware};
+pub use rate_limit::{RateLimiter, RemoteMetadata};
+pub use subscription_limit::{ConnectionSubscriptions, SubscriptionLimiter, SubscriptionMetadata};
 
 /// Construct rpc `IoHandler`
-pub fn rpc_handler<M: PubSubMetadata>(
+pub fn rpc_handler<M: PubSubMetadata + SubscriptionMetadata + RemoteMetadata>(
 	extension: impl IoHandlerExtension<M>,
 	rpc_middleware: RpcMiddleware,
 ) -> RpcHandler<M> {
@@ -117,8 +124,7 @@ mod inner {
 
 	/// Type alias for ipc server
 	pub type IpcServer = ipc::Server;
-	/// Type alias for http server
-	pub type HttpServer = http::Server;
+	pub use crate::http_server::HttpServer;
 	/// Type alias for ws server
 	pub type WsServer = ws::Server;
 
@@ -134,32 +140,39 @@ mod inner {
 
 	/// Start HTTP server listening on given address.
 	///
+	/// The metadata of the calls carry the IP address of their peer.
+	///
 	/// **Note**: Only available if `not(target_os = "unknown")`.
-	pub fn start_http<M: pubsub::PubSubMetadata + Default + Unpin>(
+	pub fn start_http<M>(
 		addr: &std::net::SocketAddr,
 		thread_pool_size: Option<usize>,
 		cors: Option<&Vec<String>>,
+		hosts: &[String],
 		io: RpcHandler<M>,
 		maybe_max_payload_mb: Option<usize>,
-	) -> io::Result<http::Server> {
+	) -> io::Result<HttpServer>
+	where
+		M: PubSubMetadata + SubscriptionMetadata + RemoteMetadata + Default + Unpin,
+	{
 		let max_request_body_size = maybe_max_payload_mb
 			.map(|mb| mb.saturating_mul(MEGABYTE))
 			.unwrap_or(RPC_MAX_PAYLOAD_DEFAULT);
 
-		http::ServerBuilder::new(io)
-			.threads(thread_pool_size.unwrap_or(HTTP_THREADS))
-			.health_api(("/health", "system_health"))
-			.allowed_hosts(hosts_filtering(cors.is_some()))
-			.rest_api(if cors.is_some() { http::RestApi::Secure } else { http::RestApi::Unsecure })
-			.cors(map_cors::<http::AccessControlAllowOrigin>(cors))
-			.max_request_body_size(max_request_body_size)
-			.start_http(addr)
+		HttpServer::start(
+			addr,
+			thread_pool_size.unwrap_or(HTTP_THREADS),
+			map_cors::<http::AccessControlAllowOrigin>(cors).into(),
+			hosts_filtering(cors.is_some(), hosts).into(),
+			if cors.is_some() { http::RestApi::Secure } else { http::RestApi::Unsecure },
+			io,
+			max_request_body_size,
+		)
 	}
 
 	/// Start IPC server listening on given path.
 	///
 	/// **Note**: Only available if `not(target_os = "unknown")`.
-	pub fn start_ipc<M: pubsub::PubSubMetadata + Default>(
+	pub fn start_ipc<M: PubSubMetadata + SubscriptionMetadata + RemoteMetadata + Default>(
 		addr: &str,
 		io: RpcHandler<M>,
 		server_metrics: ServerMetrics,
@@ -178,35 +191,46 @@ mod inner {
 	///
 	/// **Note**: Only available if `not(target_os = "unknown")`.
 	pub fn start_ws<
-		M: pubsub::PubSubMetadata + From<futures::channel::mpsc::UnboundedSender<String>>,
+		M: PubSubMetadata
+			+ SubscriptionMetadata
+			+ RemoteMetadata
+			+ From<futures::channel::mpsc::UnboundedSender<String>>,
 	>(
 		addr: &std::net::SocketAddr,
 		max_connections: Option<usize>,
 		cors: Option<&Vec<String>>,
+		hosts: &[String],
 		io: RpcHandler<M>,
 		maybe_max_payload_mb: Option<usize>,
+		maybe_max_response_mb: Option<usize>,
 		server_metrics: ServerMetrics,
 	) -> io::Result<ws::Server> {
 		let rpc_max_payload = maybe_max_payload_mb
 			.map(|mb| mb.saturating_mul(MEGABYTE))
 			.unwrap_or(RPC_MAX_PAYLOAD_DEFAULT);
-		ws::ServerBuilder::with_meta_extractor(io, |context: &ws::RequestContext| {
-			context.sender().into()
-		})
-		.max_payload(rpc_max_payload)
-		.max_connections(max_connections.unwrap_or(WS_MAX_CONNECTIONS))
-		.allowed_origins(map_cors(cors))
-		.allowed_hosts(hosts_filtering(cors.is_some()))
-		.session_stats(server_metrics)
-		.start(addr)
-		.map_err(|err| match err {
-			ws::Error::Io(io) => io,
-			ws::Error::ConnectionClosed => io::ErrorKind::BrokenPipe.into(),
-			e => {
-				error!("{}", e);
-				io::ErrorKind::Other.into()
-			},
-		})
+		let mut builder =
+			ws::ServerBuilder::with_meta_extractor(io, |context: &ws::RequestContext| {
+				context.sender().into()
+			});
+		// The responses are limited by the middleware, the buffer only has to fit them.
+		if let Some(mb) = maybe_max_response_mb {
+			builder = builder.max_out_buffer_capacity(mb.saturating_mul(MEGABYTE));
+		}
+		builder
+			.max_payload(rpc_max_payload)
+			.max_connections(max_connections.unwrap_or(WS_MAX_CONNECTIONS))
+			.allowed_origins(map_cors(cors))
+			.allowed_hosts(hosts_filtering(cors.is_some(), hosts))
+			.session_stats(server_metrics)
+			.start(addr)
+			.map_err(|err| match err {
+				ws::Error::Io(io) => io,
+				ws::Error::ConnectionClosed => io::ErrorKind::BrokenPipe.into(),
+				e => {
+					error!("{}", e);
+					io::ErrorKind::Other.into()
+				},
+			})
 	}
 
 	fn map_cors<T: for<'a> From<&'a str>>(
@@ -216,12 +240,12 @@ mod inner {
 			.into()
 	}
 
-	fn hosts_filtering(enable: bool) -> http::DomainsValidation<http::Host> {
+	fn hosts_filtering(enable: bool, hosts: &[String]) -> http::DomainsValidation<http::Host> {
 		if enable {
 			// NOTE The listening address is whitelisted by default.
-			// Setting an empty vector here enables the validation
-			// and allows only the listening address.
-			http::DomainsValidation::AllowOnly(vec![])
+			// Setting only the extra `hosts` here enables the validation
+			// and allows them besides the listening address.
+			http::DomainsValidation::AllowOnly(hosts.iter().map(Into::into).collect())
 		} else {
 			http::DomainsValidation::Disabled
 		}
@@ -18,9 +18,9 @@
 
 //! Middleware for RPC requests.
 
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::Arc};
 
-use jsonrpc_core::{FutureOutput, FutureResponse, Metadata, Middleware as RequestMiddleware};
+use jsonrpc_core::{FutureOutput, FutureResponse, Middleware as RequestMiddleware};
 use prometheus_endpoint::{
 	register, CounterVec, HistogramOpts, HistogramVec, Opts, PrometheusError, Registry, U64,
 };
@@ -28,7 +28,19 @@ use prometheus_endpoint::{
 use futures::{future::Either, Future, FutureExt};
 use pubsub::PubSubMetadata;
 
-use crate::RpcHandler;
+use crate::{
+	subscription_limit::LimitExceeded, RateLimiter, RemoteMetadata, RpcHandler,
+	SubscriptionLimiter, SubscriptionMetadata,
+};
+
+/// Code of the error returned to calls exceeding the rate limit.
+const RATE_LIMITED_ERROR: i64 = -32029;
+
+/// Code of the error returned to subscriptions exceeding the limit of their connection.
+const TOO_MANY_SUBSCRIPTIONS_ERROR: i64 = -32030;
+
+/// Code of the error returned to calls whose response exceeds the maximum size.
+const RESPONSE_TOO_LARGE_ERROR: i64 = -32031;
 
 /// Metrics for RPC middleware
 #[derive(Debug, Clone)]
@@ -106,9 +118,9 @@ impl RpcMetrics {
 pub fn method_names<F, M, E>(gen_handler: F) -> Result<HashSet<String>, E>
 where
 	F: FnOnce(RpcMiddleware) -> Result<RpcHandler<M>, E>,
-	M: PubSubMetadata,
+	M: PubSubMetadata + SubscriptionMetadata + RemoteMetadata,
 {
-	let io = gen_handler(RpcMiddleware::new(None, HashSet::new(), "dummy"))?;
+	let io = gen_handler(RpcMiddleware::new(None, HashSet::new(), "dummy", None, None, None))?;
 	Ok(io.iter().map(|x| x.0.clone()).collect())
 }
 
@@ -117,6 +129,9 @@ pub struct RpcMiddleware {
 	metrics: Option<RpcMetrics>,
 	known_rpc_method_names: HashSet<String>,
 	transport_label: String,
+	rate_limiter: Option<Arc<RateLimiter>>,
+	subscription_limiter: Option<Arc<SubscriptionLimiter>>,
+	max_response_size: Option<usize>,
 }
 
 impl RpcMiddleware {
@@ -124,16 +139,32 @@ impl RpcMiddleware {
 	///
 	/// - `metrics`: Will be used to report statistics.
 	/// - `transport_label`: The label that is used when reporting the statistics.
+	/// - `rate_limiter`: Rejects the calls exceeding the rate limit of their peer.
+	/// - `subscription_limiter`: Rejects the subscriptions exceeding the limit of their connection.
+	/// - `max_response_size`: Replaces the responses larger than this number of bytes by an error,
+	///   for the single calls as for the whole batches.
 	pub fn new(
 		metrics: Option<RpcMetrics>,
 		known_rpc_method_names: HashSet<String>,
 		transport_label: &str,
+		rate_limiter: Option<Arc<RateLimiter>>,
+		subscription_limiter: Option<Arc<SubscriptionLimiter>>,
+		max_response_size: Option<usize>,
 	) -> Self {
-		RpcMiddleware { metrics, known_rpc_method_names, transport_label: transport_label.into() }
+		RpcMiddleware {
+			metrics,
+			known_rpc_method_names,
+			transport_label: transport_label.into(),
+			rate_limiter,
+			subscription_limiter,
+			max_response_size,
+		}
 	}
 }
 
-impl<M: Metadata> RequestMiddleware<M> for RpcMiddleware {
+impl<M: PubSubMetadata + SubscriptionMetadata + RemoteMetadata> RequestMiddleware<M>
+	for RpcMiddleware
+{
 	type Future = FutureResponse;
 	type CallFuture = FutureOutput;
 
@@ -149,13 +180,17 @@ impl<M: Metadata> RequestMiddleware<M> for RpcMiddleware {
 	{
 		let metrics = self.metrics.clone();
 		let transport_label = self.transport_label.clone();
+		let max_response_size = self.max_response_size;
 		if let Some(ref metrics) = metrics {
 			metrics.requests_started.with_label_values(&[transport_label.as_str()]).inc();
 		}
 		let r = next(request, meta);
 		Either::Left(
 			async move {
-				let r = r.await;
+				let mut r = r.await;
+				if let Some(max_response_size) = max_response_size {
+					r = limit_batch_size(r, max_response_size);
+				}
 				if let Some(ref metrics) = metrics {
 					metrics.requests_finished.with_label_values(&[transport_label.as_str()]).inc();
 				}
@@ -168,18 +203,55 @@ impl<M: Metadata> RequestMiddleware<M> for RpcMiddleware {
 	fn on_call<F, X>(
 		&self,
 		call: jsonrpc_core::Call,
-		meta: M,
+		mut meta: M,
 		next: F,
 	) -> Either<Self::CallFuture, X>
 	where
 		F: Fn(jsonrpc_core::Call, M) -> X + Send + Sync,
 		X: Future<Output = Option<jsonrpc_core::Output>> + Send + 'static,
 	{
+		if let Some(ref rate_limiter) = self.rate_limiter {
+			if !rate_limiter.try_acquire(meta.remote_ip(), meta.session().as_ref()) {
+				log::debug!(target: "rpc", "[{}] Rate limit exceeded", self.transport_label);
+				return Either::Left(
+					futures::future::ready(rate_limited(&call, rate_limiter)).boxed(),
+				)
+			}
+		}
+
+		let mut pending_subscription = None;
+		if let (Some(limiter), Some(session), jsonrpc_core::Call::MethodCall(method_call)) =
+			(&self.subscription_limiter, meta.session(), &call)
+		{
+			meta = meta.with_subscriptions(limiter.subscriptions(&session));
+			match limiter.check(&session, method_call) {
+				Ok(pending) => pending_subscription = pending,
+				Err(LimitExceeded) => {
+					log::debug!(
+						target: "rpc",
+						"[{}] Subscription limit reached for {}",
+						self.transport_label,
+						method_call.method,
+					);
+					let error = jsonrpc_core::Error {
+						code: jsonrpc_core::ErrorCode::ServerError(TOO_MANY_SUBSCRIPTIONS_ERROR),
+						message: format!(
+							"Limit of {} subscriptions per connection reached",
+							limiter.max_per_connection(),
+						),
+						data: None,
+					};
+					return Either::Left(futures::future::ready(reject(&call, error)).boxed())
+				},
+			}
+		}
+
 		#[cfg(not(target_os = "unknown"))]
 		let start = std::time::Instant::now();
 		let name = call_name(&call, &self.known_rpc_method_names).to_owned();
 		let metrics = self.metrics.clone();
 		let transport_label = self.transport_label.clone();
+		let max_response_size = self.max_response_size;
 		log::trace!(target: "rpc_metrics", "[{}] {} call: {:?}", transport_label, name, &call);
 		if let Some(ref metrics) = metrics {
 			metrics
@@ -190,7 +262,13 @@ impl<M: Metadata> RequestMiddleware<M> for RpcMiddleware {
 		let r = next(call, meta);
 		Either::Left(
 			async move {
-				let r = r.await;
+				let mut r = r.await;
+				if let Some(pending) = pending_subscription {
+					pending.complete(&r);
+				}
+				if let Some(max_response_size) = max_response_size {
+					r = limit_response_size(r, max_response_size);
+				}
 				#[cfg(not(target_os = "unknown"))]
 				let micros = start.elapsed().as_micros();
 				// seems that std::time is not implemented for browser target
@@ -241,9 +319,224 @@ fn call_name<'a>(call: &'a jsonrpc_core::Call, known_methods: &HashSet<String>)
 	}
 }
 
+fn rate_limited(
+	call: &jsonrpc_core::Call,
+	rate_limiter: &RateLimiter,
+) -> Option<jsonrpc_core::Output> {
+	let error = jsonrpc_core::Error {
+		code: jsonrpc_core::ErrorCode::ServerError(RATE_LIMITED_ERROR),
+		message: format!(
+			"Rate limit of {} calls per second exceeded",
+			rate_limiter.calls_per_second()
+		),
+		data: None,
+	};
+
+	reject(call, error)
+}
+
+/// Replace `output` by an error if it serializes to more than `max_size` bytes.
+fn limit_response_size(
+	output: Option<jsonrpc_core::Output>,
+	max_size: usize,
+) -> Option<jsonrpc_core::Output> {
+	let output = output?;
+	match serialized_size(&output) {
+		Some(size) if size > max_size => Some(too_large(output, size, max_size)),
+		_ => Some(output),
+	}
+}
+
+/// Replace every output of a batch `response` by an error if the batch serializes to more than
+/// `max_size` bytes, even though each of its outputs fits.
+fn limit_batch_size(
+	response: Option<jsonrpc_core::Response>,
+	max_size: usize,
+) -> Option<jsonrpc_core::Response> {
+	match response? {
+		jsonrpc_core::Response::Batch(outputs) => {
+			let size = serialized_size(&outputs).unwrap_or_default();
+			if size <= max_size {
+				return Some(jsonrpc_core::Response::Batch(outputs))
+			}
+			Some(jsonrpc_core::Response::Batch(
+				outputs.into_iter().map(|output| too_large(output, size, max_size)).collect(),
+			))
+		},
+		single => Some(single),
+	}
+}
+
+/// The number of bytes of `value` serialized to JSON, without buffering it.
+fn serialized_size(value: &impl jsonrpc_core::serde::Serialize) -> Option<usize> {
+	struct Counter(usize);
+
+	impl std::io::Write for Counter {
+		fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+			self.0 += buf.len();
+			Ok(buf.len())
+		}
+
+		fn flush(&mut self) -> std::io::Result<()> {
+			Ok(())
+		}
+	}
+
+	let mut counter = Counter(0);
+	serde_json::to_writer(&mut counter, value).ok()?;
+	Some(counter.0)
+}
+
+fn too_large(output: jsonrpc_core::Output, size: usize, max_size: usize) -> jsonrpc_core::Output {
+	log::debug!(target: "rpc", "Response of {} bytes exceeds the limit of {} bytes", size, max_size);
+	let error = jsonrpc_core::Error {
+		code: jsonrpc_core::ErrorCode::ServerError(RESPONSE_TOO_LARGE_ERROR),
+		message: format!(
+			"Response of {} bytes exceeds the maximum response size of {} bytes",
+			size, max_size,
+		),
+		data: None,
+	};
+	let (jsonrpc, id) = match output {
+		jsonrpc_core::Output::Success(success) => (success.jsonrpc, success.id),
+		jsonrpc_core::Output::Failure(failure) => (failure.jsonrpc, failure.id),
+	};
+	jsonrpc_core::Output::from(Err(error), id, jsonrpc)
+}
+
+fn reject(call: &jsonrpc_core::Call, error: jsonrpc_core::Error) -> Option<jsonrpc_core::Output> {
+	let (jsonrpc, id) = match call {
+		jsonrpc_core::Call::MethodCall(ref call) => (call.jsonrpc, call.id.clone()),
+		jsonrpc_core::Call::Invalid { ref id } => (Some(jsonrpc_core::Version::V2), id.clone()),
+		jsonrpc_core::Call::Notification(_) => return None,
+	};
+
+	Some(jsonrpc_core::Output::from(Err(error), id, jsonrpc))
+}
+
 fn is_success(output: &Option<jsonrpc_core::Output>) -> bool {
 	match output {
 		Some(jsonrpc_core::Output::Success(..)) => true,
 		_ => false,
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn large_responses_are_replaced_by_an_error() {
+		let output = |result: &str| {
+			Some(jsonrpc_core::Output::from(
+				Ok(result.into()),
+				jsonrpc_core::Id::Num(1),
+				Some(jsonrpc_core::Version::V2),
+			))
+		};
+
+		assert_eq!(limit_response_size(output("small"), 64), output("small"));
+		assert_eq!(limit_response_size(None, 0), None);
+
+		match limit_response_size(output(&"large".repeat(16)), 64) {
+			Some(jsonrpc_core::Output::Failure(failure)) => {
+				assert_eq!(failure.id, jsonrpc_core::Id::Num(1));
+				assert_eq!(
+					failure.error.code,
+					jsonrpc_core::ErrorCode::ServerError(RESPONSE_TOO_LARGE_ERROR),
+				);
+			},
+			output => panic!("Unexpected output {:?}", output),
+		}
+	}
+
+	#[test]
+	fn large_batches_are_replaced_by_errors() {
+		let output = |id| {
+			jsonrpc_core::Output::from(
+				Ok("response".into()),
+				jsonrpc_core::Id::Num(id),
+				Some(jsonrpc_core::Version::V2),
+			)
+		};
+		let batch =
+			|size| Some(jsonrpc_core::Response::Batch((0..size).map(output).collect::<Vec<_>>()));
+		let limit = serialized_size(&output(0)).unwrap() * 2;
+
+		assert_eq!(limit_batch_size(batch(1), limit), batch(1));
+		assert_eq!(limit_batch_size(None, 0), None);
+		let single = Some(jsonrpc_core::Response::Single(output(0)));
+		assert_eq!(limit_batch_size(single.clone(), 0), single);
+
+		match limit_batch_size(batch(4), limit) {
+			Some(jsonrpc_core::Response::Batch(outputs)) => {
+				assert_eq!(outputs.len(), 4);
+				for (id, output) in outputs.into_iter().enumerate() {
+					match output {
+						jsonrpc_core::Output::Failure(failure) => {
+							assert_eq!(failure.id, jsonrpc_core::Id::Num(id as u64));
+							assert_eq!(
+								failure.error.code,
+								jsonrpc_core::ErrorCode::ServerError(RESPONSE_TOO_LARGE_ERROR),
+							);
+						},
+						output => panic!("Unexpected output {:?}", output),
+					}
+				}
+			},
+			response => panic!("Unexpected response {:?}", response),
+		}
+	}
+
+	#[derive(Clone, Default)]
+	struct Metadata(Option<Arc<pubsub::Session>>);
+
+	impl jsonrpc_core::Metadata for Metadata {}
+
+	impl PubSubMetadata for Metadata {
+		fn session(&self) -> Option<Arc<pubsub::Session>> {
+			self.0.clone()
+		}
+	}
+
+	impl SubscriptionMetadata for Metadata {
+		fn with_subscriptions(self, _: crate::ConnectionSubscriptions) -> Self {
+			self
+		}
+	}
+
+	impl RemoteMetadata for Metadata {
+		fn with_remote_ip(self, _: std::net::IpAddr) -> Self {
+			self
+		}
+
+		fn remote_ip(&self) -> Option<std::net::IpAddr> {
+			None
+		}
+	}
+
+	#[test]
+	fn subscription_limit_applies_to_batches() {
+		let limiter = SubscriptionLimiter::new(std::num::NonZeroU32::new(1).unwrap());
+		let middleware =
+			RpcMiddleware::new(None, HashSet::new(), "test", None, Some(Arc::new(limiter)), None);
+		let mut io = jsonrpc_core::MetaIoHandler::with_middleware(middleware);
+		let ids = Arc::new(std::sync::atomic::AtomicU64::new(0));
+		io.add_method("test_subscribe", move |_| {
+			let id = ids.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+			async move { Ok(jsonrpc_core::Value::from(id)) }
+		});
+		let session = pubsub::Session::new(futures::channel::mpsc::unbounded().0);
+		let metadata = Metadata(Some(Arc::new(session)));
+
+		let request = r#"[
+			{"jsonrpc":"2.0","method":"test_subscribe","params":[],"id":1},
+			{"jsonrpc":"2.0","method":"test_subscribe","params":[],"id":2}
+		]"#;
+		let response = io.handle_request_sync(request, metadata).unwrap();
+		let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+
+		assert_eq!(response[0]["result"], 0);
+		assert_eq!(response[1]["error"]["code"], TOO_MANY_SUBSCRIPTIONS_ERROR);
+	}
+}
@@ -0,0 +1,230 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Rate limiting of RPC calls.
+
+use std::{
+	collections::HashMap,
+	net::IpAddr,
+	num::NonZeroU32,
+	sync::{Arc, Mutex, PoisonError},
+	time::{Duration, Instant},
+};
+
+use pubsub::Session;
+
+/// Time after which an untouched bucket is full again, and can be dropped.
+const REFILL_TIME: Duration = Duration::from_secs(1);
+
+/// Metadata of an RPC call that can carry the IP address of the peer making it.
+pub trait RemoteMetadata {
+	/// Attach the IP address of the peer the call is received from.
+	fn with_remote_ip(self, remote_ip: IpAddr) -> Self;
+
+	/// The IP address of the peer the call is received from, if the transport exposes it.
+	fn remote_ip(&self) -> Option<IpAddr>;
+}
+
+struct Bucket {
+	tokens: f64,
+	last_update: Instant,
+}
+
+impl Bucket {
+	fn full(capacity: f64, now: Instant) -> Self {
+		Self { tokens: capacity, last_update: now }
+	}
+
+	/// Refill the bucket up to `capacity`, then take a token if there is one.
+	fn try_take(&mut self, capacity: f64, now: Instant) -> bool {
+		let elapsed = now.saturating_duration_since(self.last_update).as_secs_f64();
+		self.tokens = (self.tokens + elapsed * capacity).min(capacity);
+		self.last_update = now;
+
+		if self.tokens >= 1.0 {
+			self.tokens -= 1.0;
+			true
+		} else {
+			false
+		}
+	}
+}
+
+/// Buckets of the peers that made calls recently.
+struct Peers {
+	buckets: HashMap<IpAddr, Bucket>,
+	last_sweep: Instant,
+}
+
+/// Token bucket rate limiter of RPC calls, keyed by the IP address of the peers making them.
+///
+/// The HTTP server passes the address of the peers to the calls' metadata, but the WS server
+/// doesn't expose it, and the headers of the requests, like `X-Forwarded-For`, are chosen by the
+/// clients. The calls without an address are therefore limited per connection for the transports
+/// keeping one, like WS, whose number of connections is bounded by the server, and share a
+/// single bucket otherwise.
+///
+/// Every bucket allows a burst of up to `calls_per_second` calls, after which it refills at
+/// `calls_per_second`. The bucket of a connection is dropped when the connection closes, the
+/// bucket of a peer once it has been full again for a while.
+pub struct RateLimiter {
+	calls_per_second: u32,
+	/// Bucket of the calls made without an address or a connection.
+	shared: Mutex<Bucket>,
+	/// Buckets of the peers, by address.
+	peers: Mutex<Peers>,
+	/// Buckets of the open connections, for the calls without an address.
+	connections: Arc<Mutex<HashMap<usize, Bucket>>>,
+}
+
+impl RateLimiter {
+	/// Create a new rate limiter allowing `calls_per_second` calls per peer.
+	pub fn new(calls_per_second: NonZeroU32) -> Self {
+		let capacity = f64::from(calls_per_second.get());
+		let now = Instant::now();
+		Self {
+			calls_per_second: calls_per_second.get(),
+			shared: Mutex::new(Bucket::full(capacity, now)),
+			peers: Mutex::new(Peers { buckets: HashMap::new(), last_sweep: now }),
+			connections: Default::default(),
+		}
+	}
+
+	/// The number of calls allowed per second and peer.
+	pub fn calls_per_second(&self) -> u32 {
+		self.calls_per_second
+	}
+
+	/// Take a token from the bucket of the peer at `remote_ip`, from the bucket of the connection
+	/// of `session` without an address, or from the shared bucket without either, returning
+	/// `false` if the bucket is empty.
+	pub fn try_acquire(&self, remote_ip: Option<IpAddr>, session: Option<&Arc<Session>>) -> bool {
+		let now = Instant::now();
+		let capacity = f64::from(self.calls_per_second);
+
+		if let Some(remote_ip) = remote_ip {
+			let mut peers = self.peers.lock().unwrap_or_else(PoisonError::into_inner);
+			// The buckets untouched for `REFILL_TIME` are full, dropping them changes nothing.
+			if now.saturating_duration_since(peers.last_sweep) >= REFILL_TIME {
+				peers.buckets.retain(|_, bucket| {
+					now.saturating_duration_since(bucket.last_update) < REFILL_TIME
+				});
+				peers.last_sweep = now;
+			}
+			return peers
+				.buckets
+				.entry(remote_ip)
+				.or_insert_with(|| Bucket::full(capacity, now))
+				.try_take(capacity, now)
+		}
+
+		let session = match session {
+			Some(session) => session,
+			None =>
+				return self
+					.shared
+					.lock()
+					.unwrap_or_else(PoisonError::into_inner)
+					.try_take(capacity, now),
+		};
+
+		let mut connections = self.connections.lock().unwrap_or_else(PoisonError::into_inner);
+		let key = Arc::as_ptr(session) as usize;
+		connections
+			.entry(key)
+			.or_insert_with(|| {
+				let connections = self.connections.clone();
+				session.on_drop(move || {
+					connections.lock().unwrap_or_else(PoisonError::into_inner).remove(&key);
+				});
+				Bucket::full(capacity, now)
+			})
+			.try_take(capacity, now)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn session() -> Arc<Session> {
+		Arc::new(Session::new(futures::channel::mpsc::unbounded().0))
+	}
+
+	fn ip(last: u8) -> Option<IpAddr> {
+		Some(IpAddr::from([10, 0, 0, last]))
+	}
+
+	#[test]
+	fn limits_each_peer_separately() {
+		let limiter = RateLimiter::new(NonZeroU32::new(2).unwrap());
+		let (a, b) = (session(), session());
+
+		assert!(limiter.try_acquire(ip(1), Some(&a)));
+		assert!(limiter.try_acquire(ip(1), Some(&b)));
+		assert!(!limiter.try_acquire(ip(1), None));
+
+		assert!(limiter.try_acquire(ip(2), Some(&a)));
+		assert!(limiter.try_acquire(None, Some(&a)));
+		assert!(limiter.try_acquire(None, None));
+	}
+
+	#[test]
+	fn limits_each_connection_separately_without_an_address() {
+		let limiter = RateLimiter::new(NonZeroU32::new(2).unwrap());
+		let (a, b) = (session(), session());
+
+		assert!(limiter.try_acquire(None, Some(&a)));
+		assert!(limiter.try_acquire(None, Some(&a)));
+		assert!(!limiter.try_acquire(None, Some(&a)));
+
+		assert!(limiter.try_acquire(None, Some(&b)));
+		assert!(limiter.try_acquire(None, None));
+		assert!(limiter.try_acquire(None, None));
+		assert!(!limiter.try_acquire(None, None));
+	}
+
+	#[test]
+	fn drops_the_buckets_of_closed_connections() {
+		let limiter = RateLimiter::new(NonZeroU32::new(1).unwrap());
+		let a = session();
+
+		assert!(limiter.try_acquire(None, Some(&a)));
+		assert_eq!(limiter.connections.lock().unwrap().len(), 1);
+		drop(a);
+		assert!(limiter.connections.lock().unwrap().is_empty());
+	}
+
+	#[test]
+	fn drops_the_buckets_of_idle_peers() {
+		let limiter = RateLimiter::new(NonZeroU32::new(1).unwrap());
+
+		assert!(limiter.try_acquire(ip(1), None));
+		{
+			let mut peers = limiter.peers.lock().unwrap();
+			let past = Instant::now() - REFILL_TIME;
+			peers.last_sweep = past;
+			peers.buckets.get_mut(&ip(1).unwrap()).unwrap().last_update = past;
+		}
+		assert!(limiter.try_acquire(ip(2), None));
+		assert_eq!(
+			limiter.peers.lock().unwrap().buckets.keys().collect::<Vec<_>>(),
+			vec![&ip(2).unwrap()]
+		);
+	}
+}
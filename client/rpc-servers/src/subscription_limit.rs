@@ -0,0 +1,283 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Limit of the active subscriptions of every RPC connection.
+
+use std::{
+	collections::{HashMap, HashSet},
+	num::NonZeroU32,
+	sync::{Arc, Mutex, PoisonError},
+};
+
+use jsonrpc_core::{MethodCall, Output, Params, Value};
+use pubsub::{Session, SubscriptionId};
+
+type Connections = Arc<Mutex<HashMap<usize, Connection>>>;
+
+/// The subscriptions of a connection.
+#[derive(Default)]
+struct Connection {
+	/// Slots taken by the subscriptions whose call is still running.
+	reserved: usize,
+	/// Ids of the active subscriptions.
+	active: HashSet<String>,
+	/// Ids of the subscriptions ended by the server before their call completed.
+	ended: HashSet<String>,
+}
+
+/// Limits the number of active subscriptions of every connection.
+///
+/// Subscriptions are recognized by the name of their method, which contains `subscribe` or
+/// `watch`, and `unsubscribe` or `unwatch` for the methods cancelling them. A subscription counts
+/// towards the limit from the moment its call arrives, until it is cancelled, it is ended by the
+/// server through [`ConnectionSubscriptions::end`] or its connection is closed.
+pub struct SubscriptionLimiter {
+	max_per_connection: u32,
+	connections: Connections,
+}
+
+/// Metadata of an RPC call that can carry the subscriptions of its connection.
+pub trait SubscriptionMetadata {
+	/// Attach the subscriptions of the connection the call is made on.
+	fn with_subscriptions(self, subscriptions: ConnectionSubscriptions) -> Self;
+}
+
+/// Handle on the subscriptions of a connection, for the servers ending subscriptions themselves.
+#[derive(Clone)]
+pub struct ConnectionSubscriptions {
+	connections: Connections,
+	connection: usize,
+}
+
+impl ConnectionSubscriptions {
+	/// Release the slot of the subscription `id`, which the server ended.
+	pub fn end(&self, id: SubscriptionId) {
+		let id = Value::from(id).to_string();
+		let mut connections = self.connections.lock().unwrap_or_else(PoisonError::into_inner);
+		if let Some(connection) = connections.get_mut(&self.connection) {
+			// The call opening the subscription may still be completing.
+			if !connection.active.remove(&id) && connection.reserved > 0 {
+				connection.ended.insert(id);
+			}
+		}
+	}
+}
+
+/// The call would open a subscription beyond the limit of its connection.
+pub(crate) struct LimitExceeded;
+
+/// A subscription change, applied once the call making it succeeds.
+///
+/// The slot reserved by a subscription is released if the call fails or is dropped.
+pub(crate) struct Pending {
+	connections: Connections,
+	connection: usize,
+	change: Option<Change>,
+}
+
+enum Change {
+	Subscribe,
+	Unsubscribe(String),
+}
+
+impl SubscriptionLimiter {
+	/// Create a new limiter allowing `max_per_connection` active subscriptions per connection.
+	pub fn new(max_per_connection: NonZeroU32) -> Self {
+		Self { max_per_connection: max_per_connection.get(), connections: Default::default() }
+	}
+
+	/// The number of active subscriptions allowed per connection.
+	pub fn max_per_connection(&self) -> u32 {
+		self.max_per_connection
+	}
+
+	/// The subscriptions of the connection of `session`.
+	pub(crate) fn subscriptions(&self, session: &Arc<Session>) -> ConnectionSubscriptions {
+		ConnectionSubscriptions { connections: self.connections.clone(), connection: key(session) }
+	}
+
+	/// Check a call made on the connection of `session`, reserving a slot for the subscriptions.
+	///
+	/// Returns the subscription change to apply if the call succeeds, `None` for the calls that
+	/// don't open or cancel a subscription.
+	pub(crate) fn check(
+		&self,
+		session: &Arc<Session>,
+		call: &MethodCall,
+	) -> Result<Option<Pending>, LimitExceeded> {
+		let method = call.method.to_ascii_lowercase();
+		let change = if method.contains("unsubscribe") || method.contains("unwatch") {
+			match &call.params {
+				Params::Array(params) if !params.is_empty() =>
+					Change::Unsubscribe(params[0].to_string()),
+				_ => return Ok(None),
+			}
+		} else if method.contains("subscribe") || method.contains("watch") {
+			Change::Subscribe
+		} else {
+			return Ok(None)
+		};
+
+		if let Change::Subscribe = change {
+			let mut connections = self.connections.lock().unwrap_or_else(PoisonError::into_inner);
+			let connection = connections.entry(key(session)).or_insert_with(|| {
+				let connections = self.connections.clone();
+				let connection = key(session);
+				session.on_drop(move || {
+					connections.lock().unwrap_or_else(PoisonError::into_inner).remove(&connection);
+				});
+				Connection::default()
+			});
+
+			if connection.reserved + connection.active.len() >= self.max_per_connection as usize {
+				return Err(LimitExceeded)
+			}
+			connection.reserved += 1;
+		}
+
+		Ok(Some(Pending {
+			connections: self.connections.clone(),
+			connection: key(session),
+			change: Some(change),
+		}))
+	}
+}
+
+impl Pending {
+	/// Apply the change if the call succeeded, the result of a subscription being its id.
+	pub(crate) fn complete(mut self, output: &Option<Output>) {
+		let result = match output {
+			Some(Output::Success(success)) => &success.result,
+			_ => return,
+		};
+
+		let mut connections = self.connections.lock().unwrap_or_else(PoisonError::into_inner);
+		if let Some(connection) = connections.get_mut(&self.connection) {
+			match self.change.take() {
+				Some(Change::Subscribe) => {
+					connection.reserved -= 1;
+					let id = result.to_string();
+					if !connection.ended.remove(&id) {
+						connection.active.insert(id);
+					}
+				},
+				Some(Change::Unsubscribe(id)) => {
+					connection.active.remove(&id);
+				},
+				None => {},
+			}
+		}
+	}
+}
+
+impl Drop for Pending {
+	fn drop(&mut self) {
+		if let Some(Change::Subscribe) = self.change {
+			let mut connections = self.connections.lock().unwrap_or_else(PoisonError::into_inner);
+			if let Some(connection) = connections.get_mut(&self.connection) {
+				connection.reserved -= 1;
+			}
+		}
+	}
+}
+
+/// Identifies the connection of a session, for as long as the session lives.
+fn key(session: &Arc<Session>) -> usize {
+	Arc::as_ptr(session) as usize
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use jsonrpc_core::{Id, Success, Value, Version};
+
+	fn call(method: &str, params: Vec<Value>) -> MethodCall {
+		MethodCall {
+			jsonrpc: Some(Version::V2),
+			method: method.into(),
+			params: Params::Array(params),
+			id: Id::Num(1),
+		}
+	}
+
+	fn success(result: Value) -> Option<Output> {
+		Some(Output::Success(Success { jsonrpc: Some(Version::V2), result, id: Id::Num(1) }))
+	}
+
+	#[test]
+	fn limits_each_connection_separately() {
+		let limiter = SubscriptionLimiter::new(NonZeroU32::new(2).unwrap());
+		let session = Arc::new(Session::new(futures::channel::mpsc::unbounded().0));
+		let other = Arc::new(Session::new(futures::channel::mpsc::unbounded().0));
+		let subscribe = call("chain_subscribeNewHeads", Vec::new());
+
+		for id in &["a", "b"] {
+			let pending = limiter.check(&session, &subscribe).ok().unwrap().unwrap();
+			pending.complete(&success(Value::from(*id)));
+		}
+		assert!(limiter.check(&session, &subscribe).is_err());
+		assert!(limiter.check(&other, &subscribe).is_ok());
+		assert!(limiter.check(&session, &call("system_health", Vec::new())).is_ok());
+
+		let unsubscribe = call("chain_unsubscribeNewHeads", vec![Value::from("a")]);
+		let pending = limiter.check(&session, &unsubscribe).ok().unwrap().unwrap();
+		pending.complete(&success(Value::Bool(true)));
+		assert!(limiter.check(&session, &subscribe).is_ok());
+
+		// The connections are forgotten once closed.
+		drop((session, other));
+		assert!(limiter.connections.lock().unwrap().is_empty());
+	}
+
+	#[test]
+	fn reserves_slots_until_the_calls_complete() {
+		let limiter = SubscriptionLimiter::new(NonZeroU32::new(1).unwrap());
+		let session = Arc::new(Session::new(futures::channel::mpsc::unbounded().0));
+		let subscribe = call("chain_subscribeNewHeads", Vec::new());
+
+		let pending = limiter.check(&session, &subscribe).ok().unwrap().unwrap();
+		assert!(limiter.check(&session, &subscribe).is_err());
+
+		// A failed call releases its slot.
+		pending.complete(&None);
+		let pending = limiter.check(&session, &subscribe).ok().unwrap().unwrap();
+		drop(pending);
+		let pending = limiter.check(&session, &subscribe).ok().unwrap().unwrap();
+		pending.complete(&success(Value::from("a")));
+		assert!(limiter.check(&session, &subscribe).is_err());
+	}
+
+	#[test]
+	fn subscriptions_ended_by_the_server_release_their_slot() {
+		let limiter = SubscriptionLimiter::new(NonZeroU32::new(1).unwrap());
+		let session = Arc::new(Session::new(futures::channel::mpsc::unbounded().0));
+		let subscribe = call("author_submitAndWatchExtrinsic", Vec::new());
+		let subscriptions = limiter.subscriptions(&session);
+
+		let pending = limiter.check(&session, &subscribe).ok().unwrap().unwrap();
+		pending.complete(&success(Value::from("a")));
+		assert!(limiter.check(&session, &subscribe).is_err());
+		subscriptions.end(SubscriptionId::String("a".into()));
+
+		// Even when ended before the call completes.
+		let pending = limiter.check(&session, &subscribe).ok().unwrap().unwrap();
+		subscriptions.end(SubscriptionId::String("b".into()));
+		pending.complete(&success(Value::from("b")));
+		assert!(limiter.check(&session, &subscribe).is_ok());
+	}
+}
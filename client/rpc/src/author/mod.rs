@@ -180,7 +180,7 @@ where
 
 	fn watch_extrinsic(
 		&self,
-		_metadata: Self::Metadata,
+		metadata: Self::Metadata,
 		subscriber: Subscriber<TransactionStatus<TxHash<P>, BlockHash<P>>>,
 		xt: Bytes,
 	) {
@@ -206,6 +206,8 @@ where
 			});
 
 		let subscriptions = self.subscriptions.clone();
+		// The session isn't kept, its drop cancels the subscription.
+		let connection_subscriptions = metadata.subscriptions();
 
 		let future = async move {
 			let tx_stream = match submit.await {
@@ -219,12 +221,20 @@ where
 				},
 			};
 
-			subscriptions.add(subscriber, move |sink| {
+			// The subscription ends by itself once the transaction reaches a final status.
+			let (id_tx, id_rx) = futures::channel::oneshot::channel();
+			let id = subscriptions.add(subscriber, move |sink| {
 				tx_stream
 					.map(|v| Ok(Ok(v)))
 					.forward(sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e)))
-					.map(drop)
+					.then(move |_| id_rx)
+					.map(move |id| {
+						if let (Ok(id), Some(subscriptions)) = (id, connection_subscriptions) {
+							subscriptions.end(id);
+						}
+					})
 			});
+			let _ = id_tx.send(id);
 		};
 
 		let res = self.subscriptions.executor().spawn_obj(future.boxed().into());
@@ -40,7 +40,7 @@ use sc_client_api::{
 use sp_rpc::{list::ListOrValue, number::NumberOrHex};
 use sp_runtime::{
 	generic::{BlockId, SignedBlock},
-	traits::{Block as BlockT, Header, NumberFor},
+	traits::{Block as BlockT, Header, NumberFor, One, Saturating},
 };
 
 use self::error::{Error, FutureResult, Result};
@@ -191,6 +191,58 @@ where
 	) -> RpcResult<bool> {
 		Ok(self.subscriptions().cancel(id))
 	}
+
+	/// Confirmed head subscription
+	fn subscribe_confirmed_heads(
+		&self,
+		_metadata: crate::Metadata,
+		subscriber: Subscriber<Block::Header>,
+		depth: u32,
+	) {
+		let depth = NumberFor::<Block>::from(depth);
+		let confirmed = self.client().info().best_number.saturating_sub(depth);
+		let client = self.client().clone();
+		// The headers are looked up by number on the best chain, which skips the blocks that
+		// were retracted, and catches up with the blocks imported without a notification.
+		let mut next = confirmed + One::one();
+
+		subscribe_headers(
+			self.client(),
+			self.subscriptions(),
+			subscriber,
+			|| self.client().hash(confirmed).ok().flatten().unwrap_or_default(),
+			|| {
+				self.client()
+					.import_notification_stream()
+					.filter(|notification| future::ready(notification.is_new_best))
+					.flat_map(move |notification| {
+						let confirmed = notification.header.number().saturating_sub(depth);
+						let mut headers = Vec::new();
+						while next <= confirmed {
+							match client.header(BlockId::number(next)) {
+								Ok(Some(header)) => headers.push(Ok(header)),
+								Ok(None) => break,
+								Err(err) => {
+									headers.push(Err(client_err(err).into()));
+									break
+								},
+							}
+							next += One::one();
+						}
+						stream::iter(headers)
+					})
+			},
+		)
+	}
+
+	/// Unsubscribe from confirmed head subscription.
+	fn unsubscribe_confirmed_heads(
+		&self,
+		_metadata: Option<crate::Metadata>,
+		id: SubscriptionId,
+	) -> RpcResult<bool> {
+		Ok(self.subscriptions().cancel(id))
+	}
 }
 
 /// Create new state API that works on full node.
@@ -203,7 +255,10 @@ where
 	Block::Header: Unpin,
 	Client: BlockBackend<Block> + HeaderBackend<Block> + BlockchainEvents<Block> + 'static,
 {
-	Chain { backend: Box::new(self::chain_full::FullChain::new(client, subscriptions)) }
+	Chain {
+		backend: Box::new(self::chain_full::FullChain::new(client, subscriptions)),
+		confirmation_depth: None,
+	}
 }
 
 /// Create new state API that works on light node.
@@ -226,12 +281,24 @@ where
 			remote_blockchain,
 			fetcher,
 		)),
+		confirmation_depth: None,
 	}
 }
 
 /// Chain API with subscriptions support.
 pub struct Chain<Block: BlockT, Client> {
 	backend: Box<dyn ChainBackend<Client, Block>>,
+	confirmation_depth: Option<u32>,
+}
+
+impl<Block: BlockT, Client> Chain<Block, Client> {
+	/// Use `depth` for the confirmed head subscriptions that don't give a depth.
+	///
+	/// Without it, these subscriptions have to give their depth.
+	pub fn with_confirmation_depth(mut self, depth: u32) -> Self {
+		self.confirmation_depth = Some(depth);
+		self
+	}
 }
 
 impl<Block, Client> ChainApi<NumberFor<Block>, Block::Hash, Block::Header, SignedBlock<Block>>
@@ -310,6 +377,29 @@ where
 	) -> RpcResult<bool> {
 		self.backend.unsubscribe_finalized_heads(metadata, id)
 	}
+
+	fn subscribe_confirmed_heads(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<Block::Header>,
+		depth: Option<u32>,
+	) {
+		match depth.or(self.confirmation_depth) {
+			Some(depth) => self.backend.subscribe_confirmed_heads(metadata, subscriber, depth),
+			None => {
+				let err = Error::Other("No confirmation depth given".into());
+				let _ = subscriber.reject(err.into());
+			},
+		}
+	}
+
+	fn unsubscribe_confirmed_heads(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> RpcResult<bool> {
+		self.backend.unsubscribe_confirmed_heads(metadata, id)
+	}
 }
 
 /// Subscribe to new headers.
@@ -246,3 +246,42 @@ fn should_notify_about_finalized_block() {
 	executor::block_on((&mut transport).take(2).collect::<Vec<_>>());
 	assert!(executor::block_on(transport.next()).is_none());
 }
+
+#[test]
+fn should_notify_about_confirmed_blocks() {
+	let (subscriber, id, mut transport) = Subscriber::new_test("test");
+	let mut hashes = Vec::new();
+
+	{
+		let mut client = Arc::new(substrate_test_runtime_client::new());
+		let api = new_full(client.clone(), SubscriptionManager::new(Arc::new(TaskExecutor)))
+			.with_confirmation_depth(2);
+
+		api.subscribe_confirmed_heads(Default::default(), subscriber, None);
+
+		// assert id assigned
+		assert!(matches!(executor::block_on(id), Ok(Ok(SubscriptionId::String(_)))));
+
+		for _ in 0..3 {
+			let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+			hashes.push(block.hash());
+			executor::block_on(client.import(BlockOrigin::Own, block)).unwrap();
+		}
+	}
+
+	// The genesis block, then block #1 once block #3 is imported.
+	let notifications = executor::block_on((&mut transport).take(2).collect::<Vec<_>>());
+	assert!(notifications[1].contains(&format!("{:?}", hashes[0])));
+	assert!(executor::block_on(transport.next()).is_none());
+}
+
+#[test]
+fn should_reject_confirmed_heads_subscription_without_depth() {
+	let (subscriber, id, _transport) = Subscriber::new_test("test");
+	let client = Arc::new(substrate_test_runtime_client::new());
+	let api = new_full(client, SubscriptionManager::new(Arc::new(TaskExecutor)));
+
+	api.subscribe_confirmed_heads(Default::default(), subscriber, None);
+
+	assert!(matches!(executor::block_on(id), Ok(Err(_))));
+}
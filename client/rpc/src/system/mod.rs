@@ -27,7 +27,7 @@ use sp_utils::mpsc::TracingUnboundedSender;
 
 pub use self::{
 	gen_client::Client as SystemClient,
-	helpers::{Health, NodeRole, PeerInfo, SyncState, SystemInfo},
+	helpers::{Health, HealthRuntime, NodeRole, PeerInfo, SyncState, SystemInfo},
 };
 pub use sc_rpc_api::system::*;
 
@@ -110,10 +110,17 @@ impl<B: traits::Block> SystemApi<B::Hash, <B::Header as HeaderT>::Number> for Sy
 		Ok(self.info.properties.clone())
 	}
 
-	fn system_health(&self) -> Receiver<Health> {
+	fn system_health(&self) -> rpc::BoxFuture<rpc::Result<Health>> {
 		let (tx, rx) = oneshot::channel();
 		let _ = self.send_back.unbounded_send(Request::Health(tx));
-		Receiver(rx)
+		async move {
+			match rx.await {
+				Ok(health) if health.is_warming_up => Err(error::Error::NotHealthy(health).into()),
+				Ok(health) => Ok(health),
+				Err(_) => Err(rpc::Error::internal_error()),
+			}
+		}
+		.boxed()
 	}
 
 	fn system_local_peer_id(&self) -> Receiver<String> {
@@ -34,12 +34,19 @@ struct Status {
 	pub peers: usize,
 	pub is_syncing: bool,
 	pub is_dev: bool,
+	pub is_warming_up: bool,
 	pub peer_id: PeerId,
 }
 
 impl Default for Status {
 	fn default() -> Status {
-		Status { peer_id: PeerId::random(), peers: 0, is_syncing: false, is_dev: false }
+		Status {
+			peer_id: PeerId::random(),
+			peers: 0,
+			is_syncing: false,
+			is_dev: false,
+			is_warming_up: false,
+		}
 	}
 }
 
@@ -55,6 +62,8 @@ fn api<T: Into<Option<Status>>>(sync: T) -> System<Block> {
 						peers: status.peers,
 						is_syncing: status.is_syncing,
 						should_have_peers,
+						is_warming_up: status.is_warming_up,
+						runtime: None,
 					});
 				},
 				Request::LocalPeerId(sender) => {
@@ -170,35 +179,76 @@ fn system_type_works() {
 #[test]
 fn system_health() {
 	assert_matches!(
-		wait_receiver(api(None).system_health()),
-		Health { peers: 0, is_syncing: false, should_have_peers: true }
+		executor::block_on(api(None).system_health()).unwrap(),
+		Health { peers: 0, is_syncing: false, should_have_peers: true, .. }
 	);
 
 	assert_matches!(
-		wait_receiver(
-			api(Status { peer_id: PeerId::random(), peers: 5, is_syncing: true, is_dev: true })
-				.system_health()
-		),
-		Health { peers: 5, is_syncing: true, should_have_peers: false }
+		executor::block_on(
+			api(Status {
+				peer_id: PeerId::random(),
+				peers: 5,
+				is_syncing: true,
+				is_dev: true,
+				is_warming_up: false
+			})
+			.system_health()
+		)
+		.unwrap(),
+		Health { peers: 5, is_syncing: true, should_have_peers: false, .. }
 	);
 
 	assert_eq!(
-		wait_receiver(
-			api(Status { peer_id: PeerId::random(), peers: 5, is_syncing: false, is_dev: false })
-				.system_health()
-		),
-		Health { peers: 5, is_syncing: false, should_have_peers: true }
+		executor::block_on(
+			api(Status {
+				peer_id: PeerId::random(),
+				peers: 5,
+				is_syncing: false,
+				is_dev: false,
+				is_warming_up: false
+			})
+			.system_health()
+		)
+		.unwrap(),
+		Health {
+			peers: 5,
+			is_syncing: false,
+			should_have_peers: true,
+			is_warming_up: false,
+			runtime: None
+		}
 	);
 
 	assert_eq!(
-		wait_receiver(
-			api(Status { peer_id: PeerId::random(), peers: 0, is_syncing: false, is_dev: true })
-				.system_health()
-		),
-		Health { peers: 0, is_syncing: false, should_have_peers: false }
+		executor::block_on(
+			api(Status {
+				peer_id: PeerId::random(),
+				peers: 0,
+				is_syncing: false,
+				is_dev: true,
+				is_warming_up: false
+			})
+			.system_health()
+		)
+		.unwrap(),
+		Health {
+			peers: 0,
+			is_syncing: false,
+			should_have_peers: false,
+			is_warming_up: false,
+			runtime: None
+		}
 	);
 }
 
+#[test]
+fn system_health_fails_while_warming_up() {
+	let status = Status { is_warming_up: true, ..Default::default() };
+	let error = executor::block_on(api(status).system_health()).unwrap_err();
+	assert_eq!(error.code, rpc::ErrorCode::ServerError(2001));
+	assert_eq!(error.data.unwrap()["isWarmingUp"], true);
+}
+
 #[test]
 fn system_local_peer_id_works() {
 	assert_eq!(
@@ -223,8 +273,14 @@ fn system_local_listen_addresses_works() {
 #[test]
 fn system_peers() {
 	let peer_id = PeerId::random();
-	let req = api(Status { peer_id: peer_id.clone(), peers: 1, is_syncing: false, is_dev: true })
-		.system_peers();
+	let req = api(Status {
+		peer_id: peer_id.clone(),
+		peers: 1,
+		is_syncing: false,
+		is_dev: true,
+		is_warming_up: false,
+	})
+	.system_peers();
 	let res = executor::block_on(req).unwrap();
 
 	assert_eq!(
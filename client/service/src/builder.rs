@@ -22,8 +22,8 @@ use crate::{
 	config::{Configuration, KeystoreConfig, PrometheusConfig, TransactionStorageMode},
 	error::Error,
 	metrics::MetricsService,
-	start_rpc_servers, MallocSizeOfWasm, RpcHandlers, SpawnTaskHandle, TaskManager,
-	TransactionPoolAdapter,
+	start_rpc_servers, track_runtime_health, MallocSizeOfWasm, RpcHandlers, SpawnTaskHandle,
+	TaskManager, TransactionPoolAdapter,
 };
 use futures::{channel::oneshot, future::ready, FutureExt, StreamExt};
 use jsonrpc_pubsub::manager::SubscriptionManager;
@@ -307,6 +307,8 @@ where
 			source: config.database.clone(),
 			keep_blocks: config.keep_blocks.clone(),
 			transaction_storage: config.transaction_storage.clone(),
+			disable_state_cache_layer: config.disable_state_cache_layer,
+			read_only: config.database_read_only,
 		};
 
 		let backend = new_db_backend(db_config)?;
@@ -384,6 +386,8 @@ where
 			source: config.database.clone(),
 			keep_blocks: config.keep_blocks.clone(),
 			transaction_storage: config.transaction_storage.clone(),
+			disable_state_cache_layer: config.disable_state_cache_layer,
+			read_only: config.database_read_only,
 		};
 		sc_client_db::light::LightStorage::new(db_settings)?
 	};
@@ -504,26 +508,42 @@ where
 	TCl: Send + Sync + ProvideRuntimeApi<TBl> + BlockchainEvents<TBl> + 'static,
 	<TCl as ProvideRuntimeApi<TBl>>::Api: sc_offchain::OffchainWorkerApi<TBl>,
 {
-	let offchain_workers = Some(Arc::new(sc_offchain::OffchainWorkers::new(client.clone())));
+	let offchain_workers = match config.offchain_worker.threads {
+		Some(threads) => sc_offchain::OffchainWorkers::with_threads(client.clone(), threads),
+		None => sc_offchain::OffchainWorkers::new(client.clone()),
+	};
+	let offchain_workers = Some(Arc::new(match config.offchain_worker.http_concurrency {
+		Some(limit) => offchain_workers.with_http_concurrency(limit),
+		None => offchain_workers,
+	}));
 
 	// Inform the offchain worker about new imported blocks
 	if let Some(offchain) = offchain_workers.clone() {
-		spawn_handle.spawn(
-			"offchain-notifications",
+		let is_validator = config.role.is_authority();
+		let spawner = spawn_handle.clone();
+		let notifications = move || {
 			sc_offchain::notification_future(
-				config.role.is_authority(),
+				is_validator,
 				client.clone(),
-				offchain,
-				Clone::clone(&spawn_handle),
+				offchain.clone(),
+				spawner.clone(),
 				network.clone(),
-			),
-		);
+			)
+		};
+		match config.offchain_worker.restart_policy {
+			Some(policy) =>
+				spawn_handle.spawn_supervised("offchain-notifications", policy, notifications),
+			None => spawn_handle.spawn("offchain-notifications", notifications()),
+		}
 	}
 
 	offchain_workers
 }
 
 /// Spawn the tasks that are required to run a node.
+///
+/// The HTTP, WebSocket and IPC RPC servers are bound before this returns, so they are listening
+/// by the time [`NetworkStarter::start_network`] is called afterwards.
 pub fn spawn_tasks<TBl, TBackend, TExPool, TRpc, TCl>(
 	params: SpawnTasksParams<TBl, TCl, TExPool, TRpc, TBackend>,
 ) -> Result<RpcHandlers, Error>
@@ -600,19 +620,33 @@ where
 	);
 
 	// Prometheus metrics.
-	let metrics_service =
-		if let Some(PrometheusConfig { port, registry }) = config.prometheus_config.clone() {
-			// Set static metrics.
-			let metrics = MetricsService::with_prometheus(telemetry.clone(), &registry, &config)?;
-			spawn_handle.spawn(
+	let metrics_service = if let Some(PrometheusConfig { port, registry, unix_socket, serve }) =
+		config.prometheus_config.clone()
+	{
+		// Set static metrics.
+		let metrics = MetricsService::with_prometheus(telemetry, &registry, &config)?;
+		match unix_socket {
+			_ if !serve => (),
+			#[cfg(unix)]
+			Some(path) => {
+				crate::remove_stale_unix_socket(&path)?;
+				spawn_handle.spawn(
+					"prometheus-endpoint",
+					prometheus_endpoint::init_prometheus_unix(path, registry).map(drop),
+				);
+			},
+			#[cfg(not(unix))]
+			Some(_) => return Err("Unix domain sockets are not supported on this platform".into()),
+			None => spawn_handle.spawn(
 				"prometheus-endpoint",
 				prometheus_endpoint::init_prometheus(port, registry).map(drop),
-			);
+			),
+		}
 
-			metrics
-		} else {
-			MetricsService::new(telemetry.clone())
-		};
+		metrics
+	} else {
+		MetricsService::new(telemetry)
+	};
 
 	// Periodically updated metrics and telemetry updates.
 	spawn_handle.spawn(
@@ -640,17 +674,28 @@ where
 	};
 	let rpc_metrics = sc_rpc_server::RpcMetrics::new(config.prometheus_registry())?;
 	let server_metrics = sc_rpc_server::ServerMetrics::new(config.prometheus_registry())?;
-	let rpc = start_rpc_servers(&config, gen_handler, rpc_metrics.clone(), server_metrics)?;
+	let (rpc, rpc_addresses) =
+		start_rpc_servers(&config, gen_handler, rpc_metrics.clone(), server_metrics)?;
 	// This is used internally, so don't restrict access to unsafe RPC
 	let known_rpc_method_names =
 		sc_rpc_server::method_names(|m| gen_handler(sc_rpc::DenyUnsafe::No, m))?;
-	let rpc_handlers = RpcHandlers(Arc::new(
-		gen_handler(
-			sc_rpc::DenyUnsafe::No,
-			sc_rpc_server::RpcMiddleware::new(rpc_metrics, known_rpc_method_names, "inbrowser"),
-		)?
-		.into(),
-	));
+	let rpc_handlers = RpcHandlers(
+		Arc::new(
+			gen_handler(
+				sc_rpc::DenyUnsafe::No,
+				sc_rpc_server::RpcMiddleware::new(
+					rpc_metrics,
+					known_rpc_method_names,
+					"inbrowser",
+					None,
+					None,
+					None,
+				),
+			)?
+			.into(),
+		),
+		rpc_addresses,
+	);
 
 	// Spawn informant task
 	spawn_handle.spawn(
@@ -798,6 +843,10 @@ where
 			);
 			(chain, state, child_state)
 		};
+	let chain = match config.rpc_confirmation_depth {
+		Some(depth) => chain.with_confirmation_depth(depth),
+		None => chain,
+	};
 
 	let author =
 		sc_rpc::author::Author::new(client, transaction_pool, subscriptions, keystore, deny_unsafe);
@@ -808,18 +857,37 @@ where
 		offchain::OffchainApi::to_delegate(offchain)
 	});
 
-	Ok(sc_rpc_server::rpc_handler(
-		(
-			state::StateApi::to_delegate(state),
-			state::ChildStateApi::to_delegate(child_state),
-			chain::ChainApi::to_delegate(chain),
-			maybe_offchain_rpc,
-			author::AuthorApi::to_delegate(author),
-			system::SystemApi::to_delegate(system),
-			rpc_extensions_builder.build(deny_unsafe, task_executor)?,
-		),
-		rpc_middleware,
-	))
+	let extensions = (
+		state::StateApi::to_delegate(state),
+		state::ChildStateApi::to_delegate(child_state),
+		chain::ChainApi::to_delegate(chain),
+		maybe_offchain_rpc,
+		author::AuthorApi::to_delegate(author),
+		system::SystemApi::to_delegate(system),
+		rpc_extensions_builder.build(deny_unsafe, task_executor)?,
+	);
+	Ok(match &config.rpc_modules {
+		Some(modules) =>
+			sc_rpc_server::rpc_handler(only_modules(extensions, modules), rpc_middleware),
+		None => sc_rpc_server::rpc_handler(extensions, rpc_middleware),
+	})
+}
+
+/// The methods of `extension` whose module, the part of their name before the first `_`, is one
+/// of `modules`.
+fn only_modules(
+	extension: impl sc_rpc::RpcExtension<sc_rpc::Metadata>,
+	modules: &[String],
+) -> Vec<(String, jsonrpc_core::RemoteProcedure<sc_rpc::Metadata>)> {
+	let mut io = jsonrpc_core::MetaIoHandler::default();
+	extension.augment(&mut io);
+	io.iter()
+		.filter(|(name, _)| {
+			let module = name.split('_').next().unwrap_or_default();
+			modules.iter().any(|served| served == module)
+		})
+		.map(|(name, method)| (name.clone(), method.clone()))
+		.collect()
 }
 
 /// Parameters to pass into `build_network`.
@@ -864,6 +932,7 @@ where
 		+ ProofProvider<TBl>
 		+ HeaderBackend<TBl>
 		+ BlockchainEvents<TBl>
+		+ CallApiAt<TBl>
 		+ 'static,
 	TExPool: MaintainedTransactionPool<Block = TBl, Hash = <TBl as BlockT>::Hash> + 'static,
 	TImpQu: ImportQueue<TBl> + 'static,
@@ -881,6 +950,8 @@ where
 
 	let transaction_pool_adapter = Arc::new(TransactionPoolAdapter {
 		imports_external_transactions: !matches!(config.role, Role::Light),
+		propagation: config.network.transaction_propagation,
+		received: Default::default(),
 		pool: transaction_pool,
 		client: client.clone(),
 	});
@@ -935,6 +1006,11 @@ where
 			// Allow both outgoing and incoming requests.
 			let (handler, protocol_config) =
 				WarpSyncRequestHandler::new(protocol_id.clone(), provider.clone());
+			let handler = match config.network.max_concurrent_warp_requests {
+				Some(limit) =>
+					handler.with_max_concurrent_requests(limit, Box::new(spawn_handle.clone())),
+				None => handler,
+			};
 			spawn_handle.spawn("warp_sync_request_handler", handler.run());
 			protocol_config
 		};
@@ -995,13 +1071,19 @@ where
 
 	let (system_rpc_tx, system_rpc_rx) = tracing_unbounded("mpsc_system_rpc");
 
+	let runtime_health = Arc::new(parking_lot::Mutex::new(None));
+	spawn_handle
+		.spawn("runtime-health", track_runtime_health(client.clone(), runtime_health.clone()));
+
 	let future = build_network_future(
 		config.role.clone(),
 		network_mut,
 		client,
+		runtime_health,
 		system_rpc_rx,
 		has_bootnodes,
 		config.announce_block,
+		config.health_warmup,
 	);
 
 	// TODO: Normally, one is supposed to pass a list of notifications protocols supported by the
@@ -1051,7 +1133,31 @@ impl NetworkStarter {
 	/// Start the network. Call this after all sub-components have been initialized.
 	///
 	/// > **Note**: If you don't call this function, the networking will not work.
+	///
+	/// The network worker isn't polled before this is called, so calling it after [`spawn_tasks`]
+	/// guarantees that the RPC servers are listening before the node connects to any peer.
 	pub fn start_network(self) {
 		let _ = self.0.send(());
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::only_modules;
+	use jsonrpc_core::{MetaIoHandler, Value};
+
+	#[test]
+	fn only_modules_keeps_the_methods_of_the_served_modules() {
+		let mut io = MetaIoHandler::<sc_rpc::Metadata>::default();
+		for method in &["author_submitExtrinsic", "system_name", "state_getStorage", "authorfoo"] {
+			io.add_sync_method(method, |_| Ok(Value::Null));
+		}
+
+		let mut served: Vec<_> = only_modules(io, &["author".into(), "system".into()])
+			.into_iter()
+			.map(|(name, _)| name)
+			.collect();
+		served.sort();
+		assert_eq!(served, vec!["author_submitExtrinsic", "system_name"]);
+	}
+}
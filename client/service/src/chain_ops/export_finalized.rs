@@ -0,0 +1,161 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error::Error;
+use codec::Encode;
+use futures::{future, prelude::*};
+use log::info;
+use serde_json::{json, Value};
+use sp_core::bytes::to_hex;
+use sp_runtime::{
+	generic::{BlockId, DigestItem, SignedBlock},
+	traits::{Block as BlockT, Header as HeaderT, NumberFor, One, SaturatedConversion, Zero},
+	ConsensusEngineId,
+};
+
+use sc_client_api::{BlockBackend, UsageProvider};
+use std::{io::Write, pin::Pin, sync::Arc, task::Poll};
+
+/// Performs the export of the finalized blocks.
+///
+/// With `scale`, the output has the same format as the binary output of [`export_blocks`],
+/// otherwise every block is written as a JSON object on its own line, with the header and the
+/// digest logs decoded.
+///
+/// [`export_blocks`]: super::export_blocks
+pub fn export_finalized_blocks<B, C>(
+	client: Arc<C>,
+	mut output: impl Write + 'static,
+	from: NumberFor<B>,
+	to: Option<NumberFor<B>>,
+	scale: bool,
+) -> Pin<Box<dyn Future<Output = Result<(), Error>>>>
+where
+	C: BlockBackend<B> + UsageProvider<B> + 'static,
+	B: BlockT,
+{
+	let mut block = from;
+
+	let finalized = client.usage_info().chain.finalized_number;
+	let last = match to {
+		Some(to) if to > finalized =>
+			return Box::pin(future::err(
+				format!("Block #{} is not finalized, last finalized block is #{}", to, finalized)
+					.into(),
+			)),
+		Some(to) => to,
+		None => finalized,
+	};
+
+	let mut wrote_header = false;
+
+	// Like `export_blocks`, the export re-schedules itself after every block, which keeps it
+	// interruptible. Every block is flushed before moving on to the next one, so that a slow
+	// reader of the output holds back the export instead of letting it buffer blocks.
+	let export = future::poll_fn(move |cx| {
+		if last < block {
+			return Poll::Ready(Err("Invalid block range specified".into()))
+		}
+
+		if !wrote_header {
+			info!("Exporting finalized blocks from #{} to #{}", block, last);
+			if scale {
+				let len = last.saturated_into::<u64>() - block.saturated_into::<u64>() + 1;
+				output.write_all(&len.encode())?;
+			}
+			wrote_header = true;
+		}
+
+		let signed_block = match client.block(&BlockId::number(block))? {
+			Some(signed_block) => signed_block,
+			None => return Poll::Ready(Err(format!("Finalized block #{} not found", block).into())),
+		};
+
+		if scale {
+			output.write_all(&signed_block.encode())?;
+		} else {
+			serde_json::to_writer(&mut output, &block_json(&signed_block))
+				.map_err(|e| format!("Error writing JSON: {}", e))?;
+			output.write_all(b"\n")?;
+		}
+		output.flush()?;
+
+		if (block % 10000u32.into()).is_zero() {
+			info!("#{}", block);
+		}
+		if block == last {
+			return Poll::Ready(Ok(()))
+		}
+		block += One::one();
+
+		// Re-schedule the task in order to continue the operation.
+		cx.waker().wake_by_ref();
+		Poll::Pending
+	});
+
+	Box::pin(export)
+}
+
+fn block_json<B: BlockT>(signed_block: &SignedBlock<B>) -> Value {
+	let header = signed_block.block.header();
+	let logs: Vec<_> = header.digest().logs().iter().map(digest_item_json).collect();
+	let extrinsics: Vec<_> = signed_block
+		.block
+		.extrinsics()
+		.iter()
+		.map(|xt| to_hex(&xt.encode(), false))
+		.collect();
+	let justifications: Vec<_> = signed_block
+		.justifications
+		.iter()
+		.flat_map(|justifications| justifications.iter())
+		.map(|(engine, data)| engine_data_json("justification", engine, data))
+		.collect();
+
+	json!({
+		"number": (*header.number()).saturated_into::<u64>(),
+		"hash": format!("{:?}", header.hash()),
+		"parentHash": format!("{:?}", header.parent_hash()),
+		"stateRoot": format!("{:?}", header.state_root()),
+		"extrinsicsRoot": format!("{:?}", header.extrinsics_root()),
+		"digest": logs,
+		"extrinsics": extrinsics,
+		"justifications": justifications,
+	})
+}
+
+fn digest_item_json<Hash: std::fmt::Debug>(item: &DigestItem<Hash>) -> Value {
+	match item {
+		DigestItem::ChangesTrieRoot(root) =>
+			json!({ "type": "changesTrieRoot", "root": format!("{:?}", root) }),
+		DigestItem::PreRuntime(engine, data) => engine_data_json("preRuntime", engine, data),
+		DigestItem::Consensus(engine, data) => engine_data_json("consensus", engine, data),
+		DigestItem::Seal(engine, data) => engine_data_json("seal", engine, data),
+		DigestItem::ChangesTrieSignal(signal) =>
+			json!({ "type": "changesTrieSignal", "data": to_hex(&signal.encode(), false) }),
+		DigestItem::Other(data) => json!({ "type": "other", "data": to_hex(data, false) }),
+	}
+}
+
+fn engine_data_json(kind: &str, engine: &ConsensusEngineId, data: &[u8]) -> Value {
+	json!({
+		"type": kind,
+		"engine": String::from_utf8_lossy(engine),
+		"data": to_hex(data, false),
+	})
+}
@@ -0,0 +1,33 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{client::genesis::construct_genesis_block, error::Error, ChainSpec};
+use sp_core::storage::Storage;
+use sp_runtime::traits::{Block as BlockT, HashFor};
+use sp_state_machine::InMemoryBackend;
+
+/// Build the genesis storage of the given chain spec.
+pub fn build_genesis_storage(spec: &dyn ChainSpec) -> Result<Storage, Error> {
+	spec.as_storage_builder().build_storage().map_err(Error::Other)
+}
+
+/// Compute the genesis block of the given genesis storage, without writing it to a database.
+pub fn genesis_block<B: BlockT>(storage: Storage) -> B {
+	let backend = InMemoryBackend::<HashFor<B>>::from(storage);
+	construct_genesis_block::<B>(*backend.root())
+}
@@ -0,0 +1,127 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error::Error;
+use log::{info, warn};
+use sc_client_api::{backend::Backend, BlockBackend, ExecutorProvider};
+use sp_core::{
+	traits::{CodeExecutor, RuntimeCode, SpawnNamed, WrappedRuntimeCode},
+	ExecutionContext,
+};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, NumberFor, One, Zero},
+};
+use sp_state_machine::{ExecutionStrategy, StateMachine};
+
+/// A block whose execution with the new runtime failed.
+#[derive(Debug)]
+pub struct Divergence<B: BlockT> {
+	/// Number of the block.
+	pub number: NumberFor<B>,
+	/// Hash of the block.
+	pub hash: B::Hash,
+	/// Why the execution failed.
+	pub error: String,
+}
+
+/// Re-execute the blocks from `from` to `to` with the wasm runtime `code` instead of the runtime
+/// they were imported with, returning the blocks that failed.
+///
+/// Every block is executed on top of the state of its parent, which therefore has to be
+/// available. Since the runtime checks the resulting state root against the one of the header,
+/// any difference in the state written by the new runtime shows up as a divergence.
+pub fn migration_test<B, BA, C, E>(
+	client: &C,
+	backend: &BA,
+	executor: &E,
+	code: &[u8],
+	from: NumberFor<B>,
+	to: NumberFor<B>,
+	spawn_handle: impl SpawnNamed + Clone + Send + 'static,
+) -> Result<Vec<Divergence<B>>, Error>
+where
+	B: BlockT,
+	BA: Backend<B>,
+	C: BlockBackend<B> + ExecutorProvider<B>,
+	E: CodeExecutor + Clone + 'static,
+{
+	if to < from {
+		return Err("Invalid block range specified".into())
+	}
+
+	let code_fetcher = WrappedRuntimeCode(code.into());
+	let runtime_code = RuntimeCode {
+		code_fetcher: &code_fetcher,
+		// The number of heap pages the executor was configured with.
+		heap_pages: None,
+		hash: sp_core::blake2_256(code).to_vec(),
+	};
+
+	info!("Re-executing blocks #{} to #{} with the new runtime", from, to);
+
+	let mut divergences = Vec::new();
+	let mut number = from;
+	loop {
+		let block = client
+			.block(&BlockId::Number(number))?
+			.ok_or_else(|| format!("Block #{} not found", number))?
+			.block;
+		let (mut header, extrinsics) = block.deconstruct();
+		let hash = header.hash();
+		let parent = BlockId::Hash(*header.parent_hash());
+
+		// The seal is added by the author after executing the block, and is stripped before import.
+		if header.digest().logs().last().map_or(false, |log| log.as_seal().is_some()) {
+			header.digest_mut().pop();
+		}
+		let block = B::new(header, extrinsics);
+
+		let state = backend.state_at(parent).map_err(|e| {
+			format!("State of the parent of block #{} is not available: {}", number, e)
+		})?;
+		let extensions =
+			client.execution_extensions().extensions(&parent, ExecutionContext::Importing);
+
+		let result = StateMachine::<_, _, NumberFor<B>, _>::new(
+			&state,
+			None,
+			&mut Default::default(),
+			executor,
+			"Core_execute_block",
+			&block.encode(),
+			extensions,
+			&runtime_code,
+			spawn_handle.clone(),
+		)
+		.execute(ExecutionStrategy::AlwaysWasm);
+
+		if let Err(e) = result {
+			warn!("Block #{} ({:?}) diverges: {:?}", number, hash, e);
+			divergences.push(Divergence { number, hash, error: format!("{:?}", e) });
+		}
+
+		if (number % 10000u32.into()).is_zero() {
+			info!("#{}", number);
+		}
+		if number == to {
+			return Ok(divergences)
+		}
+		number += One::one();
+	}
+}
@@ -19,13 +19,23 @@
 //! Chain utilities.
 
 mod check_block;
+mod compact_db;
 mod export_blocks;
+mod export_finalized;
 mod export_raw_state;
+mod genesis;
 mod import_blocks;
+mod migration_test;
+mod prune_forks;
 mod revert_chain;
 
 pub use check_block::*;
+pub use compact_db::*;
 pub use export_blocks::*;
+pub use export_finalized::*;
 pub use export_raw_state::*;
+pub use genesis::*;
 pub use import_blocks::*;
+pub use migration_test::*;
+pub use prune_forks::*;
 pub use revert_chain::*;
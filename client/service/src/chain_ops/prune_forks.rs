@@ -0,0 +1,174 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error::Error;
+use log::info;
+use sc_client_api::Backend;
+use sp_blockchain::{lowest_common_ancestor, Backend as _, HeaderBackend, HeaderMetadata};
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+/// A leaf of the chain, with the block where its branch leaves the best chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fork<B: BlockT> {
+	/// Hash of the leaf.
+	pub leaf_hash: B::Hash,
+	/// Number of the leaf.
+	pub leaf_number: NumberFor<B>,
+	/// Hash of the last block the branch shares with the best chain.
+	pub fork_point_hash: B::Hash,
+	/// Number of the last block the branch shares with the best chain.
+	pub fork_point_number: NumberFor<B>,
+}
+
+impl<B: BlockT> Fork<B> {
+	/// Whether the leaf is the best block.
+	pub fn is_canonical(&self) -> bool {
+		self.leaf_hash == self.fork_point_hash
+	}
+
+	/// Whether the branch leaves the best chain below `finalized`, so it can't ever be finalized.
+	pub fn is_stale(&self, finalized: NumberFor<B>) -> bool {
+		self.fork_point_number < finalized
+	}
+}
+
+/// List the leaves of the chain, from the highest to the lowest.
+pub fn list_forks<B, BA>(backend: &BA) -> Result<Vec<Fork<B>>, Error>
+where
+	B: BlockT,
+	BA: Backend<B>,
+{
+	let blockchain = backend.blockchain();
+	let best_hash = blockchain.info().best_hash;
+
+	blockchain
+		.leaves()?
+		.into_iter()
+		.map(|leaf_hash| {
+			let leaf_number = blockchain.header_metadata(leaf_hash)?.number;
+			let fork_point = lowest_common_ancestor(blockchain, leaf_hash, best_hash)?;
+			Ok(Fork {
+				leaf_hash,
+				leaf_number,
+				fork_point_hash: fork_point.hash,
+				fork_point_number: fork_point.number,
+			})
+		})
+		.collect()
+}
+
+/// Remove the branches of the stale forks, that leave the best chain below the finalized block.
+///
+/// Returns the number of blocks that were removed.
+pub fn prune_forks<B, BA>(backend: &BA) -> Result<u64, Error>
+where
+	B: BlockT,
+	BA: Backend<B>,
+{
+	let blockchain = backend.blockchain();
+	let finalized = blockchain.info().finalized_number;
+	let mut pruned = 0;
+
+	for fork in list_forks(backend)?.into_iter().filter(|fork| fork.is_stale(finalized)) {
+		// Walk down the branch, until a block that is still the parent of other blocks.
+		let mut hash = fork.leaf_hash;
+		loop {
+			let parent = blockchain.header_metadata(hash)?.parent;
+			backend.remove_leaf_block(&hash)?;
+			pruned += 1;
+
+			if parent == fork.fork_point_hash || !blockchain.leaves()?.contains(&parent) {
+				break
+			}
+			hash = parent;
+		}
+
+		info!(
+			"Pruned the fork from #{} ({}) to #{} ({})",
+			fork.fork_point_number, fork.fork_point_hash, fork.leaf_number, fork.leaf_hash,
+		);
+	}
+
+	info!("Pruned {} blocks of stale forks", pruned);
+	Ok(pruned)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sc_client_api::backend::{BlockImportOperation, NewBlockState};
+	use sc_client_db::Backend as DbBackend;
+	use sp_core::H256;
+	use sp_runtime::{
+		generic::BlockId,
+		traits::{BlakeTwo256, Hash as _, Header as _},
+	};
+	use substrate_test_runtime::{Block, Header};
+
+	fn insert(backend: &DbBackend<Block>, number: u64, parent_hash: H256, salt: u64) -> H256 {
+		let header = Header::new(
+			number,
+			H256::from_low_u64_be(salt),
+			BlakeTwo256::trie_root(Vec::new()),
+			parent_hash,
+			Default::default(),
+		);
+		let hash = header.hash();
+		let mut op = backend.begin_operation().unwrap();
+		backend.begin_state_operation(&mut op, BlockId::Hash(parent_hash)).unwrap();
+		op.set_block_data(header, Some(Vec::new()), None, None, NewBlockState::Best)
+			.unwrap();
+		backend.commit_operation(op).unwrap();
+		hash
+	}
+
+	#[test]
+	fn prunes_the_stale_forks_only() {
+		let backend = DbBackend::<Block>::new_test(10, 10);
+		let block0 = insert(&backend, 0, Default::default(), 0);
+		let block1 = insert(&backend, 1, block0, 0);
+		// A fork leaving the best chain at #1, below the finalized block.
+		let stale2 = insert(&backend, 2, block1, 1);
+		let stale3 = insert(&backend, 3, stale2, 1);
+		let block2 = insert(&backend, 2, block1, 0);
+		// A fork leaving the best chain at the finalized block, which can still be finalized.
+		let live3 = insert(&backend, 3, block2, 2);
+		let block3 = insert(&backend, 3, block2, 0);
+		backend.finalize_block(BlockId::Hash(block1), None).unwrap();
+		backend.finalize_block(BlockId::Hash(block2), None).unwrap();
+
+		let forks = list_forks(&backend).unwrap();
+		assert_eq!(forks.len(), 3);
+		let fork_of = |leaf| forks.iter().find(|fork| fork.leaf_hash == leaf).unwrap();
+		assert!(fork_of(block3).is_canonical());
+		assert_eq!(fork_of(stale3).fork_point_hash, block1);
+		assert!(fork_of(stale3).is_stale(2));
+		assert_eq!(fork_of(live3).fork_point_hash, block2);
+		assert!(!fork_of(live3).is_stale(2));
+
+		assert_eq!(prune_forks(&backend).unwrap(), 2);
+		let blockchain = backend.blockchain();
+		assert_eq!(blockchain.header(BlockId::Hash(stale2)).unwrap(), None);
+		assert_eq!(blockchain.header(BlockId::Hash(stale3)).unwrap(), None);
+		let mut leaves = blockchain.leaves().unwrap();
+		leaves.sort();
+		let mut expected = vec![block3, live3];
+		expected.sort();
+		assert_eq!(leaves, expected);
+	}
+}
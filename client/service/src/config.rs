@@ -30,18 +30,21 @@ pub use sc_network::{
 	Multiaddr,
 };
 
+use crate::task_manager::RestartPolicy;
 use prometheus_endpoint::Registry;
 use sc_chain_spec::ChainSpec;
-pub use sc_telemetry::TelemetryEndpoints;
+pub use sc_telemetry::{ReconnectPolicy as TelemetryReconnectPolicy, TelemetryEndpoints};
 pub use sc_transaction_pool::Options as TransactionPoolOptions;
 use sp_core::crypto::SecretString;
 use std::{
 	future::Future,
 	io,
 	net::SocketAddr,
+	num::NonZeroU32,
 	path::{Path, PathBuf},
 	pin::Pin,
 	sync::Arc,
+	time::Duration,
 };
 #[cfg(not(target_os = "unknown"))]
 use tempfile::TempDir;
@@ -67,10 +70,14 @@ pub struct Configuration {
 	pub keystore_remote: Option<String>,
 	/// Configuration for the database.
 	pub database: DatabaseSource,
+	/// Open the database read-only, without locking it and rejecting every change.
+	pub database_read_only: bool,
 	/// Size of internal state cache in Bytes
 	pub state_cache_size: usize,
 	/// Size in percent of cache size dedicated to child tries
 	pub state_cache_child_ratio: Option<usize>,
+	/// Read the state straight from the database, bypassing the state cache.
+	pub disable_state_cache_layer: bool,
 	/// State pruning settings.
 	pub state_pruning: PruningMode,
 	/// Number of blocks to keep in the db.
@@ -99,14 +106,39 @@ pub struct Configuration {
 	pub rpc_http_threads: Option<usize>,
 	/// CORS settings for HTTP & WS servers. `None` if all origins are allowed.
 	pub rpc_cors: Option<Vec<String>>,
+	/// Hosts accepted by the HTTP & WS servers besides their listening address, when the `Host`
+	/// header is checked, that is when `rpc_cors` isn't `None`.
+	pub rpc_allowed_hosts: Vec<String>,
 	/// RPC methods to expose (by default only a safe subset or all of them).
 	pub rpc_methods: RpcMethods,
 	/// Maximum payload of rpc request/responses.
 	pub rpc_max_payload: Option<usize>,
+	/// Maximum size of the HTTP and WS RPC requests in megabytes, `rpc_max_payload` if `None`.
+	pub rpc_max_request_size: Option<usize>,
+	/// Maximum size of the HTTP and WS RPC responses in megabytes, `None` if only limited by
+	/// `rpc_max_payload`.
+	pub rpc_max_response_size: Option<usize>,
+	/// Maximum number of RPC calls per second and peer address, or connection for WS, `None` if
+	/// unlimited.
+	pub rpc_rate_limit: Option<NonZeroU32>,
+	/// Maximum number of active subscriptions per WS RPC connection, `None` if unlimited.
+	pub rpc_max_subscriptions_per_connection: Option<NonZeroU32>,
+	/// Default depth of the confirmed head RPC subscriptions, `None` if they have to give one.
+	pub rpc_confirmation_depth: Option<u32>,
+	/// The RPC modules served, like `author` or `system`, every module if `None`.
+	///
+	/// The module of a method is the part of its name before the first `_`. The `rpc_methods`
+	/// method listing the served methods is always served.
+	pub rpc_modules: Option<Vec<String>>,
+	/// How long the health RPC reports the node as not ready after it started, `None` if it is
+	/// ready right away.
+	pub health_warmup: Option<Duration>,
 	/// Prometheus endpoint configuration. `None` if disabled.
 	pub prometheus_config: Option<PrometheusConfig>,
 	/// Telemetry service URL. `None` if disabled.
 	pub telemetry_endpoints: Option<TelemetryEndpoints>,
+	/// How the telemetry reconnects to the endpoints.
+	pub telemetry_reconnect: TelemetryReconnectPolicy,
 	/// The default number of 64KB pages to allocate for Wasm execution
 	pub default_heap_pages: Option<u64>,
 	/// Should offchain workers be executed.
@@ -179,6 +211,13 @@ pub struct OffchainWorkerConfig {
 	pub enabled: bool,
 	/// allow writes from the runtime to the offchain worker database.
 	pub indexing_enabled: bool,
+	/// Number of threads running the offchain workers, one per CPU if `None`.
+	pub threads: Option<usize>,
+	/// Maximum number of offchain HTTP requests in progress at the same time, unlimited if `None`.
+	pub http_concurrency: Option<usize>,
+	/// Restart the task running the offchain workers according to this policy when it fails,
+	/// never if `None`.
+	pub restart_policy: Option<RestartPolicy>,
 }
 
 /// Configuration of the Prometheus endpoint.
@@ -188,6 +227,10 @@ pub struct PrometheusConfig {
 	pub port: SocketAddr,
 	/// A metrics registry to use. Useful for setting the metric prefix.
 	pub registry: Registry,
+	/// Unix domain socket to listen on instead of `port`.
+	pub unix_socket: Option<PathBuf>,
+	/// Whether the registry is served, `false` only collects the metrics.
+	pub serve: bool,
 }
 
 impl PrometheusConfig {
@@ -199,8 +242,22 @@ impl PrometheusConfig {
 			port,
 			registry: Registry::new_custom(Some("substrate".into()), None)
 				.expect("this can only fail if the prefix is empty"),
+			unix_socket: None,
+			serve: true,
 		}
 	}
+
+	/// Listen on the Unix domain socket at `path` instead of the port.
+	pub fn with_unix_socket(mut self, path: PathBuf) -> Self {
+		self.unix_socket = Some(path);
+		self
+	}
+
+	/// Collect the metrics without serving them, for the node to export them itself.
+	pub fn without_endpoint(mut self) -> Self {
+		self.serve = false;
+		self
+	}
 }
 
 impl Configuration {
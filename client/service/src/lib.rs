@@ -34,13 +34,22 @@ mod client;
 mod metrics;
 mod task_manager;
 
-use std::{collections::HashMap, io, net::SocketAddr, pin::Pin, task::Poll};
+use std::{
+	collections::HashMap,
+	io,
+	net::SocketAddr,
+	pin::Pin,
+	task::Poll,
+	time::{Duration, Instant},
+};
 
 use codec::{Decode, Encode};
-use futures::{stream, Future, FutureExt, Stream, StreamExt};
+use futures::{stream, Future, FutureExt, Stream, StreamExt, TryFutureExt};
 use log::{debug, error, warn};
 use parity_util_mem::MallocSizeOf;
-use sc_network::PeerId;
+use parking_lot::Mutex;
+use sc_network::{config::TransactionPropagation, PeerId};
+use sp_api::CallApiAt;
 use sp_runtime::{
 	generic::BlockId,
 	traits::{Block as BlockT, Header as HeaderT},
@@ -75,9 +84,10 @@ pub use sc_rpc::Metadata as RpcMetadata;
 pub use sc_tracing::TracingReceiver;
 pub use sc_transaction_pool::Options as TransactionPoolOptions;
 pub use sc_transaction_pool_api::{error::IntoPoolError, InPoolTransaction, TransactionPool};
+use sc_transaction_pool_api::{TransactionStatus, TransactionStatusStreamFor, TxHash};
 #[doc(hidden)]
 pub use std::{ops::Deref, result::Result, sync::Arc};
-pub use task_manager::{SpawnTaskHandle, TaskManager};
+pub use task_manager::{RestartPolicy, SpawnTaskHandle, TaskManager};
 
 const DEFAULT_PROTOCOL_ID: &str = "sup";
 
@@ -95,8 +105,21 @@ impl<T> MallocSizeOfWasm for T {}
 #[derive(Clone)]
 pub struct RpcHandlers(
 	Arc<jsonrpc_core::MetaIoHandler<sc_rpc::Metadata, sc_rpc_server::RpcMiddleware>>,
+	RpcAddresses,
 );
 
+/// The addresses the HTTP and WebSocket RPC servers listen on.
+///
+/// They differ from the configured ones when the configured port is `0`, or when it was taken and
+/// the servers fell back to a random port.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RpcAddresses {
+	/// Address of the HTTP server, `None` if it isn't running.
+	pub http: Option<SocketAddr>,
+	/// Address of the WebSocket server, `None` if it isn't running.
+	pub ws: Option<SocketAddr>,
+}
+
 impl RpcHandlers {
 	/// Starts an RPC query.
 	///
@@ -121,6 +144,11 @@ impl RpcHandlers {
 	) -> Arc<jsonrpc_core::MetaIoHandler<sc_rpc::Metadata, sc_rpc_server::RpcMiddleware>> {
 		self.0.clone()
 	}
+
+	/// The addresses the HTTP and WebSocket RPC servers listen on.
+	pub fn addresses(&self) -> RpcAddresses {
+		self.1
+	}
 }
 
 /// An incomplete set of chain components, but enough to run the chain ops subcommands.
@@ -146,6 +174,9 @@ pub struct PartialComponents<Client, Backend, SelectChain, ImportQueue, Transact
 /// Builds a never-ending future that continuously polls the network.
 ///
 /// The `status_sink` contain a list of senders to send a periodic network status to.
+///
+/// With a `health_warmup`, the health RPC reports the node as warming up until this long after
+/// the start, and for as long as it is major syncing by then.
 async fn build_network_future<
 	B: BlockT,
 	C: BlockchainEvents<B> + HeaderBackend<B>,
@@ -154,15 +185,20 @@ async fn build_network_future<
 	role: Role,
 	mut network: sc_network::NetworkWorker<B, H>,
 	client: Arc<C>,
+	runtime: Arc<Mutex<Option<sc_rpc::system::HealthRuntime>>>,
 	mut rpc_rx: TracingUnboundedReceiver<sc_rpc::system::Request<B>>,
 	should_have_peers: bool,
 	announce_imported_blocks: bool,
+	health_warmup: Option<Duration>,
 ) {
 	let mut imported_blocks_stream = client.import_notification_stream().fuse();
 
 	// Current best block at initialization, to report to the RPC layer.
 	let starting_block = client.info().best_number;
 
+	// End of the warm-up, cleared once the warm-up is over.
+	let mut warmup_end = health_warmup.map(|warmup| Instant::now() + warmup);
+
 	// Stream of finalized blocks reported by the client.
 	let mut finality_notification_stream = {
 		let mut finality_notification_stream = client.finality_notification_stream().fuse();
@@ -217,10 +253,16 @@ async fn build_network_future<
 			request = rpc_rx.select_next_some() => {
 				match request {
 					sc_rpc::system::Request::Health(sender) => {
+						let is_syncing = network.service().is_major_syncing();
+						if warmup_end.map_or(false, |end| Instant::now() >= end && !is_syncing) {
+							warmup_end = None;
+						}
 						let _ = sender.send(sc_rpc::system::Health {
 							peers: network.peers_debug_info().len(),
-							is_syncing: network.service().is_major_syncing(),
+							is_syncing,
 							should_have_peers,
+							is_warming_up: warmup_end.is_some(),
+							runtime: runtime.lock().clone(),
 						});
 					},
 					sc_rpc::system::Request::LocalPeerId(sender) => {
@@ -304,6 +346,46 @@ async fn build_network_future<
 	}
 }
 
+/// Keep `runtime` at the version of the runtime of the finalized block, for the health RPC.
+///
+/// Reading the version can execute the runtime, which is why this doesn't happen in the network
+/// future answering the RPC.
+async fn track_runtime_health<B, C>(
+	client: Arc<C>,
+	runtime: Arc<Mutex<Option<sc_rpc::system::HealthRuntime>>>,
+) where
+	B: BlockT,
+	C: BlockchainEvents<B> + HeaderBackend<B> + CallApiAt<B>,
+{
+	let mut finality_notifications = client.finality_notification_stream();
+	*runtime.lock() = runtime_health(&*client, client.info().finalized_hash);
+	while let Some(mut notification) = finality_notifications.next().await {
+		// Only the version at the latest finalized block matters.
+		while let Some(Some(next)) = finality_notifications.next().now_or_never() {
+			notification = next;
+		}
+		*runtime.lock() = runtime_health(&*client, notification.hash);
+	}
+}
+
+/// The version of the runtime at `hash`, as reported by the health RPC.
+fn runtime_health<B: BlockT, C: CallApiAt<B>>(
+	client: &C,
+	hash: B::Hash,
+) -> Option<sc_rpc::system::HealthRuntime> {
+	match client.runtime_version_at(&BlockId::Hash(hash)) {
+		Ok(version) => Some(sc_rpc::system::HealthRuntime {
+			spec_name: version.spec_name.to_string(),
+			spec_version: version.spec_version,
+			impl_version: version.impl_version,
+		}),
+		Err(e) => {
+			debug!("Failed to get the runtime version at {}: {}", hash, e);
+			None
+		},
+	}
+}
+
 // Wrapper for HTTP and WS servers that makes sure they are properly shut down.
 #[cfg(not(target_os = "unknown"))]
 mod waiting {
@@ -311,8 +393,7 @@ mod waiting {
 	impl Drop for HttpServer {
 		fn drop(&mut self) {
 			if let Some(server) = self.0.take() {
-				server.close_handle().close();
-				server.wait();
+				server.close();
 			}
 		}
 	}
@@ -338,8 +419,32 @@ mod waiting {
 	}
 }
 
+/// Remove the Unix domain socket at `path` if nothing listens on it anymore.
+///
+/// The servers remove their socket when they shut down, a node that didn't shut down cleanly
+/// leaves it behind and binding the path again would fail. Files that aren't sockets are left
+/// alone.
+#[cfg(unix)]
+fn remove_stale_unix_socket(path: &std::path::Path) -> io::Result<()> {
+	use std::os::unix::{fs::FileTypeExt, net::UnixStream};
+
+	match std::fs::symlink_metadata(path) {
+		Ok(metadata) if metadata.file_type().is_socket() => match UnixStream::connect(path) {
+			Ok(_) => Err(io::Error::new(
+				io::ErrorKind::AddrInUse,
+				format!("Unix domain socket `{}` is in use", path.display()),
+			)),
+			Err(_) => {
+				debug!("Removing stale Unix domain socket `{}`", path.display());
+				std::fs::remove_file(path)
+			},
+		},
+		_ => Ok(()),
+	}
+}
+
 /// Starts RPC servers that run in their own thread, and returns an opaque object that keeps them
-/// alive.
+/// alive, along with the addresses they listen on.
 #[cfg(not(target_os = "unknown"))]
 fn start_rpc_servers<
 	H: FnMut(
@@ -351,7 +456,7 @@ fn start_rpc_servers<
 	mut gen_handler: H,
 	rpc_metrics: Option<sc_rpc_server::RpcMetrics>,
 	server_metrics: sc_rpc_server::ServerMetrics,
-) -> Result<Box<dyn std::any::Any + Send>, Error> {
+) -> Result<(Box<dyn std::any::Any + Send>, RpcAddresses), Error> {
 	fn maybe_start_server<T, F>(
 		address: Option<SocketAddr>,
 		mut start: F,
@@ -385,11 +490,23 @@ fn start_rpc_servers<
 	}
 
 	let rpc_method_names = sc_rpc_server::method_names(|m| gen_handler(sc_rpc::DenyUnsafe::No, m))?;
-	Ok(Box::new((
+	let rate_limiter = config
+		.rpc_rate_limit
+		.map(|limit| Arc::new(sc_rpc_server::RateLimiter::new(limit)));
+	let subscription_limiter = config
+		.rpc_max_subscriptions_per_connection
+		.map(|max| Arc::new(sc_rpc_server::SubscriptionLimiter::new(max)));
+	let max_request_size = config.rpc_max_request_size.or(config.rpc_max_payload);
+	let max_response_size = config
+		.rpc_max_response_size
+		.map(|mb| mb.saturating_mul(sc_rpc_server::MEGABYTE));
+	let servers = (
 		config
 			.rpc_ipc
 			.as_ref()
 			.map(|path| {
+				#[cfg(unix)]
+				remove_stale_unix_socket(path.as_ref())?;
 				sc_rpc_server::start_ipc(
 					&*path,
 					gen_handler(
@@ -398,6 +515,9 @@ fn start_rpc_servers<
 							rpc_metrics.clone(),
 							rpc_method_names.clone(),
 							"ipc",
+							None,
+							None,
+							None,
 						),
 					)?,
 					server_metrics.clone(),
@@ -410,15 +530,19 @@ fn start_rpc_servers<
 				address,
 				config.rpc_http_threads,
 				config.rpc_cors.as_ref(),
+				&config.rpc_allowed_hosts,
 				gen_handler(
 					deny_unsafe(&address, &config.rpc_methods),
 					sc_rpc_server::RpcMiddleware::new(
 						rpc_metrics.clone(),
 						rpc_method_names.clone(),
 						"http",
+						rate_limiter.clone(),
+						None,
+						max_response_size,
 					),
 				)?,
-				config.rpc_max_payload,
+				max_request_size,
 			)
 			.map_err(Error::from)
 		})?
@@ -428,25 +552,35 @@ fn start_rpc_servers<
 				address,
 				config.rpc_ws_max_connections,
 				config.rpc_cors.as_ref(),
+				&config.rpc_allowed_hosts,
 				gen_handler(
 					deny_unsafe(&address, &config.rpc_methods),
 					sc_rpc_server::RpcMiddleware::new(
 						rpc_metrics.clone(),
 						rpc_method_names.clone(),
 						"ws",
+						rate_limiter.clone(),
+						subscription_limiter.clone(),
+						max_response_size,
 					),
 				)?,
-				config.rpc_max_payload,
+				max_request_size,
+				config.rpc_max_response_size,
 				server_metrics.clone(),
 			)
 			.map_err(Error::from)
 		})?
 		.map(|s| waiting::WsServer(Some(s))),
-	)))
+	);
+	let addresses = RpcAddresses {
+		http: servers.1.as_ref().and_then(|s| s.0.as_ref()).map(|s| *s.address()),
+		ws: servers.2.as_ref().and_then(|s| s.0.as_ref()).map(|s| *s.addr()),
+	};
+	Ok((Box::new(servers), addresses))
 }
 
 /// Starts RPC servers that run in their own thread, and returns an opaque object that keeps them
-/// alive.
+/// alive, along with the addresses they listen on.
 #[cfg(target_os = "unknown")]
 fn start_rpc_servers<
 	H: FnMut(
@@ -458,8 +592,8 @@ fn start_rpc_servers<
 	_: H,
 	_: Option<sc_rpc_server::RpcMetrics>,
 	_: sc_rpc_server::ServerMetrics,
-) -> Result<Box<dyn std::any::Any + Send + Sync>, error::Error> {
-	Ok(Box::new(()))
+) -> Result<(Box<dyn std::any::Any + Send + Sync>, RpcAddresses), error::Error> {
+	Ok((Box::new(()), Default::default()))
 }
 
 /// An RPC session. Used to perform in-memory RPC queries (ie. RPC queries that don't go through
@@ -481,13 +615,60 @@ impl RpcSession {
 	}
 }
 
+/// The status stream of a transaction received from the network, once it is imported.
+type ReceivedWatcher<P> = Option<Pin<Box<TransactionStatusStreamFor<P>>>>;
+
 /// Transaction pool adapter.
-pub struct TransactionPoolAdapter<C, P> {
+pub struct TransactionPoolAdapter<C, P: TransactionPool> {
 	imports_external_transactions: bool,
+	propagation: TransactionPropagation,
+	/// The transactions imported from the network, only tracked with
+	/// [`TransactionPropagation::Local`].
+	received: Arc<Mutex<HashMap<TxHash<P>, ReceivedWatcher<P>>>>,
 	pool: Arc<P>,
 	client: Arc<C>,
 }
 
+impl<C, P: TransactionPool> TransactionPoolAdapter<C, P> {
+	/// Whether the transaction `hash` may be gossiped to the peers.
+	fn propagates(&self, hash: &TxHash<P>) -> bool {
+		match self.propagation {
+			TransactionPropagation::All => true,
+			TransactionPropagation::Local => !self.received.lock().contains_key(hash),
+			TransactionPropagation::None => false,
+		}
+	}
+
+	/// Forget the received transactions that left the pool for good, as reported by their
+	/// status streams.
+	fn prune_received(&self) {
+		self.received.lock().retain(|_, watcher| {
+			let watcher = match watcher {
+				Some(watcher) => watcher,
+				// Still being imported.
+				None => return true,
+			};
+			while let Some(status) = watcher.next().now_or_never() {
+				match status {
+					// An included transaction may come back to the pool on a reorg.
+					Some(TransactionStatus::Future) |
+					Some(TransactionStatus::Ready) |
+					Some(TransactionStatus::Broadcast(_)) |
+					Some(TransactionStatus::InBlock(_)) |
+					Some(TransactionStatus::Retracted(_)) => {},
+					Some(TransactionStatus::FinalityTimeout(_)) |
+					Some(TransactionStatus::Finalized(_)) |
+					Some(TransactionStatus::Usurped(_)) |
+					Some(TransactionStatus::Dropped) |
+					Some(TransactionStatus::Invalid) |
+					None => return false,
+				}
+			}
+			true
+		});
+	}
+}
+
 /// Get transactions for propagation.
 ///
 /// Function extracted to simplify the test and prevent creating `ServiceFactory`.
@@ -517,7 +698,16 @@ where
 	E: 'static + IntoPoolError + From<sc_transaction_pool_api::error::Error>,
 {
 	fn transactions(&self) -> Vec<(H, B::Extrinsic)> {
-		transactions_to_propagate(&*self.pool)
+		match self.propagation {
+			TransactionPropagation::All => transactions_to_propagate(&*self.pool),
+			TransactionPropagation::Local => {
+				self.prune_received();
+				let mut transactions = transactions_to_propagate(&*self.pool);
+				transactions.retain(|(hash, _)| self.propagates(hash));
+				transactions
+			},
+			TransactionPropagation::None => Vec::new(),
+		}
 	}
 
 	fn hash_of(&self, transaction: &B::Extrinsic) -> H {
@@ -541,11 +731,42 @@ where
 
 		let best_block_id = BlockId::hash(self.client.info().best_hash);
 
-		let import_future = self.pool.submit_one(
-			&best_block_id,
-			sc_transaction_pool_api::TransactionSource::External,
-			uxt,
-		);
+		// Marked as received before the import, which notifies the network of the transaction.
+		let received = match self.propagation {
+			TransactionPropagation::Local => {
+				let hash = self.pool.hash_of(&uxt);
+				let mut received = self.received.lock();
+				(!received.contains_key(&hash)).then(|| {
+					received.insert(hash.clone(), None);
+					(hash, self.received.clone())
+				})
+			},
+			TransactionPropagation::All | TransactionPropagation::None => None,
+		};
+
+		let source = sc_transaction_pool_api::TransactionSource::External;
+		let import_future = match received {
+			// The status of the transaction tells when it leaves the pool.
+			Some((hash, received)) => self
+				.pool
+				.submit_and_watch(&best_block_id, source, uxt)
+				.map(move |result| match result {
+					Ok(watcher) => {
+						if let Some(entry) = received.lock().get_mut(&hash) {
+							*entry = Some(watcher);
+						}
+						Ok(())
+					},
+					Err(e) => {
+						// Not imported, so the transaction is invalid or keeps the origin it has
+						// in the pool.
+						received.lock().remove(&hash);
+						Err(e)
+					},
+				})
+				.boxed(),
+			None => self.pool.submit_one(&best_block_id, source, uxt).map_ok(drop).boxed(),
+		};
 		Box::pin(async move {
 			match import_future.await {
 				Ok(_) => TransactionImport::NewGood,
@@ -572,6 +793,10 @@ where
 	}
 
 	fn transaction(&self, hash: &H) -> Option<B::Extrinsic> {
+		if !self.propagates(hash) {
+			return None
+		}
+
 		self.pool.ready_transaction(hash).and_then(
 			// Only propagable transactions should be resolved for network service.
 			|tx| if tx.is_propagable() { Some(tx.data().clone()) } else { None },
@@ -627,4 +852,124 @@ mod tests {
 		// this should not panic
 		let _ = transactions[0].1.transfer();
 	}
+
+	#[test]
+	fn only_propagates_local_transactions_when_configured() {
+		use sc_network::config::TransactionPool as _;
+
+		let (client, longest_chain) = TestClientBuilder::new().build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let pool =
+			BasicPool::new_full(Default::default(), true.into(), None, spawner, client.clone());
+		let best = block_on(longest_chain.best_chain()).unwrap();
+		let transfer = |nonce| {
+			Transfer {
+				amount: 5,
+				nonce,
+				from: AccountKeyring::Alice.into(),
+				to: Default::default(),
+			}
+			.into_signed_tx()
+		};
+		let adapter = |propagation| TransactionPoolAdapter {
+			imports_external_transactions: true,
+			propagation,
+			received: Default::default(),
+			pool: pool.clone(),
+			client: client.clone(),
+		};
+
+		let local = transfer(0);
+		let source = sp_runtime::transaction_validity::TransactionSource::External;
+		block_on(pool.submit_one(&BlockId::hash(best.hash()), source, local.clone())).unwrap();
+		let adapter_local = adapter(TransactionPropagation::Local);
+		let received = transfer(1);
+		assert!(matches!(
+			block_on(adapter_local.import(received.clone())),
+			TransactionImport::NewGood
+		));
+		assert_eq!(pool.status().ready, 2);
+
+		let transactions = adapter_local.transactions();
+		assert_eq!(transactions.len(), 1);
+		assert_eq!(transactions[0].1, local);
+		assert!(adapter_local.transaction(&pool.hash_of(&received)).is_none());
+		assert!(adapter_local.transaction(&pool.hash_of(&local)).is_some());
+
+		let adapter_none = adapter(TransactionPropagation::None);
+		assert!(adapter_none.transactions().is_empty());
+		assert!(adapter_none.transaction(&pool.hash_of(&local)).is_none());
+	}
+
+	#[test]
+	fn keeps_the_mark_of_received_future_transactions() {
+		use sc_network::config::TransactionPool as _;
+
+		let (client, longest_chain) = TestClientBuilder::new().build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let pool =
+			BasicPool::new_full(Default::default(), true.into(), None, spawner, client.clone());
+		let best = BlockId::hash(block_on(longest_chain.best_chain()).unwrap().hash());
+		let transfer = |nonce| {
+			Transfer {
+				amount: 5,
+				nonce,
+				from: AccountKeyring::Alice.into(),
+				to: Default::default(),
+			}
+			.into_signed_tx()
+		};
+		let adapter = TransactionPoolAdapter {
+			imports_external_transactions: true,
+			propagation: TransactionPropagation::Local,
+			received: Default::default(),
+			pool: pool.clone(),
+			client: client.clone(),
+		};
+		let source = sp_runtime::transaction_validity::TransactionSource::External;
+
+		block_on(pool.submit_one(&best, source, transfer(0))).unwrap();
+		let received = transfer(2);
+		assert!(matches!(block_on(adapter.import(received.clone())), TransactionImport::NewGood));
+		assert_eq!(pool.status().future, 1);
+		assert_eq!(adapter.transactions().len(), 1);
+
+		// The received transaction becomes ready, and is still not propagated.
+		block_on(pool.submit_one(&best, source, transfer(1))).unwrap();
+		assert_eq!(pool.status().ready, 3);
+		let transactions = adapter.transactions();
+		assert_eq!(transactions.len(), 2);
+		assert!(transactions.iter().all(|(_, tx)| *tx != received));
+
+		// The mark is dropped once the transaction leaves the pool.
+		pool.remove_invalid(&[pool.hash_of(&received)]);
+		adapter.transactions();
+		assert!(adapter.received.lock().is_empty());
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn removes_stale_unix_sockets_only() {
+		use std::os::unix::net::UnixListener;
+
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("rpc.sock");
+
+		// Dropping the listener leaves the socket file behind.
+		drop(UnixListener::bind(&path).unwrap());
+		remove_stale_unix_socket(&path).unwrap();
+		assert!(!path.exists());
+
+		let _listener = UnixListener::bind(&path).unwrap();
+		let err = remove_stale_unix_socket(&path).unwrap_err();
+		assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+		assert!(path.exists());
+
+		let file = dir.path().join("file");
+		std::fs::write(&file, b"").unwrap();
+		remove_stale_unix_socket(&file).unwrap();
+		assert!(file.exists());
+	}
 }
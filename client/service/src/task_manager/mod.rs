@@ -34,7 +34,12 @@ use prometheus_endpoint::{
 	Registry, U64,
 };
 use sp_utils::mpsc::{tracing_unbounded, TracingUnboundedReceiver, TracingUnboundedSender};
-use std::{panic, pin::Pin, result::Result};
+use std::{
+	panic,
+	pin::Pin,
+	result::Result,
+	time::{Duration, Instant},
+};
 use tracing_futures::Instrument;
 
 mod prometheus_future;
@@ -72,6 +77,22 @@ impl SpawnTaskHandle {
 		self.spawn_inner(name, task, TaskType::Blocking)
 	}
 
+	/// Spawns the task built by `task`, restarting it according to `policy`.
+	///
+	/// Whenever the task ends or panics, a new one is built by `task` and spawned after an
+	/// exponential backoff. Once the task has ended more than `policy.max_restarts` times, it is
+	/// given up on and the service keeps running without it.
+	pub fn spawn_supervised<F>(
+		&self,
+		name: &'static str,
+		policy: RestartPolicy,
+		task: impl FnMut() -> F + Send + 'static,
+	) where
+		F: Future<Output = ()> + Send + 'static,
+	{
+		self.spawn_inner(name, supervise(name, policy, task), TaskType::Async)
+	}
+
 	/// Helper function that implements the spawning logic. See `spawn` and `spawn_blocking`.
 	fn spawn_inner(
 		&self,
@@ -152,6 +173,85 @@ impl sp_core::traits::SpawnNamed for SpawnTaskHandle {
 	}
 }
 
+/// How a supervised task is restarted, see [`SpawnTaskHandle::spawn_supervised`] and
+/// [`SpawnEssentialTaskHandle::spawn_supervised`].
+#[derive(Debug, Clone, Copy)]
+pub struct RestartPolicy {
+	/// Number of times the task is restarted before it is considered failed.
+	pub max_restarts: u32,
+	/// Delay before the first restart, doubled after every restart.
+	pub initial_backoff: Duration,
+	/// Upper bound of the delay between two restarts.
+	pub max_backoff: Duration,
+	/// A task running for this long is considered recovered, and its restarts are counted again
+	/// from zero.
+	pub stable_period: Duration,
+}
+
+impl RestartPolicy {
+	/// Restart the task up to `max_restarts` times, with the default backoff.
+	pub fn new(max_restarts: u32) -> Self {
+		Self { max_restarts, ..Default::default() }
+	}
+
+	/// The delay before the restart following `restarts` previous restarts.
+	fn backoff(&self, restarts: u32) -> Duration {
+		let factor = 2u32.checked_pow(restarts).unwrap_or(u32::MAX);
+		self.initial_backoff
+			.checked_mul(factor)
+			.map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+	}
+}
+
+impl Default for RestartPolicy {
+	fn default() -> Self {
+		Self {
+			max_restarts: 5,
+			initial_backoff: Duration::from_secs(1),
+			max_backoff: Duration::from_secs(60),
+			stable_period: Duration::from_secs(10 * 60),
+		}
+	}
+}
+
+/// Run the task built by `task` until it has ended more than `policy.max_restarts` times in a
+/// row, building a new one after every end.
+async fn supervise<F>(
+	name: &'static str,
+	policy: RestartPolicy,
+	mut task: impl FnMut() -> F + Send + 'static,
+) where
+	F: Future<Output = ()> + Send + 'static,
+{
+	let mut restarts = 0;
+	loop {
+		let started = Instant::now();
+		let outcome = match panic::AssertUnwindSafe(task()).catch_unwind().await {
+			Ok(()) => "ended",
+			Err(_) => "panicked",
+		};
+		if started.elapsed() >= policy.stable_period {
+			restarts = 0;
+		}
+		if restarts == policy.max_restarts {
+			error!("Task `{}` {} after {} restarts.", name, outcome, restarts);
+			return
+		}
+
+		let backoff = policy.backoff(restarts);
+		restarts += 1;
+		log::warn!(
+			"Task `{}` {}, restarting it in {:?} ({}/{}).",
+			name,
+			outcome,
+			backoff,
+			restarts,
+			policy.max_restarts,
+		);
+		futures_timer::Delay::new(backoff).await;
+	}
+}
+
 /// A wrapper over `SpawnTaskHandle` that will notify a receiver whenever any
 /// task spawned through it fails. The service should be on the receiver side
 /// and will shut itself down whenever it receives any message, i.e. an
@@ -189,6 +289,21 @@ impl SpawnEssentialTaskHandle {
 		self.spawn_inner(name, task, TaskType::Blocking)
 	}
 
+	/// Spawns the essential task built by `task`, restarting it according to `policy`.
+	///
+	/// Like [`SpawnTaskHandle::spawn_supervised`], except that the service is shut down once the
+	/// task has ended more than `policy.max_restarts` times.
+	pub fn spawn_supervised<F>(
+		&self,
+		name: &'static str,
+		policy: RestartPolicy,
+		task: impl FnMut() -> F + Send + 'static,
+	) where
+		F: Future<Output = ()> + Send + 'static,
+	{
+		self.spawn_inner(name, supervise(name, policy, task), TaskType::Async)
+	}
+
 	fn spawn_inner(
 		&self,
 		name: &'static str,
@@ -315,9 +430,13 @@ impl TaskManager {
 			// The keep_alive stuff is holding references to some RPC handles etc. These
 			// RPC handles spawn their own tokio stuff and that doesn't like to be closed in an
 			// async context. So, we move the deletion to some other thread.
-			std::thread::spawn(move || {
+			//
+			// The shutdown waits for the deletion, so that e.g. the temporary base path of
+			// `--tmp` is removed before the process exits.
+			let _ = std::thread::spawn(move || {
 				let _ = keep_alive;
-			});
+			})
+			.join();
 		})
 	}
 
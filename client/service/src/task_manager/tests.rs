@@ -16,7 +16,10 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{config::TaskExecutor, task_manager::TaskManager};
+use crate::{
+	config::TaskExecutor,
+	task_manager::{RestartPolicy, TaskManager},
+};
 use futures::{future::FutureExt, pin_mut, select};
 use parking_lot::Mutex;
 use std::{any::Any, sync::Arc, time::Duration};
@@ -126,6 +129,19 @@ fn ensure_keep_alive_during_shutdown() {
 	drop_tester.wait_on_drop();
 }
 
+#[test]
+fn ensure_keep_alive_is_dropped_once_shutdown_completes() {
+	let runtime = tokio::runtime::Runtime::new().unwrap();
+	let handle = runtime.handle().clone();
+	let task_executor: TaskExecutor = (move |future, _| handle.spawn(future).map(|_| ())).into();
+
+	let mut task_manager = new_task_manager(task_executor);
+	let drop_tester = DropTester::new();
+	task_manager.keep_alive(drop_tester.new_ref());
+	runtime.block_on(task_manager.clean_shutdown());
+	assert_eq!(drop_tester, 0);
+}
+
 #[test]
 fn ensure_blocking_futures_are_awaited_on_shutdown() {
 	let runtime = tokio::runtime::Runtime::new().unwrap();
@@ -218,6 +234,140 @@ fn ensure_task_manager_future_ends_with_error_when_essential_task_fails() {
 	drop_tester.wait_on_drop();
 }
 
+#[test]
+fn ensure_supervised_essential_task_is_restarted_before_failing() {
+	let runtime = tokio::runtime::Runtime::new().unwrap();
+	let handle = runtime.handle().clone();
+	let task_executor: TaskExecutor = (move |future, _| handle.spawn(future).map(|_| ())).into();
+
+	let mut task_manager = new_task_manager(task_executor);
+	let spawn_essential_handle = task_manager.spawn_essential_handle();
+	let policy = RestartPolicy {
+		max_restarts: 2,
+		initial_backoff: Duration::from_millis(10),
+		max_backoff: Duration::from_millis(20),
+		stable_period: Duration::from_secs(60),
+	};
+	let attempts = Arc::new(Mutex::new(0));
+	let counter = attempts.clone();
+	spawn_essential_handle.spawn_supervised("task1", policy, move || {
+		*counter.lock() += 1;
+		async { panic!("task failed") }
+	});
+	runtime
+		.block_on(task_manager.future())
+		.expect_err("future()'s Result must be Err");
+	assert_eq!(*attempts.lock(), 3);
+	runtime.block_on(task_manager.clean_shutdown());
+}
+
+#[test]
+fn ensure_supervised_essential_task_keeps_the_service_alive_once_recovered() {
+	let runtime = tokio::runtime::Runtime::new().unwrap();
+	let handle = runtime.handle().clone();
+	let task_executor: TaskExecutor = (move |future, _| handle.spawn(future).map(|_| ())).into();
+
+	let mut task_manager = new_task_manager(task_executor);
+	let spawn_essential_handle = task_manager.spawn_essential_handle();
+	let drop_tester = DropTester::new();
+	let keep_alive = drop_tester.clone();
+	let mut failed = false;
+	spawn_essential_handle.spawn_supervised("task1", RestartPolicy::new(1), move || {
+		let fail = !std::mem::replace(&mut failed, true);
+		let keep_alive = keep_alive.new_ref();
+		async move {
+			if fail {
+				panic!("task failed");
+			}
+			run_background_task(keep_alive).await
+		}
+	});
+	runtime.block_on(async {
+		let t1 = task_manager.future().fuse();
+		let t2 = tokio::time::sleep(Duration::from_secs(3)).fuse();
+
+		pin_mut!(t1, t2);
+
+		select! {
+			res = t1 => panic!("task should have been restarted: {:?}", res),
+			_ = t2 => {},
+		}
+	});
+	assert_eq!(drop_tester, 1);
+	runtime.block_on(task_manager.clean_shutdown());
+	drop_tester.wait_on_drop();
+}
+
+#[test]
+fn ensure_supervised_task_is_given_up_without_failing_the_service() {
+	let runtime = tokio::runtime::Runtime::new().unwrap();
+	let handle = runtime.handle().clone();
+	let task_executor: TaskExecutor = (move |future, _| handle.spawn(future).map(|_| ())).into();
+
+	let mut task_manager = new_task_manager(task_executor);
+	let spawn_handle = task_manager.spawn_handle();
+	let policy = RestartPolicy {
+		max_restarts: 1,
+		initial_backoff: Duration::from_millis(10),
+		max_backoff: Duration::from_millis(10),
+		stable_period: Duration::from_secs(60),
+	};
+	let attempts = Arc::new(Mutex::new(0));
+	let counter = attempts.clone();
+	spawn_handle.spawn_supervised("task1", policy, move || {
+		*counter.lock() += 1;
+		async { panic!("task failed") }
+	});
+	runtime.block_on(async {
+		let t1 = task_manager.future().fuse();
+		let t2 = tokio::time::sleep(Duration::from_secs(1)).fuse();
+
+		pin_mut!(t1, t2);
+
+		select! {
+			res = t1 => panic!("the service should keep running: {:?}", res),
+			_ = t2 => {},
+		}
+	});
+	assert_eq!(*attempts.lock(), 2);
+	runtime.block_on(task_manager.clean_shutdown());
+}
+
+#[test]
+fn ensure_supervised_task_restarts_are_reset_after_the_stable_period() {
+	let runtime = tokio::runtime::Runtime::new().unwrap();
+	let handle = runtime.handle().clone();
+	let task_executor: TaskExecutor = (move |future, _| handle.spawn(future).map(|_| ())).into();
+
+	let mut task_manager = new_task_manager(task_executor);
+	let spawn_essential_handle = task_manager.spawn_essential_handle();
+	let policy = RestartPolicy {
+		max_restarts: 1,
+		initial_backoff: Duration::from_millis(10),
+		max_backoff: Duration::from_millis(10),
+		stable_period: Duration::from_millis(50),
+	};
+	let attempts = Arc::new(Mutex::new(0));
+	let counter = attempts.clone();
+	spawn_essential_handle.spawn_supervised("task1", policy, move || {
+		*counter.lock() += 1;
+		tokio::time::sleep(Duration::from_millis(100))
+	});
+	runtime.block_on(async {
+		let t1 = task_manager.future().fuse();
+		let t2 = tokio::time::sleep(Duration::from_secs(1)).fuse();
+
+		pin_mut!(t1, t2);
+
+		select! {
+			res = t1 => panic!("the restarts should have been reset: {:?}", res),
+			_ = t2 => {},
+		}
+	});
+	assert!(*attempts.lock() > 2);
+	runtime.block_on(task_manager.clean_shutdown());
+}
+
 #[test]
 fn ensure_children_tasks_ends_when_task_manager_terminated() {
 	let runtime = tokio::runtime::Runtime::new().unwrap();
@@ -235,8 +235,10 @@ fn node_config<
 		keystore_remote: Default::default(),
 		keystore: KeystoreConfig::Path { path: root.join("key"), password: None },
 		database: DatabaseSource::RocksDb { path: root.join("db"), cache_size: 128 },
+		database_read_only: false,
 		state_cache_size: 16777216,
 		state_cache_child_ratio: None,
+		disable_state_cache_layer: false,
 		state_pruning: Default::default(),
 		keep_blocks: KeepBlocks::All,
 		transaction_storage: TransactionStorageMode::BlockBody,
@@ -250,10 +252,19 @@ fn node_config<
 		rpc_ws_max_connections: None,
 		rpc_http_threads: None,
 		rpc_cors: None,
+		rpc_allowed_hosts: Vec::new(),
 		rpc_methods: Default::default(),
 		rpc_max_payload: None,
+		rpc_max_request_size: None,
+		rpc_max_response_size: None,
+		rpc_rate_limit: None,
+		rpc_max_subscriptions_per_connection: None,
+		rpc_confirmation_depth: None,
+		rpc_modules: None,
+		health_warmup: None,
 		prometheus_config: None,
 		telemetry_endpoints: None,
+		telemetry_reconnect: Default::default(),
 		default_heap_pages: None,
 		offchain_worker: Default::default(),
 		force_authoring: false,
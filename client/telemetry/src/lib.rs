@@ -56,6 +56,7 @@ mod transport;
 
 pub use endpoints::*;
 pub use error::*;
+pub use node::ReconnectPolicy;
 use node::*;
 use transport::*;
 
@@ -116,6 +117,10 @@ pub struct TelemetryWorker {
 	register_sender: mpsc::UnboundedSender<Register>,
 	id_counter: Arc<atomic::AtomicU64>,
 	transport: WsTrans,
+	reconnect: ReconnectPolicy,
+	node_map: HashMap<Id, Vec<(VerbosityLevel, Multiaddr)>>,
+	node_pool: HashMap<Multiaddr, Node<WsTrans>>,
+	pending_connection_notifications: Vec<(Multiaddr, ConnectionNotifierSender)>,
 }
 
 impl TelemetryWorker {
@@ -134,9 +139,20 @@ impl TelemetryWorker {
 			register_sender,
 			id_counter: Arc::new(atomic::AtomicU64::new(1)),
 			transport,
+			reconnect: ReconnectPolicy::default(),
+			node_map: HashMap::new(),
+			node_pool: HashMap::new(),
+			pending_connection_notifications: Vec::new(),
 		})
 	}
 
+	/// Reconnect to the telemetry servers according to `reconnect`, instead of every 10 to 20
+	/// seconds.
+	pub fn with_reconnect_policy(mut self, reconnect: ReconnectPolicy) -> Self {
+		self.reconnect = reconnect;
+		self
+	}
+
 	/// Get a new [`TelemetryWorkerHandle`].
 	///
 	/// This is used when you want to register with the [`TelemetryWorker`].
@@ -152,23 +168,28 @@ impl TelemetryWorker {
 	///
 	/// This should be run in a background task.
 	pub async fn run(mut self) {
-		let mut node_map: HashMap<Id, Vec<(VerbosityLevel, Multiaddr)>> = HashMap::new();
-		let mut node_pool: HashMap<Multiaddr, _> = HashMap::new();
-		let mut pending_connection_notifications: Vec<_> = Vec::new();
+		self.run_in_place().await
+	}
 
+	/// Run the telemetry worker, keeping its state in `self`.
+	///
+	/// Unlike [`TelemetryWorker::run`], the worker can be run again if the future panics or is
+	/// dropped, with the telemetries registered so far.
+	pub async fn run_in_place(&mut self) {
 		loop {
 			futures::select! {
 				message = self.message_receiver.next() => Self::process_message(
 					message,
-					&mut node_pool,
-					&node_map,
+					&mut self.node_pool,
+					&self.node_map,
 				).await,
 				init_payload = self.register_receiver.next() => Self::process_register(
 					init_payload,
-					&mut node_pool,
-					&mut node_map,
-					&mut pending_connection_notifications,
+					&mut self.node_pool,
+					&mut self.node_map,
+					&mut self.pending_connection_notifications,
 					self.transport.clone(),
+					self.reconnect,
 				).await,
 			}
 		}
@@ -180,6 +201,7 @@ impl TelemetryWorker {
 		node_map: &mut HashMap<Id, Vec<(VerbosityLevel, Multiaddr)>>,
 		pending_connection_notifications: &mut Vec<(Multiaddr, ConnectionNotifierSender)>,
 		transport: WsTrans,
+		reconnect: ReconnectPolicy,
 	) {
 		let input = input.expect("the stream is never closed; qed");
 
@@ -211,13 +233,19 @@ impl TelemetryWorker {
 				for (addr, verbosity) in endpoints {
 					log::trace!(
 						target: "telemetry",
-						"Initializing telemetry for: {:?}",
-						addr,
+						"Initializing telemetry for: {}",
+						Redacted(&addr),
 					);
 					node_map.entry(id.clone()).or_default().push((verbosity, addr.clone()));
 
 					let node = node_pool.entry(addr.clone()).or_insert_with(|| {
-						Node::new(transport.clone(), addr.clone(), Vec::new(), Vec::new())
+						Node::new(
+							transport.clone(),
+							addr.clone(),
+							Vec::new(),
+							Vec::new(),
+							reconnect,
+						)
 					});
 
 					node.connection_messages.extend(connection_message.clone());
@@ -293,7 +321,7 @@ impl TelemetryWorker {
 					target: "telemetry",
 					"Received message for unknown node ({}). This is a bug. \
 					Message sent: {}",
-					addr,
+					Redacted(&addr),
 					serde_json::to_string(&message)
 						.unwrap_or_else(|err| format!(
 							"could not be serialized ({}): {:?}",
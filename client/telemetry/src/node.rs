@@ -18,7 +18,7 @@
 
 use crate::TelemetryPayload;
 use futures::{channel::mpsc, prelude::*};
-use libp2p::{core::transport::Transport, Multiaddr};
+use libp2p::{core::transport::Transport, multiaddr::Protocol, Multiaddr};
 use rand::Rng as _;
 use std::{
 	fmt, mem,
@@ -36,6 +36,40 @@ pub(crate) fn connection_notifier_channel() -> (ConnectionNotifierSender, Connec
 	mpsc::channel(0)
 }
 
+/// Displays the address of a telemetry node without its WebSocket path, which may contain the
+/// credentials of the node.
+pub(crate) struct Redacted<'a>(pub(crate) &'a Multiaddr);
+
+impl fmt::Display for Redacted<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for protocol in self.0.iter() {
+			match protocol {
+				Protocol::Ws(path) if path != "/" => f.write_str("/x-parity-ws/<redacted>")?,
+				Protocol::Wss(path) if path != "/" => f.write_str("/x-parity-wss/<redacted>")?,
+				protocol => write!(f, "{}", protocol)?,
+			}
+		}
+		Ok(())
+	}
+}
+
+/// How a telemetry node reconnects after the connection is lost or fails to establish.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+	/// Minimum pause before dialing again, the actual pause is randomly up to twice as long so
+	/// that nodes don't all reconnect at the same time.
+	pub interval: Duration,
+	/// Number of failed attempts in a row after which the node stops reconnecting, never if
+	/// `None`.
+	pub max_retries: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+	fn default() -> Self {
+		Self { interval: Duration::from_secs(10), max_retries: None }
+	}
+}
+
 /// Handler for a single telemetry node.
 ///
 /// This is a wrapper `Sink` around a network `Sink` with 3 particularities:
@@ -59,6 +93,10 @@ pub(crate) struct Node<TTrans: Transport> {
 	pub(crate) connection_messages: Vec<TelemetryPayload>,
 	/// Notifier for when the connection (re-)establishes.
 	pub(crate) telemetry_connection_notifier: Vec<ConnectionNotifierSender>,
+	/// How to reconnect.
+	reconnect: ReconnectPolicy,
+	/// Number of failed attempts since the connection was last established.
+	failures: u32,
 }
 
 enum NodeSocket<TTrans: Transport> {
@@ -70,19 +108,12 @@ enum NodeSocket<TTrans: Transport> {
 	ReconnectNow,
 	/// Waiting before attempting to dial again.
 	WaitingReconnect(Delay),
+	/// Too many attempts failed, the node doesn't reconnect anymore.
+	GaveUp,
 	/// Temporary transition state.
 	Poisoned,
 }
 
-impl<TTrans: Transport> NodeSocket<TTrans> {
-	fn wait_reconnect() -> NodeSocket<TTrans> {
-		let random_delay = rand::thread_rng().gen_range(10, 20);
-		let delay = Delay::new(Duration::from_secs(random_delay));
-		log::trace!(target: "telemetry", "Pausing for {} secs before reconnecting", random_delay);
-		NodeSocket::WaitingReconnect(delay)
-	}
-}
-
 struct NodeSocketConnected<TTrans: Transport> {
 	/// Where to send data.
 	sink: TTrans::Output,
@@ -97,6 +128,7 @@ impl<TTrans: Transport> Node<TTrans> {
 		addr: Multiaddr,
 		connection_messages: Vec<serde_json::Map<String, serde_json::Value>>,
 		telemetry_connection_notifier: Vec<ConnectionNotifierSender>,
+		reconnect: ReconnectPolicy,
 	) -> Self {
 		Node {
 			addr,
@@ -104,7 +136,28 @@ impl<TTrans: Transport> Node<TTrans> {
 			transport,
 			connection_messages,
 			telemetry_connection_notifier,
+			reconnect,
+			failures: 0,
+		}
+	}
+
+	/// The state after a failed attempt, or after the connection got lost.
+	fn wait_reconnect(&mut self) -> NodeSocket<TTrans> {
+		self.failures = self.failures.saturating_add(1);
+		if self.reconnect.max_retries.map_or(false, |max| self.failures > max) {
+			log::warn!(
+				target: "telemetry",
+				"Giving up on {} after {} failed attempts",
+				Redacted(&self.addr),
+				self.failures,
+			);
+			return NodeSocket::GaveUp
 		}
+
+		let interval = self.reconnect.interval;
+		let random_delay = interval + interval.mul_f64(rand::thread_rng().gen::<f64>());
+		log::trace!(target: "telemetry", "Pausing for {:?} before reconnecting", random_delay);
+		NodeSocket::WaitingReconnect(Delay::new(random_delay))
 	}
 }
 
@@ -153,8 +206,8 @@ where
 					Poll::Ready(Ok(())) => {
 						match self.as_mut().try_send_connection_messages(cx, &mut conn) {
 							Poll::Ready(Err(err)) => {
-								log::warn!(target: "telemetry", "⚠️  Disconnected from {}: {:?}", self.addr, err);
-								socket = NodeSocket::wait_reconnect();
+								log::warn!(target: "telemetry", "⚠️  Disconnected from {}: {:?}", Redacted(&self.addr), err);
+								socket = self.wait_reconnect();
 							},
 							Poll::Ready(Ok(())) => {
 								self.socket = NodeSocket::Connected(conn);
@@ -167,8 +220,8 @@ where
 						}
 					},
 					Poll::Ready(Err(err)) => {
-						log::warn!(target: "telemetry", "⚠️  Disconnected from {}: {:?}", self.addr, err);
-						socket = NodeSocket::wait_reconnect();
+						log::warn!(target: "telemetry", "⚠️  Disconnected from {}: {:?}", Redacted(&self.addr), err);
+						socket = self.wait_reconnect();
 					},
 					Poll::Pending => {
 						self.socket = NodeSocket::Connected(conn);
@@ -177,7 +230,8 @@ where
 				},
 				NodeSocket::Dialing(mut s) => match Future::poll(Pin::new(&mut s), cx) {
 					Poll::Ready(Ok(sink)) => {
-						log::debug!(target: "telemetry", "✅ Connected to {}", self.addr);
+						log::debug!(target: "telemetry", "✅ Connected to {}", Redacted(&self.addr));
+						self.failures = 0;
 
 						for sender in self.telemetry_connection_notifier.iter_mut() {
 							let _ = sender.send(());
@@ -212,18 +266,18 @@ where
 					},
 					Poll::Pending => break NodeSocket::Dialing(s),
 					Poll::Ready(Err(err)) => {
-						log::warn!(target: "telemetry", "❌ Error while dialing {}: {:?}", self.addr, err);
-						socket = NodeSocket::wait_reconnect();
+						log::warn!(target: "telemetry", "❌ Error while dialing {}: {:?}", Redacted(&self.addr), err);
+						socket = self.wait_reconnect();
 					},
 				},
 				NodeSocket::ReconnectNow => match self.transport.clone().dial(self.addr.clone()) {
 					Ok(d) => {
-						log::trace!(target: "telemetry", "Re-dialing {}", self.addr);
+						log::trace!(target: "telemetry", "Re-dialing {}", Redacted(&self.addr));
 						socket = NodeSocket::Dialing(d);
 					},
 					Err(err) => {
-						log::warn!(target: "telemetry", "❌ Error while re-dialing {}: {:?}", self.addr, err);
-						socket = NodeSocket::wait_reconnect();
+						log::warn!(target: "telemetry", "❌ Error while re-dialing {}: {:?}", Redacted(&self.addr), err);
+						socket = self.wait_reconnect();
 					},
 				},
 				NodeSocket::WaitingReconnect(mut s) => {
@@ -233,8 +287,9 @@ where
 						break NodeSocket::WaitingReconnect(s)
 					}
 				},
+				NodeSocket::GaveUp => break NodeSocket::GaveUp,
 				NodeSocket::Poisoned => {
-					log::error!(target: "telemetry", "‼️ Poisoned connection with {}", self.addr);
+					log::error!(target: "telemetry", "‼️ Poisoned connection with {}", Redacted(&self.addr));
 					break NodeSocket::Poisoned
 				},
 			}
@@ -266,6 +321,8 @@ where
 			NodeSocket::ReconnectNow => log::trace!(target: "telemetry", "Reconnecting"),
 			// Waiting before attempting to dial again.
 			NodeSocket::WaitingReconnect(_) => {},
+			// Not reconnecting anymore.
+			NodeSocket::GaveUp => {},
 			// Temporary transition state.
 			NodeSocket::Poisoned => log::trace!(target: "telemetry", "Poisoned"),
 		}
@@ -281,7 +338,7 @@ where
 					// https://github.com/libp2p/rust-libp2p/issues/2021 for
 					// what we could do to improve this.
 					log::trace!(target: "telemetry", "[poll_flush] Error: {:?}", e);
-					self.socket = NodeSocket::wait_reconnect();
+					self.socket = self.wait_reconnect();
 					Poll::Ready(Ok(()))
 				},
 				Poll::Ready(Ok(())) => Poll::Ready(Ok(())),
@@ -307,7 +364,25 @@ impl<TTrans: Transport> fmt::Debug for NodeSocket<TTrans> {
 			Dialing(_) => "Dialing",
 			ReconnectNow => "ReconnectNow",
 			WaitingReconnect(_) => "WaitingReconnect",
+			GaveUp => "GaveUp",
 			Poisoned => "Poisoned",
 		})
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn redacts_the_websocket_path() {
+		let addr = libp2p::multiaddr::from_url("wss://telemetry.example/submit/token").unwrap();
+		assert_eq!(
+			Redacted(&addr).to_string(),
+			"/dns/telemetry.example/tcp/443/x-parity-wss/<redacted>",
+		);
+
+		let addr = libp2p::multiaddr::from_url("ws://127.0.0.1:8000").unwrap();
+		assert_eq!(Redacted(&addr).to_string(), addr.to_string());
+	}
+}
@@ -0,0 +1,118 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::any::TypeId;
+use tracing::{
+	span::{Attributes, Record},
+	subscriber::Interest,
+	Event, Id, Metadata, Subscriber,
+};
+use tracing_core::span::Current;
+use tracing_subscriber::{
+	filter::LevelFilter,
+	registry::{Data, LookupSpan},
+};
+
+/// A [`Subscriber`] that can look up the data of its spans, like the subscribers built by
+/// [`LoggerBuilder`](super::LoggerBuilder).
+trait SpanSubscriber: Subscriber + for<'a> LookupSpan<'a, Data = Data<'a>> + Send + Sync {}
+
+impl<S> SpanSubscriber for S where
+	S: Subscriber + for<'a> LookupSpan<'a, Data = Data<'a>> + Send + Sync
+{
+}
+
+/// The subscriber of the logger, with its concrete type erased.
+///
+/// The type of the subscriber built by [`LoggerBuilder`](super::LoggerBuilder) depends on its
+/// settings, custom layers are added on top of this type instead.
+pub struct BoxedSubscriber(Box<dyn SpanSubscriber>);
+
+impl BoxedSubscriber {
+	pub(crate) fn new<S>(subscriber: S) -> Self
+	where
+		S: Subscriber + for<'a> LookupSpan<'a, Data = Data<'a>> + Send + Sync,
+	{
+		Self(Box::new(subscriber))
+	}
+}
+
+impl Subscriber for BoxedSubscriber {
+	fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+		self.0.register_callsite(metadata)
+	}
+
+	fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+		self.0.enabled(metadata)
+	}
+
+	fn max_level_hint(&self) -> Option<LevelFilter> {
+		self.0.max_level_hint()
+	}
+
+	fn new_span(&self, span: &Attributes<'_>) -> Id {
+		self.0.new_span(span)
+	}
+
+	fn record(&self, span: &Id, values: &Record<'_>) {
+		self.0.record(span, values)
+	}
+
+	fn record_follows_from(&self, span: &Id, follows: &Id) {
+		self.0.record_follows_from(span, follows)
+	}
+
+	fn event(&self, event: &Event<'_>) {
+		self.0.event(event)
+	}
+
+	fn enter(&self, span: &Id) {
+		self.0.enter(span)
+	}
+
+	fn exit(&self, span: &Id) {
+		self.0.exit(span)
+	}
+
+	fn clone_span(&self, id: &Id) -> Id {
+		self.0.clone_span(id)
+	}
+
+	fn try_close(&self, id: Id) -> bool {
+		self.0.try_close(id)
+	}
+
+	fn current_span(&self) -> Current {
+		self.0.current_span()
+	}
+
+	unsafe fn downcast_raw(&self, id: TypeId) -> Option<*const ()> {
+		if id == TypeId::of::<Self>() {
+			return Some(self as *const Self as *const ())
+		}
+		self.0.downcast_raw(id)
+	}
+}
+
+impl<'a> LookupSpan<'a> for BoxedSubscriber {
+	type Data = Data<'a>;
+
+	fn span_data(&'a self, id: &Id) -> Option<Self::Data> {
+		self.0.span_data(id)
+	}
+}
@@ -22,10 +22,13 @@
 
 #![warn(missing_docs)]
 
+mod boxed_subscriber;
 mod directives;
 mod event_format;
 mod layers;
 
+pub use boxed_subscriber::BoxedSubscriber;
+
 pub use directives::*;
 pub use sc_tracing_proc_macro::*;
 
@@ -38,7 +41,7 @@ use tracing_subscriber::{
 		MakeWriter, SubscriberBuilder,
 	},
 	layer::{self, SubscriberExt},
-	registry::LookupSpan,
+	registry::{Data, LookupSpan},
 	EnvFilter, FmtSubscriber, Layer, Registry,
 };
 
@@ -96,7 +99,7 @@ fn prepare_subscriber<N, E, F, W>(
 			fn() -> std::io::Stderr,
 		>,
 	) -> SubscriberBuilder<N, E, F, W>,
-) -> Result<impl Subscriber + for<'a> LookupSpan<'a>>
+) -> Result<impl Subscriber + for<'a> LookupSpan<'a, Data = Data<'a>>>
 where
 	N: for<'writer> FormatFields<'writer> + 'static,
 	E: FormatEvent<Registry, N> + 'static,
@@ -200,12 +203,16 @@ where
 	Ok(subscriber)
 }
 
+/// Adds a custom layer on top of the logger, see [`LoggerBuilder::with_custom_layer`].
+type CustomLayer = Box<dyn FnOnce(BoxedSubscriber) -> tracing::Dispatch>;
+
 /// A builder that is used to initialize the global logger.
 pub struct LoggerBuilder {
 	directives: String,
 	profiling: Option<(crate::TracingReceiver, String)>,
 	log_reloading: bool,
 	force_colors: Option<bool>,
+	custom_layer: Option<CustomLayer>,
 }
 
 impl LoggerBuilder {
@@ -216,6 +223,7 @@ impl LoggerBuilder {
 			profiling: None,
 			log_reloading: true,
 			force_colors: None,
+			custom_layer: None,
 		}
 	}
 
@@ -241,6 +249,19 @@ impl LoggerBuilder {
 		self
 	}
 
+	/// Add a layer on top of the logging and profiling layers, e.g. to export the spans to a
+	/// distributed tracing system.
+	///
+	/// The layer only sees the spans and events enabled by the log filters. Calling this again
+	/// replaces the previous layer, layers can be combined with [`Layer::and_then`].
+	pub fn with_custom_layer<L>(&mut self, layer: L) -> &mut Self
+	where
+		L: Layer<BoxedSubscriber> + Send + Sync + 'static,
+	{
+		self.custom_layer = Some(Box::new(move |subscriber| subscriber.with(layer).into()));
+		self
+	}
+
 	/// Initialize the global logger
 	///
 	/// This sets various global logging and tracing instances and thus may only be called once.
@@ -255,7 +276,7 @@ impl LoggerBuilder {
 				)?;
 				let profiling = crate::ProfilingLayer::new(tracing_receiver, &profiling_targets);
 
-				tracing::subscriber::set_global_default(subscriber.with(profiling))?;
+				set_global_default(subscriber.with(profiling), self.custom_layer)?;
 
 				Ok(())
 			} else {
@@ -267,7 +288,7 @@ impl LoggerBuilder {
 				)?;
 				let profiling = crate::ProfilingLayer::new(tracing_receiver, &profiling_targets);
 
-				tracing::subscriber::set_global_default(subscriber.with(profiling))?;
+				set_global_default(subscriber.with(profiling), self.custom_layer)?;
 
 				Ok(())
 			}
@@ -278,7 +299,7 @@ impl LoggerBuilder {
 						enable_log_reloading!(builder)
 					})?;
 
-				tracing::subscriber::set_global_default(subscriber)?;
+				set_global_default(subscriber, self.custom_layer)?;
 
 				Ok(())
 			} else {
@@ -287,7 +308,7 @@ impl LoggerBuilder {
 						builder
 					})?;
 
-				tracing::subscriber::set_global_default(subscriber)?;
+				set_global_default(subscriber, self.custom_layer)?;
 
 				Ok(())
 			}
@@ -295,12 +316,29 @@ impl LoggerBuilder {
 	}
 }
 
+/// Set `subscriber`, with the `custom_layer` on top of it if any, as the global default.
+fn set_global_default<S>(subscriber: S, custom_layer: Option<CustomLayer>) -> Result<()>
+where
+	S: Subscriber + for<'a> LookupSpan<'a, Data = Data<'a>> + Send + Sync + 'static,
+{
+	match custom_layer {
+		Some(custom_layer) =>
+			tracing::dispatcher::set_global_default(custom_layer(BoxedSubscriber::new(subscriber)))?,
+		None => tracing::subscriber::set_global_default(subscriber)?,
+	}
+
+	Ok(())
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use crate as sc_tracing;
-	use std::{env, process::Command};
-	use tracing::{metadata::Kind, subscriber::Interest, Callsite, Level, Metadata};
+	use parking_lot::Mutex;
+	use std::{env, process::Command, sync::Arc};
+	use tracing::{
+		metadata::Kind, span::Attributes, subscriber::Interest, Callsite, Id, Level, Metadata,
+	};
 
 	const EXPECTED_LOG_MESSAGE: &'static str = "yeah logging works as expected";
 	const EXPECTED_NODE_NAME: &'static str = "THE_NODE";
@@ -359,6 +397,41 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn custom_layer_sees_spans() {
+		if env::var("RUN_TEST_CUSTOM_LAYER").is_ok() {
+			struct SpanNamesLayer(Arc<Mutex<Vec<String>>>);
+
+			impl<S> Layer<S> for SpanNamesLayer
+			where
+				S: Subscriber + for<'a> LookupSpan<'a>,
+			{
+				fn new_span(&self, _: &Attributes<'_>, id: &Id, ctx: layer::Context<'_, S>) {
+					let span = ctx.span(id).expect("the span was just created");
+					self.0.lock().push(span.name().to_string());
+				}
+			}
+
+			let names = Arc::new(Mutex::new(Vec::new()));
+			let mut logger = LoggerBuilder::new("");
+			logger.with_custom_layer(SpanNamesLayer(names.clone()));
+			logger.init().unwrap();
+
+			tracing::info_span!("enabled-span").in_scope(|| {});
+			tracing::trace_span!("filtered-span").in_scope(|| {});
+
+			assert_eq!(*names.lock(), vec!["enabled-span".to_string()]);
+		} else {
+			let status = Command::new(env::current_exe().unwrap())
+				.arg("custom_layer_sees_spans")
+				.env("RUN_TEST_CUSTOM_LAYER", "1")
+				.output()
+				.unwrap()
+				.status;
+			assert!(status.success());
+		}
+	}
+
 	/// This test ensures that using dash (`-`) in the target name in logs and directives actually
 	/// work.
 	#[test]
@@ -76,6 +76,10 @@ type ReadyIteratorFor<PoolApi> =
 
 type PolledIterator<PoolApi> = Pin<Box<dyn Future<Output = ReadyIteratorFor<PoolApi>> + Send>>;
 
+/// Decides whether a transaction submitted to the pool is admitted, before it is validated by the
+/// runtime.
+pub type AdmissionFilter<Block> = Arc<dyn Fn(&<Block as BlockT>::Extrinsic) -> bool + Send + Sync>;
+
 /// A transaction pool for a full node.
 pub type FullPool<Block, Client> = BasicPool<FullChainApi<Client, Block>, Block>;
 /// A transaction pool for a light node.
@@ -94,6 +98,7 @@ where
 	revalidation_queue: Arc<revalidation::RevalidationQueue<PoolApi>>,
 	ready_poll: Arc<Mutex<ReadyPoll<ReadyIteratorFor<PoolApi>, Block>>>,
 	metrics: PrometheusMetrics,
+	admission_filter: Option<AdmissionFilter<Block>>,
 }
 
 struct ReadyPoll<T, Block: BlockT> {
@@ -188,6 +193,7 @@ where
 				revalidation_strategy: Arc::new(Mutex::new(RevalidationStrategy::Always)),
 				ready_poll: Default::default(),
 				metrics: Default::default(),
+				admission_filter: None,
 			},
 			background_task,
 			notifier,
@@ -231,6 +237,25 @@ where
 			})),
 			ready_poll: Arc::new(Mutex::new(ReadyPoll::new(best_block_number))),
 			metrics: PrometheusMetrics::new(prometheus),
+			admission_filter: None,
+		}
+	}
+
+	/// Reject the external and local transactions for which `filter` returns `false` as invalid
+	/// calls.
+	///
+	/// The transactions of the retracted blocks are submitted as external ones, while the ones
+	/// already in the pool aren't filtered again when they are revalidated.
+	pub fn with_admission_filter(mut self, filter: AdmissionFilter<Block>) -> Self {
+		self.admission_filter = Some(filter);
+		self
+	}
+
+	/// Whether the transaction submitted from `source` is admitted into the pool.
+	fn admits(&self, source: TransactionSource, xt: &graph::ExtrinsicFor<PoolApi>) -> bool {
+		match (source, &self.admission_filter) {
+			(TransactionSource::External | TransactionSource::Local, Some(filter)) => filter(xt),
+			_ => true,
 		}
 	}
 
@@ -268,7 +293,21 @@ where
 		self.metrics
 			.report(|metrics| metrics.submitted_transactions.inc_by(xts.len() as u64));
 
-		async move { pool.submit_at(&at, source, xts).await }.boxed()
+		let admitted = xts.iter().map(|xt| self.admits(source, xt)).collect::<Vec<_>>();
+		let xts = xts.into_iter().zip(&admitted).filter(|(_, admitted)| **admitted);
+		let xts = xts.map(|(xt, _)| xt).collect::<Vec<_>>();
+
+		async move {
+			let mut results = pool.submit_at(&at, source, xts).await?.into_iter();
+			Ok(admitted
+				.into_iter()
+				.map(|admitted| match admitted {
+					true => results.next().expect("one result per admitted transaction; qed"),
+					false => Err(not_admitted()),
+				})
+				.collect())
+		}
+		.boxed()
 	}
 
 	fn submit_one(
@@ -282,6 +321,10 @@ where
 
 		self.metrics.report(|metrics| metrics.submitted_transactions.inc());
 
+		if !self.admits(source, &xt) {
+			return future::ready(Err(not_admitted())).boxed()
+		}
+
 		async move { pool.submit_one(&at, source, xt).await }.boxed()
 	}
 
@@ -296,6 +339,10 @@ where
 
 		self.metrics.report(|metrics| metrics.submitted_transactions.inc());
 
+		if !self.admits(source, &xt) {
+			return future::ready(Err(not_admitted())).boxed()
+		}
+
 		async move {
 			let watcher = pool.submit_and_watch(&at, source, xt).await?;
 
@@ -412,9 +459,29 @@ where
 		prometheus: Option<&PrometheusRegistry>,
 		spawner: impl SpawnEssentialNamed,
 		client: Arc<Client>,
+	) -> Arc<Self> {
+		Self::new_full_with_admission_filter(
+			options,
+			is_validator,
+			prometheus,
+			spawner,
+			client,
+			None,
+		)
+	}
+
+	/// Create new basic transaction pool for a full node, only admitting the transactions
+	/// accepted by `admission_filter`.
+	pub fn new_full_with_admission_filter(
+		options: graph::Options,
+		is_validator: IsValidator,
+		prometheus: Option<&PrometheusRegistry>,
+		spawner: impl SpawnEssentialNamed,
+		client: Arc<Client>,
+		admission_filter: Option<AdmissionFilter<Block>>,
 	) -> Arc<Self> {
 		let pool_api = Arc::new(FullChainApi::new(client.clone(), prometheus, &spawner));
-		let pool = Arc::new(Self::with_revalidation_type(
+		let mut pool = Self::with_revalidation_type(
 			options,
 			is_validator,
 			pool_api,
@@ -422,7 +489,11 @@ where
 			RevalidationType::Full,
 			spawner,
 			client.usage_info().chain.best_number,
-		));
+		);
+		if let Some(filter) = admission_filter {
+			pool = pool.with_admission_filter(filter);
+		}
+		let pool = Arc::new(pool);
 
 		// make transaction pool available for off-chain runtime calls.
 		client.execution_extensions().register_transaction_pool(&pool);
@@ -456,6 +527,10 @@ where
 			traits::SaturatedConversion, transaction_validity::TransactionValidityError,
 		};
 
+		if !self.admits(TransactionSource::Local, &xt) {
+			return Err(not_admitted())
+		}
+
 		let validity = self
 			.api
 			.validate_transaction_blocking(at, TransactionSource::Local, xt.clone())?
@@ -485,6 +560,14 @@ where
 	}
 }
 
+/// The error of the transactions rejected by the admission filter of the pool.
+fn not_admitted<E: From<sc_transaction_pool_api::error::Error>>() -> E {
+	sc_transaction_pool_api::error::Error::InvalidTransaction(
+		sp_runtime::transaction_validity::InvalidTransaction::Call,
+	)
+	.into()
+}
+
 #[cfg_attr(test, derive(Debug))]
 enum RevalidationStatus<N> {
 	/// The revalidation has never been completed.
@@ -1007,3 +1007,36 @@ fn stale_transactions_are_pruned() {
 	assert_eq!(pool.status().future, 0);
 	assert_eq!(pool.status().ready, 0);
 }
+
+#[test]
+fn admission_filter_only_applies_to_submitted_transactions() {
+	use sc_transaction_pool_api::error::{Error as PoolError, IntoPoolError};
+
+	let (pool, _guard, _notifier) = maintained_pool();
+	let pool =
+		pool.with_admission_filter(Arc::new(|xt: &Extrinsic| xt.transfer().from != Bob.into()));
+	let not_admitted = |err: substrate_test_runtime_transaction_pool::Error| {
+		matches!(err.into_pool_error(), Ok(PoolError::InvalidTransaction(InvalidTransaction::Call)))
+	};
+
+	let err = block_on(pool.submit_one(&BlockId::number(0), SOURCE, uxt(Bob, 0))).unwrap_err();
+	assert!(not_admitted(err));
+	let local = TransactionSource::Local;
+	let err = block_on(pool.submit_and_watch(&BlockId::number(0), local, uxt(Bob, 0)))
+		.map(drop)
+		.unwrap_err();
+	assert!(not_admitted(err));
+
+	let mut results =
+		block_on(pool.submit_at(&BlockId::number(0), SOURCE, vec![uxt(Bob, 0), uxt(Alice, 209)]))
+			.unwrap()
+			.into_iter();
+	assert!(not_admitted(results.next().unwrap().unwrap_err()));
+	assert!(results.next().unwrap().is_ok());
+	assert_eq!(pool.status().ready, 1);
+
+	// The transactions of the imported blocks aren't filtered.
+	block_on(pool.submit_one(&BlockId::number(0), TransactionSource::InBlock, uxt(Bob, 0)))
+		.unwrap();
+	assert_eq!(pool.status().ready, 2);
+}
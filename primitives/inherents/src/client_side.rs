@@ -125,3 +125,45 @@ impl InherentDataProvider for Tuple {
 		None
 	}
 }
+
+/// An optional inherent data provider, which provides nothing when `None`.
+///
+/// This allows leaving out a provider at runtime, e.g. when the runtime doesn't use its inherent.
+#[async_trait::async_trait]
+impl<T: InherentDataProvider> InherentDataProvider for Option<T> {
+	fn provide_inherent_data(&self, inherent_data: &mut InherentData) -> Result<(), Error> {
+		match self {
+			Some(provider) => provider.provide_inherent_data(inherent_data),
+			None => Ok(()),
+		}
+	}
+
+	async fn try_handle_error(
+		&self,
+		identifier: &InherentIdentifier,
+		error: &[u8],
+	) -> Option<Result<(), Error>> {
+		match self {
+			Some(provider) => provider.try_handle_error(identifier, error).await,
+			None => None,
+		}
+	}
+}
+
+/// A boxed inherent data provider.
+///
+/// This allows choosing the providers at runtime while keeping a single type.
+#[async_trait::async_trait]
+impl<T: InherentDataProvider + ?Sized> InherentDataProvider for Box<T> {
+	fn provide_inherent_data(&self, inherent_data: &mut InherentData) -> Result<(), Error> {
+		(**self).provide_inherent_data(inherent_data)
+	}
+
+	async fn try_handle_error(
+		&self,
+		identifier: &InherentIdentifier,
+		error: &[u8],
+	) -> Option<Result<(), Error>> {
+		(**self).try_handle_error(identifier, error).await
+	}
+}
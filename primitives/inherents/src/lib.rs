@@ -465,6 +465,15 @@ mod tests {
 		assert_eq!(inherent_data.get_data::<u32>(&TEST_INHERENT_0).unwrap().unwrap(), 42u32);
 	}
 
+	#[test]
+	fn optional_provider_provides_nothing_when_none() {
+		let inherent_data = Some(TestInherentDataProvider).create_inherent_data().unwrap();
+		assert_eq!(inherent_data.get_data::<u32>(&TEST_INHERENT_0).unwrap().unwrap(), 42u32);
+
+		let inherent_data = None::<TestInherentDataProvider>.create_inherent_data().unwrap();
+		assert!(inherent_data.get_data::<u32>(&TEST_INHERENT_0).unwrap().is_none());
+	}
+
 	#[test]
 	fn check_inherents_result_encodes_and_decodes() {
 		let mut result = CheckInherentsResult::new();
@@ -42,13 +42,53 @@ pub fn base_path() -> BasePath {
 	}
 }
 
+/// The database backend of the test node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+	/// RocksDB in the [`base_path`].
+	RocksDb,
+	/// Kept in memory, nothing is written to disk.
+	InMemory,
+}
+
+impl Default for Backend {
+	fn default() -> Self {
+		Backend::RocksDb
+	}
+}
+
 /// Produces a default configuration object, suitable for use with most set ups.
 pub fn default_config(
+	task_executor: TaskExecutor,
+	chain_spec: Box<dyn ChainSpec>,
+) -> Configuration {
+	default_config_with_backend(task_executor, chain_spec, Backend::default())
+}
+
+/// Produces a default configuration object like [`default_config`], with the given database
+/// `backend`.
+///
+/// With [`Backend::InMemory`], the database and keystore are kept in memory and no base path is
+/// created.
+pub fn default_config_with_backend(
 	task_executor: TaskExecutor,
 	mut chain_spec: Box<dyn ChainSpec>,
+	backend: Backend,
 ) -> Configuration {
-	let base_path = base_path();
-	let root_path = base_path.path().to_path_buf().join("chains").join(chain_spec.id());
+	let base_path = match backend {
+		Backend::RocksDb => Some(base_path()),
+		Backend::InMemory => None,
+	};
+	let (keystore, database) = match &base_path {
+		Some(base_path) => {
+			let root_path = base_path.path().join("chains").join(chain_spec.id());
+			(
+				KeystoreConfig::Path { path: root_path.join("key"), password: None },
+				DatabaseSource::RocksDb { path: root_path.join("db"), cache_size: 128 },
+			)
+		},
+		None => (KeystoreConfig::InMemory, DatabaseSource::in_memory()),
+	};
 
 	let storage = chain_spec
 		.as_storage_builder()
@@ -78,10 +118,12 @@ pub fn default_config(
 		task_executor: task_executor.into(),
 		transaction_pool: Default::default(),
 		network: network_config,
-		keystore: KeystoreConfig::Path { path: root_path.join("key"), password: None },
-		database: DatabaseSource::RocksDb { path: root_path.join("db"), cache_size: 128 },
+		keystore,
+		database,
+		database_read_only: false,
 		state_cache_size: 16777216,
 		state_cache_child_ratio: None,
+		disable_state_cache_layer: false,
 		chain_spec,
 		wasm_method: WasmExecutionMethod::Interpreted,
 		execution_strategies: ExecutionStrategies {
@@ -97,10 +139,19 @@ pub fn default_config(
 		rpc_ws_max_connections: None,
 		rpc_http_threads: None,
 		rpc_cors: None,
+		rpc_allowed_hosts: Vec::new(),
 		rpc_methods: Default::default(),
 		rpc_max_payload: None,
+		rpc_max_request_size: None,
+		rpc_max_response_size: None,
+		rpc_rate_limit: None,
+		rpc_max_subscriptions_per_connection: None,
+		rpc_confirmation_depth: None,
+		rpc_modules: None,
+		health_warmup: None,
 		prometheus_config: None,
 		telemetry_endpoints: None,
+		telemetry_reconnect: Default::default(),
 		default_heap_pages: None,
 		offchain_worker: Default::default(),
 		force_authoring: false,
@@ -110,7 +161,7 @@ pub fn default_config(
 		tracing_receiver: Default::default(),
 		max_runtime_instances: 8,
 		announce_block: true,
-		base_path: Some(base_path),
+		base_path,
 		wasm_runtime_overrides: None,
 		informant_output_format,
 		disable_log_reloading: false,
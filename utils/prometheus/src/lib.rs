@@ -39,6 +39,8 @@ pub use sourced::{MetricSource, SourcedCounter, SourcedGauge, SourcedMetric};
 
 #[cfg(not(target_os = "unknown"))]
 pub use known_os::init_prometheus;
+#[cfg(unix)]
+pub use known_os::init_prometheus_unix;
 #[cfg(target_os = "unknown")]
 pub use unknown_os::init_prometheus;
 
@@ -65,12 +67,17 @@ mod unknown_os {
 #[cfg(not(target_os = "unknown"))]
 mod known_os {
 	use super::*;
+	use futures_util::{
+		io::{AsyncRead, AsyncWrite},
+		stream::Stream,
+	};
 	use hyper::{
 		http::StatusCode,
 		server::Server,
 		service::{make_service_fn, service_fn},
 		Body, Request, Response,
 	};
+	use networking::Incoming;
 
 	#[derive(Debug, derive_more::Display, derive_more::From)]
 	pub enum Error {
@@ -144,15 +151,51 @@ mod known_os {
 		init_prometheus_with_listener(listener, registry).await
 	}
 
+	/// Initializes the metrics context, and starts an HTTP server listening on the Unix domain
+	/// socket at `path` to serve metrics.
+	///
+	/// The socket is removed once the server stops, or the future is dropped.
+	#[cfg(unix)]
+	pub async fn init_prometheus_unix(
+		path: std::path::PathBuf,
+		registry: Registry,
+	) -> Result<(), Error> {
+		let listener = async_std::os::unix::net::UnixListener::bind(&path).await?;
+		let socket = SocketFile(path);
+
+		log::info!("〽️ Prometheus exporter started at {}", socket.0.display());
+
+		serve(Incoming(listener.incoming()), registry).await
+	}
+
+	/// Removes the socket file at its path when dropped.
+	#[cfg(unix)]
+	struct SocketFile(std::path::PathBuf);
+
+	#[cfg(unix)]
+	impl Drop for SocketFile {
+		fn drop(&mut self) {
+			if let Err(err) = std::fs::remove_file(&self.0) {
+				log::debug!("Failed to remove the socket {}: {}", self.0.display(), err);
+			}
+		}
+	}
+
 	/// Init prometheus using the given listener.
 	pub(crate) async fn init_prometheus_with_listener(
 		listener: async_std::net::TcpListener,
 		registry: Registry,
 	) -> Result<(), Error> {
-		use networking::Incoming;
-
 		log::info!("〽️ Prometheus exporter started at {}", listener.local_addr()?);
 
+		serve(Incoming(listener.incoming()), registry).await
+	}
+
+	async fn serve<S, C>(incoming: Incoming<S>, registry: Registry) -> Result<(), Error>
+	where
+		S: Stream<Item = std::io::Result<C>> + Unpin,
+		C: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+	{
 		let service = make_service_fn(move |_| {
 			let registry = registry.clone();
 
@@ -163,8 +206,7 @@ mod known_os {
 			}
 		});
 
-		let server =
-			Server::builder(Incoming(listener.incoming())).executor(Executor).serve(service);
+		let server = Server::builder(incoming).executor(Executor).serve(service);
 
 		let result = server.await.map_err(Into::into);
 
@@ -213,4 +255,39 @@ mod tests {
 			assert!(body.contains(&format!("{} 0", METRIC_NAME)));
 		});
 	}
+
+	#[cfg(unix)]
+	#[test]
+	fn prometheus_works_on_unix_socket() {
+		use async_std::{os::unix::net::UnixStream, prelude::*};
+
+		let dir = std::env::temp_dir().join(format!("prometheus-uds-{}", std::process::id()));
+		std::fs::create_dir_all(&dir).expect("Creates the socket directory");
+		let path = dir.join("metrics.sock");
+
+		let runtime = tokio::runtime::Runtime::new().expect("Creates the runtime");
+		let server = runtime.spawn(init_prometheus_unix(path.clone(), Registry::default()));
+
+		let response = runtime.block_on(async {
+			let mut stream = loop {
+				match UnixStream::connect(&path).await {
+					Ok(stream) => break stream,
+					Err(_) => async_std::task::sleep(std::time::Duration::from_millis(10)).await,
+				}
+			};
+			stream.write_all(b"GET /metrics HTTP/1.0\r\n\r\n").await.expect("Sends request");
+			let mut response = String::new();
+			stream.read_to_string(&mut response).await.expect("Reads response");
+			response
+		});
+
+		assert!(response.starts_with("HTTP/1.0 200 OK"), "{}", response);
+
+		// Stopping the server removes its socket.
+		server.abort();
+		assert!(runtime.block_on(server).unwrap_err().is_cancelled());
+		assert!(!path.exists());
+
+		let _ = std::fs::remove_dir_all(&dir);
+	}
 }
@@ -22,11 +22,16 @@ use futures_util::{
 };
 use std::task::{Context, Poll};
 
-pub struct Incoming<'a>(pub async_std::net::Incoming<'a>);
+/// The connections accepted by a listener, served by hyper.
+pub struct Incoming<S>(pub S);
 
-impl hyper::server::accept::Accept for Incoming<'_> {
-	type Conn = TcpStream;
-	type Error = async_std::io::Error;
+impl<S, C> hyper::server::accept::Accept for Incoming<S>
+where
+	S: Stream<Item = std::io::Result<C>> + Unpin,
+	C: AsyncRead + AsyncWrite + Unpin,
+{
+	type Conn = Connection<C>;
+	type Error = std::io::Error;
 
 	fn poll_accept(
 		self: Pin<&mut Self>,
@@ -34,13 +39,14 @@ impl hyper::server::accept::Accept for Incoming<'_> {
 	) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
 		Pin::new(&mut Pin::into_inner(self).0)
 			.poll_next(cx)
-			.map(|opt| opt.map(|res| res.map(TcpStream)))
+			.map(|opt| opt.map(|res| res.map(Connection)))
 	}
 }
 
-pub struct TcpStream(pub async_std::net::TcpStream);
+/// An async-std connection, usable with tokio.
+pub struct Connection<C>(pub C);
 
-impl tokio::io::AsyncRead for TcpStream {
+impl<C: AsyncRead + Unpin> tokio::io::AsyncRead for Connection<C> {
 	fn poll_read(
 		self: Pin<&mut Self>,
 		cx: &mut Context,
@@ -52,7 +58,7 @@ impl tokio::io::AsyncRead for TcpStream {
 	}
 }
 
-impl tokio::io::AsyncWrite for TcpStream {
+impl<C: AsyncWrite + Unpin> tokio::io::AsyncWrite for Connection<C> {
 	fn poll_write(
 		self: Pin<&mut Self>,
 		cx: &mut Context,